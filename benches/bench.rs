@@ -4,20 +4,22 @@ extern crate test;
 
 use orion::core::options::KeccakVariantOption;
 use orion::core::options::ShaVariantOption;
+use orion::core::types::{Password, SecretKey};
 use orion::hazardous::cshake::CShake;
 use orion::hazardous::hkdf::Hkdf;
 use orion::hazardous::hmac::Hmac;
+use orion::hazardous::kangarootwelve::KangarooTwelve;
 use orion::hazardous::pbkdf2::Pbkdf2;
 use test::Bencher;
 
 #[bench]
 fn bench_hmac(b: &mut Bencher) {
     b.iter(|| {
-        let hmac = Hmac {
-            secret_key: vec![0x01; 32],
-            data: vec![0x01; 32],
-            sha2: ShaVariantOption::SHA256,
-        };
+        let hmac = Hmac::new(
+            &SecretKey::from_slice(&[0x01; 32]).unwrap(),
+            &[0x01; 32],
+            ShaVariantOption::SHA256,
+        ).unwrap();
 
         hmac.finalize();
     });
@@ -26,13 +28,8 @@ fn bench_hmac(b: &mut Bencher) {
 #[bench]
 fn bench_hkdf(b: &mut Bencher) {
     b.iter(|| {
-        let hkdf = Hkdf {
-            salt: vec![0x01; 32],
-            ikm: vec![0x01; 32],
-            info: vec![0x01; 32],
-            length: 32,
-            hmac: ShaVariantOption::SHA256,
-        };
+        let hkdf = Hkdf::new(&[0x01; 32], &[0x01; 32], &[0x01; 32], 32, ShaVariantOption::SHA256)
+            .unwrap();
 
         hkdf.derive_key().unwrap();
     });
@@ -41,13 +38,9 @@ fn bench_hkdf(b: &mut Bencher) {
 #[bench]
 fn bench_pbkdf2(b: &mut Bencher) {
     b.iter(|| {
-        let pbkdf = Pbkdf2 {
-            salt: vec![0x01; 32],
-            password: vec![0x01; 32],
-            iterations: 10000,
-            dklen: 32,
-            hmac: ShaVariantOption::SHA256,
-        };
+        let password = Password::from_slice(&[0x01; 32]).unwrap();
+        let pbkdf =
+            Pbkdf2::new(&password, &[0x01; 32], 10000, 32, ShaVariantOption::SHA256).unwrap();
 
         pbkdf.derive_key().unwrap();
     });
@@ -56,14 +49,22 @@ fn bench_pbkdf2(b: &mut Bencher) {
 #[bench]
 fn bench_cshake(b: &mut Bencher) {
     b.iter(|| {
-        let cshake = CShake {
+        let cshake = CShake::new(&[0x01; 32], &[], &[0x01; 32], 64, KeccakVariantOption::KECCAK256)
+            .unwrap();
+
+        cshake.finalize().unwrap();
+    });
+}
+
+#[bench]
+fn bench_kangarootwelve(b: &mut Bencher) {
+    b.iter(|| {
+        let k12 = KangarooTwelve {
             input: vec![0x01; 32],
-            name: vec![0x00; 0],
             custom: vec![0x01; 32],
-            length: 64,
-            keccak: KeccakVariantOption::KECCAK256,
+            length: 32,
         };
 
-        cshake.finalize().unwrap();
+        k12.finalize().unwrap();
     });
 }
@@ -22,13 +22,7 @@ fn fuzz_cshake(
     let mut mod_custom = custom.to_vec();
     mod_custom.push(0u8);
 
-    let cshake = CShake {
-        input: input.to_vec(),
-        name: name.to_vec(),
-        custom: mod_custom,
-        length: len_rand,
-        keccak,
-    };
+    let cshake = CShake::new(input, name, &mod_custom, len_rand, keccak).unwrap();
 
     let hash = cshake.finalize().unwrap();
 
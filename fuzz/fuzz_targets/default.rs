@@ -4,12 +4,13 @@ extern crate libfuzzer_sys;
 extern crate orion;
 extern crate rand;
 
-use orion::core::util;
+use orion::core::types::{Password, Salt, SecretKey};
 use orion::default;
 use rand::Rng;
 
 fn fuzz_default(data: &[u8]) -> () {
-    let rand_salt = util::gen_rand_key(64).unwrap();
+    let rand_salt = Salt::generate(64).unwrap();
+    let rand_key = SecretKey::generate(64).unwrap();
     let mut rng = rand::thread_rng();
 
     // cSHAKE `custom` can't be empty
@@ -27,10 +28,11 @@ fn fuzz_default(data: &[u8]) -> () {
             len_hkdf,
         ).unwrap();
 
-        default::hmac_verify(&default::hmac(&rand_salt, data).unwrap(), &rand_salt, data).unwrap();
+        default::hmac_verify(&default::hmac(&rand_key, data).unwrap(), &rand_key, data).unwrap();
 
         let mut password = data.to_vec();
         password.extend_from_slice(&[0u8; 14]);
+        let password = Password::from_slice(&password).unwrap();
 
         default::pbkdf2_verify(&default::pbkdf2(&password).unwrap(), &password).unwrap();
 
@@ -12,15 +12,9 @@ fn fuzz_hkdf(salt: &[u8], ikm: &[u8], info: &[u8], len_max: usize, hmac: ShaVari
     let mut rng = rand::thread_rng();
     let okm_len_rand = rng.gen_range(1, len_max + 1);
 
-    let dk = Hkdf {
-        salt: salt.to_vec(),
-        ikm: ikm.to_vec(),
-        info: info.to_vec(),
-        length: okm_len_rand,
-        hmac,
-    };
-
-    let prk = dk.extract(ikm, salt);
+    let dk = Hkdf::new(salt, ikm, info, okm_len_rand, hmac).unwrap();
+
+    let prk = dk.extract();
     let dk_fin = dk.expand(&prk).unwrap();
 
     assert_eq!(dk_fin, dk.derive_key().unwrap());
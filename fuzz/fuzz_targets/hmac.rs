@@ -3,18 +3,20 @@
 extern crate libfuzzer_sys;
 extern crate orion;
 use orion::core::options::ShaVariantOption;
+use orion::core::types::SecretKey;
 use orion::hazardous::hmac::*;
 
 fn fuzz_hmac(secret_key: &[u8], data: &[u8], sha2: ShaVariantOption) {
-    let mac = Hmac {
-        secret_key: secret_key.to_vec(),
-        data: data.to_vec(),
-        sha2,
+    let key = match SecretKey::from_slice(secret_key) {
+        Ok(key) => key,
+        Err(_) => return,
     };
 
-    let (ipad, opad) = mac.pad_key(secret_key);
-    let mac_def = mac.finalize();
-    let mac_pbkdf2 = pbkdf2_hmac(&ipad, &opad, &mac.data, mac.sha2);
+    let mac = Hmac::new(&key, data, sha2).unwrap();
+
+    let (ipad, opad) = pad_key(secret_key, sha2);
+    let mac_def = mac.finalize().as_bytes().to_vec();
+    let mac_pbkdf2 = pbkdf2_hmac(&ipad, &opad, data, sha2);
 
     assert_eq!(mac_def, mac_pbkdf2);
     assert_eq!(mac.verify(&mac_def).unwrap(), true);
@@ -5,23 +5,23 @@ extern crate orion;
 extern crate rand;
 
 use orion::core::options::ShaVariantOption;
+use orion::core::types::Password;
 use orion::hazardous::pbkdf2::Pbkdf2;
 use rand::prelude::*;
 
 fn fuzz_pbkdf2(password: &[u8], salt: &[u8], hmac: ShaVariantOption) {
+    if password.is_empty() {
+        return;
+    }
+
     let mut rng = rand::thread_rng();
 
     if rng.gen() {
         let iter: usize = rng.gen_range(1, 10001);
         let len: usize = rng.gen_range(1, 1025);
 
-        let dk = Pbkdf2 {
-            password: password.to_vec(),
-            salt: salt.to_vec(),
-            iterations: iter,
-            dklen: len,
-            hmac,
-        };
+        let password = Password::from_slice(password).unwrap();
+        let dk = Pbkdf2::new(&password, salt, iter, len, hmac).unwrap();
 
         assert_eq!(dk.verify(&dk.derive_key().unwrap()).unwrap(), true);
     }
@@ -6,6 +6,7 @@ extern crate rand;
 extern crate ring;
 
 use orion::core::options::ShaVariantOption;
+use orion::core::types::{Password, SecretKey};
 use orion::hazardous::hkdf;
 use orion::hazardous::hmac;
 use orion::hazardous::pbkdf2;
@@ -31,13 +32,14 @@ fn ro_hmac(buf1: &[u8], buf2: &[u8], sha2: ShaVariantOption) {
     let s_key = ring_hmac::SigningKey::new(return_digest(sha2), key.as_ref());
     let ring_signature = ring_hmac::sign(&s_key, message.as_ref());
 
-    let orion_hmac = hmac::Hmac {
-        secret_key: key.to_vec(),
-        data: message.to_vec(),
-        sha2,
+    let secret_key = match SecretKey::from_slice(&key) {
+        Ok(secret_key) => secret_key,
+        Err(_) => return,
     };
 
-    let orion_signature = orion_hmac.finalize();
+    let orion_hmac = hmac::Hmac::new(&secret_key, &message, sha2).unwrap();
+
+    let orion_signature = orion_hmac.finalize().as_bytes().to_vec();
     let v_key = ring_hmac::VerificationKey::new(return_digest(sha2), key.as_ref());
 
     let mut ring_res = false;
@@ -76,15 +78,9 @@ fn ro_hkdf(buf1: &[u8], buf2: &[u8], buf3: &[u8], hmac: ShaVariantOption) {
 
     let mut out_okm = vec![0u8; okm_len];
 
-    let orion_hkdf = hkdf::Hkdf {
-        salt: salt.to_vec(),
-        ikm: ikm.to_vec(),
-        info: info.to_vec(),
-        length: okm_len,
-        hmac,
-    };
+    let orion_hkdf = hkdf::Hkdf::new(&salt, &ikm, &info, okm_len, hmac).unwrap();
 
-    let orion_prk = orion_hkdf.extract(&orion_hkdf.salt, &orion_hkdf.ikm);
+    let orion_prk = orion_hkdf.extract();
     let orion_okm = orion_hkdf.expand(&orion_prk).unwrap();
     let orion_derived = orion_hkdf.derive_key().unwrap();
 
@@ -101,6 +97,10 @@ fn ro_pbkdf2(buf1: &[u8], buf2: &[u8], hmac: ShaVariantOption) {
     let salt = buf1.to_vec();
     let password = buf2.to_vec();
 
+    if password.is_empty() {
+        return;
+    }
+
     let mut rng = rand::thread_rng();
 
     let iter: usize = rng.gen_range(1, 10001);
@@ -108,13 +108,8 @@ fn ro_pbkdf2(buf1: &[u8], buf2: &[u8], hmac: ShaVariantOption) {
 
     let mut dk_out = vec![0u8; len];
 
-    let dk = pbkdf2::Pbkdf2 {
-        password: password.to_vec(),
-        salt: salt.to_vec(),
-        iterations: iter,
-        dklen: len,
-        hmac,
-    };
+    let dk = pbkdf2::Pbkdf2::new(&Password::from_slice(&password).unwrap(), &salt, iter, len, hmac)
+        .unwrap();
 
     ring_pbkdf2::derive(
         return_digest(hmac),
@@ -0,0 +1,597 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! orion's versioned container format for authenticated encryption, as written by
+//! `default::encrypt` and `default::encrypt_with_password`.
+//!
+//! A blob is a header followed by the ciphertext and tag. The header names the AEAD algorithm
+//! and how its key was derived, so the algorithm and parameters can change without breaking
+//! decryption of existing blobs. The whole header is authenticated as additional data.
+//!
+//! | Field | Size | Content |
+//! | --- | --- | --- |
+//! | Magic | 4 | `ORNB` |
+//! | Version | 1 | `0x01` |
+//! | AEAD | 1 | `0x01`: XChaCha20-Poly1305, `0x02`: XChaCha20-Poly1305 with key commitment |
+//! | KDF | 1 | `0x00`: none, `0x01`: PBKDF2-HMAC-SHA512/256, `0x02`: key id, |
+//! | | | `0x03`: wrapped key |
+//! | KDF parameters | variable | PBKDF2: iterations (big-endian `u32`), salt length, salt |
+//! | | | Key id: the id (big-endian `u32`) |
+//! | | | Wrapped key: its length, the wrapped key |
+//! | Nonce | 24 | Nonce for the AEAD |
+//!
+//! With key commitment, the first 32 bytes of HMAC-SHA512 of `"orion key commitment" || nonce`
+//! under the secret key are the XChaCha20-Poly1305 key, and the last 32 bytes are a commitment
+//! to the secret key. The commitment follows the header, and is authenticated with it. A blob
+//! then only opens under the one key it was sealed with, which multi-key protocols such as
+//! message franking rely on.
+//!
+//! To upgrade old blobs, read their header with `BlobHeader::parse`, and decrypt and encrypt
+//! again those that use outdated parameters.
+
+use core::errors::*;
+use core::options::ShaVariantOption;
+use core::types::{Nonce, SecretKey};
+use core::util;
+use endian::{read_u32_be, write_u32_be};
+use hazardous::hmac::Hmac;
+use hazardous::xchacha20poly1305;
+use zeroize::Zeroize;
+
+/// The magic bytes at the start of every blob.
+pub const MAGIC: [u8; 4] = *b"ORNB";
+
+/// The version of the blob format.
+pub const VERSION: u8 = 0x01;
+
+/// The smallest salt accepted for key derivation.
+const MIN_SALT_LEN: usize = 16;
+
+/// The length of the key commitment of `AeadAlgorithm::XChaCha20Poly1305Committing`.
+const COMMITMENT_LEN: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// The AEAD algorithms that can protect a blob.
+pub enum AeadAlgorithm {
+    /// XChaCha20-Poly1305 with a 24 byte nonce and a 16 byte tag.
+    XChaCha20Poly1305,
+    /// XChaCha20-Poly1305 with a 32 byte key commitment, which is counted in the tag length.
+    XChaCha20Poly1305Committing,
+}
+
+impl AeadAlgorithm {
+    fn id(self) -> u8 {
+        match self {
+            AeadAlgorithm::XChaCha20Poly1305 => 0x01,
+            AeadAlgorithm::XChaCha20Poly1305Committing => 0x02,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Self, UnknownCryptoError> {
+        match id {
+            0x01 => Ok(AeadAlgorithm::XChaCha20Poly1305),
+            0x02 => Ok(AeadAlgorithm::XChaCha20Poly1305Committing),
+            _ => Err(UnknownCryptoError),
+        }
+    }
+
+    /// Return the length of the nonce for this algorithm.
+    pub fn nonce_len(self) -> usize {
+        match self {
+            AeadAlgorithm::XChaCha20Poly1305 | AeadAlgorithm::XChaCha20Poly1305Committing => 24,
+        }
+    }
+
+    /// Return the length of the tag for this algorithm.
+    pub fn tag_len(self) -> usize {
+        match self {
+            AeadAlgorithm::XChaCha20Poly1305 => xchacha20poly1305::TAGBYTES,
+            AeadAlgorithm::XChaCha20Poly1305Committing => {
+                COMMITMENT_LEN + xchacha20poly1305::TAGBYTES
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// How the key of a blob was derived.
+pub enum KdfParams {
+    /// The secret key is used directly.
+    None,
+    /// The key is derived from a password with PBKDF2-HMAC-SHA512/256.
+    Pbkdf2Sha512Trunc256 {
+        /// The iteration count.
+        iterations: u32,
+        /// The salt, between 16 and 255 bytes.
+        salt: Vec<u8>,
+    },
+    /// The key is taken from a `default::Keyring` by its id.
+    KeyId(u32),
+    /// The key is a data-encryption key, wrapped by `default::envelope_encrypt` under a
+    /// key-encryption key. The wrapped key is between 1 and 255 bytes.
+    WrappedKey(Vec<u8>),
+}
+
+#[derive(Clone, Debug)]
+/// The header of a blob.
+pub struct BlobHeader {
+    aead: AeadAlgorithm,
+    kdf: KdfParams,
+    nonce: Nonce,
+}
+
+impl BlobHeader {
+    /// Make a header for a new blob.
+    ///
+    /// # Exceptions:
+    /// An exception will be thrown if:
+    /// - The nonce length does not match `aead`
+    /// - The PBKDF2 iteration count is 0, or the salt is shorter than 16 or longer than 255
+    /// bytes
+    /// - The wrapped key is empty or longer than 255 bytes
+    pub fn new(
+        aead: AeadAlgorithm,
+        kdf: KdfParams,
+        nonce: Nonce,
+    ) -> Result<Self, UnknownCryptoError> {
+        if nonce.as_bytes().len() != aead.nonce_len() {
+            return Err(UnknownCryptoError);
+        }
+        if let KdfParams::Pbkdf2Sha512Trunc256 { iterations, ref salt } = kdf {
+            if iterations < 1 || salt.len() < MIN_SALT_LEN || salt.len() > 255 {
+                return Err(UnknownCryptoError);
+            }
+        }
+        if let KdfParams::WrappedKey(ref wrapped_key) = kdf {
+            if wrapped_key.is_empty() || wrapped_key.len() > 255 {
+                return Err(UnknownCryptoError);
+            }
+        }
+
+        Ok(BlobHeader { aead, kdf, nonce })
+    }
+
+    /// Parse the header at the start of `blob`. Returns the header and its length.
+    ///
+    /// # Exceptions:
+    /// An exception will be thrown if:
+    /// - `blob` does not start with `MAGIC`, or has another version
+    /// - The AEAD or KDF is unknown, or its parameters are invalid
+    /// - `blob` is too short to hold the header and a tag
+    pub fn parse(blob: &[u8]) -> Result<(Self, usize), UnknownCryptoError> {
+        if blob.len() < 7 || blob[..4] != MAGIC || blob[4] != VERSION {
+            return Err(UnknownCryptoError);
+        }
+        let aead = AeadAlgorithm::from_id(blob[5])?;
+
+        let mut pos = 7;
+        let kdf = match blob[6] {
+            0x00 => KdfParams::None,
+            0x01 => {
+                if blob.len() < pos + 5 {
+                    return Err(UnknownCryptoError);
+                }
+                let iterations = read_u32_be(&blob[pos..pos + 4]);
+                let salt_len = blob[pos + 4] as usize;
+                pos += 5;
+                if blob.len() < pos + salt_len {
+                    return Err(UnknownCryptoError);
+                }
+                let salt = blob[pos..pos + salt_len].to_vec();
+                pos += salt_len;
+
+                KdfParams::Pbkdf2Sha512Trunc256 { iterations, salt }
+            }
+            0x02 => {
+                if blob.len() < pos + 4 {
+                    return Err(UnknownCryptoError);
+                }
+                pos += 4;
+
+                KdfParams::KeyId(read_u32_be(&blob[pos - 4..pos]))
+            }
+            0x03 => {
+                if blob.len() < pos + 1 {
+                    return Err(UnknownCryptoError);
+                }
+                let wrapped_len = blob[pos] as usize;
+                pos += 1;
+                if blob.len() < pos + wrapped_len {
+                    return Err(UnknownCryptoError);
+                }
+                pos += wrapped_len;
+
+                KdfParams::WrappedKey(blob[pos - wrapped_len..pos].to_vec())
+            }
+            _ => return Err(UnknownCryptoError),
+        };
+
+        if blob.len() < pos + aead.nonce_len() + aead.tag_len() {
+            return Err(UnknownCryptoError);
+        }
+        let nonce = Nonce::from_slice(&blob[pos..pos + aead.nonce_len()])?;
+        pos += aead.nonce_len();
+
+        Ok((BlobHeader::new(aead, kdf, nonce)?, pos))
+    }
+
+    /// Serialize the header.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut header = MAGIC.to_vec();
+        header.push(VERSION);
+        header.push(self.aead.id());
+        match self.kdf {
+            KdfParams::None => header.push(0x00),
+            KdfParams::Pbkdf2Sha512Trunc256 { iterations, ref salt } => {
+                header.extend_from_slice(&[0x01, 0, 0, 0, 0, salt.len() as u8]);
+                let len = header.len();
+                write_u32_be(&mut header[len - 5..len - 1], iterations);
+                header.extend_from_slice(salt);
+            }
+            KdfParams::KeyId(key_id) => {
+                header.extend_from_slice(&[0x02, 0, 0, 0, 0]);
+                let len = header.len();
+                write_u32_be(&mut header[len - 4..], key_id);
+            }
+            KdfParams::WrappedKey(ref wrapped_key) => {
+                header.extend_from_slice(&[0x03, wrapped_key.len() as u8]);
+                header.extend_from_slice(wrapped_key);
+            }
+        }
+        header.extend_from_slice(self.nonce.as_bytes());
+
+        header
+    }
+
+    /// Return the AEAD algorithm.
+    pub fn aead(&self) -> AeadAlgorithm {
+        self.aead
+    }
+
+    /// Return how the key was derived.
+    pub fn kdf(&self) -> &KdfParams {
+        &self.kdf
+    }
+
+    /// Return the nonce.
+    pub fn nonce(&self) -> &Nonce {
+        &self.nonce
+    }
+}
+
+/// Encrypt `plaintext` with the key and nonce of `header`, and return the blob.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the secret key is not valid for the AEAD of `header`
+pub fn seal(
+    secret_key: &SecretKey,
+    header: &BlobHeader,
+    plaintext: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    let mut blob = header.to_bytes();
+    let ciphertext = match header.aead {
+        AeadAlgorithm::XChaCha20Poly1305 => xchacha20poly1305::seal(
+            secret_key.unprotected_as_bytes(),
+            &header.nonce,
+            plaintext,
+            &blob,
+        )?,
+        AeadAlgorithm::XChaCha20Poly1305Committing => {
+            let (key, mut commitment) = commit_key(secret_key, &header.nonce)?;
+            blob.extend_from_slice(&commitment);
+            commitment.zeroize();
+
+            xchacha20poly1305::seal(key.unprotected_as_bytes(), &header.nonce, plaintext, &blob)?
+        }
+    };
+    blob.extend_from_slice(&ciphertext);
+
+    Ok(blob)
+}
+
+/// Verify and decrypt `blob`, whose header was parsed with `BlobHeader::parse`. The
+/// ciphertext starts at `header_len`.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the secret key is not valid for the AEAD of `header`
+/// - The blob fails authentication
+pub fn open(
+    secret_key: &SecretKey,
+    header: &BlobHeader,
+    header_len: usize,
+    blob: &[u8],
+) -> Result<Vec<u8>, ValidationCryptoError> {
+    if blob.len() < header_len {
+        return Err(ValidationCryptoError);
+    }
+    let (ad, ciphertext) = blob.split_at(header_len);
+
+    match header.aead {
+        AeadAlgorithm::XChaCha20Poly1305 => xchacha20poly1305::open(
+            secret_key.unprotected_as_bytes(),
+            &header.nonce,
+            ciphertext,
+            ad,
+        ),
+        AeadAlgorithm::XChaCha20Poly1305Committing => {
+            let key = committed_key(secret_key, header, header_len, blob)?;
+            let (ad, ciphertext) = blob.split_at(header_len + COMMITMENT_LEN);
+
+            xchacha20poly1305::open(key.unprotected_as_bytes(), &header.nonce, ciphertext, ad)
+        }
+    }
+}
+
+/// Check, in constant time and without decrypting it, that `blob` was sealed with
+/// `secret_key`. The header of `blob` was parsed with `BlobHeader::parse`, and its AEAD must
+/// be `AeadAlgorithm::XChaCha20Poly1305Committing`.
+///
+/// This does not authenticate the ciphertext, which `open` still has to do.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The AEAD of `header` does not commit to its key
+/// - The length of the secret key is not 32 bytes
+/// - The blob is not committed to `secret_key`
+pub fn verify_commitment(
+    secret_key: &SecretKey,
+    header: &BlobHeader,
+    header_len: usize,
+    blob: &[u8],
+) -> Result<bool, ValidationCryptoError> {
+    if header.aead != AeadAlgorithm::XChaCha20Poly1305Committing {
+        return Err(ValidationCryptoError);
+    }
+    committed_key(secret_key, header, header_len, blob)?;
+
+    Ok(true)
+}
+
+/// Split HMAC-SHA512 of `"orion key commitment" || nonce` into the XChaCha20-Poly1305 key and
+/// the commitment to `secret_key`.
+fn commit_key(
+    secret_key: &SecretKey,
+    nonce: &Nonce,
+) -> Result<(SecretKey, [u8; COMMITMENT_LEN]), UnknownCryptoError> {
+    if secret_key.unprotected_as_bytes().len() != 32 {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut data = b"orion key commitment".to_vec();
+    data.extend_from_slice(nonce.as_bytes());
+    let mut dk = [0u8; 64];
+    Hmac::new(secret_key, &data, ShaVariantOption::SHA512)?.finalize_into(&mut dk)?;
+
+    let key = SecretKey::from_slice(&dk[..32]);
+    let mut commitment = [0u8; COMMITMENT_LEN];
+    commitment.copy_from_slice(&dk[32..]);
+    dk.zeroize();
+
+    Ok((key?, commitment))
+}
+
+/// Check the commitment that follows the header of `blob`, and return the XChaCha20-Poly1305
+/// key.
+fn committed_key(
+    secret_key: &SecretKey,
+    header: &BlobHeader,
+    header_len: usize,
+    blob: &[u8],
+) -> Result<SecretKey, ValidationCryptoError> {
+    if blob.len() < header_len + COMMITMENT_LEN {
+        return Err(ValidationCryptoError);
+    }
+
+    let (key, mut commitment) = commit_key(secret_key, &header.nonce)?;
+    let valid = util::compare_ct(&commitment, &blob[header_len..header_len + COMMITMENT_LEN]);
+    commitment.zeroize();
+    valid?;
+
+    Ok(key)
+}
+
+#[cfg(test)]
+mod test {
+
+    use core::blob::*;
+
+    fn pbkdf2_header() -> BlobHeader {
+        let kdf = KdfParams::Pbkdf2Sha512Trunc256 {
+            iterations: 512_000,
+            salt: vec![0x61; 32],
+        };
+
+        let nonce = Nonce::from_slice(&[0x62; 24]).unwrap();
+
+        BlobHeader::new(AeadAlgorithm::XChaCha20Poly1305, kdf, nonce).unwrap()
+    }
+
+    #[test]
+    fn header_layout() {
+        let nonce = Nonce::from_slice(&[0x62; 24]).unwrap();
+        let header = BlobHeader::new(AeadAlgorithm::XChaCha20Poly1305, KdfParams::None, nonce)
+            .unwrap();
+        let bytes = header.to_bytes();
+        assert_eq!(bytes[..7], [b'O', b'R', b'N', b'B', 0x01, 0x01, 0x00]);
+        assert_eq!(bytes[7..], [0x62; 24]);
+
+        let nonce = Nonce::from_slice(&[0x62; 24]).unwrap();
+        let header =
+            BlobHeader::new(AeadAlgorithm::XChaCha20Poly1305, KdfParams::KeyId(258), nonce)
+                .unwrap();
+        let bytes = header.to_bytes();
+        assert_eq!(bytes[..11], [b'O', b'R', b'N', b'B', 0x01, 0x01, 0x02, 0, 0, 1, 2]);
+        assert_eq!(bytes[11..], [0x62; 24]);
+
+        let bytes = pbkdf2_header().to_bytes();
+        assert_eq!(bytes[..7], [b'O', b'R', b'N', b'B', 0x01, 0x01, 0x01]);
+        assert_eq!(bytes[7..12], [0x00, 0x07, 0xd0, 0x00, 32]);
+        assert_eq!(bytes[12..44], [0x61; 32]);
+        assert_eq!(bytes[44..], [0x62; 24]);
+    }
+
+    #[test]
+    fn parse_roundtrip() {
+        let header = pbkdf2_header();
+        let mut blob = header.to_bytes();
+        blob.extend_from_slice(&[0u8; 16]);
+
+        let (parsed, len) = BlobHeader::parse(&blob).unwrap();
+        assert_eq!(len, 68);
+        assert_eq!(parsed.aead(), AeadAlgorithm::XChaCha20Poly1305);
+        assert_eq!(parsed.kdf(), header.kdf());
+        assert_eq!(parsed.nonce().as_bytes(), header.nonce().as_bytes());
+        // A tag must follow the header
+        assert!(BlobHeader::parse(&blob[..83]).is_err());
+
+        let nonce = Nonce::from_slice(&[0x62; 24]).unwrap();
+        let header =
+            BlobHeader::new(AeadAlgorithm::XChaCha20Poly1305, KdfParams::KeyId(7), nonce)
+                .unwrap();
+        let mut blob = header.to_bytes();
+        blob.extend_from_slice(&[0u8; 16]);
+        let (parsed, len) = BlobHeader::parse(&blob).unwrap();
+        assert_eq!(len, 35);
+        assert_eq!(*parsed.kdf(), KdfParams::KeyId(7));
+        assert!(BlobHeader::parse(&blob[..50]).is_err());
+
+        let nonce = Nonce::from_slice(&[0x62; 24]).unwrap();
+        let kdf = KdfParams::WrappedKey(vec![0x61; 79]);
+        let header = BlobHeader::new(AeadAlgorithm::XChaCha20Poly1305, kdf, nonce).unwrap();
+        let mut blob = header.to_bytes();
+        assert_eq!(blob[6..8], [0x03, 79]);
+        blob.extend_from_slice(&[0u8; 16]);
+        let (parsed, len) = BlobHeader::parse(&blob).unwrap();
+        assert_eq!(len, 111);
+        assert_eq!(*parsed.kdf(), KdfParams::WrappedKey(vec![0x61; 79]));
+        assert!(BlobHeader::parse(&blob[..126]).is_err());
+        blob[7] = 0;
+        assert!(BlobHeader::parse(&blob).is_err());
+    }
+
+    #[test]
+    fn parse_err() {
+        let mut blob = pbkdf2_header().to_bytes();
+        blob.extend_from_slice(&[0u8; 16]);
+
+        let changes = [(0, b'o'), (4, 0x02), (5, 0x00), (5, 0x03), (6, 0x04), (11, 15)];
+        for &(idx, value) in changes.iter() {
+            let mut bad = blob.clone();
+            bad[idx] = value;
+            assert!(BlobHeader::parse(&bad).is_err());
+        }
+        let mut zero_iterations = blob.clone();
+        zero_iterations[7..11].copy_from_slice(&[0, 0, 0, 0]);
+        assert!(BlobHeader::parse(&zero_iterations).is_err());
+        // A longer salt length makes the blob too short
+        let mut long_salt = blob.clone();
+        long_salt[11] = 255;
+        assert!(BlobHeader::parse(&long_salt).is_err());
+        assert!(BlobHeader::parse(&blob[..6]).is_err());
+    }
+
+    #[test]
+    fn new_err() {
+        let nonce = Nonce::from_slice(&[0u8; 12]).unwrap();
+        assert!(BlobHeader::new(AeadAlgorithm::XChaCha20Poly1305, KdfParams::None, nonce).is_err());
+
+        let nonce = Nonce::from_slice(&[0u8; 24]).unwrap();
+        for &(iterations, salt_len) in [(0, 16), (1, 15), (1, 256)].iter() {
+            let kdf = KdfParams::Pbkdf2Sha512Trunc256 {
+                iterations,
+                salt: vec![0u8; salt_len],
+            };
+            assert!(BlobHeader::new(AeadAlgorithm::XChaCha20Poly1305, kdf, nonce.clone()).is_err());
+        }
+        for &wrapped_len in [0, 256].iter() {
+            let kdf = KdfParams::WrappedKey(vec![0u8; wrapped_len]);
+            assert!(BlobHeader::new(AeadAlgorithm::XChaCha20Poly1305, kdf, nonce.clone()).is_err());
+        }
+    }
+
+    #[test]
+    fn seal_open() {
+        let key = SecretKey::from_slice(&[0x63; 32]).unwrap();
+        let header = pbkdf2_header();
+        let mut blob = seal(&key, &header, b"Secret message").unwrap();
+        assert_eq!(blob.len(), 68 + 14 + 16);
+
+        let (parsed, len) = BlobHeader::parse(&blob).unwrap();
+        assert_eq!(open(&key, &parsed, len, &blob).unwrap(), b"Secret message");
+
+        // The header is authenticated
+        for idx in 0..blob.len() {
+            blob[idx] ^= 1;
+            if let Ok((parsed, len)) = BlobHeader::parse(&blob) {
+                assert!(open(&key, &parsed, len, &blob).is_err());
+            }
+            blob[idx] ^= 1;
+        }
+    }
+    #[test]
+    fn seal_open_committing() {
+        let key = SecretKey::from_slice(&[0x63; 32]).unwrap();
+        let other_key = SecretKey::from_slice(&[0x64; 32]).unwrap();
+        let nonce = Nonce::from_slice(&[0x62; 24]).unwrap();
+        let aead = AeadAlgorithm::XChaCha20Poly1305Committing;
+        let header = BlobHeader::new(aead, KdfParams::None, nonce).unwrap();
+        assert_eq!(header.to_bytes()[5], 0x02);
+
+        let mut blob = seal(&key, &header, b"Secret message").unwrap();
+        assert_eq!(blob.len(), 31 + 32 + 14 + 16);
+
+        let (parsed, len) = BlobHeader::parse(&blob).unwrap();
+        assert_eq!(parsed.aead(), aead);
+        assert_eq!(open(&key, &parsed, len, &blob).unwrap(), b"Secret message");
+        assert!(verify_commitment(&key, &parsed, len, &blob).unwrap());
+        assert!(open(&other_key, &parsed, len, &blob).is_err());
+        assert!(verify_commitment(&other_key, &parsed, len, &blob).is_err());
+        assert!(BlobHeader::parse(&blob[..78]).is_err());
+
+        // The commitment does not depend on the ciphertext
+        let last = blob.len() - 1;
+        blob[last] ^= 1;
+        assert!(verify_commitment(&key, &parsed, len, &blob).unwrap());
+        assert!(open(&key, &parsed, len, &blob).is_err());
+        blob[last] ^= 1;
+        for idx in 0..63 {
+            blob[idx] ^= 1;
+            if let Ok((parsed, len)) = BlobHeader::parse(&blob) {
+                assert!(open(&key, &parsed, len, &blob).is_err());
+            }
+            blob[idx] ^= 1;
+        }
+        blob[40] ^= 1;
+        assert!(verify_commitment(&key, &parsed, len, &blob).is_err());
+        blob[40] ^= 1;
+
+        // Only committing blobs can be checked for a commitment
+        let plain = seal(&key, &pbkdf2_header(), b"").unwrap();
+        let (parsed, len) = BlobHeader::parse(&plain).unwrap();
+        assert!(verify_commitment(&key, &parsed, len, &plain).is_err());
+
+        let short_key = SecretKey::from_slice(&[0x63; 16]).unwrap();
+        assert!(seal(&short_key, &header, b"").is_err());
+    }
+}
@@ -20,11 +20,12 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+#[cfg(feature = "rng")]
 use rand;
 use std::error::Error;
-use std::fmt;
+use std::{fmt, io};
 /// Opaque error.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct UnknownCryptoError;
 
 impl fmt::Display for UnknownCryptoError {
@@ -37,13 +38,10 @@ impl Error for UnknownCryptoError {
     fn description(&self) -> &str {
         "UnknownCryptoError"
     }
-
-    fn cause(&self) -> Option<&Error> {
-        None
-    }
 }
 
 // Required for rand's generators
+#[cfg(feature = "rng")]
 impl From<rand::Error> for UnknownCryptoError {
     fn from(_: rand::Error) -> Self {
         UnknownCryptoError
@@ -51,7 +49,7 @@ impl From<rand::Error> for UnknownCryptoError {
 }
 
 /// Error for a failed verification.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct ValidationCryptoError;
 
 impl fmt::Display for ValidationCryptoError {
@@ -64,8 +62,50 @@ impl Error for ValidationCryptoError {
     fn description(&self) -> &str {
         "ValidationCryptoError"
     }
+}
+
+// Any error encountered while verifying means the verification failed
+impl From<UnknownCryptoError> for ValidationCryptoError {
+    fn from(_: UnknownCryptoError) -> Self {
+        ValidationCryptoError
+    }
+}
+
+// Required for the io::Read and io::Write adapters
+impl From<UnknownCryptoError> for io::Error {
+    fn from(err: UnknownCryptoError) -> Self {
+        io::Error::new(io::ErrorKind::Other, err)
+    }
+}
+
+impl From<ValidationCryptoError> for io::Error {
+    fn from(err: ValidationCryptoError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::errors::*;
+
+    fn verify_with_unknown_err() -> Result<bool, ValidationCryptoError> {
+        Err(UnknownCryptoError)?;
+
+        Ok(true)
+    }
+
+    #[test]
+    fn unknown_to_validation_err() {
+        assert_eq!(verify_with_unknown_err(), Err(ValidationCryptoError));
+    }
+
+    #[test]
+    fn errors_to_io_err() {
+        let unknown: io::Error = UnknownCryptoError.into();
+        let validation: io::Error = ValidationCryptoError.into();
 
-    fn cause(&self) -> Option<&Error> {
-        None
+        assert_eq!(unknown.kind(), io::ErrorKind::Other);
+        assert_eq!(validation.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(validation.to_string(), "ValidationCryptoError");
     }
 }
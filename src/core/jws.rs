@@ -0,0 +1,434 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! JSON Web Signatures ([RFC 7515](https://tools.ietf.org/html/rfc7515)) in compact
+//! serialization, with the HMAC algorithms HS256, HS384 and HS512. Signed JWTs use this
+//! format.
+//!
+//! The payload is signed and returned as bytes, and is not interpreted. Checking the claims
+//! of a JWT, such as its expiry, is left to the caller.
+//!
+//! # Security:
+//! - The algorithm is always chosen by the caller, never by the token. A token with an `alg`
+//! header other than the expected one is rejected, as is one with `"alg":"none"`.
+//! - The protected header may only contain `alg` and `typ`, where `typ` must be `JWT`. Any
+//! other header parameter, such as `crit`, `kid` or `jku`, makes the token invalid.
+//! - The secret key must be at least as long as the output of the hash function, as required
+//! by [RFC 7518](https://tools.ietf.org/html/rfc7518#section-3.2).
+//!
+//! # Example:
+//! ```
+//! use orion::core::jws::{self, JwsAlgorithm};
+//! use orion::core::types::SecretKey;
+//!
+//! let key = SecretKey::from_slice(&[0x61; 32]).unwrap();
+//!
+//! let token = jws::sign(&key, JwsAlgorithm::HS256, b"{\"sub\":\"1234567890\"}").unwrap();
+//! let payload = jws::verify(&key, JwsAlgorithm::HS256, &token).unwrap();
+//! assert_eq!(payload, b"{\"sub\":\"1234567890\"}");
+//!
+//! assert!(jws::verify(&key, JwsAlgorithm::HS512, &token).is_err());
+//! ```
+
+use core::errors::*;
+use core::options::ShaVariantOption;
+use core::types::SecretKey;
+use core::util;
+use hazardous::hmac::Hmac;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// The JWS algorithms based on HMAC.
+pub enum JwsAlgorithm {
+    /// HMAC with SHA256.
+    HS256,
+    /// HMAC with SHA384.
+    HS384,
+    /// HMAC with SHA512.
+    HS512,
+}
+
+impl JwsAlgorithm {
+    /// Return the value of the `alg` header parameter for this algorithm.
+    pub fn name(self) -> &'static str {
+        match self {
+            JwsAlgorithm::HS256 => "HS256",
+            JwsAlgorithm::HS384 => "HS384",
+            JwsAlgorithm::HS512 => "HS512",
+        }
+    }
+
+    fn sha2(self) -> ShaVariantOption {
+        match self {
+            JwsAlgorithm::HS256 => ShaVariantOption::SHA256,
+            JwsAlgorithm::HS384 => ShaVariantOption::SHA384,
+            JwsAlgorithm::HS512 => ShaVariantOption::SHA512,
+        }
+    }
+
+    fn mac(self, secret_key: &SecretKey, signing_input: &str) -> Result<Hmac, UnknownCryptoError> {
+        let sha2 = self.sha2();
+        if secret_key.unprotected_as_bytes().len() < sha2.output_size() {
+            return Err(UnknownCryptoError);
+        }
+
+        Hmac::new(secret_key, signing_input.as_bytes(), sha2)
+    }
+}
+
+/// Sign `payload` and return the token `<header>.<payload>.<signature>`. The header is
+/// `{"alg":"<alg>","typ":"JWT"}`.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The secret key is shorter than the output of the hash function of `alg`
+pub fn sign(
+    secret_key: &SecretKey,
+    alg: JwsAlgorithm,
+    payload: &[u8],
+) -> Result<String, UnknownCryptoError> {
+    let header = format!("{{\"alg\":\"{}\",\"typ\":\"JWT\"}}", alg.name());
+    let signing_input = format!(
+        "{}.{}",
+        util::to_base64url(header.as_bytes()),
+        util::to_base64url(payload)
+    );
+    let tag = alg.mac(secret_key, &signing_input)?.finalize();
+
+    Ok(format!("{}.{}", signing_input, util::to_base64url(tag.as_bytes())))
+}
+
+/// Verify a token signed with `alg` and return its payload. The signature is compared in
+/// constant time, and checked before the header is parsed.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The secret key is shorter than the output of the hash function of `alg`
+/// - The token does not have exactly three parts, or a part is not canonical, unpadded
+/// base64url
+/// - The signature is invalid
+/// - The header is not a JSON object with only the members `alg` and `typ`, where `alg` is
+/// the name of `alg` and `typ`, if present, is `JWT`
+pub fn verify(
+    secret_key: &SecretKey,
+    alg: JwsAlgorithm,
+    token: &str,
+) -> Result<Vec<u8>, ValidationCryptoError> {
+    let parts: Vec<&str> = token.split('.').collect();
+    if parts.len() != 3 {
+        return Err(ValidationCryptoError);
+    }
+
+    let signing_input = &token[..parts[0].len() + 1 + parts[1].len()];
+    let signature = util::from_base64url(parts[2])?;
+    alg.mac(secret_key, signing_input)?.verify(&signature)?;
+
+    let header = util::from_base64url(parts[0])?;
+    let mut alg_seen = false;
+    for (name, value) in parse_header(&header)? {
+        match name.as_str() {
+            "alg" if value == alg.name() => alg_seen = true,
+            "typ" if value == "JWT" => (),
+            _ => return Err(ValidationCryptoError),
+        }
+    }
+    if !alg_seen {
+        return Err(ValidationCryptoError);
+    }
+
+    Ok(util::from_base64url(parts[1])?)
+}
+
+/// Parse a JSON object whose members all have string values. Duplicate member names are
+/// rejected.
+fn parse_header(json: &[u8]) -> Result<Vec<(String, String)>, UnknownCryptoError> {
+    let mut parser = JsonParser { json, pos: 0 };
+    let mut members: Vec<(String, String)> = Vec::new();
+
+    parser.expect(b'{')?;
+    if parser.peek() == Some(b'}') {
+        parser.pos += 1;
+    } else {
+        loop {
+            let name = parser.string()?;
+            parser.expect(b':')?;
+            let value = parser.string()?;
+            if members.iter().any(|&(ref seen, _)| *seen == name) {
+                return Err(UnknownCryptoError);
+            }
+            members.push((name, value));
+
+            match parser.next() {
+                Some(b',') => continue,
+                Some(b'}') => break,
+                _ => return Err(UnknownCryptoError),
+            }
+        }
+    }
+    if parser.peek().is_some() {
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(members)
+}
+
+struct JsonParser<'a> {
+    json: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    /// Skip whitespace and return the next byte without consuming it.
+    fn peek(&mut self) -> Option<u8> {
+        while let Some(&c) = self.json.get(self.pos) {
+            if c != b' ' && c != b'\t' && c != b'\n' && c != b'\r' {
+                return Some(c);
+            }
+            self.pos += 1;
+        }
+
+        None
+    }
+
+    /// Skip whitespace and consume the next byte.
+    fn next(&mut self) -> Option<u8> {
+        let c = self.peek();
+        self.pos += 1;
+
+        c
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), UnknownCryptoError> {
+        if self.next() == Some(expected) {
+            Ok(())
+        } else {
+            Err(UnknownCryptoError)
+        }
+    }
+
+    /// Consume the next byte, without skipping whitespace.
+    fn raw(&mut self) -> Result<u8, UnknownCryptoError> {
+        let c = *self.json.get(self.pos).ok_or(UnknownCryptoError)?;
+        self.pos += 1;
+
+        Ok(c)
+    }
+
+    fn hex4(&mut self) -> Result<u32, UnknownCryptoError> {
+        let mut n = 0u32;
+        for _ in 0..4 {
+            let digit = (self.raw()? as char).to_digit(16).ok_or(UnknownCryptoError)?;
+            n = (n << 4) | digit;
+        }
+
+        Ok(n)
+    }
+
+    /// Parse a string, including escapes. A `\u` escape of a lone surrogate is rejected.
+    fn string(&mut self) -> Result<String, UnknownCryptoError> {
+        self.expect(b'"')?;
+        let mut bytes = Vec::new();
+        loop {
+            match self.raw()? {
+                b'"' => break,
+                b'\\' => {
+                    let c = match self.raw()? {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let mut code = self.hex4()?;
+                            if (0xd800..0xdc00).contains(&code) {
+                                if self.raw()? != b'\\' || self.raw()? != b'u' {
+                                    return Err(UnknownCryptoError);
+                                }
+                                let low = self.hex4()?;
+                                if !(0xdc00..0xe000).contains(&low) {
+                                    return Err(UnknownCryptoError);
+                                }
+                                code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                            }
+                            ::std::char::from_u32(code).ok_or(UnknownCryptoError)?
+                        }
+                        _ => return Err(UnknownCryptoError),
+                    };
+                    let mut buf = [0u8; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                // Control characters must be escaped
+                c if c < 0x20 => return Err(UnknownCryptoError),
+                c => bytes.push(c),
+            }
+        }
+
+        String::from_utf8(bytes).map_err(|_| UnknownCryptoError)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use core::jws::*;
+
+    /// Make a token with the given header, signed with HMAC-SHA256.
+    fn forge(secret_key: &SecretKey, header: &str, payload: &[u8]) -> String {
+        let signing_input = format!(
+            "{}.{}",
+            util::to_base64url(header.as_bytes()),
+            util::to_base64url(payload)
+        );
+        let sha2 = ShaVariantOption::SHA256;
+        let tag = Hmac::new(secret_key, signing_input.as_bytes(), sha2).unwrap();
+
+        format!("{}.{}", signing_input, util::to_base64url(tag.finalize().as_bytes()))
+    }
+
+    #[test]
+    fn rfc7515_appendix_a1() {
+        let jwk_k = "AyM1SysPpbyDfgZld3umj1qzKObwVMkoqQ-EstJQLr_T-1qS0gZH75aKtMN3Yj0iPS4hcgUuTwjAz\
+                     Zr1Z9CAow";
+        let key = SecretKey::from_slice(&util::from_base64url(jwk_k).unwrap()).unwrap();
+        let token = "eyJ0eXAiOiJKV1QiLA0KICJhbGciOiJIUzI1NiJ9.\
+                     eyJpc3MiOiJqb2UiLA0KICJleHAiOjEzMDA4MTkzODAsDQogImh0dHA6Ly9leGFtcGxlLmNvbS9p\
+                     c19yb290Ijp0cnVlfQ.\
+                     dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+
+        let payload = b"{\"iss\":\"joe\",\r\n \"exp\":1300819380,\r\n \
+                        \"http://example.com/is_root\":true}";
+
+        assert_eq!(verify(&key, JwsAlgorithm::HS256, token).unwrap(), &payload[..]);
+        assert!(verify(&key, JwsAlgorithm::HS384, token).is_err());
+        assert!(verify(&key, JwsAlgorithm::HS512, token).is_err());
+    }
+
+    #[test]
+    fn sign_known_tokens() {
+        let key = SecretKey::from_slice(&(0..64).collect::<Vec<u8>>()).unwrap();
+        let payload = b"{\"sub\":\"1234567890\"}";
+        let expected = [
+            (
+                JwsAlgorithm::HS256,
+                "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.eyJzdWIiOiIxMjM0NTY3ODkwIn0.\
+                 Uu1vlYRM-sIYSMDd0wtMy9oNOVsxr9hzvC2C6wFBuRg",
+            ),
+            (
+                JwsAlgorithm::HS384,
+                "eyJhbGciOiJIUzM4NCIsInR5cCI6IkpXVCJ9.eyJzdWIiOiIxMjM0NTY3ODkwIn0.\
+                 tJa0bLmDdl6NlV7F_Qw1L9IJxVIlXVbmdPbr7zU4ms5zPB17Q9mljgtEy5_4q61d",
+            ),
+            (
+                JwsAlgorithm::HS512,
+                "eyJhbGciOiJIUzUxMiIsInR5cCI6IkpXVCJ9.eyJzdWIiOiIxMjM0NTY3ODkwIn0.\
+                 VrJ-hmsHkISNzZ7g219AYvsDCsrfyp2YiHqz4m17AJvuHj6woOA5638HArRW20LkLUDDTQXDRSy3oSqEz\
+                 I4OXA",
+            ),
+        ];
+
+        for &(alg, token) in expected.iter() {
+            assert_eq!(sign(&key, alg, payload).unwrap(), token);
+            assert_eq!(verify(&key, alg, token).unwrap(), &payload[..]);
+        }
+    }
+
+    #[test]
+    fn key_too_short() {
+        let key = SecretKey::from_slice(&[0x61; 47]).unwrap();
+
+        assert!(sign(&key, JwsAlgorithm::HS256, b"").is_ok());
+        assert!(sign(&key, JwsAlgorithm::HS384, b"").is_err());
+        assert!(sign(&key, JwsAlgorithm::HS512, b"").is_err());
+
+        let token = sign(&SecretKey::from_slice(&[0x61; 48]).unwrap(), JwsAlgorithm::HS384, b"")
+            .unwrap();
+        assert!(verify(&key, JwsAlgorithm::HS384, &token).is_err());
+    }
+
+    #[test]
+    fn tampered_token() {
+        let key = SecretKey::from_slice(&[0x61; 32]).unwrap();
+        let token = sign(&key, JwsAlgorithm::HS256, b"payload").unwrap();
+        let other_key = SecretKey::from_slice(&[0x62; 32]).unwrap();
+        assert!(verify(&other_key, JwsAlgorithm::HS256, &token).is_err());
+
+        let parts: Vec<&str> = token.split('.').collect();
+        let tampered = [
+            format!("{}.{}", parts[0], parts[1]),
+            format!("{}.{}.{}.", parts[0], parts[1], parts[2]),
+            format!("{}.{}.{}", parts[0], util::to_base64url(b"Payload"), parts[2]),
+            format!("{}.{}.{}", parts[0], parts[1], &parts[2][..42]),
+            format!("{}.{}.{}=", parts[0], parts[1], parts[2]),
+            format!("{}.{}.", parts[0], parts[1]),
+        ];
+        for token in tampered.iter() {
+            assert!(verify(&key, JwsAlgorithm::HS256, token).is_err(), "{}", token);
+        }
+    }
+
+    #[test]
+    fn strict_header() {
+        let key = SecretKey::from_slice(&[0x61; 32]).unwrap();
+        let valid = [
+            "{\"alg\":\"HS256\"}",
+            "{\"typ\":\"JWT\",\"alg\":\"HS256\"}",
+            " { \"alg\" : \"HS256\" ,\t\"typ\"\r\n:\"JWT\" } ",
+            "{\"alg\":\"HS\\u0032\\u00356\",\"typ\":\"\\u004aWT\"}",
+        ];
+        let invalid = [
+            "",
+            "{}",
+            "[]",
+            "{\"alg\":\"none\"}",
+            "{\"alg\":\"HS512\"}",
+            "{\"alg\":\"hs256\"}",
+            "{\"alg\":\"HS256\",\"alg\":\"HS256\"}",
+            "{\"alg\":\"HS256\",\"typ\":\"jwt\"}",
+            "{\"alg\":\"HS256\",\"typ\":\"JOSE\"}",
+            "{\"alg\":\"HS256\",\"typ\":\"J\\/WT\"}",
+            "{\"alg\":\"HS256\",\"kid\":\"1\"}",
+            "{\"alg\":\"HS256\",\"crit\":[\"exp\"]}",
+            "{\"alg\":\"HS256\",\"b64\":false}",
+            "{\"alg\":\"HS256\",}",
+            "{\"alg\":\"HS256\"}}",
+            "{\"alg\":\"HS256\"",
+            "{\"alg\":\"HS256\"} x",
+            "{alg:\"HS256\"}",
+            "{\"alg\":\"HS256\\\"}",
+            "{\"alg\":\"HS25\\x6\"}",
+            "{\"alg\":\"HS256\",\"typ\":\"\\ud800\"}",
+            "{\"alg\":\"HS256\",\"typ\":\"J\nWT\"}",
+        ];
+
+        for header in valid.iter() {
+            let token = forge(&key, header, b"payload");
+            let payload = verify(&key, JwsAlgorithm::HS256, &token).unwrap();
+            assert_eq!(payload, b"payload", "{}", header);
+        }
+        for header in invalid.iter() {
+            let token = forge(&key, header, b"payload");
+            assert!(verify(&key, JwsAlgorithm::HS256, &token).is_err(), "{}", header);
+        }
+    }
+}
@@ -20,11 +20,26 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+/// The versioned container format written by `default::encrypt`.
+pub mod blob;
+
 /// Errors for orion's cryptographic operations.
 pub mod errors;
 
+/// Parsing and serialization of PHC strings for password hashes.
+pub mod phc;
+
+/// JSON Web Signatures with the HMAC algorithms.
+pub mod jws;
+
+/// Password hashes in the modular crypt format used by passlib.
+pub mod passlib;
+
 /// SHA2/Keccak options and hashing.
 pub mod options;
 
+/// Newtypes for secret keys, passwords, salts, nonces and tags.
+pub mod types;
+
 /// Utility functions such as constant time comparison.
 pub mod util;
@@ -20,79 +20,341 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+use core::errors::UnknownCryptoError;
 use sha2;
 use sha2::Digest;
+use std::fmt;
+use tiny_keccak::Keccak;
 
-#[derive(Clone, Copy)]
-/// SHA2 options and hashing.
+/// A hash function that HMAC, HKDF and PBKDF2 can be used with. It is implemented for
+/// `ShaVariantOption`, and can be implemented for other hash functions, such as BLAKE2.
+pub trait HashFunction: Copy + fmt::Debug {
+    /// Incremental hash state, created with `init`.
+    type State: HashState + Clone;
+
+    /// Return the output size in bytes.
+    fn output_size(self) -> usize;
+
+    /// Return the block size in bytes, as used for the HMAC key padding.
+    fn block_size(self) -> usize;
+
+    /// Return a new incremental hash state.
+    fn init(self) -> Self::State;
+
+    /// Return the digest of a given byte slice.
+    fn hash(self, data: &[u8]) -> Vec<u8> {
+        let mut state = self.init();
+        state.update(data);
+
+        state.finalize()
+    }
+
+    /// Hash many independent messages, writing their digests one after another into `dst`, which
+    /// must have a length of `messages.len() * output_size`. The default implementation hashes
+    /// the messages one at a time, so implementations that can process several buffers in
+    /// parallel, such as SIMD multi-buffer hashing, should override it.
+    fn hash_many(self, messages: &[&[u8]], dst: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        let output_size = self.output_size();
+        if dst.len() != messages.len() * output_size {
+            return Err(UnknownCryptoError);
+        }
+
+        for (message, digest) in messages.iter().zip(dst.chunks_mut(output_size)) {
+            let mut state = self.init();
+            state.update(message);
+            state.finalize_into(digest);
+        }
+
+        Ok(())
+    }
+}
+
+/// Incremental hash state of a `HashFunction`.
+pub trait HashState {
+    /// Add data to be hashed.
+    fn update(&mut self, data: &[u8]);
+
+    /// Return the digest of all data passed to `update`.
+    fn finalize(self) -> Vec<u8>;
+
+    /// Write the digest of all data passed to `update` into `dst`, which must have the length
+    /// of the output size. The default implementation calls `finalize`, so implementations that
+    /// can avoid allocating should override it.
+    fn finalize_into(self, dst: &mut [u8])
+    where
+        Self: Sized,
+    {
+        dst.copy_from_slice(&self.finalize());
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+/// SHA2 and SHA3 options and hashing.
 pub enum ShaVariantOption {
     SHA256,
     SHA384,
     SHA512,
     SHA512Trunc256,
+    SHA3_256,
+    SHA3_512,
 }
 
 impl ShaVariantOption {
-    /// Return the output size in bytes, matching SHA2 variant.
+    /// Return the output size in bytes, matching SHA variant.
     pub fn output_size(self) -> usize {
         match self {
             ShaVariantOption::SHA256 => 32,
             ShaVariantOption::SHA384 => 48,
             ShaVariantOption::SHA512 => 64,
             ShaVariantOption::SHA512Trunc256 => 32,
+            ShaVariantOption::SHA3_256 => 32,
+            ShaVariantOption::SHA3_512 => 64,
         }
     }
 
-    /// Return blocksize in bytes, matching SHA2 variant.
-    pub fn blocksize(self) -> usize {
+    /// Return blocksize in bytes, matching SHA variant. For SHA3 this is the rate of the
+    /// sponge function, as used for HMAC in
+    /// [FIPS 202](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf).
+    pub fn block_size(self) -> usize {
         match self {
             ShaVariantOption::SHA256 => 64,
             ShaVariantOption::SHA384 => 128,
             ShaVariantOption::SHA512 => 128,
             ShaVariantOption::SHA512Trunc256 => 128,
+            ShaVariantOption::SHA3_256 => 136,
+            ShaVariantOption::SHA3_512 => 72,
+        }
+    }
+
+    #[deprecated(note = "use block_size")]
+    /// Return blocksize in bytes, matching SHA variant.
+    pub fn blocksize(self) -> usize {
+        self.block_size()
+    }
+
+    /// Return the name of the SHA variant, as used in FIPS 180-4 and FIPS 202.
+    pub fn name(self) -> &'static str {
+        match self {
+            ShaVariantOption::SHA256 => "SHA-256",
+            ShaVariantOption::SHA384 => "SHA-384",
+            ShaVariantOption::SHA512 => "SHA-512",
+            ShaVariantOption::SHA512Trunc256 => "SHA-512/256",
+            ShaVariantOption::SHA3_256 => "SHA3-256",
+            ShaVariantOption::SHA3_512 => "SHA3-512",
         }
     }
 
-    /// Return a SHA2 digest of a given byte slice.
+    /// Return the object identifier of the SHA variant in dotted notation, as registered by
+    /// [NIST](https://csrc.nist.gov/projects/computer-security-objects-register/algorithm-registration).
+    pub fn oid(self) -> &'static str {
+        match self {
+            ShaVariantOption::SHA256 => "2.16.840.1.101.3.4.2.1",
+            ShaVariantOption::SHA384 => "2.16.840.1.101.3.4.2.2",
+            ShaVariantOption::SHA512 => "2.16.840.1.101.3.4.2.3",
+            ShaVariantOption::SHA512Trunc256 => "2.16.840.1.101.3.4.2.6",
+            ShaVariantOption::SHA3_256 => "2.16.840.1.101.3.4.2.8",
+            ShaVariantOption::SHA3_512 => "2.16.840.1.101.3.4.2.10",
+        }
+    }
+
+    /// Return a SHA2 or SHA3 digest of a given byte slice.
     pub fn hash(self, data: &[u8]) -> Vec<u8> {
         match self {
             ShaVariantOption::SHA256 => {
                 let mut hash = sha2::Sha256::default();
-                hash.input(data);
-                hash.result().to_vec()
+                hash.update(data);
+                hash.finalize().to_vec()
             }
             ShaVariantOption::SHA384 => {
                 let mut hash = sha2::Sha384::default();
-                hash.input(data);
-                hash.result().to_vec()
+                hash.update(data);
+                hash.finalize().to_vec()
             }
             ShaVariantOption::SHA512 => {
                 let mut hash = sha2::Sha512::default();
-                hash.input(data);
-                hash.result().to_vec()
+                hash.update(data);
+                hash.finalize().to_vec()
             }
             ShaVariantOption::SHA512Trunc256 => {
-                let mut hash = sha2::Sha512Trunc256::default();
-                hash.input(data);
-                hash.result().to_vec()
+                let mut hash = sha2::Sha512_256::default();
+                hash.update(data);
+                hash.finalize().to_vec()
+            }
+            ShaVariantOption::SHA3_256 => {
+                let mut digest = vec![0u8; 32];
+                let mut hash = Keccak::new_sha3_256();
+                hash.update(data);
+                hash.finalize(&mut digest);
+                digest
+            }
+            ShaVariantOption::SHA3_512 => {
+                let mut digest = vec![0u8; 64];
+                let mut hash = Keccak::new_sha3_512();
+                hash.update(data);
+                hash.finalize(&mut digest);
+                digest
             }
         }
     }
 }
 
-#[derive(Clone, Copy)]
+impl HashFunction for ShaVariantOption {
+    type State = ShaState;
+
+    fn output_size(self) -> usize {
+        ShaVariantOption::output_size(self)
+    }
+
+    fn block_size(self) -> usize {
+        ShaVariantOption::block_size(self)
+    }
+
+    fn init(self) -> ShaState {
+        ShaState(match self {
+            ShaVariantOption::SHA256 => ShaStateInner::SHA256(sha2::Sha256::default()),
+            ShaVariantOption::SHA384 => ShaStateInner::SHA384(sha2::Sha384::default()),
+            ShaVariantOption::SHA512 => ShaStateInner::SHA512(sha2::Sha512::default()),
+            ShaVariantOption::SHA512Trunc256 => {
+                ShaStateInner::SHA512Trunc256(sha2::Sha512_256::default())
+            }
+            ShaVariantOption::SHA3_256 => ShaStateInner::SHA3(Keccak::new_sha3_256(), 32),
+            ShaVariantOption::SHA3_512 => ShaStateInner::SHA3(Keccak::new_sha3_512(), 64),
+        })
+    }
+
+    fn hash(self, data: &[u8]) -> Vec<u8> {
+        ShaVariantOption::hash(self, data)
+    }
+}
+
+/// Incremental hash state of a `ShaVariantOption`, created with `HashFunction::init`.
+#[derive(Clone)]
+pub struct ShaState(ShaStateInner);
+
+#[derive(Clone)]
+enum ShaStateInner {
+    SHA256(sha2::Sha256),
+    SHA384(sha2::Sha384),
+    SHA512(sha2::Sha512),
+    SHA512Trunc256(sha2::Sha512_256),
+    SHA3(Keccak, usize),
+}
+
+impl fmt::Debug for ShaState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ShaState {{ state: *** }}")
+    }
+}
+
+impl HashState for ShaState {
+    fn update(&mut self, data: &[u8]) {
+        match self.0 {
+            ShaStateInner::SHA256(ref mut hash) => hash.update(data),
+            ShaStateInner::SHA384(ref mut hash) => hash.update(data),
+            ShaStateInner::SHA512(ref mut hash) => hash.update(data),
+            ShaStateInner::SHA512Trunc256(ref mut hash) => hash.update(data),
+            ShaStateInner::SHA3(ref mut hash, _) => hash.update(data),
+        }
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        match self.0 {
+            ShaStateInner::SHA256(hash) => hash.finalize().to_vec(),
+            ShaStateInner::SHA384(hash) => hash.finalize().to_vec(),
+            ShaStateInner::SHA512(hash) => hash.finalize().to_vec(),
+            ShaStateInner::SHA512Trunc256(hash) => hash.finalize().to_vec(),
+            ShaStateInner::SHA3(hash, output_size) => {
+                let mut digest = vec![0u8; output_size];
+                hash.finalize(&mut digest);
+                digest
+            }
+        }
+    }
+
+    fn finalize_into(self, dst: &mut [u8]) {
+        match self.0 {
+            ShaStateInner::SHA256(hash) => dst.copy_from_slice(&hash.finalize()),
+            ShaStateInner::SHA384(hash) => dst.copy_from_slice(&hash.finalize()),
+            ShaStateInner::SHA512(hash) => dst.copy_from_slice(&hash.finalize()),
+            ShaStateInner::SHA512Trunc256(hash) => dst.copy_from_slice(&hash.finalize()),
+            ShaStateInner::SHA3(hash, output_size) => {
+                assert_eq!(dst.len(), output_size);
+                hash.finalize(dst)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
 /// Keccak options.
 pub enum KeccakVariantOption {
     KECCAK256,
     KECCAK512,
 }
 
+impl KeccakVariantOption {
+    /// Return the rate in bytes of the respective Keccak sponge function.
+    pub fn block_size(self) -> usize {
+        match self {
+            KeccakVariantOption::KECCAK256 => 168,
+            KeccakVariantOption::KECCAK512 => 136,
+        }
+    }
+
+    /// Return the name of the cSHAKE function using this Keccak variant, as used in
+    /// [NIST SP 800-185](https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-185.pdf).
+    /// cSHAKE has no registered object identifier.
+    pub fn name(self) -> &'static str {
+        match self {
+            KeccakVariantOption::KECCAK256 => "cSHAKE128",
+            KeccakVariantOption::KECCAK512 => "cSHAKE256",
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use core::options::ShaVariantOption;
+    use core::options::{HashFunction, KeccakVariantOption, ShaVariantOption};
     extern crate hex;
     use self::hex::decode;
 
+    #[test]
+    fn hash_many_equals_hash() {
+        let messages: [&[u8]; 3] = [b"", b"abc", &[0x61; 200]];
+
+        for sha2 in [ShaVariantOption::SHA256, ShaVariantOption::SHA3_512].iter() {
+            let mut digests = vec![0u8; messages.len() * sha2.output_size()];
+            HashFunction::hash_many(*sha2, &messages, &mut digests).unwrap();
+
+            for (message, digest) in messages.iter().zip(digests.chunks(sha2.output_size())) {
+                assert_eq!(digest, &sha2.hash(message)[..]);
+            }
+            assert!(HashFunction::hash_many(*sha2, &messages, &mut digests[1..]).is_err());
+        }
+    }
+
+    #[test]
+    fn sha_metadata() {
+        let sha512_256 = ShaVariantOption::SHA512Trunc256;
+        let sha3_256 = ShaVariantOption::SHA3_256;
+
+        assert_eq!(sha512_256.output_size(), 32);
+        assert_eq!(sha512_256.block_size(), 128);
+        assert_eq!(sha512_256.name(), "SHA-512/256");
+        assert_eq!(sha512_256.oid(), "2.16.840.1.101.3.4.2.6");
+        assert_eq!(sha3_256.block_size(), 136);
+        assert_eq!(sha3_256.name(), "SHA3-256");
+        assert_eq!(sha3_256.oid(), "2.16.840.1.101.3.4.2.8");
+    }
+
+    #[test]
+    fn keccak_metadata() {
+        assert_eq!(KeccakVariantOption::KECCAK256.block_size(), 168);
+        assert_eq!(KeccakVariantOption::KECCAK512.block_size(), 136);
+        assert_eq!(KeccakVariantOption::KECCAK256.name(), "cSHAKE128");
+        assert_eq!(KeccakVariantOption::KECCAK512.name(), "cSHAKE256");
+    }
+
     // These test cases are some picks from
     // the [NIST SHAVS](https://csrc.nist.gov/projects/cryptographic-algorithm-validation-program/secure-hashing#shavs)
     #[test]
@@ -141,4 +403,25 @@ mod test {
 
         assert_eq!(expected_md, actual_md);
     }
+
+    // Test vectors from the NIST [SHA3 examples](https://csrc.nist.gov/projects/cryptographic-standards-and-guidelines/example-values)
+    #[test]
+    fn sha3_256_abc() {
+        let expected_md =
+            decode("3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532").unwrap();
+        let actual_md = ShaVariantOption::SHA3_256.hash("abc".as_bytes());
+
+        assert_eq!(expected_md, actual_md);
+    }
+
+    #[test]
+    fn sha3_512_abc() {
+        let expected_md = decode(
+            "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e\
+             10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0",
+        ).unwrap();
+        let actual_md = ShaVariantOption::SHA3_512.hash("abc".as_bytes());
+
+        assert_eq!(expected_md, actual_md);
+    }
 }
@@ -0,0 +1,218 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! PBKDF2 password hashes in the modular crypt format used by passlib, and by Django-style
+//! systems built on it: `$pbkdf2-sha256$<rounds>$<salt>$<hash>`.
+//!
+//! The salt and hash are encoded with passlib's "adapted base64", which is base64 without
+//! padding and with `.` instead of `+`. The hash is always as long as the output of the
+//! chosen hash function.
+//!
+//! This lets existing user databases be verified with orion. To move them over to
+//! `default::password_hash`, re-hash the password after it has been verified with
+//! `passlib::pbkdf2_verify`.
+//!
+//! # Example:
+//! ```
+//! use orion::core::options::ShaVariantOption;
+//! use orion::core::passlib;
+//! use orion::core::types::Password;
+//!
+//! let password = Password::from_slice(b"password").unwrap();
+//! let hash = "$pbkdf2-sha256$1212$4vjV83LKPjQzk31VI4E0Vw\
+//!             $hsYF68OiOUPdDZ1Fg.fJPeq1h/gXXY7acBp9/6c.tmQ";
+//!
+//! assert!(passlib::pbkdf2_verify(hash, &password).unwrap());
+//! ```
+
+use core::errors::*;
+use core::options::ShaVariantOption;
+use core::phc;
+use core::types::Password;
+use core::util;
+use hazardous::pbkdf2::Pbkdf2;
+
+/// The maximum salt length accepted by passlib.
+const MAX_SALT_LEN: usize = 1024;
+
+/// Return the passlib identifier for `sha2`. Only SHA256 and SHA512 are supported.
+fn scheme_id(sha2: ShaVariantOption) -> Result<&'static str, UnknownCryptoError> {
+    match sha2 {
+        ShaVariantOption::SHA256 => Ok("pbkdf2-sha256"),
+        ShaVariantOption::SHA512 => Ok("pbkdf2-sha512"),
+        _ => Err(UnknownCryptoError),
+    }
+}
+
+fn derive(
+    password: &Password,
+    salt: &[u8],
+    rounds: usize,
+    sha2: ShaVariantOption,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    if salt.len() > MAX_SALT_LEN || rounds > u32::max_value() as usize {
+        return Err(UnknownCryptoError);
+    }
+
+    Pbkdf2::new(password, salt, rounds, sha2.output_size(), sha2)?.derive_key()
+}
+
+/// Hash a password with PBKDF2 and encode it as `$pbkdf2-<sha2>$<rounds>$<salt>$<hash>`.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `sha2` is not `SHA256` or `SHA512`
+/// - `rounds` is less than 1 or does not fit in a `u32`
+/// - The length of `salt` is greater than 1024
+///
+/// # Security:
+/// passlib generates a salt of 16 bytes, and a salt of at least this length should be used.
+pub fn pbkdf2_encode(
+    password: &Password,
+    salt: &[u8],
+    rounds: usize,
+    sha2: ShaVariantOption,
+) -> Result<String, UnknownCryptoError> {
+    let id = scheme_id(sha2)?;
+    let hash = derive(password, salt, rounds, sha2)?;
+
+    Ok(format!(
+        "${}${}${}${}",
+        id,
+        rounds,
+        util::to_ab64(salt),
+        util::to_ab64(&hash)
+    ))
+}
+
+/// Verify a password against a `$pbkdf2-sha256$` or `$pbkdf2-sha512$` hash, in constant time.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The hash is malformed or uses another algorithm
+/// - The rounds are not a decimal without sign or leading zeros
+/// - The hash is not as long as the output of the hash function
+/// - The password does not match
+pub fn pbkdf2_verify(hash: &str, password: &Password) -> Result<bool, ValidationCryptoError> {
+    let parts: Vec<&str> = hash.split('$').collect();
+    if parts.len() != 5 || !parts[0].is_empty() {
+        return Err(ValidationCryptoError);
+    }
+
+    let sha2 = if parts[1] == scheme_id(ShaVariantOption::SHA256)? {
+        ShaVariantOption::SHA256
+    } else if parts[1] == scheme_id(ShaVariantOption::SHA512)? {
+        ShaVariantOption::SHA512
+    } else {
+        return Err(ValidationCryptoError);
+    };
+
+    let rounds = phc::parse_decimal(parts[2])? as usize;
+    let salt = util::from_ab64(parts[3])?;
+    let expected = util::from_ab64(parts[4])?;
+    if expected.len() != sha2.output_size() {
+        return Err(ValidationCryptoError);
+    }
+
+    let computed = derive(password, &salt, rounds, sha2)?;
+    util::compare_ct(&computed, &expected)?;
+
+    Ok(true)
+}
+
+#[test]
+fn test_passlib_vectors() {
+    // From the passlib test suite
+    let password = Password::from_slice(b"password").unwrap();
+    let sha256 = "$pbkdf2-sha256$1212$4vjV83LKPjQzk31VI4E0Vw\
+                  $hsYF68OiOUPdDZ1Fg.fJPeq1h/gXXY7acBp9/6c.tmQ";
+    let sha512 = "$pbkdf2-sha512$1212$RHY0Fr3IDMSVO/RSZyb5ow\
+                  $eNLfBK.eVozomMr.1gYa17k9B7KIK25NOEshvhrSX.esqY3s.FvWZViXz4KoLlQI.BzY/\
+                  YTNJOiKc5gBYFYGww";
+
+    assert!(pbkdf2_verify(sha256, &password).unwrap());
+    assert!(pbkdf2_verify(sha512, &password).unwrap());
+
+    let wrong = Password::from_slice(b"Password").unwrap();
+    assert!(pbkdf2_verify(sha256, &wrong).is_err());
+    assert!(pbkdf2_verify(sha512, &wrong).is_err());
+}
+
+#[test]
+fn test_encode() {
+    let password = Password::from_slice(b"Secret password").unwrap();
+    let salt: Vec<u8> = (0..16).collect();
+
+    assert_eq!(
+        pbkdf2_encode(&password, &salt, 1000, ShaVariantOption::SHA256).unwrap(),
+        "$pbkdf2-sha256$1000$AAECAwQFBgcICQoLDA0ODw$VmPDVNir4Jz2e/xOyPmvS/WJ3IhYU.lyhYf2PUCN/mI"
+    );
+    let sha512 = pbkdf2_encode(&password, &salt, 1000, ShaVariantOption::SHA512).unwrap();
+    assert_eq!(
+        sha512,
+        "$pbkdf2-sha512$1000$AAECAwQFBgcICQoLDA0ODw$Fgen1PhC0nIYpoYOAUj5pXI4kUn0nLkDRl.SteVcDoij55Z\
+         lSTAw98z/a8OkN511N1AltSqcn81pKl16REJN1A"
+    );
+    assert!(pbkdf2_verify(&sha512, &password).unwrap());
+    // An empty salt is allowed by passlib
+    let empty = pbkdf2_encode(&password, b"", 1, ShaVariantOption::SHA256).unwrap();
+    assert!(pbkdf2_verify(&empty, &password).unwrap());
+}
+
+#[test]
+fn test_encode_err() {
+    let password = Password::from_slice(b"Secret password").unwrap();
+
+    assert!(pbkdf2_encode(&password, b"salt", 1000, ShaVariantOption::SHA384).is_err());
+    assert!(pbkdf2_encode(&password, b"salt", 1000, ShaVariantOption::SHA3_256).is_err());
+    assert!(pbkdf2_encode(&password, b"salt", 0, ShaVariantOption::SHA256).is_err());
+    assert!(pbkdf2_encode(&password, &[0u8; 1025], 1, ShaVariantOption::SHA256).is_err());
+    assert!(pbkdf2_encode(&password, &[0u8; 1024], 1, ShaVariantOption::SHA256).is_ok());
+}
+
+#[test]
+fn test_verify_malformed() {
+    let password = Password::from_slice(b"Secret password").unwrap();
+    let salt = "AAECAwQFBgcICQoLDA0ODw";
+    let hash = "VmPDVNir4Jz2e/xOyPmvS/WJ3IhYU.lyhYf2PUCN/mI";
+    assert!(pbkdf2_verify(&format!("$pbkdf2-sha256$1000${}${}", salt, hash), &password).unwrap());
+
+    let malformed = [
+        format!("pbkdf2-sha256$1000${}${}", salt, hash),
+        format!("$pbkdf2-sha512$1000${}${}", salt, hash),
+        format!("$pbkdf2-sha384$1000${}${}", salt, hash),
+        format!("$pbkdf2$1000${}${}", salt, hash),
+        format!("$pbkdf2-sha256$0${}${}", salt, hash),
+        format!("$pbkdf2-sha256$01000${}${}", salt, hash),
+        format!("$pbkdf2-sha256$+1000${}${}", salt, hash),
+        format!("$pbkdf2-sha256$i=1000${}${}", salt, hash),
+        format!("$pbkdf2-sha256$1000${}${}$", salt, hash),
+        format!("$pbkdf2-sha256$1000${}", salt),
+        format!("$pbkdf2-sha256$1000${}==${}", salt, hash),
+        format!("$pbkdf2-sha256$1000${}${}", salt, hash.replace('.', "+")),
+        format!("$pbkdf2-sha256$1000${}${}", salt, &hash[..42]),
+    ];
+
+    for hash in malformed.iter() {
+        assert!(pbkdf2_verify(hash, &password).is_err(), "{}", hash);
+    }
+}
@@ -0,0 +1,444 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Serialization and parsing of the
+//! [PHC string format](https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md)
+//! for password hashes.
+
+use core::errors::*;
+use core::util;
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use zeroize::Zeroize;
+
+/// The maximum length of an identifier or parameter name.
+const MAX_NAME_LEN: usize = 32;
+
+/// A parsed PHC string of the form
+/// `$<id>[$v=<version>][$<param>=<value>(,<param>=<value>)*][$<salt>[$<hash>]]`.
+///
+/// The salt and hash are binary, and encoded as base64 without padding. This is what
+/// `default::password_hash` produces, and what Argon2 and most other password hashing
+/// functions use.
+///
+/// Fields `salt` and `hash` are zeroed out on drop.
+///
+/// # Exceptions:
+/// An exception will be thrown by `new` and `parse` if:
+/// - The identifier or a parameter name is empty, longer than 32 characters or contains
+/// characters other than `a-z`, `0-9` and `-`
+/// - A parameter value is empty or contains characters other than `a-z`, `A-Z`, `0-9`, `/`,
+/// `+`, `.` and `-`
+/// - A parameter name is used more than once, or is `v`
+/// - The version is not a decimal without leading zeros
+/// - The salt or hash is not canonical, unpadded base64, or a hash is given without a salt
+///
+/// # Security:
+/// Use `verify_hash` to compare a computed hash with the one in the string, so that the
+/// comparison is done in constant time.
+///
+/// # Example:
+/// ```
+/// use orion::core::phc::PhcString;
+///
+/// let phc = PhcString::new("pbkdf2-sha256", None, &[("i", "100000")], b"Some salt", b"hash")
+///     .unwrap();
+/// let parsed = PhcString::parse(&phc.to_string()).unwrap();
+///
+/// assert_eq!(parsed.id(), "pbkdf2-sha256");
+/// assert_eq!(parsed.param_decimal("i").unwrap(), 100000);
+/// assert_eq!(parsed.salt(), Some(&b"Some salt"[..]));
+/// assert!(parsed.verify_hash(b"hash").is_ok());
+/// ```
+pub struct PhcString {
+    id: String,
+    version: Option<u32>,
+    params: Vec<(String, String)>,
+    salt: Option<Vec<u8>>,
+    hash: Option<Vec<u8>>,
+}
+
+impl Drop for PhcString {
+    fn drop(&mut self) {
+        if let Some(ref mut salt) = self.salt {
+            salt.zeroize();
+        }
+        if let Some(ref mut hash) = self.hash {
+            hash.zeroize();
+        }
+    }
+}
+
+impl fmt::Debug for PhcString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "PhcString {{ id: {:?}, version: {:?}, params: {:?}, salt: ***, hash: *** }}",
+            self.id, self.version, self.params
+        )
+    }
+}
+
+impl fmt::Display for PhcString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "${}", self.id)?;
+        if let Some(version) = self.version {
+            write!(f, "$v={}", version)?;
+        }
+        for (idx, &(ref name, ref value)) in self.params.iter().enumerate() {
+            let separator = if idx == 0 { '$' } else { ',' };
+            write!(f, "{}{}={}", separator, name, value)?;
+        }
+        if let Some(ref salt) = self.salt {
+            write!(f, "${}", util::to_base64(salt))?;
+        }
+        if let Some(ref hash) = self.hash {
+            write!(f, "${}", util::to_base64(hash))?;
+        }
+
+        Ok(())
+    }
+}
+
+// A PHC string is serialized as its string form, and validated with `parse` when deserialized.
+#[cfg(feature = "serde")]
+impl Serialize for PhcString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for PhcString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let phc = String::deserialize(deserializer)?;
+
+        PhcString::parse(&phc).map_err(de::Error::custom)
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .bytes()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == b'-')
+}
+
+fn is_valid_value(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || c == b'/' || c == b'+' || c == b'.' || c == b'-')
+}
+
+/// Parse a decimal without sign or leading zeros, which fits in a `u32`. This is how numeric
+/// parameters are written in PHC strings and other password hash formats.
+pub fn parse_decimal(value: &str) -> Result<u32, UnknownCryptoError> {
+    if value.is_empty() || (value.len() > 1 && value.starts_with('0')) {
+        return Err(UnknownCryptoError);
+    }
+    if !value.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(UnknownCryptoError);
+    }
+
+    value.parse::<u32>().map_err(|_| UnknownCryptoError)
+}
+
+impl PhcString {
+    /// Make a PHC string from its parts, which are validated the same way as with `parse`. An
+    /// empty `salt` or `hash` is left out.
+    pub fn new(
+        id: &str,
+        version: Option<u32>,
+        params: &[(&str, &str)],
+        salt: &[u8],
+        hash: &[u8],
+    ) -> Result<Self, UnknownCryptoError> {
+        if !is_valid_name(id) || (salt.is_empty() && !hash.is_empty()) {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut phc = PhcString {
+            id: id.to_string(),
+            version,
+            params: Vec::with_capacity(params.len()),
+            salt: None,
+            hash: None,
+        };
+        for &(name, value) in params.iter() {
+            phc.push_param(name, value)?;
+        }
+        if !salt.is_empty() {
+            phc.salt = Some(salt.to_vec());
+        }
+        if !hash.is_empty() {
+            phc.hash = Some(hash.to_vec());
+        }
+
+        Ok(phc)
+    }
+
+    fn push_param(&mut self, name: &str, value: &str) -> Result<(), UnknownCryptoError> {
+        let duplicate = self.param(name).is_some();
+        if !is_valid_name(name) || name == "v" || !is_valid_value(value) || duplicate {
+            return Err(UnknownCryptoError);
+        }
+        self.params.push((name.to_string(), value.to_string()));
+
+        Ok(())
+    }
+
+    /// Parse and validate a PHC string.
+    pub fn parse(phc: &str) -> Result<Self, UnknownCryptoError> {
+        let mut fields = phc.split('$');
+        // The string must start with `$`, so the first field is empty
+        if fields.next() != Some("") {
+            return Err(UnknownCryptoError);
+        }
+
+        let id = fields.next().unwrap_or("");
+        if !is_valid_name(id) {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut phc = PhcString {
+            id: id.to_string(),
+            version: None,
+            params: Vec::new(),
+            salt: None,
+            hash: None,
+        };
+
+        let mut fields = fields.peekable();
+        if let Some(version) = fields.peek().and_then(|field| field.strip_prefix("v=")) {
+            phc.version = Some(parse_decimal(version)?);
+            fields.next();
+        }
+        if fields.peek().map_or(false, |field| field.contains('=')) {
+            for param in fields.next().unwrap().split(',') {
+                let mut pair = param.splitn(2, '=');
+                let name = pair.next().unwrap();
+                let value = pair.next().ok_or(UnknownCryptoError)?;
+                phc.push_param(name, value)?;
+            }
+        }
+        if let Some(salt) = fields.next() {
+            phc.salt = Some(util::from_base64(salt)?);
+        }
+        if let Some(hash) = fields.next() {
+            phc.hash = Some(util::from_base64(hash)?);
+        }
+        // Empty salt or hash fields are not allowed, nor are any fields after the hash
+        let empty_salt = phc.salt.as_ref().map_or(false, |salt| salt.is_empty());
+        let empty_hash = phc.hash.as_ref().map_or(false, |hash| hash.is_empty());
+        if empty_salt || empty_hash || fields.next().is_some() {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(phc)
+    }
+
+    /// Return the identifier of the algorithm.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Return the version of the algorithm, if the string has one.
+    pub fn version(&self) -> Option<u32> {
+        self.version
+    }
+
+    /// Return all parameters as name-value pairs, in the order they appear in the string.
+    pub fn params(&self) -> &[(String, String)] {
+        &self.params
+    }
+
+    /// Return the value of the parameter `name`, if the string has it.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|&&(ref param, _)| param == name)
+            .map(|&(_, ref value)| value.as_str())
+    }
+
+    /// Return the value of the parameter `name` as a decimal. An exception will be thrown if the
+    /// parameter is missing, or is not a decimal without sign or leading zeros that fits in a
+    /// `u32`.
+    pub fn param_decimal(&self, name: &str) -> Result<usize, UnknownCryptoError> {
+        let value = self.param(name).ok_or(UnknownCryptoError)?;
+
+        Ok(parse_decimal(value)? as usize)
+    }
+
+    /// Return the decoded salt, if the string has one.
+    pub fn salt(&self) -> Option<&[u8]> {
+        self.salt.as_ref().map(|salt| salt.as_slice())
+    }
+
+    /// Return the decoded hash, if the string has one.
+    pub fn hash(&self) -> Option<&[u8]> {
+        self.hash.as_ref().map(|hash| hash.as_slice())
+    }
+
+    /// Compare `computed` with the hash in the string, in constant time. An exception will be
+    /// thrown if the string has no hash, or if the hashes differ in length or content.
+    pub fn verify_hash(&self, computed: &[u8]) -> Result<bool, ValidationCryptoError> {
+        match self.hash {
+            Some(ref hash) if util::compare_ct(computed, hash).is_ok() => Ok(true),
+            _ => Err(ValidationCryptoError),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use core::phc::*;
+
+    #[test]
+    fn parse_argon2() {
+        let phc = PhcString::parse(
+            "$argon2id$v=19$m=65536,t=2,p=1$c29tZXNhbHQ$RdescudvJCsgt3ub+b+dWRWJTmaaJObG",
+        ).unwrap();
+
+        assert_eq!(phc.id(), "argon2id");
+        assert_eq!(phc.version(), Some(19));
+        assert_eq!(phc.param_decimal("m").unwrap(), 65536);
+        assert_eq!(phc.param("t"), Some("2"));
+        assert_eq!(phc.param("p"), Some("1"));
+        assert_eq!(phc.param("x"), None);
+        assert_eq!(phc.salt(), Some(&b"somesalt"[..]));
+        assert_eq!(phc.hash().unwrap().len(), 24);
+    }
+
+    #[test]
+    fn roundtrip() {
+        let strings = [
+            "$pbkdf2-sha512-256",
+            "$argon2i$v=19",
+            "$pbkdf2-sha256$i=1000",
+            "$pbkdf2-sha256$c29tZXNhbHQ",
+            "$argon2id$v=19$m=65536,t=2,p=1$c29tZXNhbHQ$RdescudvJCsgt3ub+b+dWRWJTmaaJObG",
+            "$scrypt$ln=15,r=8,p=1,data=a/b+c.d-e$c29tZXNhbHQ$aGFzaA",
+        ];
+
+        for phc in strings.iter() {
+            assert_eq!(PhcString::parse(phc).unwrap().to_string(), *phc);
+        }
+    }
+
+    #[test]
+    fn new_equals_parse() {
+        let phc = PhcString::new("argon2id", Some(19), &[("m", "65536"), ("t", "2")], b"salt", b"h")
+            .unwrap();
+
+        assert_eq!(phc.to_string(), "$argon2id$v=19$m=65536,t=2$c2FsdA$aA");
+        assert!(PhcString::new("argon2id", None, &[], b"", b"hash").is_err());
+        assert!(PhcString::new("Argon2id", None, &[], b"salt", b"hash").is_err());
+        assert!(PhcString::new("argon2id", None, &[("m", "")], b"salt", b"hash").is_err());
+        assert!(PhcString::new("argon2id", None, &[("m", "1"), ("m", "2")], b"", b"").is_err());
+        assert!(PhcString::new("argon2id", None, &[("v", "19")], b"", b"").is_err());
+    }
+
+    #[test]
+    fn parse_rejects_malformed() {
+        let malformed = [
+            "",
+            "$",
+            "argon2id$v=19",
+            "$argon2id$",
+            "$Argon2id",
+            "$argon2_id",
+            "$abcdefghijklmnopqrstuvwxyz0123456",
+            "$argon2id$v=019",
+            "$argon2id$v=+19",
+            "$argon2id$v=",
+            "$argon2id$v=19$v=19",
+            "$argon2id$m=1,m=2",
+            "$argon2id$m=1,,t=2",
+            "$argon2id$m=1,t",
+            "$argon2id$m=1,t=",
+            "$argon2id$m=1,T=2",
+            "$argon2id$m=1,t=a_b",
+            "$argon2id$m=1$c29tZXNhbHQ=",
+            "$argon2id$m=1$c29tZXNhbHQ$",
+            "$argon2id$m=1$$aGFzaA",
+            "$argon2id$m=1$c29tZXNhbHQ$aGFzaA$",
+            "$argon2id$m=1$c29tZXNhbHQ$aGFzaA$aGFzaA",
+            "$argon2id$m=1$c29tZXNhbHR$aGFzaA",
+        ];
+
+        for phc in malformed.iter() {
+            assert!(PhcString::parse(phc).is_err(), "{}", phc);
+        }
+    }
+
+    #[test]
+    fn param_decimal_is_strict() {
+        let phc = PhcString::parse("$pbkdf2$a=0,b=01,c=4294967295,d=4294967296,e=1e3").unwrap();
+
+        assert_eq!(phc.param_decimal("a").unwrap(), 0);
+        assert!(phc.param_decimal("b").is_err());
+        assert_eq!(phc.param_decimal("c").unwrap(), 4_294_967_295);
+        assert!(phc.param_decimal("d").is_err());
+        assert!(phc.param_decimal("e").is_err());
+        assert!(phc.param_decimal("f").is_err());
+    }
+
+    #[test]
+    fn verify_hash() {
+        let phc = PhcString::parse("$pbkdf2$i=1$c29tZXNhbHQ$aGFzaA").unwrap();
+
+        assert!(phc.verify_hash(b"hash").unwrap());
+        assert!(phc.verify_hash(b"hasH").is_err());
+        assert!(phc.verify_hash(b"hash2").is_err());
+        assert!(PhcString::parse("$pbkdf2$i=1").unwrap().verify_hash(b"").is_err());
+    }
+
+    #[test]
+    fn debug_is_redacted() {
+        let phc = PhcString::parse("$pbkdf2$i=1$c29tZXNhbHQ$aGFzaA").unwrap();
+
+        assert_eq!(
+            format!("{:?}", phc),
+            "PhcString { id: \"pbkdf2\", version: None, params: [(\"i\", \"1\")], salt: ***, \
+             hash: *** }"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_roundtrip() {
+        extern crate serde_json;
+
+        let phc_str = "$pbkdf2-sha256$i=100000$c29tZXNhbHQ$aGFzaA";
+        let phc = PhcString::parse(phc_str).unwrap();
+
+        let json = serde_json::to_string(&phc).unwrap();
+        assert_eq!(json, format!("\"{}\"", phc_str));
+        assert_eq!(serde_json::from_str::<PhcString>(&json).unwrap().to_string(), phc_str);
+        assert!(serde_json::from_str::<PhcString>("\"pbkdf2-sha256\"").is_err());
+        assert!(serde_json::from_str::<PhcString>("[1, 2, 3]").is_err());
+    }
+}
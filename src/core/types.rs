@@ -0,0 +1,949 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+#[cfg(feature = "rng")]
+use core::util::OsRandom;
+use core::util::SecureRandom;
+use core::{errors::*, util};
+use std::convert::TryFrom;
+use std::fmt;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+// Comparison with `==` is done in constant time, using `util::compare_ct`. Values of different
+// lengths are never equal.
+macro_rules! impl_ct_eq {
+    ($name:ident) => {
+        impl_ct_eq!($name, as_bytes);
+    };
+    ($name:ident, $bytes:ident) => {
+        impl PartialEq for $name {
+            fn eq(&self, other: &$name) -> bool {
+                util::compare_ct(self.$bytes(), other.$bytes()).is_ok()
+            }
+        }
+
+        impl Eq for $name {}
+    };
+}
+
+// Hex and `TryFrom<&[u8]>` conversions, which validate the length the same way as `from_slice`
+macro_rules! impl_conversions {
+    ($name:ident) => {
+        impl_conversions!($name, as_bytes);
+    };
+    ($name:ident, $bytes:ident) => {
+        impl $name {
+            /// Make this type from a hex string. The decoded bytes are validated the same way
+            /// as with `from_slice`.
+            pub fn from_hex(hex: &str) -> Result<Self, UnknownCryptoError> {
+                let mut bytes = util::from_hex(hex)?;
+                let res = $name::from_slice(&bytes);
+                bytes.zeroize();
+
+                res
+            }
+
+            /// Return the bytes as a lowercase hex string.
+            pub fn to_hex(&self) -> String {
+                util::to_hex(self.$bytes())
+            }
+        }
+
+        impl<'a> TryFrom<&'a [u8]> for $name {
+            type Error = UnknownCryptoError;
+
+            fn try_from(slice: &'a [u8]) -> Result<Self, UnknownCryptoError> {
+                $name::from_slice(slice)
+            }
+        }
+    };
+}
+
+/// A secret key. The key bytes can only be set through `from_slice` or `generate`, and are
+/// zeroed out on drop.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the key is less than 1
+///
+/// # Security:
+/// `SecretKey` does not implement `Clone` or `Display`, and its `Debug` output does not include
+/// the key, so that keys are not accidentally copied or logged. Use `generate` to create a new
+/// random key. It implements `Zeroize` and `ZeroizeOnDrop`, so structs that embed a `SecretKey`
+/// can derive zeroization. Note that `to_hex` returns the key in a `String` that is not zeroed
+/// out on drop. Comparing two keys with `==` is done in constant time. The raw key bytes are
+/// only available through `unprotected_as_bytes`, so that any code extracting them is easy to
+/// find and audit.
+///
+/// # Example:
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::core::types::SecretKey;
+///
+/// let key = SecretKey::generate(32).unwrap();
+/// let imported = SecretKey::from_slice(key.unprotected_as_bytes()).unwrap();
+///
+/// assert_eq!(key, imported);
+/// # }
+/// ```
+pub struct SecretKey {
+    value: Vec<u8>,
+}
+
+impl Zeroize for SecretKey {
+    fn zeroize(&mut self) {
+        self.value.zeroize()
+    }
+}
+
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        self.zeroize()
+    }
+}
+
+impl ZeroizeOnDrop for SecretKey {}
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecretKey {{ value: *** }}")
+    }
+}
+
+impl SecretKey {
+    /// Make a `SecretKey` from a byte slice.
+    pub fn from_slice(slice: &[u8]) -> Result<Self, UnknownCryptoError> {
+        if slice.is_empty() {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(SecretKey {
+            value: slice.to_vec(),
+        })
+    }
+
+    #[cfg(feature = "rng")]
+    /// Generate a random `SecretKey` of a given length using a CSPRNG.
+    pub fn generate(length: usize) -> Result<Self, UnknownCryptoError> {
+        Self::generate_with(&mut OsRandom, length)
+    }
+
+    /// Generate a random `SecretKey` of a given length using `rng` as the source of randomness.
+    pub fn generate_with<R: SecureRandom>(
+        rng: &mut R,
+        length: usize,
+    ) -> Result<Self, UnknownCryptoError> {
+        Ok(SecretKey {
+            value: util::gen_rand_key_with(rng, length)?,
+        })
+    }
+
+    /// Return the raw key bytes. Every use of this should be audited, since the returned
+    /// slice is not protected by any of the guarantees `SecretKey` gives.
+    pub fn unprotected_as_bytes(&self) -> &[u8] {
+        &self.value
+    }
+}
+
+impl_ct_eq!(SecretKey, unprotected_as_bytes);
+
+impl_conversions!(SecretKey, unprotected_as_bytes);
+
+/// A password, for use with password-based key derivation. The password bytes are zeroed
+/// out on drop.
+///
+/// With the `mlock` feature, the password is kept in a `ProtectedBuffer`, so that its memory is
+/// locked and not written to swap. Use `is_locked` to check whether the memory is locked.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the password is less than 1
+///
+/// # Security:
+/// `Password` does not implement `Clone` or `Display`, and its `Debug` output does not include
+/// the password, so that passwords are not accidentally copied or logged. Comparing two
+/// passwords with `==` is done in constant time. The raw password bytes are only available
+/// through `unprotected_as_bytes`.
+///
+/// # Example:
+/// ```
+/// use orion::core::types::Password;
+///
+/// let password = Password::from_slice("Secret password".as_bytes()).unwrap();
+/// ```
+pub struct Password {
+    #[cfg(feature = "mlock")]
+    value: ProtectedBuffer,
+    #[cfg(not(feature = "mlock"))]
+    value: Vec<u8>,
+}
+
+impl Zeroize for Password {
+    #[cfg(feature = "mlock")]
+    fn zeroize(&mut self) {
+        self.value.value.zeroize()
+    }
+
+    #[cfg(not(feature = "mlock"))]
+    fn zeroize(&mut self) {
+        self.value.zeroize()
+    }
+}
+
+impl Drop for Password {
+    fn drop(&mut self) {
+        self.zeroize()
+    }
+}
+
+impl ZeroizeOnDrop for Password {}
+
+impl fmt::Debug for Password {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Password {{ value: *** }}")
+    }
+}
+
+impl Password {
+    /// Make a `Password` from a byte slice.
+    pub fn from_slice(slice: &[u8]) -> Result<Self, UnknownCryptoError> {
+        if slice.is_empty() {
+            return Err(UnknownCryptoError);
+        }
+
+        #[cfg(feature = "mlock")]
+        let value = ProtectedBuffer::from_slice(slice)?;
+        #[cfg(not(feature = "mlock"))]
+        let value = slice.to_vec();
+
+        Ok(Password { value })
+    }
+
+    #[cfg(feature = "mlock")]
+    /// Return whether the memory of the password is locked.
+    pub fn is_locked(&self) -> bool {
+        self.value.is_locked()
+    }
+
+    #[cfg(not(feature = "mlock"))]
+    /// Return whether the memory of the password is locked. This is always `false` without the
+    /// `mlock` feature.
+    pub fn is_locked(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "mlock")]
+    /// Return the raw password bytes. Every use of this should be audited, since the returned
+    /// slice is not protected by any of the guarantees `Password` gives.
+    pub fn unprotected_as_bytes(&self) -> &[u8] {
+        self.value.unprotected_as_bytes()
+    }
+
+    #[cfg(not(feature = "mlock"))]
+    /// Return the raw password bytes. Every use of this should be audited, since the returned
+    /// slice is not protected by any of the guarantees `Password` gives.
+    pub fn unprotected_as_bytes(&self) -> &[u8] {
+        &self.value
+    }
+}
+
+impl_ct_eq!(Password, unprotected_as_bytes);
+
+/// A buffer for long-lived secrets, such as master keys. The buffer is zeroed out on drop.
+///
+/// With the `mlock` feature enabled, the memory of the buffer is locked with `mlock` (or
+/// `VirtualLock` on Windows), so that it is not swapped to disk. If locking fails, for example
+/// because the limit on locked memory has been reached or the platform doesn't support it, the
+/// buffer falls back to normal memory. Use `is_locked` to check whether the memory is locked.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the buffer is less than 1
+///
+/// # Security:
+/// `ProtectedBuffer` does not implement `Clone` or `Display`, and its `Debug` output does not
+/// include the contents, so that secrets are not accidentally copied or logged. Comparing two
+/// buffers with `==` is done in constant time. The raw bytes are only available through
+/// `unprotected_as_bytes`. Locked memory does not protect against an attacker that can read the
+/// memory of the process. No guard pages or canaries are placed around the buffer, so an
+/// overflow from neighbouring memory is not detected.
+///
+/// # Example:
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::core::types::ProtectedBuffer;
+///
+/// let master_key = ProtectedBuffer::generate(32).unwrap();
+/// assert_eq!(master_key.unprotected_as_bytes().len(), 32);
+/// # }
+/// ```
+pub struct ProtectedBuffer {
+    // Declared before `value`, so that the memory is unlocked before it is deallocated
+    #[cfg(feature = "mlock")]
+    lock: Option<region::LockGuard>,
+    value: Box<[u8]>,
+}
+
+impl Drop for ProtectedBuffer {
+    fn drop(&mut self) {
+        self.value.zeroize()
+    }
+}
+
+impl ZeroizeOnDrop for ProtectedBuffer {}
+
+impl fmt::Debug for ProtectedBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ProtectedBuffer {{ value: *** }}")
+    }
+}
+
+impl ProtectedBuffer {
+    /// Lock the memory of `value`, if the `mlock` feature is enabled.
+    fn new(value: Box<[u8]>) -> Self {
+        ProtectedBuffer {
+            #[cfg(feature = "mlock")]
+            lock: region::lock(value.as_ptr(), value.len()).ok(),
+            value,
+        }
+    }
+
+    /// Make a `ProtectedBuffer` from a byte slice.
+    pub fn from_slice(slice: &[u8]) -> Result<Self, UnknownCryptoError> {
+        if slice.is_empty() {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut buffer = Self::new(vec![0u8; slice.len()].into_boxed_slice());
+        buffer.value.copy_from_slice(slice);
+
+        Ok(buffer)
+    }
+
+    #[cfg(feature = "rng")]
+    /// Generate a random `ProtectedBuffer` of a given length using a CSPRNG. The random bytes
+    /// are written directly into the buffer, after its memory has been locked.
+    pub fn generate(length: usize) -> Result<Self, UnknownCryptoError> {
+        if length < 1 {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut buffer = Self::new(vec![0u8; length].into_boxed_slice());
+        util::secure_rand_bytes(&mut buffer.value)?;
+
+        Ok(buffer)
+    }
+
+    #[cfg(feature = "mlock")]
+    /// Return whether the memory of the buffer is locked.
+    pub fn is_locked(&self) -> bool {
+        self.lock.is_some()
+    }
+
+    #[cfg(not(feature = "mlock"))]
+    /// Return whether the memory of the buffer is locked. This is always `false` without the
+    /// `mlock` feature.
+    pub fn is_locked(&self) -> bool {
+        false
+    }
+
+    /// Return the raw bytes of the buffer. Every use of this should be audited, since the
+    /// returned slice is not protected by any of the guarantees `ProtectedBuffer` gives.
+    pub fn unprotected_as_bytes(&self) -> &[u8] {
+        &self.value
+    }
+}
+
+impl_ct_eq!(ProtectedBuffer, unprotected_as_bytes);
+
+/// A salt. Salts are not secret, but should be unique and are therefore normally generated
+/// with `generate`.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the salt is less than 1
+///
+/// # Example:
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::core::types::Salt;
+///
+/// let salt = Salt::generate(16).unwrap();
+/// let stored = Salt::from_slice(salt.as_bytes()).unwrap();
+///
+/// assert_eq!(salt, stored);
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Salt {
+    value: Vec<u8>,
+}
+
+impl Salt {
+    /// Make a `Salt` from a byte slice.
+    pub fn from_slice(slice: &[u8]) -> Result<Self, UnknownCryptoError> {
+        if slice.is_empty() {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(Salt {
+            value: slice.to_vec(),
+        })
+    }
+
+    #[cfg(feature = "rng")]
+    /// Generate a random `Salt` of a given length using a CSPRNG.
+    pub fn generate(length: usize) -> Result<Self, UnknownCryptoError> {
+        Self::generate_with(&mut OsRandom, length)
+    }
+
+    /// Generate a random `Salt` of a given length using `rng` as the source of randomness.
+    pub fn generate_with<R: SecureRandom>(
+        rng: &mut R,
+        length: usize,
+    ) -> Result<Self, UnknownCryptoError> {
+        Ok(Salt {
+            value: util::gen_rand_key_with(rng, length)?,
+        })
+    }
+
+    /// Return the salt bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.value
+    }
+}
+
+impl_ct_eq!(Salt);
+
+impl_conversions!(Salt);
+
+/// A nonce for ChaCha20 (12 bytes) or XChaCha20 (24 bytes).
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the nonce is not 12 or 24 bytes
+///
+/// # Security:
+/// A nonce must never be used more than once with the same key. Random nonces should only be
+/// used with XChaCha20, as 12 bytes are too short to rule out collisions.
+///
+/// # Example:
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::core::types::Nonce;
+///
+/// let nonce = Nonce::generate(24).unwrap();
+///
+/// assert!(Nonce::from_slice(&[0u8; 12]).is_ok());
+/// assert!(Nonce::from_slice(&[0u8; 16]).is_err());
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Nonce {
+    value: Vec<u8>,
+}
+
+impl Nonce {
+    /// Make a `Nonce` from a byte slice.
+    pub fn from_slice(slice: &[u8]) -> Result<Self, UnknownCryptoError> {
+        if slice.len() != 12 && slice.len() != 24 {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(Nonce {
+            value: slice.to_vec(),
+        })
+    }
+
+    #[cfg(feature = "rng")]
+    /// Generate a random `Nonce` of a given length using a CSPRNG.
+    pub fn generate(length: usize) -> Result<Self, UnknownCryptoError> {
+        Self::generate_with(&mut OsRandom, length)
+    }
+
+    /// Generate a random `Nonce` of a given length using `rng` as the source of randomness.
+    pub fn generate_with<R: SecureRandom>(
+        rng: &mut R,
+        length: usize,
+    ) -> Result<Self, UnknownCryptoError> {
+        if length != 12 && length != 24 {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(Nonce {
+            value: util::gen_rand_key_with(rng, length)?,
+        })
+    }
+
+    /// Return the nonce bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.value
+    }
+}
+
+impl_ct_eq!(Nonce);
+
+impl_conversions!(Nonce);
+
+/// A counter nonce for ChaCha20 (12 bytes) or XChaCha20 (24 bytes), for protocols that use
+/// sequence numbers as nonces. The counter is a little-endian integer, like libsodium's
+/// `sodium_increment`.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the nonce is not 12 or 24 bytes
+/// - Incrementing would overflow the counter
+///
+/// # Security:
+/// A counter nonce must never be reset or restarted from an earlier value with the same key.
+/// Instead of wrapping around to zero, `increment` returns an error, after which a new key must
+/// be used.
+///
+/// # Example:
+/// ```
+/// use orion::core::types::CounterNonce;
+///
+/// let mut counter = CounterNonce::new(12).unwrap();
+/// assert_eq!(counter.as_bytes(), &[0u8; 12]);
+///
+/// counter.increment().unwrap();
+/// assert_eq!(counter.as_bytes()[0], 1);
+///
+/// let nonce = counter.to_nonce();
+/// ```
+#[derive(Clone, Debug)]
+pub struct CounterNonce {
+    value: Vec<u8>,
+}
+
+impl CounterNonce {
+    /// Make a `CounterNonce` of a given length, starting at zero.
+    pub fn new(length: usize) -> Result<Self, UnknownCryptoError> {
+        if length != 12 && length != 24 {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(CounterNonce {
+            value: vec![0u8; length],
+        })
+    }
+
+    /// Make a `CounterNonce` starting at the little-endian value in a byte slice.
+    pub fn from_slice(slice: &[u8]) -> Result<Self, UnknownCryptoError> {
+        if slice.len() != 12 && slice.len() != 24 {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(CounterNonce {
+            value: slice.to_vec(),
+        })
+    }
+
+    /// Increment the counter by one, in constant time. If the counter would overflow, an error
+    /// is returned and the counter is left unchanged.
+    pub fn increment(&mut self) -> Result<(), UnknownCryptoError> {
+        let mut next = [0u8; 24];
+        let next = &mut next[..self.value.len()];
+        let mut carry: u16 = 1;
+        for (n, byte) in next.iter_mut().zip(self.value.iter()) {
+            carry += u16::from(*byte);
+            *n = carry as u8;
+            carry >>= 8;
+        }
+
+        // Only a counter of all 0xff bytes has a carry left
+        if carry != 0 {
+            return Err(UnknownCryptoError);
+        }
+
+        self.value.copy_from_slice(next);
+
+        Ok(())
+    }
+
+    /// Return the current nonce as a `Nonce`.
+    pub fn to_nonce(&self) -> Nonce {
+        Nonce {
+            value: self.value.clone(),
+        }
+    }
+
+    /// Return the current nonce bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.value
+    }
+}
+
+impl_ct_eq!(CounterNonce);
+
+/// An authentication tag, such as the output of HMAC or Poly1305. The tag is stored inline,
+/// so creating one does not allocate.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the tag is less than 1
+/// - The length of the tag is greater than 64
+///
+/// # Security:
+/// Comparing two tags with `==` is done in constant time. Tags of different lengths are
+/// never equal.
+///
+/// # Example:
+/// ```
+/// use orion::core::types::Tag;
+///
+/// let tag = Tag::from_slice(&[1u8; 32]).unwrap();
+///
+/// assert!(tag == Tag::from_slice(&[1u8; 32]).unwrap());
+/// assert!(tag != Tag::from_slice(&[1u8; 16]).unwrap());
+/// ```
+#[derive(Clone)]
+pub struct Tag {
+    value: [u8; 64],
+    len: usize,
+}
+
+impl fmt::Debug for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Tag {{ value: {:?} }}", self.as_bytes())
+    }
+}
+
+impl Tag {
+    /// Make a `Tag` from a byte slice.
+    pub fn from_slice(slice: &[u8]) -> Result<Self, UnknownCryptoError> {
+        if slice.is_empty() || slice.len() > 64 {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut value = [0u8; 64];
+        value[..slice.len()].copy_from_slice(slice);
+
+        Ok(Tag {
+            value,
+            len: slice.len(),
+        })
+    }
+
+    /// Make a `Tag` from the first `len` bytes of `value`, for callers that have already
+    /// checked that `len` is between 1 and 64.
+    pub(crate) fn from_array(value: [u8; 64], len: usize) -> Self {
+        debug_assert!(len > 0 && len <= 64);
+
+        Tag { value, len }
+    }
+
+    /// Return the tag bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.value[..self.len]
+    }
+}
+
+impl_ct_eq!(Tag);
+
+impl_conversions!(Tag);
+
+// Only non-secret types can be serialized. `SecretKey` and `Password` are deliberately excluded.
+#[cfg(feature = "serde")]
+macro_rules! impl_serde {
+    ($name:ident) => {
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_bytes(self.as_bytes())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let bytes = Vec::<u8>::deserialize(deserializer)?;
+
+                $name::from_slice(&bytes).map_err(de::Error::custom)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "serde")]
+impl_serde!(Salt);
+#[cfg(feature = "serde")]
+impl_serde!(Nonce);
+#[cfg(feature = "serde")]
+impl_serde!(Tag);
+
+#[cfg(test)]
+mod test {
+    use core::types::*;
+
+    #[test]
+    fn secret_key_from_slice() {
+        assert_eq!(SecretKey::from_slice(&[1u8; 32]).unwrap().unprotected_as_bytes(), &[1u8; 32]);
+        assert!(SecretKey::from_slice(&[]).is_err());
+    }
+
+    #[test]
+    fn secret_debug_is_redacted() {
+        let key = SecretKey::from_slice(&[0x61; 32]).unwrap();
+        let password = Password::from_slice("Secret password".as_bytes()).unwrap();
+
+        assert_eq!(format!("{:?}", key), "SecretKey { value: *** }");
+        assert_eq!(format!("{:?}", password), "Password { value: *** }");
+    }
+
+    #[test]
+    fn secret_key_zeroize() {
+        let mut key = SecretKey::from_slice(&[1u8; 32]).unwrap();
+        key.zeroize();
+
+        assert!(key.unprotected_as_bytes().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn secret_key_generate() {
+        let key = SecretKey::generate(64).unwrap();
+        let other_key = SecretKey::generate(64).unwrap();
+
+        assert_eq!(key.unprotected_as_bytes().len(), 64);
+        assert_ne!(key, other_key);
+        assert!(SecretKey::generate(0).is_err());
+    }
+
+    /// Deterministic source that fills every buffer with the same byte, for testing only.
+    struct FixedRandom(u8);
+
+    impl SecureRandom for FixedRandom {
+        fn fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), UnknownCryptoError> {
+            for byte in dst.iter_mut() {
+                *byte = self.0;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn generate_with_custom_rng() {
+        let mut rng = FixedRandom(0x2a);
+
+        let key = SecretKey::generate_with(&mut rng, 32).unwrap();
+        let salt = Salt::generate_with(&mut rng, 16).unwrap();
+        let nonce = Nonce::generate_with(&mut rng, 24).unwrap();
+
+        assert_eq!(key.unprotected_as_bytes(), &[0x2a; 32]);
+        assert_eq!(salt.as_bytes(), &[0x2a; 16]);
+        assert_eq!(nonce.as_bytes(), &[0x2a; 24][..]);
+        assert!(SecretKey::generate_with(&mut rng, 0).is_err());
+        assert!(Nonce::generate_with(&mut rng, 16).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn protected_buffer() {
+        let buffer = ProtectedBuffer::from_slice(&[1u8; 32]).unwrap();
+        let generated = ProtectedBuffer::generate(32).unwrap();
+
+        assert_eq!(buffer.unprotected_as_bytes(), &[1u8; 32]);
+        assert_eq!(buffer, ProtectedBuffer::from_slice(&[1u8; 32]).unwrap());
+        assert_ne!(generated.unprotected_as_bytes(), &[0u8; 32]);
+        assert_ne!(generated, ProtectedBuffer::generate(32).unwrap());
+        assert_eq!(format!("{:?}", buffer), "ProtectedBuffer { value: *** }");
+        assert!(ProtectedBuffer::from_slice(&[]).is_err());
+        assert!(ProtectedBuffer::generate(0).is_err());
+        #[cfg(not(feature = "mlock"))]
+        assert!(!buffer.is_locked());
+    }
+
+    #[test]
+    fn password_from_slice() {
+        let password = Password::from_slice(b"pass\0word").unwrap();
+
+        assert_eq!(password.unprotected_as_bytes(), b"pass\0word");
+        assert!(Password::from_slice(&[]).is_err());
+        #[cfg(not(feature = "mlock"))]
+        assert!(!password.is_locked());
+    }
+
+    #[test]
+    #[cfg(feature = "mlock")]
+    fn password_mlock() {
+        let mut password = Password::from_slice("Secret password".as_bytes()).unwrap();
+
+        assert!(password.is_locked());
+        assert_eq!(password.unprotected_as_bytes(), b"Secret password");
+        assert_eq!(password, Password::from_slice("Secret password".as_bytes()).unwrap());
+
+        password.zeroize();
+        assert_eq!(password.unprotected_as_bytes(), &[0u8; 15]);
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn salt_from_slice_and_generate() {
+        assert_eq!(Salt::from_slice(&[1u8; 16]).unwrap().as_bytes(), &[1u8; 16]);
+        assert_eq!(Salt::generate(32).unwrap().as_bytes().len(), 32);
+        assert!(Salt::from_slice(&[]).is_err());
+        assert!(Salt::generate(0).is_err());
+    }
+
+    #[test]
+    fn counter_nonce_increment() {
+        let mut counter = CounterNonce::new(12).unwrap();
+        counter.increment().unwrap();
+        assert_eq!(counter.as_bytes(), &[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+        let mut carry = [0xffu8; 24];
+        carry[23] = 0x00;
+        let mut counter = CounterNonce::from_slice(&carry).unwrap();
+        counter.increment().unwrap();
+        let mut expected = [0u8; 24];
+        expected[23] = 0x01;
+        assert_eq!(counter.as_bytes(), &expected[..]);
+        assert_eq!(counter.to_nonce().as_bytes(), &expected[..]);
+    }
+
+    #[test]
+    fn counter_nonce_overflow() {
+        for len in [12, 24].iter() {
+            let max = vec![0xffu8; *len];
+            let mut counter = CounterNonce::from_slice(&max).unwrap();
+
+            assert!(counter.increment().is_err());
+            assert_eq!(counter.as_bytes(), &max[..]);
+        }
+
+        assert!(CounterNonce::new(16).is_err());
+        assert!(CounterNonce::from_slice(&[0u8; 8]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn nonce_length_validation() {
+        assert!(Nonce::from_slice(&[0u8; 12]).is_ok());
+        assert!(Nonce::from_slice(&[0u8; 24]).is_ok());
+        assert!(Nonce::from_slice(&[0u8; 0]).is_err());
+        assert!(Nonce::from_slice(&[0u8; 16]).is_err());
+        assert_eq!(Nonce::generate(12).unwrap().as_bytes().len(), 12);
+        assert_eq!(Nonce::generate(24).unwrap().as_bytes().len(), 24);
+        assert!(Nonce::generate(32).is_err());
+    }
+
+    #[test]
+    fn tag_from_slice() {
+        assert_eq!(Tag::from_slice(&[1u8; 32]).unwrap().as_bytes(), &[1u8; 32]);
+        assert!(Tag::from_slice(&[]).is_err());
+        assert!(Tag::from_slice(&[1u8; 64]).is_ok());
+        assert!(Tag::from_slice(&[1u8; 65]).is_err());
+    }
+
+    #[test]
+    fn tag_eq() {
+        let tag = Tag::from_slice(&[1u8; 32]).unwrap();
+
+        assert_eq!(tag, Tag::from_slice(&[1u8; 32]).unwrap());
+        assert_ne!(tag, Tag::from_slice(&[2u8; 32]).unwrap());
+        assert_ne!(tag, Tag::from_slice(&[1u8; 31]).unwrap());
+        assert_ne!(tag, Tag::from_slice(&[1u8; 33]).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_roundtrip() {
+        extern crate serde_json;
+
+        let salt = Salt::generate(16).unwrap();
+        let nonce = Nonce::generate(24).unwrap();
+        let tag = Tag::from_slice(&[1u8; 32]).unwrap();
+
+        let salt_json = serde_json::to_string(&salt).unwrap();
+        let nonce_json = serde_json::to_string(&nonce).unwrap();
+        let tag_json = serde_json::to_string(&tag).unwrap();
+
+        assert_eq!(serde_json::from_str::<Salt>(&salt_json).unwrap(), salt);
+        assert_eq!(serde_json::from_str::<Nonce>(&nonce_json).unwrap(), nonce);
+        assert_eq!(serde_json::from_str::<Tag>(&tag_json).unwrap(), tag);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_validates_length() {
+        extern crate serde_json;
+
+        assert!(serde_json::from_str::<Salt>("[]").is_err());
+        assert!(serde_json::from_str::<Nonce>("[1, 2, 3]").is_err());
+        assert!(serde_json::from_str::<Tag>("[]").is_err());
+    }
+
+    #[test]
+    fn hex_conversions() {
+        let key = SecretKey::from_hex("00ff").unwrap();
+        let salt = Salt::from_hex("0123456789abcdef").unwrap();
+        let nonce = Nonce::from_hex(&"0a".repeat(12)).unwrap();
+        let tag = Tag::from_hex("FFFF").unwrap();
+
+        assert_eq!(key.unprotected_as_bytes(), &[0x00, 0xff]);
+        assert_eq!(salt.to_hex(), "0123456789abcdef");
+        assert_eq!(nonce.as_bytes(), &[0x0a; 12]);
+        assert_eq!(tag.to_hex(), "ffff");
+
+        assert!(SecretKey::from_hex("").is_err());
+        assert!(Salt::from_hex("0x").is_err());
+        assert!(Nonce::from_hex(&"0a".repeat(16)).is_err());
+        assert!(Tag::from_hex(&"0a".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn try_from_slice() {
+        assert!(SecretKey::try_from(&[1u8; 32][..]).is_ok());
+        assert!(Salt::try_from(&[1u8; 16][..]).is_ok());
+        assert!(Nonce::try_from(&[1u8; 24][..]).is_ok());
+        assert!(Tag::try_from(&[1u8; 16][..]).is_ok());
+
+        assert!(SecretKey::try_from(&[][..]).is_err());
+        assert!(Salt::try_from(&[][..]).is_err());
+        assert!(Nonce::try_from(&[1u8; 16][..]).is_err());
+        assert!(Tag::try_from(&[][..]).is_err());
+    }
+
+    #[test]
+    fn ct_eq() {
+        let key = SecretKey::from_slice(&[1u8; 32]).unwrap();
+        let password = Password::from_slice(&[1u8; 32]).unwrap();
+        let salt = Salt::from_slice(&[1u8; 32]).unwrap();
+        let nonce = Nonce::from_slice(&[1u8; 12]).unwrap();
+
+        assert!(key == SecretKey::from_slice(&[1u8; 32]).unwrap());
+        assert!(key != SecretKey::from_slice(&[1u8; 31]).unwrap());
+        assert!(password == Password::from_slice(&[1u8; 32]).unwrap());
+        assert!(password != Password::from_slice(&[2u8; 32]).unwrap());
+        assert_eq!(salt, Salt::from_slice(&[1u8; 32]).unwrap());
+        assert_ne!(salt, Salt::from_slice(&[1u8; 33]).unwrap());
+        assert_eq!(nonce, Nonce::from_slice(&[1u8; 12]).unwrap());
+        assert_ne!(nonce, Nonce::from_slice(&[1u8; 24]).unwrap());
+    }
+}
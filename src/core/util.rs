@@ -22,23 +22,181 @@
 
 use constant_time_eq::constant_time_eq;
 use core::errors;
+#[cfg(all(feature = "rng", not(target_arch = "wasm32")))]
 use rand::{rngs::OsRng, RngCore};
+#[cfg(all(feature = "rng", not(target_arch = "wasm32")))]
+use std::cell::RefCell;
+use zeroize::Zeroize;
 
+/// A source of cryptographically secure random bytes. Implement this to generate keys, salts
+/// and nonces with a different RNG than the operating system's, such as a hardware RNG on
+/// embedded targets or a deterministic source in tests.
+///
+/// # Security:
+/// Implementations must be cryptographically secure. A deterministic source must never be used
+/// outside of tests.
+pub trait SecureRandom {
+    /// Fill `dst` with random bytes.
+    fn fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), errors::UnknownCryptoError>;
+}
+
+#[cfg(all(feature = "rng", not(target_arch = "wasm32")))]
+thread_local! {
+    // Initialized on first use, so that each thread only opens the OS source once
+    static OS_RNG: RefCell<Option<OsRng>> = RefCell::new(None);
+}
+
+#[cfg(feature = "rng")]
+#[derive(Clone, Copy, Debug, Default)]
+/// The operating system's CSPRNG, using rand's
+/// [OsRng](https://docs.rs/rand/0.5.1/rand/rngs/struct.OsRng.html). This is the source used
+/// by `secure_rand_bytes`, `gen_rand_key` and the `generate` functions of the types. The
+/// `OsRng` handle is created once per thread and reused across calls.
+///
+/// On `wasm32` targets, [getrandom](https://docs.rs/getrandom) is used instead, which calls
+/// `crypto.getRandomValues` in browsers and Node.js.
+pub struct OsRandom;
+
+#[cfg(all(feature = "rng", not(target_arch = "wasm32")))]
+impl SecureRandom for OsRandom {
+    fn fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), errors::UnknownCryptoError> {
+        OS_RNG.with(|cached| {
+            let mut cached = cached.borrow_mut();
+            if cached.is_none() {
+                *cached = Some(OsRng::new()?);
+            }
+
+            // The generator was initialized above
+            cached.as_mut().unwrap().try_fill_bytes(dst)?;
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(all(feature = "rng", target_arch = "wasm32"))]
+impl SecureRandom for OsRandom {
+    fn fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), errors::UnknownCryptoError> {
+        getrandom::getrandom(dst).map_err(|_| errors::UnknownCryptoError)
+    }
+}
+
+#[cfg(feature = "rng")]
 #[inline(never)]
+/// Fill an existing buffer with random bytes, without allocating. This uses rand's
+/// [OsRng](https://docs.rs/rand/0.5.1/rand/rngs/struct.OsRng.html). The length of `dst` must
+/// be >= 1.
+pub fn secure_rand_bytes(dst: &mut [u8]) -> Result<(), errors::UnknownCryptoError> {
+    if dst.is_empty() {
+        return Err(errors::UnknownCryptoError);
+    }
+
+    OsRandom.fill_bytes(dst)
+}
+
+#[cfg(feature = "rng")]
 /// Return a random byte vector of a given length. This uses rand's
 /// [OsRng](https://docs.rs/rand/0.5.1/rand/rngs/struct.OsRng.html). Length must be >= 1.
 pub fn gen_rand_key(len: usize) -> Result<Vec<u8>, errors::UnknownCryptoError> {
+    gen_rand_key_with(&mut OsRandom, len)
+}
+
+/// Return a random byte vector of a given length, using `rng` as the source of randomness.
+/// Length must be >= 1.
+pub fn gen_rand_key_with<R: SecureRandom>(
+    rng: &mut R,
+    len: usize,
+) -> Result<Vec<u8>, errors::UnknownCryptoError> {
     if len < 1 {
         return Err(errors::UnknownCryptoError);
     }
 
     let mut rand_vec = vec![0x00; len];
-    let mut generator = OsRng::new()?;
-    generator.try_fill_bytes(&mut rand_vec)?;
+    rng.fill_bytes(&mut rand_vec)?;
 
     Ok(rand_vec)
 }
 
+#[cfg(feature = "rng")]
+/// Return `count` random byte vectors of length `len` each. All keys are filled from a single
+/// request to the RNG, which is faster than calling `gen_rand_key` `count` times when many keys
+/// are needed. Both `count` and `len` must be >= 1.
+pub fn gen_rand_keys(count: usize, len: usize) -> Result<Vec<Vec<u8>>, errors::UnknownCryptoError> {
+    if count < 1 || len < 1 {
+        return Err(errors::UnknownCryptoError);
+    }
+
+    let total = count.checked_mul(len).ok_or(errors::UnknownCryptoError)?;
+    let mut rand_vec = gen_rand_key(total)?;
+    let keys = rand_vec.chunks(len).map(|key| key.to_vec()).collect();
+    rand_vec.zeroize();
+
+    Ok(keys)
+}
+
+#[cfg(feature = "rng")]
+/// Return a random 32 byte array. This uses rand's OsRng, like `gen_rand_key`.
+pub fn gen_rand_key_32() -> Result<[u8; 32], errors::UnknownCryptoError> {
+    let mut rand_arr = [0u8; 32];
+    secure_rand_bytes(&mut rand_arr)?;
+
+    Ok(rand_arr)
+}
+
+#[cfg(feature = "rng")]
+/// Return a random 64 byte array. This uses rand's OsRng, like `gen_rand_key`.
+pub fn gen_rand_key_64() -> Result<[u8; 64], errors::UnknownCryptoError> {
+    let mut rand_arr = [0u8; 64];
+    secure_rand_bytes(&mut rand_arr)?;
+
+    Ok(rand_arr)
+}
+
+#[cfg(feature = "rng")]
+/// Return a random `u32`. This uses rand's OsRng, like `gen_rand_key`.
+pub fn gen_rand_u32() -> Result<u32, errors::UnknownCryptoError> {
+    let mut rand_arr = [0u8; 4];
+    secure_rand_bytes(&mut rand_arr)?;
+
+    Ok(u32::from_le_bytes(rand_arr))
+}
+
+#[cfg(feature = "rng")]
+/// Return a random `u64`. This uses rand's OsRng, like `gen_rand_key`.
+pub fn gen_rand_u64() -> Result<u64, errors::UnknownCryptoError> {
+    let mut rand_arr = [0u8; 8];
+    secure_rand_bytes(&mut rand_arr)?;
+
+    Ok(u64::from_le_bytes(rand_arr))
+}
+
+#[cfg(feature = "rng")]
+/// Return a uniformly random `u64` in the range `low..high`, that is including `low` and
+/// excluding `high`. Values are rejection-sampled, so there is no modulo bias. An error is
+/// returned if `low >= high`.
+pub fn gen_rand_range(low: u64, high: u64) -> Result<u64, errors::UnknownCryptoError> {
+    if low >= high {
+        return Err(errors::UnknownCryptoError);
+    }
+
+    let range = high - low;
+    // 2^64 mod range. Values below this would make the lowest results more likely.
+    let threshold = range.wrapping_neg() % range;
+    loop {
+        let value = gen_rand_u64()?;
+        if value >= threshold {
+            return Ok(low + value % range);
+        }
+    }
+}
+
+/// Overwrite `buf` with zeroes, using the [zeroize](https://crates.io/crates/zeroize) crate.
+/// The writes are volatile and followed by a compiler fence, so they are not optimized away even
+/// if `buf` is never read again. This is how orion clears its own secrets.
+pub fn wipe(buf: &mut [u8]) {
+    buf.zeroize();
+}
+
 /// Compare two equal length slices in constant time, using the
 /// [constant_time_eq](https://crates.io/crates/constant_time_eq) crate.
 pub fn compare_ct(a: &[u8], b: &[u8]) -> Result<bool, errors::UnknownCryptoError> {
@@ -53,12 +211,450 @@ pub fn compare_ct(a: &[u8], b: &[u8]) -> Result<bool, errors::UnknownCryptoError
     }
 }
 
+/// Compare `input` with `expected` in constant time, also when their lengths differ. This is
+/// meant for comparing an attacker-supplied value, such as a received MAC, with a known value.
+/// The running time only depends on the length of `expected`, so neither the content nor the
+/// length of `input` is leaked. Returns `true` if both slices are equal.
+pub fn compare_ct_any_len(input: &[u8], expected: &[u8]) -> bool {
+    let len_diff = input.len() ^ expected.len();
+    let mut diff = 0u8;
+
+    for (idx, expected_byte) in expected.iter().enumerate() {
+        // Wrap around `input`, so that every byte of `expected` is compared to something
+        let input_byte = if input.is_empty() {
+            0
+        } else {
+            input[idx % input.len()]
+        };
+        diff |= input_byte ^ *expected_byte;
+    }
+
+    (len_diff | usize::from(diff)) == 0
+}
+
+/// Return 0xff if `condition` is true and 0x00 otherwise, without branching.
+fn ct_bool_mask(condition: bool) -> u8 {
+    u8::from(condition).wrapping_neg()
+}
+
+/// Return `a` if `condition` is true and `b` otherwise, in constant time with regard to
+/// `condition`, `a` and `b`.
+pub fn ct_select(condition: bool, a: u8, b: u8) -> u8 {
+    let mask = ct_bool_mask(condition);
+
+    (mask & a) | (!mask & b)
+}
+
+/// Copy `src` into `dst` if `condition` is true, and leave `dst` unchanged otherwise. Every
+/// byte of `dst` is written in both cases, so this runs in constant time with regard to
+/// `condition` and the contents of both slices. An error is returned if the lengths of `dst`
+/// and `src` differ.
+pub fn ct_copy_if(
+    condition: bool,
+    dst: &mut [u8],
+    src: &[u8],
+) -> Result<(), errors::UnknownCryptoError> {
+    if dst.len() != src.len() {
+        return Err(errors::UnknownCryptoError);
+    }
+
+    let mask = ct_bool_mask(condition);
+    for (d, s) in dst.iter_mut().zip(src.iter()) {
+        *d = (mask & *s) | (!mask & *d);
+    }
+
+    Ok(())
+}
+
+/// Pad `data` to a multiple of `blocksize` using the ISO/IEC 7816-4 padding scheme. A 0x80
+/// byte is appended, followed by as many zero bytes as needed to reach the block boundary.
+/// At least one byte of padding is always added. `blocksize` must be >= 1.
+pub fn pad(data: &[u8], blocksize: usize) -> Result<Vec<u8>, errors::UnknownCryptoError> {
+    if blocksize < 1 {
+        return Err(errors::UnknownCryptoError);
+    }
+
+    let pad_len = blocksize - (data.len() % blocksize);
+    let mut padded = Vec::with_capacity(data.len() + pad_len);
+    padded.extend_from_slice(data);
+    padded.push(0x80);
+    padded.resize(data.len() + pad_len, 0x00);
+
+    Ok(padded)
+}
+
+/// Remove ISO/IEC 7816-4 padding that was added with `pad`. The padding is located in constant
+/// time with regard to the content of the last block. The length of `padded` must be a non-zero
+/// multiple of `blocksize`. An error is returned if the padding is invalid.
+pub fn unpad(padded: &[u8], blocksize: usize) -> Result<Vec<u8>, errors::UnknownCryptoError> {
+    if blocksize < 1 || padded.is_empty() || padded.len() % blocksize != 0 {
+        return Err(errors::UnknownCryptoError);
+    }
+
+    let tail = &padded[(padded.len() - blocksize)..];
+    let mut acc: usize = 0;
+    let mut pad_len: usize = 0;
+    let mut valid: usize = 0;
+
+    for idx in 0..blocksize {
+        let byte = tail[blocksize - 1 - idx] as usize;
+        // `is_barrier` is 1 only for the first 0x80 byte after zero bytes, counting
+        // from the end
+        let is_barrier = ((acc.wrapping_sub(1) & pad_len.wrapping_sub(1)
+            & (byte ^ 0x80).wrapping_sub(1)) >> 8) & 1;
+        acc |= byte;
+        pad_len |= idx & (1usize.wrapping_add(!is_barrier));
+        valid |= is_barrier;
+    }
+
+    if valid != 1 {
+        return Err(errors::UnknownCryptoError);
+    }
+
+    Ok(padded[..(padded.len() - 1 - pad_len)].to_vec())
+}
+
+/// Return the length that a message of `len` bytes is padded to with the
+/// [Padmé](https://lbarman.ch/blog/padme/) scheme. Padmé leaks at most O(log log len) bits
+/// of information about the length, with an overhead of at most 12%.
+pub fn padme_len(len: usize) -> Result<usize, errors::UnknownCryptoError> {
+    if len < 2 {
+        return Ok(len);
+    }
+
+    let usize_bits = (::std::mem::size_of::<usize>() * 8) as u32;
+    // E = floor(log2(len)) and S = floor(log2(E)) + 1
+    let e = usize_bits - 1 - len.leading_zeros();
+    let s = 32 - e.leading_zeros();
+    let last_bits = e - s;
+    let bit_mask: usize = (1 << last_bits) - 1;
+
+    match len.checked_add(bit_mask) {
+        Some(padded_len) => Ok(padded_len & !bit_mask),
+        None => Err(errors::UnknownCryptoError),
+    }
+}
+
+/// Pad `data` with ISO/IEC 7816-4 padding up to the Padmé length of `data.len() + 1`, so that
+/// the padded length only leaks a bounded amount of information about the length of `data`.
+/// Use `unpad_padme` to remove the padding.
+pub fn pad_padme(data: &[u8]) -> Result<Vec<u8>, errors::UnknownCryptoError> {
+    let min_len = match data.len().checked_add(1) {
+        Some(min_len) => min_len,
+        None => return Err(errors::UnknownCryptoError),
+    };
+    let padded_len = padme_len(min_len)?;
+
+    let mut padded = Vec::with_capacity(padded_len);
+    padded.extend_from_slice(data);
+    padded.push(0x80);
+    padded.resize(padded_len, 0x00);
+
+    Ok(padded)
+}
+
+/// Remove padding that was added with `pad_padme`. The padding is located in constant time
+/// with regard to the content of `padded`. An error is returned if the padding is invalid.
+pub fn unpad_padme(padded: &[u8]) -> Result<Vec<u8>, errors::UnknownCryptoError> {
+    unpad(padded, padded.len())
+}
+
+/// Encode a nibble as a lowercase hex character, without branches or table lookups.
+fn hex_char(nibble: u8) -> char {
+    let nibble = u32::from(nibble);
+    // Add the distance from '9' + 1 to 'a' if the nibble is greater than 9
+    let alpha_offset = (9u32.wrapping_sub(nibble) >> 8) & 0x27;
+
+    (nibble + u32::from(b'0') + alpha_offset) as u8 as char
+}
+
+/// Decode a hex character, without branches or table lookups. Returns the nibble and a mask
+/// that is 0xff if `c` is not a hex character and 0x00 otherwise. This follows libsodium's
+/// `sodium_hex2bin`.
+fn hex_nibble(c: u8) -> (u8, u8) {
+    let c = u32::from(c);
+    let num = c ^ u32::from(b'0');
+    let num_mask = (num.wrapping_sub(10) >> 8) as u8;
+    let alpha = (c & !0x20).wrapping_sub(55);
+    let alpha_mask = ((alpha.wrapping_sub(10) ^ alpha.wrapping_sub(16)) >> 8) as u8;
+
+    let nibble = (num_mask & num as u8) | (alpha_mask & alpha as u8);
+
+    (nibble, !(num_mask | alpha_mask))
+}
+
+/// Encode `data` as a lowercase hex string. The encoding runs in constant time with regard to
+/// the content of `data`, so that it can be used for secret keys.
+pub fn to_hex(data: &[u8]) -> String {
+    let mut hex = String::with_capacity(data.len() * 2);
+    for byte in data {
+        hex.push(hex_char(byte >> 4));
+        hex.push(hex_char(byte & 0x0f));
+    }
+
+    hex
+}
+
+/// Decode a hex string, accepting both lower- and uppercase characters. An error is returned
+/// if `hex` has an odd length or contains non-hex characters. The decoding runs in constant
+/// time with regard to the content of `hex`, so that it can be used for secret keys.
+pub fn from_hex(hex: &str) -> Result<Vec<u8>, errors::UnknownCryptoError> {
+    if hex.len() % 2 != 0 {
+        return Err(errors::UnknownCryptoError);
+    }
+
+    let mut data = Vec::with_capacity(hex.len() / 2);
+    let mut invalid = 0u8;
+    for pair in hex.as_bytes().chunks(2) {
+        let (high, high_invalid) = hex_nibble(pair[0]);
+        let (low, low_invalid) = hex_nibble(pair[1]);
+        invalid |= high_invalid | low_invalid;
+        data.push((high << 4) | low);
+    }
+
+    // Only check for invalid characters at the end, so that their position is not leaked
+    if invalid != 0 {
+        data.zeroize();
+        return Err(errors::UnknownCryptoError);
+    }
+
+    Ok(data)
+}
+
+/// Return 0xff if `a == b` and 0x00 otherwise, without branching.
+fn ct_eq_mask(a: u8, b: u8) -> u8 {
+    (u32::from(a ^ b).wrapping_sub(1) >> 8) as u8
+}
+
+/// Return 0xff if `a >= b` and 0x00 otherwise, without branching.
+fn ct_ge_mask(a: u8, b: u8) -> u8 {
+    !((u32::from(a).wrapping_sub(u32::from(b)) >> 8) as u8)
+}
+
+/// Return 0xff if `low <= a <= high` and 0x00 otherwise, without branching.
+fn ct_range_mask(a: u8, low: u8, high: u8) -> u8 {
+    ct_ge_mask(a, low) & ct_ge_mask(high, a)
+}
+
+/// The last two characters of a base64 alphabet. The first 62 characters are the same for the
+/// standard, the URL and filename safe and the passlib alphabet.
+type Base64Symbols = (u8, u8);
+
+const BASE64_STANDARD: Base64Symbols = (b'+', b'/');
+
+const BASE64_URLSAFE: Base64Symbols = (b'-', b'_');
+
+const BASE64_PASSLIB: Base64Symbols = (b'.', b'/');
+
+/// Encode a sextet as a base64 character, without branches or table lookups.
+fn base64_char(sextet: u8, symbols: Base64Symbols) -> char {
+    let c = (ct_range_mask(sextet, 0, 25) & sextet.wrapping_add(b'A'))
+        | (ct_range_mask(sextet, 26, 51) & sextet.wrapping_add(b'a' - 26))
+        | (ct_range_mask(sextet, 52, 61) & sextet.wrapping_sub(52 - b'0'))
+        | (ct_eq_mask(sextet, 62) & symbols.0)
+        | (ct_eq_mask(sextet, 63) & symbols.1);
+
+    c as char
+}
+
+/// Decode a base64 character, without branches or table lookups. Returns the sextet and a
+/// mask that is 0xff if `c` is not in the alphabet and 0x00 otherwise.
+fn base64_sextet(c: u8, symbols: Base64Symbols) -> (u8, u8) {
+    let upper = ct_range_mask(c, b'A', b'Z');
+    let lower = ct_range_mask(c, b'a', b'z');
+    let digit = ct_range_mask(c, b'0', b'9');
+    let first = ct_eq_mask(c, symbols.0);
+    let second = ct_eq_mask(c, symbols.1);
+
+    let sextet = (upper & c.wrapping_sub(b'A'))
+        | (lower & c.wrapping_sub(b'a' - 26))
+        | (digit & c.wrapping_add(52 - b'0'))
+        | (first & 62)
+        | (second & 63);
+
+    (sextet, !(upper | lower | digit | first | second))
+}
+
+/// Encode `data` as base64 with the standard alphabet and without padding, as used in
+/// PHC strings. The encoding runs in constant time with regard to the content of `data`.
+pub fn to_base64(data: &[u8]) -> String {
+    encode_base64(data, BASE64_STANDARD)
+}
+
+/// Encode `data` as base64 with the URL and filename safe alphabet and without padding. The
+/// encoding runs in constant time with regard to the content of `data`.
+pub fn to_base64url(data: &[u8]) -> String {
+    encode_base64(data, BASE64_URLSAFE)
+}
+
+/// Encode `data` as base64 with `.` instead of `+` and without padding. This is the "adapted
+/// base64" used by passlib in modular crypt strings. The encoding runs in constant time with
+/// regard to the content of `data`.
+pub fn to_ab64(data: &[u8]) -> String {
+    encode_base64(data, BASE64_PASSLIB)
+}
+
+fn encode_base64(data: &[u8], symbols: Base64Symbols) -> String {
+    let mut b64 = String::with_capacity((data.len() * 4 + 2) / 3);
+    for chunk in data.chunks(3) {
+        let mut block = [0u8; 3];
+        block[..chunk.len()].copy_from_slice(chunk);
+        let n = (u32::from(block[0]) << 16) | (u32::from(block[1]) << 8) | u32::from(block[2]);
+
+        for idx in 0..=chunk.len() {
+            b64.push(base64_char(((n >> (18 - 6 * idx)) & 0x3f) as u8, symbols));
+        }
+    }
+
+    b64
+}
+
+/// Decode unpadded base64 with the standard alphabet. An error is returned if `b64` contains
+/// characters outside the alphabet, padding, or is not a canonical encoding. The decoding runs
+/// in constant time with regard to the content of `b64`.
+pub fn from_base64(b64: &str) -> Result<Vec<u8>, errors::UnknownCryptoError> {
+    decode_base64(b64, BASE64_STANDARD)
+}
+
+/// Decode unpadded base64 with the URL and filename safe alphabet. An error is returned if
+/// `b64` contains characters outside the alphabet, padding, or is not a canonical encoding.
+/// The decoding runs in constant time with regard to the content of `b64`.
+pub fn from_base64url(b64: &str) -> Result<Vec<u8>, errors::UnknownCryptoError> {
+    decode_base64(b64, BASE64_URLSAFE)
+}
+
+/// Decode unpadded base64 with `.` instead of `+`, as used by passlib. An error is returned if
+/// `ab64` contains characters outside the alphabet, padding, or is not a canonical encoding.
+/// The decoding runs in constant time with regard to the content of `ab64`.
+pub fn from_ab64(ab64: &str) -> Result<Vec<u8>, errors::UnknownCryptoError> {
+    decode_base64(ab64, BASE64_PASSLIB)
+}
+
+fn decode_base64(b64: &str, symbols: Base64Symbols) -> Result<Vec<u8>, errors::UnknownCryptoError> {
+    if b64.len() % 4 == 1 {
+        return Err(errors::UnknownCryptoError);
+    }
+
+    let mut data = Vec::with_capacity(b64.len() * 3 / 4);
+    let mut invalid = 0u32;
+    for chunk in b64.as_bytes().chunks(4) {
+        let mut n = 0u32;
+        for (idx, c) in chunk.iter().enumerate() {
+            let (sextet, sextet_invalid) = base64_sextet(*c, symbols);
+            invalid |= u32::from(sextet_invalid);
+            n |= u32::from(sextet) << (18 - 6 * idx);
+        }
+
+        let out_len = chunk.len() - 1;
+        // Bits that do not fit in the decoded bytes must be zero
+        invalid |= n & (0x00ff_ffff >> (8 * out_len));
+        for idx in 0..out_len {
+            data.push((n >> (16 - 8 * idx)) as u8);
+        }
+    }
+
+    // Only check for errors at the end, so that their position is not leaked
+    if invalid != 0 {
+        data.zeroize();
+        return Err(errors::UnknownCryptoError);
+    }
+
+    Ok(data)
+}
+
+/// Encode a quintet as an RFC 4648 base32 character, without branches or table lookups.
+fn base32_char(quintet: u8) -> char {
+    let c = (ct_range_mask(quintet, 0, 25) & quintet.wrapping_add(b'A'))
+        | (ct_range_mask(quintet, 26, 31) & quintet.wrapping_add(b'2' - 26));
+
+    c as char
+}
+
+/// Decode an RFC 4648 base32 character, accepting both upper- and lowercase letters, without
+/// branches or table lookups. Returns the quintet and a mask that is 0xff if `c` is not in the
+/// alphabet and 0x00 otherwise.
+fn base32_quintet(c: u8) -> (u8, u8) {
+    let upper = ct_range_mask(c, b'A', b'Z');
+    let lower = ct_range_mask(c, b'a', b'z');
+    let digit = ct_range_mask(c, b'2', b'7');
+
+    let quintet = (upper & c.wrapping_sub(b'A'))
+        | (lower & c.wrapping_sub(b'a'))
+        | (digit & c.wrapping_sub(b'2' - 26));
+
+    (quintet, !(upper | lower | digit))
+}
+
+/// Encode `data` as RFC 4648 base32, with uppercase letters and without padding, as expected
+/// by authenticator apps for TOTP/HOTP secrets. The encoding runs in constant time with regard
+/// to the content of `data`.
+pub fn to_base32(data: &[u8]) -> String {
+    let mut b32 = String::with_capacity((data.len() * 8 + 4) / 5);
+    for chunk in data.chunks(5) {
+        let mut block = [0u8; 5];
+        block[..chunk.len()].copy_from_slice(chunk);
+        let n = block
+            .iter()
+            .fold(0u64, |acc, byte| (acc << 8) | u64::from(*byte));
+
+        for idx in 0..((chunk.len() * 8 + 4) / 5) {
+            b32.push(base32_char(((n >> (35 - 5 * idx)) & 0x1f) as u8));
+        }
+    }
+
+    b32
+}
+
+/// Decode RFC 4648 base32. Both upper- and lowercase letters are accepted, and padding is
+/// optional, but must be correct if present. An error is returned if `b32` contains
+/// characters outside the alphabet or is not a canonical encoding. The decoding runs in
+/// constant time with regard to the content of `b32`.
+pub fn from_base32(b32: &str) -> Result<Vec<u8>, errors::UnknownCryptoError> {
+    let unpadded = b32.trim_end_matches('=');
+    let pad_len = b32.len() - unpadded.len();
+    // Lengths that cannot be produced by encoding whole bytes
+    if [1, 3, 6].contains(&(unpadded.len() % 8)) {
+        return Err(errors::UnknownCryptoError);
+    }
+    if pad_len != 0 && (b32.len() % 8 != 0 || pad_len >= 8) {
+        return Err(errors::UnknownCryptoError);
+    }
+
+    let mut data = Vec::with_capacity(unpadded.len() * 5 / 8);
+    let mut invalid = 0u64;
+    for chunk in unpadded.as_bytes().chunks(8) {
+        let mut n = 0u64;
+        for (idx, c) in chunk.iter().enumerate() {
+            let (quintet, quintet_invalid) = base32_quintet(*c);
+            invalid |= u64::from(quintet_invalid);
+            n |= u64::from(quintet) << (35 - 5 * idx);
+        }
+
+        let out_len = chunk.len() * 5 / 8;
+        // Bits that do not fit in the decoded bytes must be zero
+        invalid |= n & ((1u64 << (40 - 8 * out_len)) - 1);
+        for idx in 0..out_len {
+            data.push((n >> (32 - 8 * idx)) as u8);
+        }
+    }
+
+    // Only check for errors at the end, so that their position is not leaked
+    if invalid != 0 {
+        data.zeroize();
+        return Err(errors::UnknownCryptoError);
+    }
+
+    Ok(data)
+}
+
 #[test]
+#[cfg(feature = "rng")]
 fn rand_key_len_ok() {
     gen_rand_key(64).unwrap();
 }
 
 #[test]
+#[cfg(feature = "rng")]
 fn rand_key_len_error() {
     assert!(gen_rand_key(0).is_err());
 
@@ -66,6 +662,130 @@ fn rand_key_len_error() {
     assert_eq!(err, errors::UnknownCryptoError);
 }
 
+#[test]
+#[cfg(feature = "rng")]
+fn rand_keys_batch() {
+    let keys = gen_rand_keys(100, 32).unwrap();
+    let mut unique = keys.clone();
+    unique.sort();
+    unique.dedup();
+
+    assert_eq!(keys.len(), 100);
+    assert!(keys.iter().all(|key| key.len() == 32));
+    assert_eq!(unique.len(), 100);
+    assert_eq!(gen_rand_keys(1, 1).unwrap().len(), 1);
+    assert!(gen_rand_keys(0, 32).is_err());
+    assert!(gen_rand_keys(32, 0).is_err());
+    assert!(gen_rand_keys(usize::max_value(), 2).is_err());
+}
+
+#[test]
+#[cfg(feature = "rng")]
+fn rand_key_fixed_len() {
+    let key_32 = gen_rand_key_32().unwrap();
+    let key_64 = gen_rand_key_64().unwrap();
+
+    assert_ne!(key_32, [0u8; 32]);
+    assert_ne!(key_32, gen_rand_key_32().unwrap());
+    assert_ne!(&key_64[..], &[0u8; 64][..]);
+    assert_ne!(&key_64[..], &gen_rand_key_64().unwrap()[..]);
+}
+
+#[test]
+#[cfg(feature = "rng")]
+fn secure_rand_bytes_fills_buffer() {
+    let mut buf = [0u8; 48];
+    secure_rand_bytes(&mut buf).unwrap();
+    let mut other = [0u8; 48];
+    secure_rand_bytes(&mut other[..]).unwrap();
+
+    assert_ne!(&buf[..], &[0u8; 48][..]);
+    assert_ne!(&buf[..], &other[..]);
+    assert!(secure_rand_bytes(&mut []).is_err());
+}
+
+#[cfg(test)]
+/// Deterministic source that returns consecutive byte values, for testing only.
+struct CountingRandom(u8);
+
+#[cfg(test)]
+impl SecureRandom for CountingRandom {
+    fn fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), errors::UnknownCryptoError> {
+        for byte in dst.iter_mut() {
+            *byte = self.0;
+            self.0 = self.0.wrapping_add(1);
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+#[cfg(feature = "rng")]
+fn os_random_across_threads() {
+    fn gen_many() -> Vec<u64> {
+        (0..100).map(|_| gen_rand_u64().unwrap()).collect()
+    }
+
+    let handles: Vec<_> = (0..4).map(|_| ::std::thread::spawn(gen_many)).collect();
+    let mut values: Vec<u64> = handles
+        .into_iter()
+        .flat_map(|handle| handle.join().unwrap())
+        .collect();
+    values.sort();
+    values.dedup();
+
+    assert_eq!(values.len(), 400);
+}
+
+#[test]
+fn rand_key_with_custom_rng() {
+    let mut rng = CountingRandom(0);
+
+    assert_eq!(gen_rand_key_with(&mut rng, 4).unwrap(), [0, 1, 2, 3]);
+    assert_eq!(gen_rand_key_with(&mut rng, 2).unwrap(), [4, 5]);
+    assert!(gen_rand_key_with(&mut rng, 0).is_err());
+    #[cfg(feature = "rng")]
+    assert_eq!(gen_rand_key_with(&mut OsRandom, 32).unwrap().len(), 32);
+}
+
+#[test]
+#[cfg(feature = "rng")]
+fn rand_ints() {
+    assert_ne!(gen_rand_u64().unwrap(), gen_rand_u64().unwrap());
+    // Two equal u32 values are possible, but not four in a row
+    let values: Vec<u32> = (0..4).map(|_| gen_rand_u32().unwrap()).collect();
+    assert!(values.iter().any(|v| *v != values[0]));
+}
+
+#[test]
+#[cfg(feature = "rng")]
+fn rand_range() {
+    let mut seen = [false; 7];
+    for _ in 0..1000 {
+        let value = gen_rand_range(10, 17).unwrap();
+        assert!((10..17).contains(&value));
+        seen[(value - 10) as usize] = true;
+    }
+
+    assert!(seen.iter().all(|s| *s));
+    assert_eq!(gen_rand_range(5, 6).unwrap(), 5);
+    assert!(gen_rand_range(0, u64::max_value()).unwrap() < u64::max_value());
+    assert!(gen_rand_range(6, 6).is_err());
+    assert!(gen_rand_range(7, 6).is_err());
+}
+
+#[test]
+fn wipe_zeroes_buffer() {
+    let mut buf = [0xa5u8; 33];
+    wipe(&mut buf);
+    assert_eq!(&buf[..], &[0u8; 33][..]);
+
+    let mut vec = vec![0x5au8; 7];
+    wipe(&mut vec[2..]);
+    assert_eq!(vec, [0x5a, 0x5a, 0, 0, 0, 0, 0]);
+}
+
 #[test]
 fn test_ct_eq_ok() {
     let buf_1 = vec![0x06; 10];
@@ -98,3 +818,281 @@ fn test_ct_ne_reg() {
     assert!(compare_ct(&[0], &[0, 1]).is_err());
     assert!(compare_ct(&[0, 1], &[0]).is_err());
 }
+
+#[test]
+fn test_ct_any_len() {
+    assert!(compare_ct_any_len(&[0x06; 10], &[0x06; 10]));
+    assert!(compare_ct_any_len(&[], &[]));
+    assert!(!compare_ct_any_len(&[0x06; 10], &[0x76; 10]));
+    assert!(!compare_ct_any_len(&[0x06; 5], &[0x06; 10]));
+    assert!(!compare_ct_any_len(&[0x06; 10], &[0x06; 5]));
+    assert!(!compare_ct_any_len(&[], &[0x00]));
+    assert!(!compare_ct_any_len(&[0x00], &[]));
+    // Wrapping the shorter input around must not make a repeated value equal
+    assert!(!compare_ct_any_len(&[0x01, 0x02], &[0x01, 0x02, 0x01, 0x02]));
+}
+
+#[test]
+fn test_ct_select() {
+    assert_eq!(ct_select(true, 0x12, 0x34), 0x12);
+    assert_eq!(ct_select(false, 0x12, 0x34), 0x34);
+    assert_eq!(ct_select(true, 0x00, 0xff), 0x00);
+    assert_eq!(ct_select(false, 0x00, 0xff), 0xff);
+}
+
+#[test]
+fn test_ct_copy_if() {
+    let mut dst = [0x01u8; 4];
+
+    ct_copy_if(false, &mut dst, &[0x02; 4]).unwrap();
+    assert_eq!(dst, [0x01; 4]);
+    ct_copy_if(true, &mut dst, &[0x00, 0xff, 0x02, 0x03]).unwrap();
+    assert_eq!(dst, [0x00, 0xff, 0x02, 0x03]);
+    assert!(ct_copy_if(true, &mut dst, &[0x02; 3]).is_err());
+    assert!(ct_copy_if(false, &mut dst, &[0x02; 5]).is_err());
+}
+
+#[test]
+fn test_pad_lengths() {
+    assert_eq!(pad(&[], 16).unwrap(), [&[0x80], &[0u8; 15][..]].concat());
+    assert_eq!(pad(&[0x01; 15], 16).unwrap(), [&[0x01; 15][..], &[0x80]].concat());
+    assert_eq!(pad(&[0x01; 16], 16).unwrap().len(), 32);
+    assert_eq!(pad(&[0x01; 17], 16).unwrap().len(), 32);
+    assert_eq!(pad(&[0x01; 5], 1).unwrap(), [&[0x01; 5][..], &[0x80]].concat());
+}
+
+#[test]
+fn test_pad_zero_blocksize() {
+    assert!(pad(&[0x01; 5], 0).is_err());
+    assert!(unpad(&[0x80], 0).is_err());
+}
+
+#[test]
+fn test_pad_unpad_roundtrip() {
+    for blocksize in 1..40 {
+        for len in 0..100 {
+            let data: Vec<u8> = (0..len).map(|idx| (idx * 31) as u8).collect();
+            let padded = pad(&data, blocksize).unwrap();
+
+            assert_eq!(padded.len() % blocksize, 0);
+            assert_eq!(unpad(&padded, blocksize).unwrap(), data);
+        }
+    }
+}
+
+#[test]
+fn test_unpad_data_ending_in_padding_bytes() {
+    let data = [0x01, 0x80, 0x00, 0x80, 0x00];
+    let padded = pad(&data, 8).unwrap();
+
+    assert_eq!(unpad(&padded, 8).unwrap(), data);
+}
+
+#[test]
+fn test_unpad_invalid() {
+    // No 0x80 byte
+    assert!(unpad(&[0x00; 16], 16).is_err());
+    // Non-zero byte after the 0x80 byte
+    assert!(unpad(&[0x01, 0x80, 0x00, 0x01], 4).is_err());
+    // Padding not in the last block
+    assert!(unpad(&[0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], 4).is_err());
+    // Length is not a multiple of blocksize
+    assert!(unpad(&[0x01, 0x80, 0x00], 4).is_err());
+    assert!(unpad(&[], 4).is_err());
+}
+
+#[test]
+fn test_padme_len() {
+    assert_eq!(padme_len(0).unwrap(), 0);
+    assert_eq!(padme_len(1).unwrap(), 1);
+    assert_eq!(padme_len(3).unwrap(), 3);
+    assert_eq!(padme_len(9).unwrap(), 10);
+    assert_eq!(padme_len(100).unwrap(), 104);
+    assert_eq!(padme_len(1000).unwrap(), 1024);
+    assert_eq!(padme_len(1025).unwrap(), 1088);
+    assert_eq!(padme_len(65536).unwrap(), 65536);
+    assert_eq!(padme_len(65537).unwrap(), 67584);
+    assert_eq!(padme_len(1_000_000).unwrap(), 1_015_808);
+    assert!(padme_len(usize::max_value()).is_err());
+}
+
+#[test]
+fn test_padme_overhead() {
+    for len in 2..5000 {
+        let padded_len = padme_len(len).unwrap();
+
+        assert!(padded_len >= len);
+        assert!(padded_len as f64 <= (len as f64) * 1.12);
+        assert_eq!(padme_len(padded_len).unwrap(), padded_len);
+    }
+}
+
+#[test]
+fn test_pad_unpad_padme_roundtrip() {
+    for len in 0..1100 {
+        let data: Vec<u8> = (0..len).map(|idx| (idx * 31) as u8).collect();
+        let padded = pad_padme(&data).unwrap();
+
+        assert_eq!(padded.len(), padme_len(len + 1).unwrap());
+        assert_eq!(unpad_padme(&padded).unwrap(), data);
+    }
+}
+
+#[test]
+fn test_unpad_padme_invalid() {
+    assert!(unpad_padme(&[]).is_err());
+    assert!(unpad_padme(&[0x00; 10]).is_err());
+    assert!(unpad_padme(&[0x01, 0x80, 0x01]).is_err());
+}
+
+#[test]
+fn test_hex_roundtrip() {
+    let data: Vec<u8> = (0..=255).collect();
+
+    assert_eq!(to_hex(&[0x00, 0x0f, 0xa0, 0xff]), "000fa0ff");
+    assert_eq!(from_hex("000FA0ff").unwrap(), vec![0x00, 0x0f, 0xa0, 0xff]);
+    assert_eq!(from_hex(&to_hex(&data)).unwrap(), data);
+    assert!(from_hex("").unwrap().is_empty());
+}
+
+#[test]
+fn test_hex_all_chars() {
+    for c in 0..=255u8 {
+        let expected = match c {
+            b'0'..=b'9' => Some(c - b'0'),
+            b'a'..=b'f' => Some(c - b'a' + 10),
+            b'A'..=b'F' => Some(c - b'A' + 10),
+            _ => None,
+        };
+        let (nibble, invalid) = hex_nibble(c);
+
+        match expected {
+            Some(value) => assert_eq!((nibble, invalid), (value, 0x00)),
+            None => assert_eq!(invalid, 0xff),
+        }
+    }
+    for nibble in 0..16u8 {
+        assert_eq!(hex_char(nibble), b"0123456789abcdef"[nibble as usize] as char);
+    }
+}
+
+#[test]
+fn test_hex_err() {
+    assert!(from_hex("0").is_err());
+    assert!(from_hex("0g").is_err());
+    assert!(from_hex("é").is_err());
+}
+
+#[test]
+#[cfg(feature = "rng")]
+fn test_base64_roundtrip() {
+    assert_eq!(to_base64(b""), "");
+    assert_eq!(to_base64(b"f"), "Zg");
+    assert_eq!(to_base64(b"fo"), "Zm8");
+    assert_eq!(to_base64(b"foo"), "Zm9v");
+    assert_eq!(to_base64(b"foob"), "Zm9vYg");
+    assert_eq!(to_base64(&[0xfb, 0xff]), "+/8");
+    assert_eq!(to_base64url(&[0xfb, 0xff]), "-_8");
+    assert_eq!(to_base64url(b"foob"), "Zm9vYg");
+    assert_eq!(to_ab64(&[0xfb, 0xff]), "./8");
+
+    for len in 0..20 {
+        let data = gen_rand_key(len + 1).unwrap();
+        assert_eq!(from_base64(&to_base64(&data)).unwrap(), data);
+        assert_eq!(from_base64url(&to_base64url(&data)).unwrap(), data);
+    }
+}
+
+#[test]
+fn test_base64_all_chars() {
+    let standard = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let urlsafe = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+    for sextet in 0..64u8 {
+        assert_eq!(base64_char(sextet, BASE64_STANDARD), standard[sextet as usize] as char);
+        assert_eq!(base64_char(sextet, BASE64_URLSAFE), urlsafe[sextet as usize] as char);
+    }
+    let variants = [(BASE64_STANDARD, standard), (BASE64_URLSAFE, urlsafe)];
+    for c in 0..=255u8 {
+        for &(symbols, alphabet) in variants.iter() {
+            let (sextet, invalid) = base64_sextet(c, symbols);
+            match alphabet.iter().position(|a| *a == c) {
+                Some(pos) => assert_eq!((sextet, invalid), (pos as u8, 0x00)),
+                None => assert_eq!(invalid, 0xff),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_base64_err() {
+    assert!(from_base64("Z").is_err());
+    assert!(from_base64("Zg==").is_err());
+    assert!(from_base64("Zh").is_err());
+    assert!(from_base64("Zm9v-g").is_err());
+    assert!(from_base64url("Zm9v+g").is_err());
+    assert!(from_base64url("Zh").is_err());
+    assert!(from_base64url("Zg==").is_err());
+    assert!(from_ab64("+/8").is_err());
+    assert_eq!(from_ab64("./8").unwrap(), [0xfb, 0xff]);
+}
+
+#[test]
+#[cfg(feature = "rng")]
+fn test_base32_rfc4648() {
+    let vectors = [
+        ("", "", ""),
+        ("f", "MY", "MY======"),
+        ("fo", "MZXQ", "MZXQ===="),
+        ("foo", "MZXW6", "MZXW6==="),
+        ("foob", "MZXW6YQ", "MZXW6YQ="),
+        ("fooba", "MZXW6YTB", "MZXW6YTB"),
+        ("foobar", "MZXW6YTBOI", "MZXW6YTBOI======"),
+    ];
+
+    for &(data, unpadded, padded) in vectors.iter() {
+        assert_eq!(to_base32(data.as_bytes()), unpadded);
+        assert_eq!(from_base32(unpadded).unwrap(), data.as_bytes());
+        assert_eq!(from_base32(padded).unwrap(), data.as_bytes());
+        assert_eq!(from_base32(&unpadded.to_lowercase()).unwrap(), data.as_bytes());
+    }
+
+    for len in 0..20 {
+        let data = gen_rand_key(len + 1).unwrap();
+        assert_eq!(from_base32(&to_base32(&data)).unwrap(), data);
+    }
+}
+
+#[test]
+fn test_base32_err() {
+    // Invalid lengths
+    assert!(from_base32("M").is_err());
+    assert!(from_base32("MZX").is_err());
+    assert!(from_base32("MZXW6Y").is_err());
+    // Invalid or misplaced padding
+    assert!(from_base32("MY=").is_err());
+    assert!(from_base32("MY=======").is_err());
+    assert!(from_base32("MZXW6YTB========").is_err());
+    assert!(from_base32("MY==MY==").is_err());
+    // Characters outside the alphabet
+    assert!(from_base32("MZ1Q").is_err());
+    assert!(from_base32("MZ8Q").is_err());
+    // Non-zero leftover bits
+    assert!(from_base32("MZ").is_err());
+}
+
+#[test]
+fn test_base32_all_chars() {
+    let alphabet = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+    for quintet in 0..32u8 {
+        assert_eq!(base32_char(quintet), alphabet[quintet as usize] as char);
+    }
+    for c in 0..=255u8 {
+        let (quintet, invalid) = base32_quintet(c);
+        match alphabet.iter().position(|a| *a == c.to_ascii_uppercase()) {
+            Some(pos) => assert_eq!((quintet, invalid), (pos as u8, 0x00)),
+            None => assert_eq!(invalid, 0xff),
+        }
+    }
+}
@@ -20,13 +20,28 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+use core::blob::{self, AeadAlgorithm, BlobHeader, KdfParams};
 use core::options::KeccakVariantOption;
 use core::options::ShaVariantOption;
+use core::phc::PhcString;
+use core::types::{Nonce, Password, Salt, SecretKey, Tag};
 use core::{errors::*, util};
+use endian::{read_u32_be, write_u32_be, write_u64_le};
 use hazardous::cshake::CShake;
 use hazardous::hkdf::Hkdf;
 use hazardous::hmac::Hmac;
 use hazardous::pbkdf2::Pbkdf2;
+use hazardous::secretstream::{SecretStream, StreamTag, ABYTES, HEADERBYTES};
+use hazardous::xchacha20poly1305;
+use sha2;
+use sha2::Digest;
+use std::fs::{self, File, OpenOptions};
+#[cfg(feature = "rng")]
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{fmt, io, process};
+use zeroize::Zeroize;
 
 /// HMAC-SHA512/256.
 /// # Parameters:
@@ -46,53 +61,140 @@ use hazardous::pbkdf2::Pbkdf2;
 ///
 /// # Example:
 /// ```
+/// # #[cfg(feature = "rng")]
+/// # {
 /// use orion::default;
-/// use orion::core::util;
+/// use orion::core::types::SecretKey;
 ///
-/// let key = util::gen_rand_key(64).unwrap();
+/// let key = SecretKey::generate(64).unwrap();
 /// let msg = "Some message.".as_bytes();
 ///
 /// let hmac = default::hmac(&key, msg).unwrap();
+/// # }
 /// ```
-pub fn hmac(secret_key: &[u8], data: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
-    if secret_key.len() < 64 {
+pub fn hmac(secret_key: &SecretKey, data: &[u8]) -> Result<Tag, UnknownCryptoError> {
+    if secret_key.unprotected_as_bytes().len() < 64 {
         return Err(UnknownCryptoError);
     }
 
-    let mac = Hmac {
-        secret_key: secret_key.to_vec(),
-        data: data.to_vec(),
-        sha2: ShaVariantOption::SHA512Trunc256,
-    };
+    let mac = Hmac::new(secret_key, data, ShaVariantOption::SHA512Trunc256)?;
 
     Ok(mac.finalize())
 }
 
+/// HMAC-SHA512/256 with the secret key given as a byte slice. See `default::hmac`.
+pub fn hmac_from_slice(secret_key: &[u8], data: &[u8]) -> Result<Tag, UnknownCryptoError> {
+    hmac(&SecretKey::from_slice(secret_key)?, data)
+}
+
 /// Verify an HMAC-SHA512/256 against a key and data in constant time, with Double-HMAC Verification.
 /// # Example:
 ///
 /// ```
+/// # #[cfg(feature = "rng")]
+/// # {
 /// use orion::default;
-/// use orion::core::util;
+/// use orion::core::types::SecretKey;
 ///
-/// let key = util::gen_rand_key(64).unwrap();
+/// let key = SecretKey::generate(64).unwrap();
 /// let msg = "Some message.".as_bytes();
 ///
 /// let expected_hmac = default::hmac(&key, msg).unwrap();
 /// assert_eq!(default::hmac_verify(&expected_hmac, &key, &msg).unwrap(), true);
+/// # }
 /// ```
 pub fn hmac_verify(
-    expected_hmac: &[u8],
+    expected_hmac: &Tag,
+    secret_key: &SecretKey,
+    data: &[u8],
+) -> Result<bool, ValidationCryptoError> {
+    let mac = Hmac::new(secret_key, data, ShaVariantOption::SHA512Trunc256)?;
+
+    mac.verify(expected_hmac.as_bytes())
+}
+
+/// Verify an HMAC-SHA512/256 with the secret key given as a byte slice. See
+/// `default::hmac_verify`.
+pub fn hmac_verify_from_slice(
+    expected_hmac: &Tag,
     secret_key: &[u8],
     data: &[u8],
 ) -> Result<bool, ValidationCryptoError> {
-    let mac = Hmac {
-        secret_key: secret_key.to_vec(),
-        data: data.to_vec(),
-        sha2: ShaVariantOption::SHA512Trunc256,
-    };
+    hmac_verify(expected_hmac, &SecretKey::from_slice(secret_key)?, data)
+}
+
+/// Streaming HMAC-SHA512/256, for authenticating data that is not available all at once. The
+/// tag is the same as `default::hmac` over all the data passed to `update`.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the secret key is less than 64 bytes.
+/// - The tag passed to `verify` does not match.
+///
+/// # Example:
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::default;
+/// use orion::core::types::SecretKey;
+///
+/// let key = SecretKey::generate(64).unwrap();
+///
+/// let mut stream = default::HmacStream::new(&key).unwrap();
+/// stream.update("Some ".as_bytes());
+/// stream.update("message.".as_bytes());
+/// let tag = stream.finalize();
+///
+/// let mut stream = default::HmacStream::new(&key).unwrap();
+/// stream.update("Some message.".as_bytes());
+/// assert_eq!(stream.verify(&tag).unwrap(), true);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct HmacStream {
+    state: ::hazardous::hmac::HmacStream,
+}
+
+impl HmacStream {
+    /// Initialize a new `HmacStream` with a secret key.
+    pub fn new(secret_key: &SecretKey) -> Result<Self, UnknownCryptoError> {
+        if secret_key.unprotected_as_bytes().len() < 64 {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(HmacStream {
+            state: Hmac::init(secret_key, ShaVariantOption::SHA512Trunc256)?,
+        })
+    }
+
+    /// Add data to be authenticated.
+    pub fn update(&mut self, data: &[u8]) {
+        self.state.update(data);
+    }
+
+    /// Return the HMAC of all data passed to `update`.
+    pub fn finalize(self) -> Tag {
+        self.state.finalize()
+    }
+
+    /// Verify the HMAC of all data passed to `update` against `expected_hmac`, in constant
+    /// time and with Double-HMAC Verification.
+    pub fn verify(self, expected_hmac: &Tag) -> Result<bool, ValidationCryptoError> {
+        self.state.verify(expected_hmac.as_bytes())
+    }
+}
+
+/// Writing to an `HmacStream` is equivalent to calling `update`, so that data can be
+/// authenticated with `std::io::copy`.
+impl io::Write for HmacStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
 
-    mac.verify(&expected_hmac)
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
 }
 
 /// HKDF-HMAC-SHA512/256.
@@ -116,81 +218,295 @@ pub fn hmac_verify(
 ///
 /// # Example:
 /// ```
+/// # #[cfg(feature = "rng")]
+/// # {
 /// use orion::default;
-/// use orion::core::util;
+/// use orion::core::types::Salt;
 ///
-/// let salt = util::gen_rand_key(32).unwrap();
+/// let salt = Salt::generate(32).unwrap();
 /// let data = "Some data.".as_bytes();
 /// let info = "Some info.".as_bytes();
 ///
 /// let hkdf = default::hkdf(&salt, data, info, 32).unwrap();
+/// # }
 /// ```
 pub fn hkdf(
-    salt: &[u8],
+    salt: &Salt,
+    input: &[u8],
+    info: &[u8],
+    len: usize,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    hkdf_with_variant(salt, input, info, len, ShaVariantOption::SHA512Trunc256)
+}
+
+/// HKDF with a selectable HMAC variant, for when a specific hash function is required. The
+/// same validation as for `default::hkdf` is applied.
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the salt is less than 16 bytes.
+/// - `len` is 0 or greater than 255 times the output size of `sha2`.
+///
+/// # Example:
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::default;
+/// use orion::core::types::Salt;
+/// use orion::core::options::ShaVariantOption;
+///
+/// let salt = Salt::generate(32).unwrap();
+/// let data = "Some data.".as_bytes();
+/// let info = "Some info.".as_bytes();
+///
+/// let hkdf = default::hkdf_with_variant(&salt, data, info, 32, ShaVariantOption::SHA384);
+/// # }
+/// ```
+pub fn hkdf_with_variant(
+    salt: &Salt,
     input: &[u8],
     info: &[u8],
     len: usize,
+    sha2: ShaVariantOption,
 ) -> Result<Vec<u8>, UnknownCryptoError> {
-    if salt.len() < 16 {
+    if salt.as_bytes().len() < 16 {
         return Err(UnknownCryptoError);
     }
 
-    let hkdf = Hkdf {
-        salt: salt.to_vec(),
-        ikm: input.to_vec(),
-        info: info.to_vec(),
-        length: len,
-        hmac: ShaVariantOption::SHA512Trunc256,
-    };
+    let hkdf = Hkdf::new(salt.as_bytes(), input, info, len, sha2)?;
 
     hkdf.derive_key()
 }
 
+/// HKDF-HMAC-SHA512/256 with the salt given as a byte slice. See `default::hkdf`.
+pub fn hkdf_from_slice(
+    salt: &[u8],
+    input: &[u8],
+    info: &[u8],
+    len: usize,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    hkdf(&Salt::from_slice(salt)?, input, info, len)
+}
+
+/// Derive a 32 byte subkey from a master key, using HKDF-HMAC-SHA512/256.
+/// # About:
+/// This is meant for deriving many independent keys, such as a database key and a cookie key,
+/// from a single master key.
+/// - `subkey_id` selects the subkey, and `context` describes what the subkeys are used for.
+/// - The HKDF info is `le64(subkey_id) || context`, and no salt is used.
+///
+/// Subkeys derived with different `subkey_id` or `context` values are independent of each other.
+///
+/// # Parameters:
+/// - `master_key`: The master key
+/// - `subkey_id`: Identifier of the subkey
+/// - `context`: Application specific context (such as "cookies")
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the master key is less than 32 bytes.
+/// - `context` is empty.
+///
+/// # Security:
+/// The master key should always be generated using a CSPRNG, with `SecretKey::generate`.
+///
+/// # Example:
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::default;
+/// use orion::core::types::SecretKey;
+///
+/// let master_key = SecretKey::generate(32).unwrap();
+///
+/// let db_key = default::derive_subkey(&master_key, 1, "Database".as_bytes()).unwrap();
+/// let cookie_key = default::derive_subkey(&master_key, 2, "Cookies".as_bytes()).unwrap();
+/// # }
+/// ```
+pub fn derive_subkey(
+    master_key: &SecretKey,
+    subkey_id: u64,
+    context: &[u8],
+) -> Result<SecretKey, UnknownCryptoError> {
+    if master_key.unprotected_as_bytes().len() < 32 || context.is_empty() {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut info = vec![0u8; 8];
+    write_u64_le(&mut info, subkey_id);
+    info.extend_from_slice(context);
+
+    let hkdf = Hkdf::new(
+        &[],
+        master_key.unprotected_as_bytes(),
+        &info,
+        32,
+        ShaVariantOption::SHA512Trunc256,
+    )?;
+    let mut dk = hkdf.derive_key()?;
+    let subkey = SecretKey::from_slice(&dk);
+    dk.zeroize();
+
+    subkey
+}
+
+/// HKDF salt used by `derive_path`, keeping path keys apart from `derive_subkey` keys.
+const KEY_PATH_SALT: &[u8] = b"orion key path";
+
+/// Derive a 32 byte key from a master key and a `/` separated path, such as
+/// `"service/db/2024"`, using HKDF-HMAC-SHA512/256.
+/// # About:
+/// This is meant for organizing many derived keys in a hierarchy, without managing a salt
+/// or subkey id for each of them.
+/// - Each path component derives the next key from the previous one. The HKDF info is
+/// `le64(len(component)) || component`, and the salt is `"orion key path"`.
+/// - Deriving `"a"` and then `"b"` from the result is the same as deriving `"a/b"`, so a
+/// service can be handed its own subtree of keys.
+///
+/// The same master key and path always give the same key. Different paths give
+/// independent keys.
+///
+/// # Parameters:
+/// - `master_key`: The master key
+/// - `path`: Path of the key, with components separated by `/`
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the master key is less than 32 bytes.
+/// - `path` is empty, or has an empty component (such as `"a//b"` or `"/a"`).
+///
+/// # Security:
+/// The master key should always be generated using a CSPRNG, with `SecretKey::generate`.
+///
+/// # Example:
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::default;
+/// use orion::core::types::SecretKey;
+///
+/// let master_key = SecretKey::generate(32).unwrap();
+///
+/// let db_key = default::derive_path(&master_key, "service/db/2024").unwrap();
+///
+/// let service_key = default::derive_path(&master_key, "service").unwrap();
+/// assert_eq!(db_key, default::derive_path(&service_key, "db/2024").unwrap());
+/// # }
+/// ```
+pub fn derive_path(master_key: &SecretKey, path: &str) -> Result<SecretKey, UnknownCryptoError> {
+    if master_key.unprotected_as_bytes().len() < 32 || path.is_empty() {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut dk = master_key.unprotected_as_bytes().to_vec();
+    for component in path.split('/') {
+        if component.is_empty() {
+            dk.zeroize();
+            return Err(UnknownCryptoError);
+        }
+
+        let mut info = vec![0u8; 8];
+        write_u64_le(&mut info, component.len() as u64);
+        info.extend_from_slice(component.as_bytes());
+
+        let hkdf = Hkdf::new(KEY_PATH_SALT, &dk, &info, 32, ShaVariantOption::SHA512Trunc256)?;
+        dk.zeroize();
+        dk = hkdf.derive_key()?;
+    }
+    let key = SecretKey::from_slice(&dk);
+    dk.zeroize();
+
+    key
+}
+
 /// Verify an HKDF-HMAC-SHA512/256 derived key in constant time. Both derived keys must
 /// be of equal length.
 /// # Example:
 ///
 /// ```
+/// # #[cfg(feature = "rng")]
+/// # {
 /// use orion::default;
-/// use orion::core::util;
+/// use orion::core::types::Salt;
 ///
-/// let salt = util::gen_rand_key(32).unwrap();
+/// let salt = Salt::generate(32).unwrap();
 /// let data = "Some data.".as_bytes();
 /// let info = "Some info.".as_bytes();
 ///
 /// let hkdf = default::hkdf(&salt, data, info, 32).unwrap();
 /// assert_eq!(default::hkdf_verify(&hkdf, &salt, data, info, 32).unwrap(), true);
+/// # }
 /// ```
 pub fn hkdf_verify(
     expected_dk: &[u8],
-    salt: &[u8],
+    salt: &Salt,
     input: &[u8],
     info: &[u8],
     len: usize,
 ) -> Result<bool, ValidationCryptoError> {
-    let hkdf = Hkdf {
-        salt: salt.to_vec(),
-        ikm: input.to_vec(),
-        info: info.to_vec(),
-        length: len,
-        hmac: ShaVariantOption::SHA512Trunc256,
-    };
+    hkdf_verify_with_variant(expected_dk, salt, input, info, len, ShaVariantOption::SHA512Trunc256)
+}
+
+/// Verify a derived key created with `default::hkdf_with_variant` in constant time.
+/// # Example:
+///
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::default;
+/// use orion::core::types::Salt;
+/// use orion::core::options::ShaVariantOption;
+///
+/// let salt = Salt::generate(32).unwrap();
+/// let data = "Some data.".as_bytes();
+/// let info = "Some info.".as_bytes();
+/// let sha2 = ShaVariantOption::SHA512;
+///
+/// let hkdf = default::hkdf_with_variant(&salt, data, info, 32, sha2).unwrap();
+/// assert!(default::hkdf_verify_with_variant(&hkdf, &salt, data, info, 32, sha2).unwrap());
+/// # }
+/// ```
+pub fn hkdf_verify_with_variant(
+    expected_dk: &[u8],
+    salt: &Salt,
+    input: &[u8],
+    info: &[u8],
+    len: usize,
+    sha2: ShaVariantOption,
+) -> Result<bool, ValidationCryptoError> {
+    if salt.as_bytes().len() < 16 {
+        return Err(ValidationCryptoError);
+    }
+
+    let hkdf = Hkdf::new(salt.as_bytes(), input, info, len, sha2)?;
 
     hkdf.verify(&expected_dk)
 }
 
+/// Verify an HKDF-HMAC-SHA512/256 derived key with the salt given as a byte slice. See
+/// `default::hkdf_verify`.
+pub fn hkdf_verify_from_slice(
+    expected_dk: &[u8],
+    salt: &[u8],
+    input: &[u8],
+    info: &[u8],
+    len: usize,
+) -> Result<bool, ValidationCryptoError> {
+    hkdf_verify(expected_dk, &Salt::from_slice(salt)?, input, info, len)
+}
+
+#[cfg(feature = "rng")]
 /// PBKDF2-HMAC-SHA512/256. Suitable for password storage.
 /// # About:
 /// This is meant to be used for password storage.
 /// - A salt of 32 bytes is automatically generated.
 /// - The derived key length is set to 32.
 /// - 512.000 iterations are used.
-/// - The salt is prepended to the password before being passed to the PBKDF2 function.
-/// - A byte vector of 64 bytes is returned.
+/// - A PHC string of the form `$pbkdf2-sha512-256$i=<iterations>$<salt>$<hash>` is returned,
+/// the same as from `default::password_hash`.
 ///
-/// The first 32 bytes of this vector is the salt used to derive the key and the last 32 bytes
-/// is the actual derived key. When using this function with `default::pbkdf2_verify`
-/// then the seperation of salt and password are automatically handeled.
+/// The string names the PBKDF2 variant and holds the iteration count and salt, so
+/// `default::pbkdf2_verify` needs nothing else to verify a password. Increasing the iteration
+/// count later does not break verification of existing hashes.
 ///
 /// # Exceptions:
 /// An exception will be thrown if:
@@ -200,84 +516,90 @@ pub fn hkdf_verify(
 ///
 /// ```
 /// use orion::default;
+/// use orion::core::types::Password;
 ///
-/// let password = "Secret password".as_bytes();
+/// let password = Password::from_slice("Secret password".as_bytes()).unwrap();
 ///
-/// let derived_password = default::pbkdf2(password);
+/// let derived_password = default::pbkdf2(&password);
 /// ```
-pub fn pbkdf2(password: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
-    if password.len() < 14 {
-        return Err(UnknownCryptoError);
-    }
-
-    let salt: Vec<u8> = util::gen_rand_key(32).unwrap();
-    // Prepend salt to password before deriving key
-    let mut pass_extented: Vec<u8> = Vec::new();
-    pass_extented.extend_from_slice(&salt);
-    pass_extented.extend_from_slice(password);
-    // Prepend salt to derived key
-    let mut dk = Vec::new();
-    dk.extend_from_slice(&salt);
-
-    let pbkdf2_dk = Pbkdf2 {
-        password: pass_extented,
-        salt,
-        iterations: 512_000,
-        dklen: 32,
-        hmac: ShaVariantOption::SHA512Trunc256,
-    };
-
-    // Output format: First 32 bytes are the salt, last 32 bytes are the derived key
-    dk.extend_from_slice(&pbkdf2_dk.derive_key().unwrap());
-
-    if dk.len() != 64 {
-        return Err(UnknownCryptoError);
-    }
+pub fn pbkdf2(password: &Password) -> Result<String, UnknownCryptoError> {
+    password_hash(password)
+}
 
-    Ok(dk)
+#[cfg(feature = "rng")]
+/// PBKDF2-HMAC-SHA512/256 with the password given as a byte slice. See `default::pbkdf2`.
+pub fn pbkdf2_from_slice(password: &[u8]) -> Result<String, UnknownCryptoError> {
+    pbkdf2(&Password::from_slice(password)?)
 }
 
 /// Verify PBKDF2-HMAC-SHA512/256 derived key in constant time.
 /// # About:
-/// This function is meant to be used with the `default::pbkdf2` function in orion's default API. It can be
-/// used without it, but then the `expected_dk` passed to the function must be constructed just as in
-/// `default::pbkdf2`. See documention on `default::pbkdf2` for details on this.
+/// This function is meant to be used with the `default::pbkdf2` function in orion's default API.
+/// `expected_dk` is the PHC string returned by `default::pbkdf2`, and the iteration count and
+/// salt are read from it. This is the same as `default::password_hash_verify`.
+///
+/// Keys returned by `default::pbkdf2` before it returned PHC strings must be verified with
+/// `default::pbkdf2_verify_legacy`.
 /// # Exceptions:
 /// An exception will be thrown if:
-/// - The expected derived key length is not 64 bytes.
+/// - `expected_dk` is not a PHC string as returned by `default::pbkdf2`.
+/// - The iteration count is less than `PBKDF2_MIN_ITERATIONS` or greater than
+/// `PBKDF2_MAX_ITERATIONS`.
+/// - The password does not match.
 /// # Example:
 ///
 /// ```
+/// # #[cfg(feature = "rng")]
+/// # {
 /// use orion::default;
+/// use orion::core::types::Password;
 ///
-/// let password = "Secret password".as_bytes();
+/// let password = Password::from_slice("Secret password".as_bytes()).unwrap();
 ///
-/// let derived_password = default::pbkdf2(password).unwrap();
-/// assert_eq!(default::pbkdf2_verify(&derived_password, password).unwrap(), true);
+/// let derived_password = default::pbkdf2(&password).unwrap();
+/// assert_eq!(default::pbkdf2_verify(&derived_password, &password).unwrap(), true);
+/// # }
 /// ```
-pub fn pbkdf2_verify(expected_dk: &[u8], password: &[u8]) -> Result<bool, ValidationCryptoError> {
+pub fn pbkdf2_verify(
+    expected_dk: &str,
+    password: &Password,
+) -> Result<bool, ValidationCryptoError> {
+    password_hash_verify(expected_dk, password)
+}
+
+/// Verify a PBKDF2-HMAC-SHA512/256 derived key with the password given as a byte slice. See
+/// `default::pbkdf2_verify`.
+pub fn pbkdf2_verify_from_slice(
+    expected_dk: &str,
+    password: &[u8],
+) -> Result<bool, ValidationCryptoError> {
+    pbkdf2_verify(expected_dk, &Password::from_slice(password)?)
+}
+
+/// Verify a 64 byte PBKDF2-HMAC-SHA512/256 derived key, as returned by `default::pbkdf2` before
+/// it returned PHC strings, in constant time.
+/// # About:
+/// `expected_dk` is the 32 byte salt followed by the 32 byte derived key, where the salt was
+/// prepended to the password before being passed to the PBKDF2 function, with 512.000
+/// iterations. New keys should be created with `default::pbkdf2` and verified with
+/// `default::pbkdf2_verify`.
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of `expected_dk` is not 64 bytes.
+/// - The password does not match.
+pub fn pbkdf2_verify_legacy(
+    expected_dk: &[u8],
+    password: &Password,
+) -> Result<bool, ValidationCryptoError> {
     if expected_dk.len() != 64 {
         return Err(ValidationCryptoError);
     }
 
     let salt: Vec<u8> = expected_dk[..32].to_vec();
-    let mut pass_extented: Vec<u8> = Vec::new();
-    pass_extented.extend_from_slice(&salt);
-    pass_extented.extend_from_slice(password);
-
     // Prepend salt to derived key
     let mut dk = Vec::new();
     dk.extend_from_slice(&salt);
-
-    let pbkdf2_dk = Pbkdf2 {
-        password: pass_extented,
-        salt,
-        iterations: 512_000,
-        dklen: 32,
-        hmac: ShaVariantOption::SHA512Trunc256,
-    };
-
-    dk.extend_from_slice(&pbkdf2_dk.derive_key().unwrap());
+    dk.extend_from_slice(&pbkdf2_salted(password, &salt, 512_000)?);
 
     if util::compare_ct(&dk, expected_dk).is_err() {
         Err(ValidationCryptoError)
@@ -286,75 +608,1705 @@ pub fn pbkdf2_verify(expected_dk: &[u8], password: &[u8]) -> Result<bool, Valida
     }
 }
 
-/// cSHAKE256.
+/// Derive a 32 byte key with PBKDF2-HMAC-SHA512/256, where the salt is prepended to the
+/// password before being passed to the PBKDF2 function.
+fn pbkdf2_salted(
+    password: &Password,
+    salt: &[u8],
+    iterations: usize,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    let mut pass_extented: Vec<u8> = Vec::new();
+    pass_extented.extend_from_slice(salt);
+    pass_extented.extend_from_slice(password.unprotected_as_bytes());
+
+    let password = Password::from_slice(&pass_extented)?;
+    pass_extented.zeroize();
+
+    let pbkdf2_dk = Pbkdf2::new(&password, salt, iterations, 32, ShaVariantOption::SHA512Trunc256)?;
+
+    pbkdf2_dk.derive_key()
+}
+
+/// The minimum iteration count accepted by `default::pbkdf2_with_iterations`.
+pub const PBKDF2_MIN_ITERATIONS: usize = 100_000;
+
+/// The maximum iteration count accepted by `default::pbkdf2_with_iterations`, and when verifying
+/// a PHC string. This bounds the work an attacker can cause by supplying a hash to verify.
+pub const PBKDF2_MAX_ITERATIONS: usize = 10_000_000;
+
+#[cfg(feature = "rng")]
+/// PBKDF2-HMAC-SHA512/256 with a configurable iteration count. Suitable for password storage.
 /// # About:
-/// - Output length is 64
+/// This works like `default::pbkdf2`, except that the iteration count is chosen by the caller,
+/// so that it can be tuned for the hardware it runs on.
+/// - A salt of 32 bytes is automatically generated.
+/// - The derived key length is set to 32.
+/// - A PHC string of the form `$pbkdf2-sha512-256$i=<iterations>$<salt>$<hash>` is returned,
+/// the same as from `default::pbkdf2`.
 ///
-/// # Parameters:
-/// - `input`:  The main input string
-/// - `custom`: Customization string
+/// The iteration count is stored in the string, so increasing it later does not break
+/// verification of existing keys.
 ///
-/// "The customization string is intended to avoid a collision between these two cSHAKE values—it
-/// will be very difficult for an attacker to somehow force one computation (the email signature)
-/// to yield the same result as the other computation (the key fingerprint) if different values
-/// of S are used." See [NIST SP 800-185](https://csrc.nist.gov/publications/detail/sp/800-185/final) for more information.
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the password is less than 14 bytes.
+/// - `iterations` is less than `PBKDF2_MIN_ITERATIONS` or greater than `PBKDF2_MAX_ITERATIONS`.
 ///
-/// ### Note:
-/// The cSHAKE implementation currently relies on the `tiny-keccak` crate. Currently this crate
-/// will produce ***incorrect results on big-endian based systems***. See [issue here](https://github.com/debris/tiny-keccak/issues/15).
+/// # Example:
 ///
+/// ```
+/// use orion::default;
+/// use orion::core::types::Password;
+///
+/// let password = Password::from_slice("Secret password".as_bytes()).unwrap();
+///
+/// let derived_password = default::pbkdf2_with_iterations(&password, 1_000_000).unwrap();
+/// assert!(derived_password.starts_with("$pbkdf2-sha512-256$i=1000000$"));
+/// ```
+pub fn pbkdf2_with_iterations(
+    password: &Password,
+    iterations: usize,
+) -> Result<String, UnknownCryptoError> {
+    if iterations < PBKDF2_MIN_ITERATIONS || iterations > PBKDF2_MAX_ITERATIONS {
+        return Err(UnknownCryptoError);
+    }
+
+    password_hash_with_iterations(password, iterations)
+}
+
+/// Verify a PBKDF2-HMAC-SHA512/256 derived key created with `default::pbkdf2_with_iterations`,
+/// in constant time. This is the same as `default::pbkdf2_verify`.
 /// # Exceptions:
 /// An exception will be thrown if:
-/// - `custom` is empty
-/// - If the length of `custom` is greater than 65536
+/// - `expected_dk` is not a PHC string as returned by `default::pbkdf2_with_iterations`.
+/// - The iteration count is less than `PBKDF2_MIN_ITERATIONS` or greater than
+/// `PBKDF2_MAX_ITERATIONS`.
+/// - The password does not match.
+/// # Example:
+///
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::default;
+/// use orion::core::types::Password;
+///
+/// let password = Password::from_slice("Secret password".as_bytes()).unwrap();
+///
+/// let derived_password = default::pbkdf2_with_iterations(&password, 1_000_000).unwrap();
+/// assert_eq!(
+///     default::pbkdf2_with_iterations_verify(&derived_password, &password).unwrap(),
+///     true
+/// );
+/// # }
+/// ```
+pub fn pbkdf2_with_iterations_verify(
+    expected_dk: &str,
+    password: &Password,
+) -> Result<bool, ValidationCryptoError> {
+    password_hash_verify(expected_dk, password)
+}
+
+/// PHC identifier for PBKDF2-HMAC-SHA512/256, used by `default::password_hash`.
+const PASSWORD_HASH_ID: &str = "pbkdf2-sha512-256";
+
+#[cfg(feature = "rng")]
+/// Hash a password with PBKDF2-HMAC-SHA512/256, returning a PHC string. Suitable for
+/// password storage.
+/// # About:
+/// - A salt of 32 bytes is automatically generated.
+/// - The derived key length is set to 32.
+/// - 512.000 iterations are used.
+/// - The salt and derived key are base64 encoded without padding.
+///
+/// The returned string has the form `$pbkdf2-sha512-256$i=<iterations>$<salt>$<hash>` and
+/// contains everything needed to verify the password with `default::password_hash_verify`,
+/// so only this single string needs to be stored. Because the parameters are read from the
+/// string when verifying, hashes created with other parameters remain verifiable.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the password is less than 14 bytes.
 ///
 /// # Example:
 /// ```
 /// use orion::default;
+/// use orion::core::types::Password;
 ///
-/// let data = "Not so random data".as_bytes();
-/// let custom = "Custom".as_bytes();
+/// let password = Password::from_slice("Secret password".as_bytes()).unwrap();
 ///
-/// let hash = default::cshake(data, custom).unwrap();
+/// let hash = default::password_hash(&password).unwrap();
+/// assert!(hash.starts_with("$pbkdf2-sha512-256$i=512000$"));
 /// ```
-pub fn cshake(input: &[u8], custom: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
-    let cshake = CShake {
-        input: input.to_vec(),
-        name: Vec::new(),
-        custom: custom.to_vec(),
-        length: 64,
-        keccak: KeccakVariantOption::KECCAK512,
-    };
+pub fn password_hash(password: &Password) -> Result<String, UnknownCryptoError> {
+    password_hash_with_iterations(password, 512_000)
+}
+
+#[cfg(feature = "rng")]
+/// Hash a password with PBKDF2-HMAC-SHA512/256 and the given iteration count, returning a PHC
+/// string. See `default::password_hash`.
+fn password_hash_with_iterations(
+    password: &Password,
+    iterations: usize,
+) -> Result<String, UnknownCryptoError> {
+    if password.unprotected_as_bytes().len() < 14 {
+        return Err(UnknownCryptoError);
+    }
+
+    let salt = Salt::generate(32)?;
+    let pbkdf2_dk = Pbkdf2::new(
+        password,
+        salt.as_bytes(),
+        iterations,
+        32,
+        ShaVariantOption::SHA512Trunc256,
+    )?;
+
+    let phc = PhcString::new(
+        PASSWORD_HASH_ID,
+        None,
+        &[("i", &iterations.to_string())],
+        salt.as_bytes(),
+        &pbkdf2_dk.derive_key()?,
+    )?;
+
+    Ok(phc.to_string())
+}
+
+/// Verify a password against a PHC string created with `default::password_hash`, in
+/// constant time.
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The PHC string is malformed or uses another algorithm.
+/// - The iteration count is less than `PBKDF2_MIN_ITERATIONS` or greater than
+/// `PBKDF2_MAX_ITERATIONS`.
+/// - The salt is less than 16 bytes, or the hash is not 32 bytes.
+/// - The password does not match.
+///
+/// # Example:
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::default;
+/// use orion::core::types::Password;
+///
+/// let password = Password::from_slice("Secret password".as_bytes()).unwrap();
+///
+/// let hash = default::password_hash(&password).unwrap();
+/// assert_eq!(default::password_hash_verify(&hash, &password).unwrap(), true);
+/// # }
+/// ```
+pub fn password_hash_verify(
+    hash: &str,
+    password: &Password,
+) -> Result<bool, ValidationCryptoError> {
+    let phc = PhcString::parse(hash)?;
+    if phc.id() != PASSWORD_HASH_ID || phc.version().is_some() || phc.params().len() != 1 {
+        return Err(ValidationCryptoError);
+    }
+
+    let iterations = phc.param_decimal("i")?;
+    if iterations < PBKDF2_MIN_ITERATIONS || iterations > PBKDF2_MAX_ITERATIONS {
+        return Err(ValidationCryptoError);
+    }
+    // The hash must be as long as the one `default::password_hash` writes, as a truncated
+    // hash would otherwise be accepted
+    let salt = match (phc.salt(), phc.hash()) {
+        (Some(salt), Some(hash)) if salt.len() >= 16 && hash.len() == 32 => salt,
+        _ => return Err(ValidationCryptoError),
+    };
+
+    let pbkdf2_dk = Pbkdf2::new(password, salt, iterations, 32, ShaVariantOption::SHA512Trunc256)?;
+
+    phc.verify_hash(&pbkdf2_dk.derive_key()?)
+}
+
+/// SHA-512/256, for hashing data that is not secret.
+/// # About:
+/// - Output length is 32
+///
+/// This is meant for fingerprinting and checksums. To authenticate data use `default::hmac`,
+/// and to store passwords use `default::password_hash`.
+///
+/// # Example:
+/// ```
+/// use orion::default;
+///
+/// let digest = default::hash("Some data".as_bytes());
+/// assert_eq!(digest.len(), 32);
+/// ```
+pub fn hash(data: &[u8]) -> [u8; 32] {
+    let mut stream = HashStream::new();
+    stream.update(data);
+
+    stream.finalize()
+}
+
+/// Streaming SHA-512/256, for hashing data that is not available all at once. The digest is
+/// the same as `default::hash` over all the data passed to `update`.
+///
+/// # Example:
+/// ```
+/// use orion::default;
+///
+/// let mut stream = default::HashStream::new();
+/// stream.update("Some ".as_bytes());
+/// stream.update("data".as_bytes());
+///
+/// assert_eq!(stream.finalize(), default::hash("Some data".as_bytes()));
+/// ```
+#[derive(Clone, Default)]
+pub struct HashStream {
+    state: sha2::Sha512_256,
+}
+
+impl fmt::Debug for HashStream {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "HashStream {{ state: SHA512Trunc256 }}")
+    }
+}
+
+impl HashStream {
+    /// Initialize a new `HashStream`.
+    pub fn new() -> Self {
+        HashStream::default()
+    }
+
+    /// Hash more data.
+    pub fn update(&mut self, data: &[u8]) {
+        self.state.update(data);
+    }
+
+    /// Return the digest of all data passed to `update`.
+    pub fn finalize(self) -> [u8; 32] {
+        let mut digest = [0u8; 32];
+        digest.copy_from_slice(&self.state.finalize());
+
+        digest
+    }
+}
+
+/// Writing to a `HashStream` is equivalent to calling `update`, so that data can be hashed
+/// with `std::io::copy`.
+impl io::Write for HashStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Hash the contents of a file with SHA-512/256. The file is read in chunks, so it does not
+/// need to fit in memory. The digest is the same as `default::hash` over the file contents.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The file cannot be opened or read.
+///
+/// # Example:
+/// ```no_run
+/// use orion::default;
+///
+/// let digest = default::hash_file("backup.tar").unwrap();
+/// ```
+pub fn hash_file<P: AsRef<Path>>(path: P) -> io::Result<[u8; 32]> {
+    let mut file = File::open(path)?;
+    let mut stream = HashStream::new();
+    io::copy(&mut file, &mut stream)?;
+
+    Ok(stream.finalize())
+}
+
+/// Authenticate the contents of a file with HMAC-SHA512/256. The file is read in chunks, so it
+/// does not need to fit in memory. The tag is the same as `default::hmac` over the file
+/// contents.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the secret key is less than 64 bytes.
+/// - The file cannot be opened or read.
+///
+/// # Example:
+/// ```no_run
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::default;
+/// use orion::core::types::SecretKey;
+///
+/// let key = SecretKey::generate(64).unwrap();
+/// let tag = default::hmac_file(&key, "backup.tar").unwrap();
+/// # }
+/// ```
+pub fn hmac_file<P: AsRef<Path>>(secret_key: &SecretKey, path: P) -> io::Result<Tag> {
+    let mut stream = HmacStream::new(secret_key)?;
+    let mut file = File::open(path)?;
+    io::copy(&mut file, &mut stream)?;
+
+    Ok(stream.finalize())
+}
+
+/// cSHAKE256.
+/// # About:
+/// - Output length is 64
+///
+/// # Parameters:
+/// - `input`:  The main input string
+/// - `custom`: Customization string
+///
+/// "The customization string is intended to avoid a collision between these two cSHAKE values—it
+/// will be very difficult for an attacker to somehow force one computation (the email signature)
+/// to yield the same result as the other computation (the key fingerprint) if different values
+/// of S are used." See [NIST SP 800-185](https://csrc.nist.gov/publications/detail/sp/800-185/final) for more information.
+///
+/// ### Note:
+/// The cSHAKE implementation currently relies on the `tiny-keccak` crate. Currently this crate
+/// will produce ***incorrect results on big-endian based systems***. See [issue here](https://github.com/debris/tiny-keccak/issues/15).
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `custom` is empty
+/// - If the length of `custom` is greater than 65536
+///
+/// # Example:
+/// ```
+/// use orion::default;
+///
+/// let data = "Not so random data".as_bytes();
+/// let custom = "Custom".as_bytes();
+///
+/// let hash = default::cshake(data, custom).unwrap();
+/// ```
+pub fn cshake(input: &[u8], custom: &[u8]) -> Result<[u8; 64], UnknownCryptoError> {
+    let cshake = CShake::new(input, &[], custom, 64, KeccakVariantOption::KECCAK512)?;
+    let mut hash = [0u8; 64];
+    cshake.finalize_into(&mut hash)?;
+
+    Ok(hash)
+}
+
+/// Verify a cSHAKE256 hash in constant time. The expected hash must be of length 64.
+/// # Example:
+///
+/// ```
+/// use orion::default;
+///
+/// let data = "Not so random data".as_bytes();
+/// let custom = "Custom".as_bytes();
+///
+/// let hash = default::cshake(data, custom).unwrap();
+/// assert_eq!(default::cshake_verify(&hash, data, custom).unwrap(), true);
+/// ```
+pub fn cshake_verify(
+    expected: &[u8],
+    input: &[u8],
+    custom: &[u8],
+) -> Result<bool, ValidationCryptoError> {
+    let cshake = CShake::new(input, &[], custom, 64, KeccakVariantOption::KECCAK512)?;
+
+    cshake.verify(&expected)
+}
+
+/// cSHAKE256 with a selectable output length. See `default::cshake`.
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `custom` is empty
+/// - If the length of `custom` is greater than 65536
+/// - `length` is less than 16 or greater than 1024
+///
+/// # Example:
+/// ```
+/// use orion::default;
+///
+/// let data = "Not so random data".as_bytes();
+/// let custom = "Custom".as_bytes();
+///
+/// let hash = default::cshake_with_length(data, custom, 32).unwrap();
+/// assert_eq!(hash.len(), 32);
+/// ```
+pub fn cshake_with_length(
+    input: &[u8],
+    custom: &[u8],
+    length: usize,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    if length < 16 || length > 1024 {
+        return Err(UnknownCryptoError);
+    }
+
+    let cshake = CShake::new(input, &[], custom, length, KeccakVariantOption::KECCAK512)?;
+
+    cshake.finalize()
+}
+
+/// Verify a cSHAKE256 hash created with `default::cshake_with_length` in constant time. The
+/// output length is taken from the expected hash, which must be between 16 and 1024 bytes.
+/// # Example:
+///
+/// ```
+/// use orion::default;
+///
+/// let data = "Not so random data".as_bytes();
+/// let custom = "Custom".as_bytes();
+///
+/// let hash = default::cshake_with_length(data, custom, 32).unwrap();
+/// assert_eq!(default::cshake_verify_with_length(&hash, data, custom).unwrap(), true);
+/// ```
+pub fn cshake_verify_with_length(
+    expected: &[u8],
+    input: &[u8],
+    custom: &[u8],
+) -> Result<bool, ValidationCryptoError> {
+    if expected.len() < 16 || expected.len() > 1024 {
+        return Err(ValidationCryptoError);
+    }
+
+    let cshake = CShake::new(input, &[], custom, expected.len(), KeccakVariantOption::KECCAK512)?;
+
+    cshake.verify(&expected)
+}
+
+#[cfg(feature = "rng")]
+/// Commit to a value using a salted cSHAKE256 hash.
+/// # About:
+/// - A random opening value of 32 bytes is automatically generated.
+/// - The commitment is cSHAKE256(opening || data) with the customization string
+/// "orion commitment" and an output length of 64.
+/// - A tuple of (commitment, opening) is returned.
+///
+/// The commitment can be published straight away. To reveal, the committer publishes `data`
+/// together with the opening value, which anyone can check against the commitment
+/// with `default::commit_verify`. The opening value must be kept secret until then, since anyone
+/// holding it can test guesses of `data` against the commitment.
+///
+/// # Example:
+/// ```
+/// use orion::default;
+///
+/// let bid = "Bid: 300".as_bytes();
+///
+/// let (commitment, opening) = default::commit(bid).unwrap();
+/// ```
+pub fn commit(data: &[u8]) -> Result<(Vec<u8>, Vec<u8>), UnknownCryptoError> {
+    let opening: Vec<u8> = util::gen_rand_key(32)?;
+
+    let mut input: Vec<u8> = Vec::new();
+    input.extend_from_slice(&opening);
+    input.extend_from_slice(data);
+
+    let cshake = CShake::new(
+        &input,
+        &[],
+        "orion commitment".as_bytes(),
+        64,
+        KeccakVariantOption::KECCAK512,
+    )?;
+
+    Ok((cshake.finalize()?, opening))
+}
+
+/// Verify that a commitment opens to the given data, in constant time.
+/// # About:
+/// This function is meant to be used with the `default::commit` function in orion's default API.
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The opening value is not 32 bytes.
+/// - The commitment is not 64 bytes.
+/// # Example:
+///
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::default;
+///
+/// let bid = "Bid: 300".as_bytes();
+///
+/// let (commitment, opening) = default::commit(bid).unwrap();
+/// assert_eq!(default::commit_verify(&commitment, &opening, bid).unwrap(), true);
+/// # }
+/// ```
+pub fn commit_verify(
+    commitment: &[u8],
+    opening: &[u8],
+    data: &[u8],
+) -> Result<bool, ValidationCryptoError> {
+    if opening.len() != 32 || commitment.len() != 64 {
+        return Err(ValidationCryptoError);
+    }
+
+    let mut input: Vec<u8> = Vec::new();
+    input.extend_from_slice(opening);
+    input.extend_from_slice(data);
+
+    let cshake = CShake::new(
+        &input,
+        &[],
+        "orion commitment".as_bytes(),
+        64,
+        KeccakVariantOption::KECCAK512,
+    )?;
+
+    cshake.verify(commitment)
+}
+
+#[cfg(feature = "rng")]
+/// Authenticated encryption using XChaCha20-Poly1305.
+/// # About:
+/// - A random nonce of 24 bytes is automatically generated.
+/// - The output is a blob in the format of `core::blob`: a 31 byte header with the algorithm
+/// and nonce, followed by the ciphertext and the 16 byte Poly1305 tag.
+/// - The output is therefore 47 bytes longer than the plaintext.
+///
+/// When using this function with `default::decrypt` the header and tag are handled
+/// automatically.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the secret key is not 32 bytes.
+///
+/// # Security:
+/// The secret key should always be generated using a CSPRNG. `SecretKey::generate` can be
+/// used for this.
+///
+/// # Example:
+/// ```
+/// use orion::default;
+/// use orion::core::types::SecretKey;
+///
+/// let key = SecretKey::generate(32).unwrap();
+/// let ciphertext = default::encrypt(&key, "Secret message".as_bytes()).unwrap();
+/// ```
+pub fn encrypt(secret_key: &SecretKey, plaintext: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+    let nonce = Nonce::generate(24)?;
+    let header = BlobHeader::new(AeadAlgorithm::XChaCha20Poly1305, KdfParams::None, nonce)?;
+
+    blob::seal(secret_key, &header, plaintext)
+}
+
+/// Authenticated decryption using XChaCha20-Poly1305.
+/// # About:
+/// This function is meant to be used with the `default::encrypt` function in orion's default
+/// API. The tag is verified, in constant time, before anything is decrypted.
+///
+/// Ciphertexts from before the `core::blob` format, which are the 24 byte nonce followed by
+/// the ciphertext and tag, can also be decrypted. So can those of `default::encrypt_committing`,
+/// but `default::decrypt_committing` must be used where the key commitment is relied on.
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the secret key is not 32 bytes.
+/// - The ciphertext is less than 40 bytes.
+/// - The ciphertext fails authentication.
+/// # Example:
+///
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::default;
+/// use orion::core::types::SecretKey;
+///
+/// let key = SecretKey::generate(32).unwrap();
+/// let ciphertext = default::encrypt(&key, "Secret message".as_bytes()).unwrap();
+///
+/// let plaintext = default::decrypt(&key, &ciphertext).unwrap();
+/// assert_eq!(plaintext, "Secret message".as_bytes());
+/// # }
+/// ```
+pub fn decrypt(
+    secret_key: &SecretKey,
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, ValidationCryptoError> {
+    if let Ok((header, header_len)) = BlobHeader::parse(ciphertext) {
+        if *header.kdf() == KdfParams::None {
+            if let Ok(plaintext) = blob::open(secret_key, &header, header_len, ciphertext) {
+                return Ok(plaintext);
+            }
+        }
+    }
+
+    // The random nonce of a legacy ciphertext can start with the magic bytes of a blob, so it
+    // is tried whenever the ciphertext does not open as a blob
+    decrypt_legacy(secret_key, ciphertext)
+}
+
+/// Decrypt a ciphertext written by `default::encrypt` before the `core::blob` format.
+fn decrypt_legacy(
+    secret_key: &SecretKey,
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, ValidationCryptoError> {
+    if ciphertext.len() < 24 + xchacha20poly1305::TAGBYTES {
+        return Err(ValidationCryptoError);
+    }
+
+    let nonce = Nonce::from_slice(&ciphertext[..24])?;
+
+    xchacha20poly1305::open(
+        secret_key.unprotected_as_bytes(),
+        &nonce,
+        &ciphertext[24..],
+        &[],
+    )
+}
+
+#[cfg(feature = "rng")]
+/// Key-committing authenticated encryption using XChaCha20-Poly1305.
+/// # About:
+/// - A random nonce of 24 bytes is automatically generated.
+/// - The XChaCha20-Poly1305 key and a 32 byte commitment to the secret key are derived from
+/// the secret key and nonce with HMAC-SHA512. See `core::blob` for the details.
+/// - The output is a blob in the format of `core::blob`: a 31 byte header, the commitment,
+/// the ciphertext and the 16 byte Poly1305 tag. It is therefore 79 bytes longer than the
+/// plaintext.
+///
+/// Poly1305 alone does not commit to the key: a ciphertext can be crafted to decrypt, to
+/// different plaintexts, under several keys. With the commitment, a ciphertext only decrypts
+/// under the key it was encrypted with. Use this where ciphertexts are shared between
+/// several keys, such as in message franking or encrypted search.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the secret key is not 32 bytes.
+///
+/// # Security:
+/// The secret key should always be generated using a CSPRNG. `SecretKey::generate` can be
+/// used for this.
+///
+/// # Example:
+/// ```
+/// use orion::default;
+/// use orion::core::types::SecretKey;
+///
+/// let key = SecretKey::generate(32).unwrap();
+/// let ciphertext = default::encrypt_committing(&key, "Secret message".as_bytes()).unwrap();
+/// ```
+pub fn encrypt_committing(
+    secret_key: &SecretKey,
+    plaintext: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    let nonce = Nonce::generate(24)?;
+    let header =
+        BlobHeader::new(AeadAlgorithm::XChaCha20Poly1305Committing, KdfParams::None, nonce)?;
+
+    blob::seal(secret_key, &header, plaintext)
+}
+
+/// Key-committing authenticated decryption using XChaCha20-Poly1305.
+/// # About:
+/// This function is meant to be used with the `default::encrypt_committing` function in
+/// orion's default API. The commitment and then the tag are verified, in constant time,
+/// before anything is decrypted.
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the secret key is not 32 bytes.
+/// - The ciphertext was not created by `default::encrypt_committing`.
+/// - The ciphertext is not committed to the secret key, or fails authentication.
+/// # Example:
+///
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::default;
+/// use orion::core::types::SecretKey;
+///
+/// let key = SecretKey::generate(32).unwrap();
+/// let ciphertext = default::encrypt_committing(&key, "Secret message".as_bytes()).unwrap();
+///
+/// let plaintext = default::decrypt_committing(&key, &ciphertext).unwrap();
+/// assert_eq!(plaintext, "Secret message".as_bytes());
+/// # }
+/// ```
+pub fn decrypt_committing(
+    secret_key: &SecretKey,
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, ValidationCryptoError> {
+    let (header, header_len) = committing_header(ciphertext)?;
+
+    blob::open(secret_key, &header, header_len, ciphertext)
+}
+
+/// Verify, in constant time, that a ciphertext of `default::encrypt_committing` was encrypted
+/// with `secret_key`, without decrypting or authenticating the rest of it.
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the secret key is not 32 bytes.
+/// - The ciphertext was not created by `default::encrypt_committing`.
+/// - The ciphertext is not committed to the secret key.
+/// # Example:
+///
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::default;
+/// use orion::core::types::SecretKey;
+///
+/// let key = SecretKey::generate(32).unwrap();
+/// let ciphertext = default::encrypt_committing(&key, "Secret message".as_bytes()).unwrap();
+///
+/// assert!(default::verify_commitment(&key, &ciphertext).unwrap());
+/// # }
+/// ```
+pub fn verify_commitment(
+    secret_key: &SecretKey,
+    ciphertext: &[u8],
+) -> Result<bool, ValidationCryptoError> {
+    let (header, header_len) = committing_header(ciphertext)?;
+
+    blob::verify_commitment(secret_key, &header, header_len, ciphertext)
+}
+
+/// Parse the header of a ciphertext from `default::encrypt_committing`.
+fn committing_header(ciphertext: &[u8]) -> Result<(BlobHeader, usize), ValidationCryptoError> {
+    let (header, header_len) = BlobHeader::parse(ciphertext)?;
+    if header.aead() != AeadAlgorithm::XChaCha20Poly1305Committing
+        || *header.kdf() != KdfParams::None
+    {
+        return Err(ValidationCryptoError);
+    }
+
+    Ok((header, header_len))
+}
+
+/// Version byte of the header written by `default::encrypt_with_password` before the
+/// `core::blob` format.
+const LEGACY_PASSWORD_ENCRYPTION_VERSION: u8 = 0x01;
+
+/// Length of the legacy header of `default::encrypt_with_password`. That is the version byte,
+/// 4 bytes iteration count and 32 bytes salt.
+const LEGACY_PASSWORD_ENCRYPTION_HEADER: usize = 37;
+
+/// Derive the XChaCha20-Poly1305 key for `default::encrypt_with_password`.
+fn password_encryption_key(
+    password: &Password,
+    salt: &[u8],
+    iterations: usize,
+) -> Result<SecretKey, UnknownCryptoError> {
+    let pbkdf2_dk = Pbkdf2::new(password, salt, iterations, 32, ShaVariantOption::SHA512Trunc256)?;
+    let mut dk = pbkdf2_dk.derive_key()?;
+    let secret_key = SecretKey::from_slice(&dk);
+    dk.zeroize();
+
+    secret_key
+}
+
+#[cfg(feature = "rng")]
+/// Password-based authenticated encryption using PBKDF2-HMAC-SHA512/256 and
+/// XChaCha20-Poly1305.
+/// # About:
+/// - A salt of 32 bytes and a nonce of 24 bytes are automatically generated.
+/// - The 32 byte encryption key is derived from the password and salt with 512.000 iterations.
+/// - The output is a blob in the format of `core::blob`. Its 68 byte header holds the
+/// algorithms, the iteration count, the salt and the nonce, and is authenticated together
+/// with the ciphertext.
+/// - The output is therefore 84 bytes longer than the plaintext.
+///
+/// When using this function with `default::decrypt_with_password` the parameters are read from
+/// the header, so they can be changed in the future without breaking decryption of existing
+/// data.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the password is less than 14 bytes.
+///
+/// # Example:
+/// ```
+/// use orion::default;
+/// use orion::core::types::Password;
+///
+/// let password = Password::from_slice("Secret password".as_bytes()).unwrap();
+/// let ciphertext = default::encrypt_with_password(&password, "Secret message".as_bytes());
+/// ```
+pub fn encrypt_with_password(
+    password: &Password,
+    plaintext: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    if password.unprotected_as_bytes().len() < 14 {
+        return Err(UnknownCryptoError);
+    }
+
+    let iterations = 512_000;
+    let salt = Salt::generate(32)?;
+    let nonce = Nonce::generate(24)?;
+
+    let kdf = KdfParams::Pbkdf2Sha512Trunc256 {
+        iterations: iterations as u32,
+        salt: salt.as_bytes().to_vec(),
+    };
+    let header = BlobHeader::new(AeadAlgorithm::XChaCha20Poly1305, kdf, nonce)?;
+    let secret_key = password_encryption_key(password, salt.as_bytes(), iterations)?;
+
+    blob::seal(&secret_key, &header, plaintext)
+}
+
+/// Password-based authenticated decryption using PBKDF2-HMAC-SHA512/256 and
+/// XChaCha20-Poly1305.
+/// # About:
+/// This function is meant to be used with the `default::encrypt_with_password` function in
+/// orion's default API. Ciphertexts from before the `core::blob` format can also be
+/// decrypted.
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The ciphertext is too short.
+/// - The header has an unknown version or algorithm.
+/// - The iteration count in the header is less than `PBKDF2_MIN_ITERATIONS` or greater than
+/// `PBKDF2_MAX_ITERATIONS`.
+/// - The ciphertext fails authentication.
+/// # Example:
+///
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::default;
+/// use orion::core::types::Password;
+///
+/// let password = Password::from_slice("Secret password".as_bytes()).unwrap();
+/// let ciphertext =
+///     default::encrypt_with_password(&password, "Secret message".as_bytes()).unwrap();
+///
+/// let plaintext = default::decrypt_with_password(&password, &ciphertext).unwrap();
+/// assert_eq!(plaintext, "Secret message".as_bytes());
+/// # }
+/// ```
+pub fn decrypt_with_password(
+    password: &Password,
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, ValidationCryptoError> {
+    if ciphertext.first() == Some(&LEGACY_PASSWORD_ENCRYPTION_VERSION) {
+        return decrypt_with_password_legacy(password, ciphertext);
+    }
+
+    let (header, header_len, secret_key) = password_blob_key(password, ciphertext)?;
+
+    blob::open(&secret_key, &header, header_len, ciphertext)
+}
+
+/// Parse the header of a blob with a key derived from a password, and derive the key.
+fn password_blob_key(
+    password: &Password,
+    ciphertext: &[u8],
+) -> Result<(BlobHeader, usize, SecretKey), ValidationCryptoError> {
+    let (header, header_len) = BlobHeader::parse(ciphertext)?;
+    let secret_key = match *header.kdf() {
+        KdfParams::Pbkdf2Sha512Trunc256 {
+            iterations,
+            ref salt,
+        } if iterations as usize >= PBKDF2_MIN_ITERATIONS
+            && iterations as usize <= PBKDF2_MAX_ITERATIONS =>
+        {
+            password_encryption_key(password, salt, iterations as usize)?
+        }
+        _ => return Err(ValidationCryptoError),
+    };
+
+    Ok((header, header_len, secret_key))
+}
+
+/// Decrypt a ciphertext written by `default::encrypt_with_password` before the `core::blob`
+/// format.
+fn decrypt_with_password_legacy(
+    password: &Password,
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, ValidationCryptoError> {
+    if ciphertext.len() < LEGACY_PASSWORD_ENCRYPTION_HEADER + 24 + xchacha20poly1305::TAGBYTES {
+        return Err(ValidationCryptoError);
+    }
+
+    let iterations = read_u32_be(&ciphertext[1..5]) as usize;
+    if iterations < PBKDF2_MIN_ITERATIONS || iterations > PBKDF2_MAX_ITERATIONS {
+        return Err(ValidationCryptoError);
+    }
+
+    let (header, rest) = ciphertext.split_at(LEGACY_PASSWORD_ENCRYPTION_HEADER);
+    let nonce = Nonce::from_slice(&rest[..24])?;
+    let secret_key = password_encryption_key(password, &header[5..], iterations)?;
+
+    xchacha20poly1305::open(
+        secret_key.unprotected_as_bytes(),
+        &nonce,
+        &rest[24..],
+        header,
+    )
+}
+
+#[cfg(feature = "rng")]
+/// Envelope encryption using XChaCha20-Poly1305.
+/// # About:
+/// - A random 32 byte data-encryption key (DEK) is generated for every call, and the plaintext
+/// is encrypted with it.
+/// - The DEK is wrapped by encrypting it with the key-encryption key (KEK), as with
+/// `default::encrypt`. The wrapped DEK is stored in the header of the output, so it is
+/// authenticated together with the ciphertext.
+/// - The output is a blob in the format of `core::blob`, and is 127 bytes longer than the
+/// plaintext.
+///
+/// The KEK is only used to wrap DEKs, so it can be kept in a KMS or HSM. Decryption needs
+/// nothing besides the KEK and the output of this function.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the key-encryption key is not 32 bytes.
+///
+/// # Security:
+/// The key-encryption key should always be generated using a CSPRNG. `SecretKey::generate`
+/// can be used for this.
+///
+/// # Example:
+/// ```
+/// use orion::default;
+/// use orion::core::types::SecretKey;
+///
+/// let kek = SecretKey::generate(32).unwrap();
+/// let ciphertext = default::envelope_encrypt(&kek, "Secret message".as_bytes()).unwrap();
+/// ```
+pub fn envelope_encrypt(kek: &SecretKey, plaintext: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+    let dek = SecretKey::generate(32)?;
+    let wrapped_key = encrypt(kek, dek.unprotected_as_bytes())?;
+    let nonce = Nonce::generate(24)?;
+    let header = BlobHeader::new(
+        AeadAlgorithm::XChaCha20Poly1305,
+        KdfParams::WrappedKey(wrapped_key),
+        nonce,
+    )?;
+
+    blob::seal(&dek, &header, plaintext)
+}
+
+/// Envelope decryption using XChaCha20-Poly1305.
+/// # About:
+/// This function is meant to be used with the `default::envelope_encrypt` function in orion's
+/// default API. The data-encryption key is unwrapped with the key-encryption key, and then
+/// used to verify and decrypt the ciphertext.
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the key-encryption key is not 32 bytes.
+/// - The ciphertext was not created by `default::envelope_encrypt`.
+/// - The wrapped key or the ciphertext fails authentication.
+/// # Example:
+///
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::default;
+/// use orion::core::types::SecretKey;
+///
+/// let kek = SecretKey::generate(32).unwrap();
+/// let ciphertext = default::envelope_encrypt(&kek, "Secret message".as_bytes()).unwrap();
+///
+/// let plaintext = default::envelope_decrypt(&kek, &ciphertext).unwrap();
+/// assert_eq!(plaintext, "Secret message".as_bytes());
+/// # }
+/// ```
+pub fn envelope_decrypt(
+    kek: &SecretKey,
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, ValidationCryptoError> {
+    let (header, header_len) = BlobHeader::parse(ciphertext)?;
+    let wrapped_key = match *header.kdf() {
+        KdfParams::WrappedKey(ref wrapped_key) => wrapped_key,
+        _ => return Err(ValidationCryptoError),
+    };
+
+    let (wrapped_header, wrapped_header_len) = BlobHeader::parse(wrapped_key)?;
+    if *wrapped_header.kdf() != KdfParams::None {
+        return Err(ValidationCryptoError);
+    }
+    let mut dk = blob::open(kek, &wrapped_header, wrapped_header_len, wrapped_key)?;
+    let dek = SecretKey::from_slice(&dk);
+    dk.zeroize();
+
+    blob::open(&dek?, &header, header_len, ciphertext)
+}
+
+/// Subkey ids of the keys that a `Keyring` derives for each purpose.
+const KEYRING_ENCRYPTION_SUBKEY: u64 = 1;
+const KEYRING_HMAC_SUBKEY: u64 = 2;
+
+/// Context of the subkeys derived by a `Keyring`.
+const KEYRING_CONTEXT: &[u8] = b"orion keyring";
+
+/// Length of the tags created by `Keyring::hmac`. That is the 4 byte key id and the 32 byte
+/// HMAC.
+const KEYRING_TAG_LEN: usize = 36;
+
+/// A set of versioned secret keys, for rotating keys without downtime.
+/// # About:
+/// Each key has a `u32` id. Data is always encrypted or authenticated with the primary key,
+/// which is the key added last. The id of that key is stored with the output, so that data
+/// protected with older keys in the keyring can still be decrypted and verified.
+///
+/// To rotate keys, add a new key to the keyring on all servers. Once all data has been
+/// re-encrypted or has expired, remove the old key.
+///
+/// - `encrypt` writes a blob in the format of `core::blob`, using XChaCha20-Poly1305. The key
+/// id is part of the authenticated header, and the output is 51 bytes longer than the
+/// plaintext.
+/// - `hmac` returns the key id as a big-endian 4 byte integer followed by an
+/// HMAC-SHA512/256 over the key id and the data, 36 bytes in total.
+///
+/// Separate subkeys for encryption and authentication are derived from each key with
+/// `default::derive_subkey`.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - A key is shorter than 32 bytes, or its id is already in the keyring.
+/// - `encrypt` or `hmac` is called on an empty keyring.
+/// - The key of a ciphertext or tag is not in the keyring.
+/// - The ciphertext or tag fails authentication.
+///
+/// # Example:
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::default::Keyring;
+/// use orion::core::types::SecretKey;
+///
+/// let mut keyring = Keyring::new();
+/// keyring.add_key(1, SecretKey::generate(32).unwrap()).unwrap();
+/// let old_ciphertext = keyring.encrypt("Secret message".as_bytes()).unwrap();
+///
+/// // Rotate to a new key. Data encrypted with the old key can still be decrypted.
+/// keyring.add_key(2, SecretKey::generate(32).unwrap()).unwrap();
+/// assert_eq!(keyring.primary_key_id(), Some(2));
+/// assert_eq!(keyring.key_id_of(&old_ciphertext), Some(1));
+/// assert_eq!(keyring.decrypt(&old_ciphertext).unwrap(), "Secret message".as_bytes());
+///
+/// let tag = keyring.hmac("Some message.".as_bytes()).unwrap();
+/// assert!(keyring.hmac_verify(&tag, "Some message.".as_bytes()).unwrap());
+/// # }
+/// ```
+pub struct Keyring {
+    keys: Vec<(u32, SecretKey)>,
+}
+
+impl fmt::Debug for Keyring {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ids: Vec<u32> = self.keys.iter().map(|&(key_id, _)| key_id).collect();
+        write!(f, "Keyring {{ key_ids: {:?}, keys: *** }}", ids)
+    }
+}
+
+impl Default for Keyring {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Keyring {
+    /// Make an empty keyring.
+    pub fn new() -> Self {
+        Keyring { keys: Vec::new() }
+    }
+
+    /// Add a key, which becomes the primary key.
+    pub fn add_key(
+        &mut self,
+        key_id: u32,
+        secret_key: SecretKey,
+    ) -> Result<(), UnknownCryptoError> {
+        if secret_key.unprotected_as_bytes().len() < 32 || self.key(key_id).is_some() {
+            return Err(UnknownCryptoError);
+        }
+        self.keys.push((key_id, secret_key));
+
+        Ok(())
+    }
+
+    /// Remove a key. Returns `false` if it was not in the keyring. If the primary key is
+    /// removed, the key added before it becomes the primary key.
+    pub fn remove_key(&mut self, key_id: u32) -> bool {
+        let len = self.keys.len();
+        self.keys.retain(|&(id, _)| id != key_id);
+
+        self.keys.len() != len
+    }
+
+    /// Return the id of the primary key, or `None` if the keyring is empty.
+    pub fn primary_key_id(&self) -> Option<u32> {
+        self.keys.last().map(|&(key_id, _)| key_id)
+    }
+
+    /// Return the ids of all keys, from oldest to newest.
+    pub fn key_ids(&self) -> Vec<u32> {
+        self.keys.iter().map(|&(key_id, _)| key_id).collect()
+    }
+
+    /// Return the id of the key that `ciphertext` was encrypted with, without decrypting it.
+    /// Returns `None` if `ciphertext` was not created by `Keyring::encrypt`.
+    pub fn key_id_of(&self, ciphertext: &[u8]) -> Option<u32> {
+        match BlobHeader::parse(ciphertext) {
+            Ok((ref header, _)) => match *header.kdf() {
+                KdfParams::KeyId(key_id) => Some(key_id),
+                _ => None,
+            },
+            Err(_) => None,
+        }
+    }
+
+    fn key(&self, key_id: u32) -> Option<&SecretKey> {
+        self.keys
+            .iter()
+            .find(|&&(id, _)| id == key_id)
+            .map(|&(_, ref secret_key)| secret_key)
+    }
+
+    fn primary_key(&self) -> Result<(u32, &SecretKey), UnknownCryptoError> {
+        self.keys
+            .last()
+            .map(|&(key_id, ref secret_key)| (key_id, secret_key))
+            .ok_or(UnknownCryptoError)
+    }
+
+    #[cfg(feature = "rng")]
+    /// Encrypt `plaintext` with the primary key.
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+        let (key_id, secret_key) = self.primary_key()?;
+        let subkey = derive_subkey(secret_key, KEYRING_ENCRYPTION_SUBKEY, KEYRING_CONTEXT)?;
+        let nonce = Nonce::generate(24)?;
+        let header =
+            BlobHeader::new(AeadAlgorithm::XChaCha20Poly1305, KdfParams::KeyId(key_id), nonce)?;
+
+        blob::seal(&subkey, &header, plaintext)
+    }
+
+    /// Decrypt a ciphertext created by `Keyring::encrypt`, with the key it was encrypted with.
+    pub fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, ValidationCryptoError> {
+        let (header, header_len) = BlobHeader::parse(ciphertext)?;
+        let key_id = match *header.kdf() {
+            KdfParams::KeyId(key_id) => key_id,
+            _ => return Err(ValidationCryptoError),
+        };
+        let secret_key = self.key(key_id).ok_or(ValidationCryptoError)?;
+        let subkey = derive_subkey(secret_key, KEYRING_ENCRYPTION_SUBKEY, KEYRING_CONTEXT)?;
+
+        blob::open(&subkey, &header, header_len, ciphertext)
+    }
+
+    fn hmac_stream(
+        key_id: u32,
+        secret_key: &SecretKey,
+    ) -> Result<::hazardous::hmac::HmacStream, UnknownCryptoError> {
+        let subkey = derive_subkey(secret_key, KEYRING_HMAC_SUBKEY, KEYRING_CONTEXT)?;
+        let mut id = [0u8; 4];
+        write_u32_be(&mut id, key_id);
+        let mut stream = Hmac::init(&subkey, ShaVariantOption::SHA512Trunc256)?;
+        stream.update(&id);
+
+        Ok(stream)
+    }
+
+    /// Authenticate `data` with the primary key. The tag starts with the key id.
+    pub fn hmac(&self, data: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+        let (key_id, secret_key) = self.primary_key()?;
+        let mut stream = Self::hmac_stream(key_id, secret_key)?;
+        stream.update(data);
+
+        let mut tag = vec![0u8; KEYRING_TAG_LEN];
+        write_u32_be(&mut tag[..4], key_id);
+        tag[4..].copy_from_slice(stream.finalize().as_bytes());
+
+        Ok(tag)
+    }
+
+    /// Verify a tag created by `Keyring::hmac`, in constant time, with the key it was created
+    /// with.
+    pub fn hmac_verify(&self, tag: &[u8], data: &[u8]) -> Result<bool, ValidationCryptoError> {
+        if tag.len() != KEYRING_TAG_LEN {
+            return Err(ValidationCryptoError);
+        }
+
+        let key_id = read_u32_be(&tag[..4]);
+        let secret_key = self.key(key_id).ok_or(ValidationCryptoError)?;
+        let mut stream = Self::hmac_stream(key_id, secret_key)?;
+        stream.update(data);
+
+        stream.verify(&tag[4..])
+    }
+}
+
+/// Version byte of the list of keys in a `Keystore` file.
+const KEYSTORE_VERSION: u8 = 0x01;
+
+/// A file of named secret keys, encrypted with a password.
+/// # About:
+/// The keys are kept in memory once the keystore is opened. Every change is written to the
+/// file right away, by writing a new file next to it and renaming it over the old one.
+///
+/// The file is a blob in the format of `core::blob`, like the output of
+/// `default::encrypt_with_password`: the key is derived from the password with
+/// PBKDF2-HMAC-SHA512/256, using 512.000 iterations and a 32 byte salt, and the keys are
+/// encrypted with XChaCha20-Poly1305. The salt is generated when the keystore is created and
+/// kept when the file is rewritten, with a new nonce each time. The plaintext is a version
+/// byte followed by, for every key, the length of its name as 1 byte, the name, the length of
+/// the key as a big-endian 4 byte integer and the key.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the password is less than 14 bytes.
+/// - `create` is called with the path of an existing file.
+/// - The file cannot be read or written, is malformed, or fails authentication.
+/// - A name is empty, longer than 255 bytes, or already in the keystore.
+///
+/// # Example:
+/// ```no_run
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::default::Keystore;
+/// use orion::core::types::{Password, SecretKey};
+///
+/// let password = Password::from_slice("Secret password".as_bytes()).unwrap();
+///
+/// let mut keystore = Keystore::create("keys.orion", &password).unwrap();
+/// keystore.add_key("database", SecretKey::generate(32).unwrap()).unwrap();
+///
+/// let keystore = Keystore::open("keys.orion", &password).unwrap();
+/// let database_key = keystore.get_key("database").unwrap();
+/// # }
+/// ```
+pub struct Keystore {
+    path: PathBuf,
+    // The key derivation parameters and derived key are only needed to write the file
+    #[cfg(feature = "rng")]
+    kdf: KdfParams,
+    #[cfg(feature = "rng")]
+    secret_key: SecretKey,
+    keys: Vec<(String, SecretKey)>,
+}
+
+impl fmt::Debug for Keystore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Keystore {{ path: {:?}, names: {:?}, keys: *** }}",
+            self.path,
+            self.names()
+        )
+    }
+}
+
+impl Keystore {
+    #[cfg(feature = "rng")]
+    /// Create a new, empty keystore file at `path`.
+    pub fn create<P: AsRef<Path>>(path: P, password: &Password) -> io::Result<Self> {
+        if password.unprotected_as_bytes().len() < 14 {
+            return Err(UnknownCryptoError.into());
+        }
+        let path = path.as_ref();
+        // Creating the file fails if it exists, so two callers can never both create it
+        let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+
+        let result = Self::derive(path, password).and_then(|keystore| {
+            file.write_all(&keystore.seal()?)?;
+            Ok(keystore)
+        });
+        drop(file);
+        if result.is_err() {
+            let _ = fs::remove_file(path);
+        }
+
+        result
+    }
+
+    #[cfg(feature = "rng")]
+    /// Derive the key of a new, empty keystore at `path` with a fresh salt.
+    fn derive(path: &Path, password: &Password) -> io::Result<Self> {
+        let iterations = 512_000;
+        let salt = Salt::generate(32)?;
+
+        Ok(Keystore {
+            path: path.to_path_buf(),
+            kdf: KdfParams::Pbkdf2Sha512Trunc256 {
+                iterations: iterations as u32,
+                salt: salt.as_bytes().to_vec(),
+            },
+            secret_key: password_encryption_key(password, salt.as_bytes(), iterations)?,
+            keys: Vec::new(),
+        })
+    }
+
+    /// Open the keystore file at `path`, and decrypt its keys.
+    pub fn open<P: AsRef<Path>>(path: P, password: &Password) -> io::Result<Self> {
+        let path = path.as_ref();
+        let ciphertext = fs::read(path)?;
+        let (header, header_len, secret_key) = password_blob_key(password, &ciphertext)?;
+        let mut plaintext = blob::open(&secret_key, &header, header_len, &ciphertext)?;
+        let keys = Self::parse_keys(&plaintext);
+        plaintext.zeroize();
+
+        Ok(Keystore {
+            path: path.to_path_buf(),
+            #[cfg(feature = "rng")]
+            kdf: header.kdf().clone(),
+            #[cfg(feature = "rng")]
+            secret_key,
+            keys: keys?,
+        })
+    }
+
+    fn parse_keys(plaintext: &[u8]) -> Result<Vec<(String, SecretKey)>, ValidationCryptoError> {
+        if plaintext.first() != Some(&KEYSTORE_VERSION) {
+            return Err(ValidationCryptoError);
+        }
+
+        let mut keys: Vec<(String, SecretKey)> = Vec::new();
+        let mut rest = &plaintext[1..];
+        while !rest.is_empty() {
+            let name_len = rest[0] as usize;
+            if rest.len() < 1 + name_len + 4 {
+                return Err(ValidationCryptoError);
+            }
+            let name = String::from_utf8(rest[1..1 + name_len].to_vec())
+                .map_err(|_| ValidationCryptoError)?;
+            rest = &rest[1 + name_len..];
+
+            let key_len = read_u32_be(&rest[..4]) as usize;
+            if rest.len() < 4 + key_len {
+                return Err(ValidationCryptoError);
+            }
+            let secret_key = SecretKey::from_slice(&rest[4..4 + key_len])?;
+            rest = &rest[4 + key_len..];
+
+            if name.is_empty() || keys.iter().any(|&(ref seen, _)| *seen == name) {
+                return Err(ValidationCryptoError);
+            }
+            keys.push((name, secret_key));
+        }
+
+        Ok(keys)
+    }
+
+    #[cfg(feature = "rng")]
+    /// Encrypt the keys and replace the file with them.
+    fn save(&self) -> io::Result<()> {
+        let ciphertext = self.seal()?;
+
+        write_via_tmp(&self.path, |writer| writer.write_all(&ciphertext))
+    }
+
+    #[cfg(feature = "rng")]
+    /// Encrypt the keys into a blob, as they are written to the file.
+    fn seal(&self) -> io::Result<Vec<u8>> {
+        let mut plaintext = vec![KEYSTORE_VERSION];
+        for &(ref name, ref secret_key) in self.keys.iter() {
+            let key = secret_key.unprotected_as_bytes();
+            let mut key_len = [0u8; 4];
+            write_u32_be(&mut key_len, key.len() as u32);
+
+            plaintext.push(name.len() as u8);
+            plaintext.extend_from_slice(name.as_bytes());
+            plaintext.extend_from_slice(&key_len);
+            plaintext.extend_from_slice(key);
+        }
+
+        let nonce = Nonce::generate(24)?;
+        let header = BlobHeader::new(AeadAlgorithm::XChaCha20Poly1305, self.kdf.clone(), nonce)?;
+        let ciphertext = blob::seal(&self.secret_key, &header, &plaintext);
+        plaintext.zeroize();
+
+        Ok(ciphertext?)
+    }
+
+    #[cfg(feature = "rng")]
+    /// Add a key under `name`, and write the keystore to its file.
+    pub fn add_key(&mut self, name: &str, secret_key: SecretKey) -> io::Result<()> {
+        if name.is_empty() || name.len() > 255 || self.get_key(name).is_some() {
+            return Err(UnknownCryptoError.into());
+        }
+        self.keys.push((name.to_string(), secret_key));
+
+        self.save()
+    }
+
+    #[cfg(feature = "rng")]
+    /// Remove the key `name`, and write the keystore to its file. Returns `false` if there was
+    /// no such key.
+    pub fn remove_key(&mut self, name: &str) -> io::Result<bool> {
+        let len = self.keys.len();
+        self.keys.retain(|&(ref key_name, _)| key_name != name);
+        if self.keys.len() == len {
+            return Ok(false);
+        }
+
+        self.save().map(|_| true)
+    }
+
+    /// Return the key `name`, or `None` if there is no such key.
+    pub fn get_key(&self, name: &str) -> Option<&SecretKey> {
+        self.keys
+            .iter()
+            .find(|&&(ref key_name, _)| key_name == name)
+            .map(|&(_, ref secret_key)| secret_key)
+    }
+
+    /// Return the names of all keys, in the order they were added.
+    pub fn names(&self) -> Vec<&str> {
+        self.keys.iter().map(|&(ref name, _)| name.as_str()).collect()
+    }
+}
+
+/// Size of the plaintext chunks that `default::encrypt_file` encrypts as single messages of
+/// the stream.
+const FILE_CHUNKBYTES: usize = 65536;
+
+/// Read from `reader` until `buf` is full or the end of the input is reached. Returns the
+/// number of bytes read.
+fn read_chunk<R: io::Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(filled)
+}
+
+#[cfg(feature = "rng")]
+/// Authenticated encryption of a file, using streaming XChaCha20-Poly1305.
+/// # About:
+/// The file at `input` is encrypted in chunks of 64 KiB, so it does not need to fit in
+/// memory, and the result is written to `output`. The output starts with the 24-byte stream
+/// header, followed by each encrypted chunk. Every chunk adds 17 bytes, and the last chunk is
+/// marked as the end of the stream, so that a truncated file is detected on decryption.
+///
+/// The output is compatible with libsodium's secretstream, using 64 KiB messages and no
+/// additional data. It is first written to a new temporary file next to `output`, and only
+/// renamed to `output` once encryption has succeeded. If it fails, the temporary file is
+/// removed and an existing `output` is left as it was.
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the secret key is not 32 bytes.
+/// - The input file cannot be read or the output file cannot be written.
+/// # Example:
+/// ```no_run
+/// use orion::default;
+/// use orion::core::types::SecretKey;
+///
+/// let key = SecretKey::generate(32).unwrap();
+/// default::encrypt_file(&key, "backup.tar", "backup.tar.enc").unwrap();
+/// default::decrypt_file(&key, "backup.tar.enc", "backup.tar").unwrap();
+/// ```
+pub fn encrypt_file<P: AsRef<Path>, Q: AsRef<Path>>(
+    secret_key: &SecretKey,
+    input: P,
+    output: Q,
+) -> io::Result<()> {
+    let (mut stream, header) = SecretStream::init_push(secret_key.unprotected_as_bytes())?;
+    let mut reader = File::open(input)?;
+
+    write_via_tmp(output.as_ref(), |writer| {
+        let mut chunk = vec![0u8; FILE_CHUNKBYTES];
+        let result = writer
+            .write_all(&header)
+            .and_then(|_| encrypt_chunks(&mut stream, &mut reader, writer, &mut chunk));
+        chunk.zeroize();
+
+        result
+    })
+}
+
+#[cfg(feature = "rng")]
+/// Encrypt `reader` into `writer` in chunks, ending with a chunk tagged `StreamTag::FINAL`.
+fn encrypt_chunks<R: io::Read, W: io::Write>(
+    stream: &mut SecretStream,
+    reader: &mut R,
+    writer: &mut W,
+    chunk: &mut [u8],
+) -> io::Result<()> {
+    loop {
+        // A short chunk is the last one. If the file is a multiple of the chunk size, the
+        // last chunk is empty.
+        let len = read_chunk(reader, chunk)?;
+        let tag = if len < chunk.len() {
+            StreamTag::FINAL
+        } else {
+            StreamTag::MESSAGE
+        };
+
+        writer.write_all(&stream.push(&chunk[..len], &[], tag)?)?;
+        if tag == StreamTag::FINAL {
+            return writer.flush();
+        }
+    }
+}
+
+/// Authenticated decryption of a file, using streaming XChaCha20-Poly1305.
+/// # About:
+/// This function is meant to be used with the `default::encrypt_file` function in orion's
+/// default API. The file at `input` is decrypted in chunks, so it does not need to fit in
+/// memory, and the plaintext is written to `output`. Each chunk is verified before it is
+/// decrypted. The plaintext is first written to a new temporary file next to `output`, and only
+/// renamed to `output` once the whole file has been decrypted. If decryption fails at any
+/// point, the temporary file is removed and an existing `output` is left as it was.
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the secret key is not 32 bytes.
+/// - The input file cannot be read or the output file cannot be written.
+/// - A chunk fails authentication.
+/// - The input is truncated, or has data after the last chunk.
+/// # Example:
+/// ```no_run
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::default;
+/// use orion::core::types::SecretKey;
+///
+/// let key = SecretKey::generate(32).unwrap();
+/// default::encrypt_file(&key, "backup.tar", "backup.tar.enc").unwrap();
+/// default::decrypt_file(&key, "backup.tar.enc", "backup.tar").unwrap();
+/// # }
+/// ```
+pub fn decrypt_file<P: AsRef<Path>, Q: AsRef<Path>>(
+    secret_key: &SecretKey,
+    input: P,
+    output: Q,
+) -> io::Result<()> {
+    let mut reader = File::open(input)?;
+    let mut header = [0u8; HEADERBYTES];
+    if read_chunk(&mut reader, &mut header)? != HEADERBYTES {
+        return Err(ValidationCryptoError.into());
+    }
+    let mut stream = SecretStream::init_pull(secret_key.unprotected_as_bytes(), &header)?;
+
+    // Don't leave the plaintext of a forged or truncated file behind, and don't overwrite
+    // `output` unless the whole file is authentic
+    write_via_tmp(output.as_ref(), |writer| decrypt_chunks(&mut stream, &mut reader, writer))
+}
+
+/// Counter that makes the names of temporary files unique within the process.
+static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Write a new temporary file next to `output` using `write`, and rename it to `output` once
+/// `write` has succeeded. If it fails, the temporary file is removed and `output` is left as it
+/// was.
+fn write_via_tmp<F>(output: &Path, write: F) -> io::Result<()>
+where
+    F: FnOnce(&mut File) -> io::Result<()>,
+{
+    // The name holds the process ID and a counter, and the file must not exist yet, so
+    // concurrent writers never share a temporary file
+    let (tmp_path, mut writer) = loop {
+        let mut tmp_path = output.as_os_str().to_os_string();
+        let counter = TMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+        tmp_path.push(format!(".{}.{}.tmp", process::id(), counter));
+
+        match OpenOptions::new().write(true).create_new(true).open(&tmp_path) {
+            Ok(writer) => break (tmp_path, writer),
+            Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    };
+
+    let result = write(&mut writer);
+    drop(writer);
+
+    match result {
+        Ok(()) => fs::rename(&tmp_path, output),
+        Err(e) => {
+            let _ = fs::remove_file(&tmp_path);
+            Err(e)
+        }
+    }
+}
+
+/// Decrypt `reader` into `writer` in chunks, checking that the stream ends with a chunk
+/// tagged `StreamTag::FINAL` and that nothing follows it.
+fn decrypt_chunks<R: io::Read, W: io::Write>(
+    stream: &mut SecretStream,
+    reader: &mut R,
+    writer: &mut W,
+) -> io::Result<()> {
+    let mut chunk = vec![0u8; FILE_CHUNKBYTES + ABYTES];
+    loop {
+        let len = read_chunk(reader, &mut chunk)?;
+        let (mut plaintext, tag) = stream.pull(&chunk[..len], &[])?;
+        let written = writer.write_all(&plaintext);
+        plaintext.zeroize();
+        written?;
+
+        match tag {
+            StreamTag::FINAL => {
+                if read_chunk(reader, &mut [0u8; 1])? != 0 {
+                    return Err(ValidationCryptoError.into());
+                }
+                return writer.flush();
+            }
+            // Only the last chunk may be shorter than the chunk size
+            StreamTag::MESSAGE if len == chunk.len() => (),
+            _ => return Err(ValidationCryptoError.into()),
+        }
+    }
+}
+
+#[cfg(feature = "rng")]
+/// The [EFF large wordlist](https://www.eff.org/dice) of 7776 words, one per line. Licensed
+/// under [CC BY 3.0 US](https://creativecommons.org/licenses/by/3.0/us/).
+const EFF_LARGE_WORDLIST: &str = include_str!("wordlists/eff_large_wordlist.txt");
+
+#[cfg(feature = "rng")]
+/// Generate a random passphrase from the EFF large wordlist.
+/// # About:
+/// - Each word is chosen uniformly at random with rand's OsRng.
+/// - Words are separated by a single space.
+/// - Each word adds about 12.9 bits of entropy, so 6 words give about 77 bits.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `words` is less than 5.
+///
+/// # Security:
+/// The passphrase is returned in a `String` that is not zeroed out on drop.
+///
+/// # Example:
+/// ```
+/// use orion::default;
+///
+/// let passphrase = default::gen_passphrase(6).unwrap();
+/// assert_eq!(passphrase.split(' ').count(), 6);
+/// ```
+pub fn gen_passphrase(words: usize) -> Result<String, UnknownCryptoError> {
+    if words < 5 {
+        return Err(UnknownCryptoError);
+    }
+
+    let wordlist: Vec<&str> = EFF_LARGE_WORDLIST.lines().collect();
+    let mut passphrase: Vec<&str> = Vec::with_capacity(words);
+    for _ in 0..words {
+        let idx = util::gen_rand_range(0, wordlist.len() as u64)?;
+        passphrase.push(wordlist[idx as usize]);
+    }
 
-    cshake.finalize()
+    Ok(passphrase.join(" "))
 }
 
-/// Verify a cSHAKE256 hash in constant time. The expected hash must be of length 64.
-/// # Example:
+#[cfg(feature = "rng")]
+/// Generate a random token, for use as a session ID, API key or password reset token.
+/// # About:
+/// - `entropy_bytes` random bytes are generated with rand's OsRng.
+/// - The bytes are encoded as unpadded base64 with the URL and filename safe alphabet, so the
+/// token can be used in URLs and HTTP headers without further encoding.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `entropy_bytes` is less than 16.
 ///
+/// # Example:
 /// ```
 /// use orion::default;
 ///
-/// let data = "Not so random data".as_bytes();
-/// let custom = "Custom".as_bytes();
-///
-/// let hash = default::cshake(data, custom).unwrap();
-/// assert_eq!(default::cshake_verify(&hash, data, custom).unwrap(), true);
+/// let token = default::gen_token(32).unwrap();
+/// assert_eq!(token.len(), 43);
 /// ```
-pub fn cshake_verify(
-    expected: &[u8],
-    input: &[u8],
-    custom: &[u8],
-) -> Result<bool, ValidationCryptoError> {
-    let cshake = CShake {
-        input: input.to_vec(),
-        name: Vec::new(),
-        custom: custom.to_vec(),
-        length: 64,
-        keccak: KeccakVariantOption::KECCAK512,
-    };
+pub fn gen_token(entropy_bytes: usize) -> Result<String, UnknownCryptoError> {
+    if entropy_bytes < 16 {
+        return Err(UnknownCryptoError);
+    }
 
-    cshake.verify(&expected)
+    let mut bytes = util::gen_rand_key(entropy_bytes)?;
+    let token = util::to_base64url(&bytes);
+    bytes.zeroize();
+
+    Ok(token)
 }
 
 #[cfg(test)]
@@ -362,18 +2314,69 @@ mod test {
 
     extern crate hex;
     use self::hex::decode;
+    use core::options::ShaVariantOption;
+    #[cfg(feature = "rng")]
+    use core::types::Salt;
+    use core::types::{Nonce, Password, SecretKey};
     use core::util;
     use default;
+    use hazardous::pbkdf2::Pbkdf2;
+    use hazardous::xchacha20poly1305;
+    #[cfg(feature = "rng")]
+    use std::fs::{self, File};
+    #[cfg(feature = "rng")]
+    use std::io::{self, Write};
+    #[cfg(feature = "rng")]
+    use std::{
+        env,
+        path::{Path, PathBuf},
+    };
 
     #[test]
     fn hmac_secret_key_too_short() {
-        assert!(default::hmac(&vec![0x61; 10], &vec![0x61; 10]).is_err());
+        assert!(default::hmac_from_slice(&vec![0x61; 10], &vec![0x61; 10]).is_err());
     }
 
     #[test]
     fn hmac_secret_key_allowed_len() {
-        default::hmac(&vec![0x61; 64], &vec![0x61; 10]).unwrap();
-        default::hmac(&vec![0x61; 78], &vec![0x61; 10]).unwrap();
+        default::hmac_from_slice(&vec![0x61; 64], &vec![0x61; 10]).unwrap();
+        default::hmac_from_slice(&vec![0x61; 78], &vec![0x61; 10]).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn hmac_verify_typed() {
+        let key = SecretKey::generate(64).unwrap();
+        let msg = "what do ya want for nothing?".as_bytes();
+
+        let hmac = default::hmac(&key, msg).unwrap();
+
+        assert_eq!(default::hmac_verify(&hmac, &key, msg).unwrap(), true);
+        assert_eq!(hmac, default::hmac_from_slice(key.unprotected_as_bytes(), msg).unwrap());
+        assert!(default::hmac(&SecretKey::generate(63).unwrap(), msg).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn hmac_stream() {
+        let key = SecretKey::generate(64).unwrap();
+        let other_key = SecretKey::generate(64).unwrap();
+        let data = util::gen_rand_key(300).unwrap();
+        let expected = default::hmac(&key, &data).unwrap();
+
+        let mut stream = default::HmacStream::new(&key).unwrap();
+        for chunk in data.chunks(7) {
+            stream.update(chunk);
+        }
+        let mut writer = default::HmacStream::new(&key).unwrap();
+        io::copy(&mut io::Cursor::new(&data), &mut writer).unwrap();
+        let mut other = default::HmacStream::new(&other_key).unwrap();
+        other.update(&data);
+
+        assert_eq!(stream.finalize(), expected);
+        assert_eq!(writer.verify(&expected).unwrap(), true);
+        assert!(other.verify(&expected).is_err());
+        assert!(default::HmacStream::new(&SecretKey::generate(63).unwrap()).is_err());
     }
 
     #[test]
@@ -395,98 +2398,552 @@ mod test {
         ).unwrap();
         let msg = "what do ya want for nothing?".as_bytes().to_vec();
 
-        let hmac_bob = default::hmac(&sec_key_correct, &msg).unwrap();
+        let hmac_bob = default::hmac_from_slice(&sec_key_correct, &msg).unwrap();
 
         assert_eq!(
-            default::hmac_verify(&hmac_bob, &sec_key_correct, &msg).unwrap(),
+            default::hmac_verify_from_slice(&hmac_bob, &sec_key_correct, &msg).unwrap(),
             true
         );
-        assert!(default::hmac_verify(&hmac_bob, &sec_key_false, &msg).is_err());
+        assert!(default::hmac_verify_from_slice(&hmac_bob, &sec_key_false, &msg).is_err());
     }
 
     #[test]
+    #[cfg(feature = "rng")]
     fn hkdf_verify() {
         let salt = util::gen_rand_key(64).unwrap();
         let data = "Some data.".as_bytes();
         let info = "Some info.".as_bytes();
 
-        let hkdf_dk = default::hkdf(&salt, data, info, 64).unwrap();
+        let hkdf_dk = default::hkdf_from_slice(&salt, data, info, 64).unwrap();
 
         assert_eq!(
-            default::hkdf_verify(&hkdf_dk, &salt, data, info, 64).unwrap(),
+            default::hkdf_verify_from_slice(&hkdf_dk, &salt, data, info, 64).unwrap(),
             true
         );
     }
 
     #[test]
+    #[cfg(feature = "rng")]
+    fn hkdf_verify_typed() {
+        let salt = Salt::generate(64).unwrap();
+        let data = "Some data.".as_bytes();
+        let info = "Some info.".as_bytes();
+
+        let hkdf_dk = default::hkdf(&salt, data, info, 64).unwrap();
+
+        assert_eq!(default::hkdf_verify(&hkdf_dk, &salt, data, info, 64).unwrap(), true);
+        assert_eq!(
+            hkdf_dk,
+            default::hkdf_from_slice(salt.as_bytes(), data, info, 64).unwrap()
+        );
+    }
+
+    #[test]
+    fn derive_subkey() {
+        let master_key = SecretKey::from_slice(&[0x61; 32]).unwrap();
+        let context = "Context".as_bytes();
+
+        let subkey = default::derive_subkey(&master_key, 1, context).unwrap();
+
+        assert_eq!(subkey.unprotected_as_bytes().len(), 32);
+        assert_eq!(subkey, default::derive_subkey(&master_key, 1, context).unwrap());
+        assert_ne!(subkey, default::derive_subkey(&master_key, 2, context).unwrap());
+        assert_ne!(subkey, default::derive_subkey(&master_key, 1, b"Other").unwrap());
+        assert_ne!(subkey, master_key);
+    }
+
+    #[test]
+    fn derive_subkey_err() {
+        let master_key = SecretKey::from_slice(&[0x61; 32]).unwrap();
+        let short_key = SecretKey::from_slice(&[0x61; 31]).unwrap();
+
+        assert!(default::derive_subkey(&master_key, 1, b"").is_err());
+        assert!(default::derive_subkey(&short_key, 1, b"Context").is_err());
+    }
+
+    #[test]
+    fn derive_path() {
+        let master_key = SecretKey::from_slice(&[0x61; 32]).unwrap();
+
+        let key = default::derive_path(&master_key, "service/db/2024").unwrap();
+        let service_key = default::derive_path(&master_key, "service").unwrap();
+        let db_key = default::derive_path(&service_key, "db").unwrap();
+
+        assert_eq!(key.unprotected_as_bytes().len(), 32);
+        assert_eq!(key, default::derive_path(&master_key, "service/db/2024").unwrap());
+        assert_eq!(key, default::derive_path(&service_key, "db/2024").unwrap());
+        assert_eq!(key, default::derive_path(&db_key, "2024").unwrap());
+        assert_ne!(key, default::derive_path(&master_key, "service/db/2025").unwrap());
+        assert_ne!(key, default::derive_path(&master_key, "service/db2024").unwrap());
+        assert_ne!(key, default::derive_path(&master_key, "service").unwrap());
+        assert_ne!(key, master_key);
+        // Path keys are separate from subkeys with the same context.
+        assert_ne!(
+            default::derive_path(&master_key, "Context").unwrap(),
+            default::derive_subkey(&master_key, 7, b"Context").unwrap()
+        );
+    }
+
+    #[test]
+    fn derive_path_err() {
+        let master_key = SecretKey::from_slice(&[0x61; 32]).unwrap();
+        let short_key = SecretKey::from_slice(&[0x61; 31]).unwrap();
+
+        assert!(default::derive_path(&master_key, "").is_err());
+        assert!(default::derive_path(&master_key, "/").is_err());
+        assert!(default::derive_path(&master_key, "/a").is_err());
+        assert!(default::derive_path(&master_key, "a/").is_err());
+        assert!(default::derive_path(&master_key, "a//b").is_err());
+        assert!(default::derive_path(&short_key, "a").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn hkdf_with_variant() {
+        let salt = Salt::generate(64).unwrap();
+        let data = "Some data.".as_bytes();
+        let info = "Some info.".as_bytes();
+        let sha384 = ShaVariantOption::SHA384;
+
+        let dk = default::hkdf_with_variant(&salt, data, info, 64, sha384).unwrap();
+
+        assert_ne!(dk, default::hkdf(&salt, data, info, 64).unwrap());
+        assert_eq!(
+            default::hkdf(&salt, data, info, 64).unwrap(),
+            default::hkdf_with_variant(&salt, data, info, 64, ShaVariantOption::SHA512Trunc256)
+                .unwrap()
+        );
+        assert!(default::hkdf_verify_with_variant(&dk, &salt, data, info, 64, sha384).unwrap());
+        assert!(
+            default::hkdf_verify_with_variant(&dk, &salt, data, info, 64, ShaVariantOption::SHA512)
+                .is_err()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn hkdf_with_variant_err() {
+        let salt = Salt::generate(64).unwrap();
+        let short_salt = Salt::generate(15).unwrap();
+        let sha384 = ShaVariantOption::SHA384;
+
+        assert!(default::hkdf_with_variant(&short_salt, b"", b"", 64, sha384).is_err());
+        assert!(default::hkdf_with_variant(&salt, b"", b"", 255 * 48, sha384).is_ok());
+        assert!(default::hkdf_with_variant(&salt, b"", b"", 255 * 48 + 1, sha384).is_err());
+
+        let res = default::hkdf_verify_with_variant(&[0u8; 64], &short_salt, b"", b"", 64, sha384);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
     fn hkdf_verify_err() {
         let salt = util::gen_rand_key(64).unwrap();
         let data = "Some data.".as_bytes();
         let info = "Some info.".as_bytes();
 
-        let mut hkdf_dk = default::hkdf(&salt, data, info, 64).unwrap();
+        let mut hkdf_dk = default::hkdf_from_slice(&salt, data, info, 64).unwrap();
         hkdf_dk.extend_from_slice(&[0u8; 4]);
 
-        assert!(default::hkdf_verify(&hkdf_dk, &salt, data, info, 64).is_err());
+        assert!(default::hkdf_verify_from_slice(&hkdf_dk, &salt, data, info, 64).is_err());
     }
 
     #[test]
     fn hkdf_salt_too_short() {
-        assert!(default::hkdf(&vec![0x61; 10], &vec![0x61; 10], &vec![0x61; 10], 20).is_err());
+        let res = default::hkdf_from_slice(&vec![0x61; 10], &vec![0x61; 10], &vec![0x61; 10], 20);
+
+        assert!(res.is_err());
     }
 
     #[test]
     fn hkdf_salt_allowed_len() {
-        default::hkdf(&vec![0x61; 67], &vec![0x61; 10], &vec![0x61; 10], 20).unwrap();
-        default::hkdf(&vec![0x61; 89], &vec![0x61; 10], &vec![0x61; 10], 20).unwrap();
+        default::hkdf_from_slice(&vec![0x61; 67], &vec![0x61; 10], &vec![0x61; 10], 20).unwrap();
+        default::hkdf_from_slice(&vec![0x61; 89], &vec![0x61; 10], &vec![0x61; 10], 20).unwrap();
     }
 
     #[test]
+    #[cfg(feature = "rng")]
     fn pbkdf2_verify() {
-        let password = util::gen_rand_key(64).unwrap();
+        let password = Password::from_slice(&util::gen_rand_key(64).unwrap()).unwrap();
 
         let pbkdf2_dk = default::pbkdf2(&password).unwrap();
 
+        assert!(pbkdf2_dk.starts_with("$pbkdf2-sha512-256$i=512000$"));
         assert_eq!(default::pbkdf2_verify(&pbkdf2_dk, &password).unwrap(), true);
+        assert_eq!(
+            default::pbkdf2_verify_from_slice(&pbkdf2_dk, password.unprotected_as_bytes()).unwrap(),
+            true
+        );
+        assert_eq!(default::password_hash_verify(&pbkdf2_dk, &password).unwrap(), true);
+    }
+
+    #[test]
+    fn pbkdf2_verify_other_iterations() {
+        let password = Password::from_slice("Secret password".as_bytes()).unwrap();
+        let salt = [0x61; 32];
+        let dk = Pbkdf2::new(&password, &salt, 100_000, 32, ShaVariantOption::SHA512Trunc256)
+            .unwrap()
+            .derive_key()
+            .unwrap();
+
+        // The iteration count is read from the string, not assumed to be 512.000
+        let hash = format!(
+            "$pbkdf2-sha512-256$i=100000${}${}",
+            util::to_base64(&salt),
+            util::to_base64(&dk)
+        );
+        assert_eq!(default::pbkdf2_verify(&hash, &password).unwrap(), true);
+    }
+
+    #[test]
+    fn pbkdf2_verify_legacy() {
+        let password = Password::from_slice("Secret password".as_bytes()).unwrap();
+        let other_password = Password::from_slice("Secret passwore".as_bytes()).unwrap();
+
+        // The 32 byte salt followed by the derived key, as returned before PHC strings
+        let mut legacy = vec![0x24; 32];
+        legacy.extend_from_slice(&default::pbkdf2_salted(&password, &[0x24; 32], 512_000).unwrap());
+
+        assert_eq!(default::pbkdf2_verify_legacy(&legacy, &password).unwrap(), true);
+        assert!(default::pbkdf2_verify_legacy(&legacy, &other_password).is_err());
+        assert!(default::pbkdf2_verify_legacy(&legacy[..63], &password).is_err());
+        // The legacy format is not detected by `pbkdf2_verify`
+        let legacy_str = String::from_utf8_lossy(&legacy).into_owned();
+        assert!(default::pbkdf2_verify(&legacy_str, &password).is_err());
     }
 
     #[test]
+    #[cfg(feature = "rng")]
     fn pbkdf2_verify_err() {
         let password = util::gen_rand_key(64).unwrap();
 
-        let mut pbkdf2_dk = default::pbkdf2(&password).unwrap();
-        pbkdf2_dk.extend_from_slice(&[0u8; 4]);
+        let mut pbkdf2_dk = default::pbkdf2_from_slice(&password).unwrap();
+        pbkdf2_dk.push_str("AAAA");
 
-        assert!(default::pbkdf2_verify(&pbkdf2_dk, &password).is_err());
+        assert!(default::pbkdf2_verify_from_slice(&pbkdf2_dk, &password).is_err());
+        assert!(default::pbkdf2_verify_from_slice("", &password).is_err());
     }
 
     #[test]
+    #[cfg(feature = "rng")]
     fn pbkdf2_verify_expected_dk_too_long() {
         let password = util::gen_rand_key(32).unwrap();
 
-        let mut pbkdf2_dk = default::pbkdf2(&password).unwrap();
-        pbkdf2_dk.extend_from_slice(&[0u8; 1]);
+        let mut pbkdf2_dk = default::pbkdf2_from_slice(&password).unwrap();
+        pbkdf2_dk.push('A');
 
-        assert!(default::pbkdf2_verify(&pbkdf2_dk, &password).is_err());
+        assert!(default::pbkdf2_verify_from_slice(&pbkdf2_dk, &password).is_err());
     }
 
     #[test]
+    #[cfg(feature = "rng")]
     fn pbkdf2_verify_expected_dk_too_short() {
         let password = util::gen_rand_key(64).unwrap();
 
-        let pbkdf2_dk = default::pbkdf2(&password).unwrap();
+        let pbkdf2_dk = default::pbkdf2_from_slice(&password).unwrap();
 
-        assert!(default::pbkdf2_verify(&pbkdf2_dk[..63], &password).is_err());
+        assert!(default::pbkdf2_verify_from_slice(&pbkdf2_dk[..63], &password).is_err());
+        assert!(default::pbkdf2_verify_from_slice(&pbkdf2_dk[..64], &password).is_err());
     }
 
     #[test]
+    #[cfg(feature = "rng")]
     fn pbkdf2_password_too_short() {
         let password = util::gen_rand_key(13).unwrap();
 
-        assert!(default::pbkdf2(&password).is_err());
+        assert!(default::pbkdf2_from_slice(&password).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn pbkdf2_with_iterations_verify() {
+        let password = Password::from_slice(&util::gen_rand_key(64).unwrap()).unwrap();
+        let other_password = Password::from_slice(&util::gen_rand_key(64).unwrap()).unwrap();
+
+        let dk = default::pbkdf2_with_iterations(&password, 100_001).unwrap();
+
+        assert!(dk.starts_with("$pbkdf2-sha512-256$i=100001$"));
+        assert_eq!(default::pbkdf2_with_iterations_verify(&dk, &password).unwrap(), true);
+        assert_eq!(default::pbkdf2_verify(&dk, &password).unwrap(), true);
+        assert!(default::pbkdf2_with_iterations_verify(&dk, &other_password).is_err());
+
+        // The same string with the last byte of the hash removed
+        let (params, hash) = dk.split_at(dk.rfind('$').unwrap() + 1);
+        let hash = util::from_base64(hash).unwrap();
+        let truncated = format!("{}{}", params, util::to_base64(&hash[..31]));
+        assert!(default::pbkdf2_with_iterations_verify(&truncated, &password).is_err());
+
+        let other_iter = dk.replace("i=100001", "i=100000");
+        assert!(default::pbkdf2_with_iterations_verify(&other_iter, &password).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn pbkdf2_with_iterations_minimum() {
+        let password = Password::from_slice(&util::gen_rand_key(64).unwrap()).unwrap();
+        let short_password = Password::from_slice(&util::gen_rand_key(13).unwrap()).unwrap();
+
+        assert!(default::pbkdf2_with_iterations(&password, 99_999).is_err());
+        assert!(default::pbkdf2_with_iterations(&password, 10_000_001).is_err());
+        assert!(default::pbkdf2_with_iterations(&short_password, 100_000).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn password_hash_verify() {
+        let password = Password::from_slice("Secret password".as_bytes()).unwrap();
+        let other_password = Password::from_slice("Secret passwore".as_bytes()).unwrap();
+
+        let hash = default::password_hash(&password).unwrap();
+        let parts: Vec<&str> = hash.split('$').collect();
+
+        assert_eq!(parts[1..3], ["pbkdf2-sha512-256", "i=512000"]);
+        assert_eq!(util::from_base64(parts[3]).unwrap().len(), 32);
+        assert_eq!(util::from_base64(parts[4]).unwrap().len(), 32);
+        assert_ne!(hash, default::password_hash(&password).unwrap());
+        assert_eq!(default::password_hash_verify(&hash, &password).unwrap(), true);
+        assert!(default::password_hash_verify(&hash, &other_password).is_err());
+    }
+
+    #[test]
+    fn password_hash_verify_uses_embedded_params() {
+        let password = Password::from_slice("Secret password".as_bytes()).unwrap();
+        let salt = [0x61; 16];
+        let dk = Pbkdf2::new(&password, &salt, 100_000, 32, ShaVariantOption::SHA512Trunc256)
+            .unwrap()
+            .derive_key()
+            .unwrap();
+
+        let hash = format!(
+            "$pbkdf2-sha512-256$i=100000${}${}",
+            util::to_base64(&salt),
+            util::to_base64(&dk)
+        );
+        let wrong_iter = hash.replace("i=100000", "i=100001");
+        // A prefix of the correct hash is not accepted
+        let truncated = format!(
+            "$pbkdf2-sha512-256$i=100000${}${}",
+            util::to_base64(&salt),
+            util::to_base64(&dk[..16])
+        );
+
+        assert_eq!(default::password_hash_verify(&hash, &password).unwrap(), true);
+        assert!(default::password_hash_verify(&wrong_iter, &password).is_err());
+        assert!(default::password_hash_verify(&truncated, &password).is_err());
+    }
+
+    #[test]
+    fn password_hash_verify_malformed() {
+        let password = Password::from_slice("Secret password".as_bytes()).unwrap();
+        let salt = util::to_base64(&[0x61; 16]);
+        let hash = util::to_base64(&[0x62; 32]);
+
+        let malformed = [
+            format!("pbkdf2-sha512-256$i=100000${}${}", salt, hash),
+            format!("$pbkdf2-sha256$i=100000${}${}", salt, hash),
+            format!("$pbkdf2-sha512-256$i=0${}${}", salt, hash),
+            format!("$pbkdf2-sha512-256$i=0100000${}${}", salt, hash),
+            format!("$pbkdf2-sha512-256$i=+100000${}${}", salt, hash),
+            format!("$pbkdf2-sha512-256$r=100000${}${}", salt, hash),
+            format!("$pbkdf2-sha512-256$i=100000,r=8${}${}", salt, hash),
+            format!("$pbkdf2-sha512-256$v=1$i=100000${}${}", salt, hash),
+            format!("$pbkdf2-sha512-256$i=100000${}${}$", salt, hash),
+            format!("$pbkdf2-sha512-256$i=100000${}=${}", salt, hash),
+            format!("$pbkdf2-sha512-256$i=100000${}${}", util::to_base64(&[0x61; 15]), hash),
+            format!("$pbkdf2-sha512-256$i=100000${}${}", salt, util::to_base64(&[0x62; 15])),
+            format!("$pbkdf2-sha512-256$i=100000${}${}", salt, util::to_base64(&[0x62; 31])),
+            format!("$pbkdf2-sha512-256$i=100000${}${}", salt, util::to_base64(&[0x62; 33])),
+        ];
+
+        for hash in malformed.iter() {
+            assert!(default::password_hash_verify(hash, &password).is_err());
+        }
+    }
+
+    #[test]
+    fn password_hash_verify_iteration_bounds() {
+        let password = Password::from_slice("Secret password".as_bytes()).unwrap();
+        let salt = [0x61; 16];
+        let dk = Pbkdf2::new(&password, &salt, 1, 32, ShaVariantOption::SHA512Trunc256)
+            .unwrap()
+            .derive_key()
+            .unwrap();
+
+        // A valid hash with too few iterations is rejected, as is one too expensive to verify
+        let hash = format!(
+            "$pbkdf2-sha512-256$i=1${}${}",
+            util::to_base64(&salt),
+            util::to_base64(&dk)
+        );
+        let too_many = hash.replace("i=1$", "i=4294967295$");
+
+        assert!(default::password_hash_verify(&hash, &password).is_err());
+        assert!(default::pbkdf2_verify(&hash, &password).is_err());
+        assert!(default::password_hash_verify(&too_many, &password).is_err());
+    }
+
+    #[test]
+    fn hash() {
+        let expected =
+            decode("53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23").unwrap();
+
+        assert_eq!(default::hash(b"abc")[..], expected[..]);
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn hash_stream() {
+        let data = util::gen_rand_key(300).unwrap();
+
+        let mut stream = default::HashStream::new();
+        for chunk in data.chunks(7) {
+            stream.update(chunk);
+        }
+        let mut writer = default::HashStream::new();
+        io::copy(&mut io::Cursor::new(&data), &mut writer).unwrap();
+
+        assert_eq!(stream.finalize(), default::hash(&data));
+        assert_eq!(writer.finalize(), default::hash(&data));
+        assert_eq!(
+            format!("{:?}", default::HashStream::new()),
+            "HashStream { state: SHA512Trunc256 }"
+        );
+    }
+
+    #[cfg(feature = "rng")]
+    fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+        let suffix = util::to_hex(&util::gen_rand_key(8).unwrap());
+        let path = env::temp_dir().join(format!("orion-{}-{}", name, suffix));
+        File::create(&path).unwrap().write_all(contents).unwrap();
+
+        path
+    }
+
+    #[cfg(feature = "rng")]
+    /// Return the temporary files left next to `path` when writing it.
+    fn tmp_files(path: &Path) -> Vec<PathBuf> {
+        let prefix = format!("{}.", path.file_name().unwrap().to_string_lossy());
+        fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|tmp| {
+                let name = tmp.file_name().unwrap().to_string_lossy();
+                name.starts_with(&prefix) && name.ends_with(".tmp")
+            })
+            .collect()
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn keystore() {
+        let password = Password::from_slice("Secret password".as_bytes()).unwrap();
+        let path = temp_file("keystore", b"");
+        fs::remove_file(&path).unwrap();
+
+        let mut keystore = default::Keystore::create(&path, &password).unwrap();
+        let first = util::gen_rand_key(32).unwrap();
+        let second = util::gen_rand_key(64).unwrap();
+        let key = |bytes: &[u8]| SecretKey::from_slice(bytes).unwrap();
+        keystore.add_key("first", key(&first)).unwrap();
+        keystore.add_key("second", key(&second)).unwrap();
+        assert!(keystore.add_key("first", key(&first)).is_err());
+        assert!(keystore.add_key("", key(&first)).is_err());
+        assert!(keystore.add_key(&"a".repeat(256), key(&first)).is_err());
+        keystore.add_key(&"a".repeat(255), key(&first)).unwrap();
+        assert!(keystore.remove_key(&"a".repeat(255)).unwrap());
+        assert!(!keystore.remove_key("third").unwrap());
+        assert!(tmp_files(&path).is_empty());
+
+        let mut keystore = default::Keystore::open(&path, &password).unwrap();
+        assert_eq!(keystore.names(), ["first", "second"]);
+        assert_eq!(keystore.get_key("first").unwrap().unprotected_as_bytes(), &first[..]);
+        assert_eq!(keystore.get_key("second").unwrap().unprotected_as_bytes(), &second[..]);
+        assert_eq!(keystore.get_key("third"), None);
+        assert_eq!(
+            format!("{:?}", keystore),
+            format!("Keystore {{ path: {:?}, names: [\"first\", \"second\"], keys: *** }}", path)
+        );
+
+        assert!(keystore.remove_key("first").unwrap());
+        let keystore = default::Keystore::open(&path, &password).unwrap();
+        assert_eq!(keystore.names(), ["second"]);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn keystore_err() {
+        let password = Password::from_slice("Secret password".as_bytes()).unwrap();
+        let other_password = Password::from_slice("Secret passwore".as_bytes()).unwrap();
+        let short_password = Password::from_slice("Short".as_bytes()).unwrap();
+        let path = temp_file("keystore_err", b"");
+
+        // The file exists, and is left as it was
+        fs::write(&path, b"Some data").unwrap();
+        assert!(default::Keystore::create(&path, &password).is_err());
+        assert_eq!(fs::read(&path).unwrap(), b"Some data");
+        assert!(default::Keystore::open(&path, &password).is_err());
+        fs::remove_file(&path).unwrap();
+        assert!(default::Keystore::create(&path, &short_password).is_err());
+        assert!(default::Keystore::open(&path, &password).is_err());
+        assert!(!path.exists());
+
+        let mut keystore = default::Keystore::create(&path, &password).unwrap();
+        keystore.add_key("key", SecretKey::generate(32).unwrap()).unwrap();
+        assert!(default::Keystore::open(&path, &other_password).is_err());
+
+        let mut ciphertext = fs::read(&path).unwrap();
+        let last = ciphertext.len() - 1;
+        ciphertext[last] ^= 1;
+        fs::write(&path, &ciphertext).unwrap();
+        assert!(default::Keystore::open(&path, &password).is_err());
+
+        // An iteration count over PBKDF2_MAX_ITERATIONS is rejected before deriving the key
+        ciphertext[last] ^= 1;
+        ciphertext[7..11].copy_from_slice(&[0xff, 0xff, 0xff, 0xff]);
+        fs::write(&path, &ciphertext).unwrap();
+        assert!(default::Keystore::open(&path, &password).is_err());
+
+        // A file written by encrypt_with_password is not a keystore
+        let ciphertext = default::encrypt_with_password(&password, b"key").unwrap();
+        fs::write(&path, &ciphertext).unwrap();
+        assert!(default::Keystore::open(&path, &password).is_err());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn hash_file() {
+        let data = util::gen_rand_key(20_000).unwrap();
+        let path = temp_file("hash_file", &data);
+
+        let digest = default::hash_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(digest, default::hash(&data));
+        assert!(default::hash_file(&path).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn hmac_file() {
+        let key = SecretKey::generate(64).unwrap();
+        let data = util::gen_rand_key(20_000).unwrap();
+        let path = temp_file("hmac_file", &data);
+
+        let tag = default::hmac_file(&key, &path).unwrap();
+        let short_key = default::hmac_file(&SecretKey::generate(63).unwrap(), &path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(tag, default::hmac(&key, &data).unwrap());
+        assert!(short_key.is_err());
+        assert!(default::hmac_file(&key, &path).is_err());
     }
 
     #[test]
+    #[cfg(feature = "rng")]
     fn cshake_ok() {
         let data = util::gen_rand_key(64).unwrap();
         let custom = "Some custom string".as_bytes();
@@ -495,6 +2952,7 @@ mod test {
     }
 
     #[test]
+    #[cfg(feature = "rng")]
     fn cshake_empty_custom_err() {
         let data = util::gen_rand_key(64).unwrap();
         let custom = "".as_bytes();
@@ -503,6 +2961,7 @@ mod test {
     }
 
     #[test]
+    #[cfg(feature = "rng")]
     fn cshake_verify() {
         let data = util::gen_rand_key(64).unwrap();
         let custom = "Some custom string".as_bytes();
@@ -516,6 +2975,7 @@ mod test {
     }
 
     #[test]
+    #[cfg(feature = "rng")]
     fn cshake_verify_err() {
         let data = util::gen_rand_key(64).unwrap();
         let custom = "Some custom string".as_bytes();
@@ -526,6 +2986,7 @@ mod test {
     }
 
     #[test]
+    #[cfg(feature = "rng")]
     fn cshake_verify_err_len() {
         let data = util::gen_rand_key(64).unwrap();
         let custom = "Some custom string".as_bytes();
@@ -534,4 +2995,520 @@ mod test {
 
         assert!(default::cshake_verify(&cshake[..63], &data, custom).is_err());
     }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn cshake_with_length() {
+        let data = util::gen_rand_key(64).unwrap();
+        let custom = "Some custom string".as_bytes();
+
+        let hash_16 = default::cshake_with_length(&data, custom, 16).unwrap();
+        let hash_64 = default::cshake_with_length(&data, custom, 64).unwrap();
+
+        assert_eq!(hash_16.len(), 16);
+        assert_eq!(hash_64[..], default::cshake(&data, custom).unwrap()[..]);
+        assert_eq!(
+            default::cshake_verify_with_length(&hash_16, &data, custom).unwrap(),
+            true
+        );
+        assert!(default::cshake_verify_with_length(&hash_64, &data, custom).is_ok());
+        assert!(default::cshake_verify_with_length(&hash_16, &data, b"Other").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn cshake_with_length_bounds() {
+        let data = util::gen_rand_key(64).unwrap();
+        let custom = "Some custom string".as_bytes();
+
+        assert!(default::cshake_with_length(&data, custom, 15).is_err());
+        assert!(default::cshake_with_length(&data, custom, 1024).is_ok());
+        assert!(default::cshake_with_length(&data, custom, 1025).is_err());
+        assert!(default::cshake_verify_with_length(&[0u8; 15], &data, custom).is_err());
+        assert!(default::cshake_verify_with_length(&[0u8; 1025], &data, custom).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn commit_verify() {
+        let data = "Bid: 300".as_bytes();
+
+        let (commitment, opening) = default::commit(data).unwrap();
+
+        assert_eq!(
+            default::commit_verify(&commitment, &opening, data).unwrap(),
+            true
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn commit_verify_err_data() {
+        let data = "Bid: 300".as_bytes();
+
+        let (commitment, opening) = default::commit(data).unwrap();
+
+        assert!(default::commit_verify(&commitment, &opening, "Bid: 301".as_bytes()).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn commit_verify_err_opening() {
+        let data = "Bid: 300".as_bytes();
+
+        let (commitment, _) = default::commit(data).unwrap();
+        let (_, other_opening) = default::commit(data).unwrap();
+
+        assert!(default::commit_verify(&commitment, &other_opening, data).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn commit_verify_err_len() {
+        let data = "Bid: 300".as_bytes();
+
+        let (commitment, opening) = default::commit(data).unwrap();
+
+        assert!(default::commit_verify(&commitment[..63], &opening, data).is_err());
+        assert!(default::commit_verify(&commitment, &opening[..31], data).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn encrypt_decrypt() {
+        let key = SecretKey::generate(32).unwrap();
+        let plaintext = "Secret message".as_bytes();
+
+        let ciphertext = default::encrypt(&key, plaintext).unwrap();
+
+        assert_eq!(ciphertext.len(), plaintext.len() + 47);
+        assert_eq!(ciphertext[..7], [b'O', b'R', b'N', b'B', 0x01, 0x01, 0x00]);
+        assert_ne!(ciphertext, default::encrypt(&key, plaintext).unwrap());
+        assert_eq!(default::decrypt(&key, &ciphertext).unwrap(), plaintext);
+
+        let empty = default::encrypt(&key, b"").unwrap();
+        assert!(default::decrypt(&key, &empty).unwrap().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn decrypt_err() {
+        let key = SecretKey::generate(32).unwrap();
+        let other_key = SecretKey::generate(32).unwrap();
+        let mut ciphertext = default::encrypt(&key, "Secret message".as_bytes()).unwrap();
+
+        assert!(default::decrypt(&other_key, &ciphertext).is_err());
+        assert!(default::decrypt(&key, &ciphertext[..39]).is_err());
+
+        for idx in 0..ciphertext.len() {
+            ciphertext[idx] ^= 1;
+            assert!(default::decrypt(&key, &ciphertext).is_err());
+            ciphertext[idx] ^= 1;
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn encrypt_key_len_err() {
+        let key = SecretKey::generate(64).unwrap();
+
+        assert!(default::encrypt(&key, "Secret message".as_bytes()).is_err());
+        assert!(default::decrypt(&key, &[0u8; 40]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn decrypt_legacy() {
+        let key = SecretKey::generate(32).unwrap();
+        let plaintext = "Secret message".as_bytes();
+
+        // Nonce, ciphertext and tag, as written before the blob format. The second nonce
+        // starts like a blob header.
+        let nonces = [[0x61; 24], *b"ORNB\x01\x01\x00aaaaaaaaaaaaaaaaa"];
+        for nonce in nonces.iter() {
+            let nonce = Nonce::from_slice(nonce).unwrap();
+            let mut legacy = nonce.as_bytes().to_vec();
+            legacy.extend_from_slice(
+                &xchacha20poly1305::seal(key.unprotected_as_bytes(), &nonce, plaintext, &[])
+                    .unwrap(),
+            );
+
+            assert_eq!(default::decrypt(&key, &legacy).unwrap(), plaintext);
+            legacy[30] ^= 1;
+            assert!(default::decrypt(&key, &legacy).is_err());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn encrypt_decrypt_committing() {
+        let key = SecretKey::generate(32).unwrap();
+        let other_key = SecretKey::generate(32).unwrap();
+        let plaintext = "Secret message".as_bytes();
+
+        let ciphertext = default::encrypt_committing(&key, plaintext).unwrap();
+
+        assert_eq!(ciphertext.len(), plaintext.len() + 79);
+        assert_eq!(ciphertext[..7], [b'O', b'R', b'N', b'B', 0x01, 0x02, 0x00]);
+        assert_ne!(ciphertext, default::encrypt_committing(&key, plaintext).unwrap());
+        assert_eq!(default::decrypt_committing(&key, &ciphertext).unwrap(), plaintext);
+        assert_eq!(default::decrypt(&key, &ciphertext).unwrap(), plaintext);
+        assert!(default::verify_commitment(&key, &ciphertext).unwrap());
+
+        assert!(default::decrypt_committing(&other_key, &ciphertext).is_err());
+        assert!(default::verify_commitment(&other_key, &ciphertext).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn decrypt_committing_err() {
+        let key = SecretKey::generate(32).unwrap();
+        let mut ciphertext =
+            default::encrypt_committing(&key, "Secret message".as_bytes()).unwrap();
+
+        for idx in 0..ciphertext.len() {
+            ciphertext[idx] ^= 1;
+            assert!(default::decrypt_committing(&key, &ciphertext).is_err());
+            ciphertext[idx] ^= 1;
+        }
+        assert!(default::decrypt_committing(&key, &ciphertext[..78]).is_err());
+
+        // Blobs without a commitment are not accepted
+        let plain = default::encrypt(&key, "Secret message".as_bytes()).unwrap();
+        assert!(default::decrypt_committing(&key, &plain).is_err());
+        assert!(default::verify_commitment(&key, &plain).is_err());
+
+        let long_key = SecretKey::generate(64).unwrap();
+        assert!(default::encrypt_committing(&long_key, b"").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn decrypt_password_blob_err() {
+        let key = SecretKey::generate(32).unwrap();
+        let password = Password::from_slice("Secret password".as_bytes()).unwrap();
+
+        // A blob with a derived key is not accepted with a secret key, and the other way around
+        let ciphertext = default::encrypt_with_password(&password, b"").unwrap();
+        assert!(default::decrypt(&key, &ciphertext).is_err());
+        let ciphertext = default::encrypt(&key, b"").unwrap();
+        assert!(default::decrypt_with_password(&password, &ciphertext).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn envelope_encrypt_decrypt() {
+        let kek = SecretKey::generate(32).unwrap();
+        let other_kek = SecretKey::generate(32).unwrap();
+        let plaintext = "Secret message".as_bytes();
+
+        let ciphertext = default::envelope_encrypt(&kek, plaintext).unwrap();
+
+        assert_eq!(ciphertext.len(), plaintext.len() + 127);
+        assert_eq!(ciphertext[..8], [b'O', b'R', b'N', b'B', 0x01, 0x01, 0x03, 79]);
+        assert_eq!(ciphertext[8..15], [b'O', b'R', b'N', b'B', 0x01, 0x01, 0x00]);
+        assert_ne!(ciphertext, default::envelope_encrypt(&kek, plaintext).unwrap());
+        assert_eq!(default::envelope_decrypt(&kek, &ciphertext).unwrap(), plaintext);
+        assert!(default::envelope_decrypt(&other_kek, &ciphertext).is_err());
+
+        let empty = default::envelope_encrypt(&kek, b"").unwrap();
+        assert!(default::envelope_decrypt(&kek, &empty).unwrap().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn envelope_decrypt_err() {
+        let kek = SecretKey::generate(32).unwrap();
+        let mut ciphertext = default::envelope_encrypt(&kek, "Secret message".as_bytes()).unwrap();
+
+        // Every byte, including the wrapped key, is authenticated
+        for idx in 0..ciphertext.len() {
+            ciphertext[idx] ^= 1;
+            assert!(default::envelope_decrypt(&kek, &ciphertext).is_err());
+            ciphertext[idx] ^= 1;
+        }
+        assert!(default::envelope_decrypt(&kek, &ciphertext[..126]).is_err());
+
+        // Envelopes and plain blobs are not interchangeable
+        assert!(default::decrypt(&kek, &ciphertext).is_err());
+        let plain = default::encrypt(&kek, "Secret message".as_bytes()).unwrap();
+        assert!(default::envelope_decrypt(&kek, &plain).is_err());
+
+        let long_kek = SecretKey::generate(64).unwrap();
+        assert!(default::envelope_encrypt(&long_kek, b"").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn encrypt_decrypt_with_password() {
+        let password = Password::from_slice("Secret password".as_bytes()).unwrap();
+        let other_password = Password::from_slice("Secret passwore".as_bytes()).unwrap();
+        let plaintext = "Secret message".as_bytes();
+
+        let ciphertext = default::encrypt_with_password(&password, plaintext).unwrap();
+
+        assert_eq!(ciphertext.len(), plaintext.len() + 84);
+        assert_eq!(ciphertext[..7], [b'O', b'R', b'N', b'B', 0x01, 0x01, 0x01]);
+        assert_eq!(ciphertext[7..12], [0x00, 0x07, 0xd0, 0x00, 32]);
+        assert_eq!(
+            default::decrypt_with_password(&password, &ciphertext).unwrap(),
+            plaintext
+        );
+        assert!(default::decrypt_with_password(&other_password, &ciphertext).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn decrypt_with_password_err() {
+        let password = Password::from_slice("Secret password".as_bytes()).unwrap();
+        let short_password = Password::from_slice("Short".as_bytes()).unwrap();
+        let ciphertext = default::encrypt_with_password(&password, b"").unwrap();
+
+        let mut other_version = ciphertext.clone();
+        other_version[4] = 0x02;
+        let mut low_iterations = ciphertext.clone();
+        low_iterations[7..11].copy_from_slice(&[0x00, 0x01, 0x86, 0x9f]);
+        let mut high_iterations = ciphertext.clone();
+        high_iterations[7..11].copy_from_slice(&[0x00, 0x98, 0x96, 0x81]);
+        let mut other_salt = ciphertext.clone();
+        other_salt[12] ^= 1;
+
+        assert!(default::encrypt_with_password(&short_password, b"").is_err());
+        assert!(default::decrypt_with_password(&password, &ciphertext[..83]).is_err());
+        assert!(default::decrypt_with_password(&password, &other_version).is_err());
+        assert!(default::decrypt_with_password(&password, &low_iterations).is_err());
+        assert!(default::decrypt_with_password(&password, &high_iterations).is_err());
+        assert!(default::decrypt_with_password(&password, &other_salt).is_err());
+    }
+
+    #[test]
+    fn decrypt_with_password_legacy() {
+        let password = Password::from_slice("Secret password".as_bytes()).unwrap();
+        let salt = [0x61; 32];
+        let nonce = Nonce::from_slice(&[0x62; 24]).unwrap();
+        let dk = Pbkdf2::new(&password, &salt, 100_000, 32, ShaVariantOption::SHA512Trunc256)
+            .unwrap()
+            .derive_key()
+            .unwrap();
+
+        // Version byte, iterations and salt, followed by the nonce, ciphertext and tag, as
+        // written before the blob format
+        let mut legacy = vec![0x01, 0x00, 0x01, 0x86, 0xa0];
+        legacy.extend_from_slice(&salt);
+        let ciphertext = xchacha20poly1305::seal(&dk, &nonce, b"Secret message", &legacy).unwrap();
+        legacy.extend_from_slice(nonce.as_bytes());
+        legacy.extend_from_slice(&ciphertext);
+
+        assert_eq!(
+            default::decrypt_with_password(&password, &legacy).unwrap(),
+            b"Secret message"
+        );
+        legacy[1..5].copy_from_slice(&[0x00, 0x01, 0x86, 0x9f]);
+        assert!(default::decrypt_with_password(&password, &legacy).is_err());
+        legacy[1..5].copy_from_slice(&[0x00, 0x98, 0x96, 0x81]);
+        assert!(default::decrypt_with_password(&password, &legacy).is_err());
+        legacy[1..5].copy_from_slice(&[0xff, 0xff, 0xff, 0xff]);
+        assert!(default::decrypt_with_password(&password, &legacy).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn keyring_rotation() {
+        let mut keyring = default::Keyring::new();
+        assert_eq!(keyring.primary_key_id(), None);
+        assert!(keyring.encrypt(b"").is_err());
+        assert!(keyring.hmac(b"").is_err());
+
+        keyring.add_key(1, SecretKey::generate(32).unwrap()).unwrap();
+        let first_ciphertext = keyring.encrypt(b"Secret message").unwrap();
+        let first_tag = keyring.hmac(b"Some message.").unwrap();
+        assert_eq!(first_ciphertext.len(), 14 + 51);
+        assert_eq!(first_tag.len(), 36);
+        assert_eq!(first_tag[..4], [0, 0, 0, 1]);
+
+        keyring.add_key(0xdead_beef, SecretKey::generate(64).unwrap()).unwrap();
+        let second_ciphertext = keyring.encrypt(b"Secret message").unwrap();
+        let second_tag = keyring.hmac(b"Some message.").unwrap();
+        assert_eq!(keyring.primary_key_id(), Some(0xdead_beef));
+        assert_eq!(keyring.key_ids(), [1, 0xdead_beef]);
+        assert_eq!(keyring.key_id_of(&first_ciphertext), Some(1));
+        assert_eq!(keyring.key_id_of(&second_ciphertext), Some(0xdead_beef));
+        assert_eq!(second_tag[..4], [0xde, 0xad, 0xbe, 0xef]);
+
+        for ciphertext in [&first_ciphertext, &second_ciphertext].iter() {
+            assert_eq!(keyring.decrypt(ciphertext).unwrap(), b"Secret message");
+        }
+        for tag in [&first_tag, &second_tag].iter() {
+            assert!(keyring.hmac_verify(tag, b"Some message.").unwrap());
+            assert!(keyring.hmac_verify(tag, b"Some message").is_err());
+        }
+
+        // Once the old key is removed, its data can no longer be read
+        assert!(keyring.remove_key(1));
+        assert!(!keyring.remove_key(1));
+        assert!(keyring.decrypt(&first_ciphertext).is_err());
+        assert!(keyring.hmac_verify(&first_tag, b"Some message.").is_err());
+        assert_eq!(keyring.decrypt(&second_ciphertext).unwrap(), b"Secret message");
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn keyring_err() {
+        let key = SecretKey::generate(32).unwrap();
+        let mut keyring = default::Keyring::new();
+        assert!(keyring.add_key(1, SecretKey::generate(31).unwrap()).is_err());
+        keyring.add_key(1, SecretKey::from_slice(key.unprotected_as_bytes()).unwrap()).unwrap();
+        assert!(keyring.add_key(1, SecretKey::generate(32).unwrap()).is_err());
+
+        let mut ciphertext = keyring.encrypt(b"Secret message").unwrap();
+        let mut tag = keyring.hmac(b"Some message.").unwrap();
+        for idx in 0..ciphertext.len() {
+            ciphertext[idx] ^= 1;
+            assert!(keyring.decrypt(&ciphertext).is_err());
+            ciphertext[idx] ^= 1;
+        }
+        for idx in 0..tag.len() {
+            tag[idx] ^= 1;
+            assert!(keyring.hmac_verify(&tag, b"Some message.").is_err());
+            tag[idx] ^= 1;
+        }
+        assert!(keyring.hmac_verify(&tag[..35], b"Some message.").is_err());
+
+        // The key is not used directly, and other blobs are not accepted
+        assert!(default::decrypt(&key, &ciphertext).is_err());
+        let other = default::encrypt(&key, b"Secret message").unwrap();
+        assert_eq!(keyring.key_id_of(&other), None);
+        assert!(keyring.decrypt(&other).is_err());
+
+        assert_eq!(format!("{:?}", keyring), "Keyring { key_ids: [1], keys: *** }");
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn encrypt_decrypt_file() {
+        let key = SecretKey::generate(32).unwrap();
+
+        for len in [0, 1, 65535, 65536, 65537, 3 * 65536].iter() {
+            let data = util::gen_rand_key(*len + 1).unwrap()[..*len].to_vec();
+            let input = temp_file("encrypt_file", &data);
+            let encrypted = input.with_extension("enc");
+            let decrypted = input.with_extension("dec");
+
+            default::encrypt_file(&key, &input, &encrypted).unwrap();
+            default::decrypt_file(&key, &encrypted, &decrypted).unwrap();
+
+            let chunks = len / 65536 + 1;
+            assert_eq!(
+                fs::metadata(&encrypted).unwrap().len() as usize,
+                24 + len + 17 * chunks
+            );
+            assert_eq!(fs::read(&decrypted).unwrap(), data);
+
+            for path in [input, encrypted, decrypted].iter() {
+                fs::remove_file(path).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn decrypt_file_err() {
+        let key = SecretKey::generate(32).unwrap();
+        let data = util::gen_rand_key(2 * 65536 + 100).unwrap();
+        let input = temp_file("decrypt_file", &data);
+        let encrypted = input.with_extension("enc");
+        let decrypted = input.with_extension("dec");
+        default::encrypt_file(&key, &input, &encrypted).unwrap();
+        let ciphertext = fs::read(&encrypted).unwrap();
+
+        let mut modified = ciphertext.clone();
+        modified[24 + 65536] ^= 1;
+        let mut trailing = ciphertext.clone();
+        trailing.push(0);
+        let invalid = [
+            // Truncated at a chunk boundary, inside a chunk and inside the header
+            ciphertext[..24 + 2 * (65536 + 17)].to_vec(),
+            ciphertext[..ciphertext.len() - 1].to_vec(),
+            ciphertext[..23].to_vec(),
+            modified,
+            trailing,
+        ];
+
+        for bad in invalid.iter() {
+            File::create(&encrypted).unwrap().write_all(bad).unwrap();
+            assert!(default::decrypt_file(&key, &encrypted, &decrypted).is_err());
+            assert!(!decrypted.exists());
+            assert!(tmp_files(&decrypted).is_empty());
+        }
+
+        // Decrypting over an existing file with the wrong key leaves that file as it was
+        File::create(&encrypted).unwrap().write_all(&ciphertext).unwrap();
+        let wrong_key = SecretKey::generate(32).unwrap();
+        assert!(default::decrypt_file(&wrong_key, &encrypted, &input).is_err());
+        assert_eq!(fs::read(&input).unwrap(), data);
+
+        File::create(&encrypted).unwrap().write_all(&ciphertext).unwrap();
+        let other_key = SecretKey::generate(32).unwrap();
+        let short_key = SecretKey::generate(31).unwrap();
+        assert!(default::decrypt_file(&other_key, &encrypted, &decrypted).is_err());
+        assert!(default::decrypt_file(&short_key, &encrypted, &decrypted).is_err());
+        assert!(default::encrypt_file(&short_key, &input, &encrypted).is_err());
+        assert_eq!(fs::read(&encrypted).unwrap(), ciphertext);
+
+        // Reading a directory fails after the temporary file has been created
+        assert!(default::encrypt_file(&key, env::temp_dir(), &encrypted).is_err());
+        assert_eq!(fs::read(&encrypted).unwrap(), ciphertext);
+        assert!(tmp_files(&encrypted).is_empty());
+
+        for path in [input, encrypted].iter() {
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn eff_wordlist() {
+        let wordlist: Vec<&str> = default::EFF_LARGE_WORDLIST.lines().collect();
+
+        assert_eq!(wordlist.len(), 7776);
+        assert_eq!(wordlist[0], "abacus");
+        assert_eq!(wordlist[7775], "zoom");
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn gen_passphrase() {
+        let wordlist: Vec<&str> = default::EFF_LARGE_WORDLIST.lines().collect();
+
+        let passphrase = default::gen_passphrase(8).unwrap();
+        let words: Vec<&str> = passphrase.split(' ').collect();
+
+        assert_eq!(words.len(), 8);
+        assert!(words.iter().all(|word| wordlist.contains(word)));
+        assert_ne!(passphrase, default::gen_passphrase(8).unwrap());
+        assert!(default::gen_passphrase(4).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn gen_token() {
+        let token = default::gen_token(16).unwrap();
+
+        assert_eq!(token.len(), 22);
+        assert_eq!(default::gen_token(33).unwrap().len(), 44);
+        assert!(token.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_'));
+        assert_ne!(token, default::gen_token(16).unwrap());
+        assert!(default::gen_token(15).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rng")]
+    fn commit_is_hiding() {
+        let data = "Bid: 300".as_bytes();
+
+        let (commitment_1, _) = default::commit(data).unwrap();
+        let (commitment_2, _) = default::commit(data).unwrap();
+
+        assert_ne!(commitment_1, commitment_2);
+    }
 }
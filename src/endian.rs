@@ -0,0 +1,134 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Every function panics if the length of the slice does not match the size of the integer(s),
+// which would be a bug in orion.
+
+/// Read a `u32` from a 4 byte slice in little-endian format.
+pub fn read_u32_le(src: &[u8]) -> u32 {
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(src);
+
+    u32::from_le_bytes(bytes)
+}
+
+/// Read a `u32` from a 4 byte slice in big-endian format.
+pub fn read_u32_be(src: &[u8]) -> u32 {
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(src);
+
+    u32::from_be_bytes(bytes)
+}
+
+/// Read a `u64` from an 8 byte slice in little-endian format.
+pub fn read_u64_le(src: &[u8]) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(src);
+
+    u64::from_le_bytes(bytes)
+}
+
+/// Write a `u32` into a 4 byte slice in little-endian format.
+pub fn write_u32_le(dst: &mut [u8], n: u32) {
+    dst.copy_from_slice(&n.to_le_bytes());
+}
+
+/// Write a `u32` into a 4 byte slice in big-endian format.
+pub fn write_u32_be(dst: &mut [u8], n: u32) {
+    dst.copy_from_slice(&n.to_be_bytes());
+}
+
+/// Write a `u64` into an 8 byte slice in little-endian format.
+pub fn write_u64_le(dst: &mut [u8], n: u64) {
+    dst.copy_from_slice(&n.to_le_bytes());
+}
+
+/// Read `src` into the `u32`s of `dst` in little-endian format. `src` must be four times as
+/// long as `dst`.
+pub fn read_u32v_le(dst: &mut [u32], src: &[u8]) {
+    assert_eq!(dst.len() * 4, src.len());
+
+    for (word, bytes) in dst.iter_mut().zip(src.chunks(4)) {
+        *word = read_u32_le(bytes);
+    }
+}
+
+/// Write the `u32`s of `src` into `dst` in little-endian format. `dst` must be four times as
+/// long as `src`.
+pub fn write_u32v_le(dst: &mut [u8], src: &[u32]) {
+    assert_eq!(dst.len(), src.len() * 4);
+
+    for (bytes, word) in dst.chunks_mut(4).zip(src.iter()) {
+        write_u32_le(bytes, *word);
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use endian::*;
+
+    #[test]
+    fn read_write_u32() {
+        let mut buf = [0u8; 4];
+        write_u32_le(&mut buf, 0x0102_0304);
+        assert_eq!(buf, [4, 3, 2, 1]);
+        assert_eq!(read_u32_le(&buf), 0x0102_0304);
+
+        write_u32_be(&mut buf, 0x0102_0304);
+        assert_eq!(buf, [1, 2, 3, 4]);
+        assert_eq!(read_u32_be(&buf), 0x0102_0304);
+    }
+
+    #[test]
+    fn read_write_u64() {
+        let mut buf = [0u8; 8];
+        write_u64_le(&mut buf, 0x0102_0304_0506_0708);
+        assert_eq!(buf, [8, 7, 6, 5, 4, 3, 2, 1]);
+        assert_eq!(read_u64_le(&buf), 0x0102_0304_0506_0708);
+    }
+
+    #[test]
+    fn read_write_u32v() {
+        let bytes = [1u8, 0, 0, 0, 0, 1, 0, 0, 0xff, 0xff, 0xff, 0xff];
+        let mut words = [0u32; 3];
+        read_u32v_le(&mut words, &bytes);
+        assert_eq!(words, [1, 256, u32::max_value()]);
+
+        let mut out = [0u8; 12];
+        write_u32v_le(&mut out, &words);
+        assert_eq!(out, bytes);
+    }
+
+    #[test]
+    #[should_panic]
+    fn panic_on_wrong_length() {
+        read_u32_le(&[0u8; 3]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn panic_on_wrong_vector_length() {
+        let mut words = [0u32; 2];
+        read_u32v_le(&mut words, &[0u8; 9]);
+    }
+}
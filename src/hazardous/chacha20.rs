@@ -0,0 +1,296 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::errors::*;
+use endian::{read_u32v_le, write_u32v_le};
+use zeroize::Zeroize;
+
+/// The ChaCha20 constants "expand 32-byte k".
+const CONSTANTS: [u32; 4] = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574];
+
+/// ChaCha20 state. The state is zeroed out on drop.
+struct ChaChaState {
+    state: [u32; 16],
+}
+
+impl Drop for ChaChaState {
+    fn drop(&mut self) {
+        self.state.zeroize()
+    }
+}
+
+impl ChaChaState {
+    /// Initialize a state from a key and a nonce. With a 12 byte nonce, the block counter is
+    /// placed before the nonce as in the IETF variant. With a 16 byte nonce, the nonce occupies
+    /// all of the last four words, which is what HChaCha20 expects.
+    fn init(key: &[u8], nonce: &[u8]) -> Result<Self, UnknownCryptoError> {
+        if key.len() != 32 {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut state = [0u32; 16];
+        state[..4].copy_from_slice(&CONSTANTS);
+        read_u32v_le(&mut state[4..12], key);
+
+        match nonce.len() {
+            12 => read_u32v_le(&mut state[13..16], nonce),
+            16 => read_u32v_le(&mut state[12..16], nonce),
+            _ => return Err(UnknownCryptoError),
+        }
+
+        Ok(ChaChaState { state })
+    }
+
+    /// The ChaCha quarter round on the working state.
+    fn quarter_round(x: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+        x[a] = x[a].wrapping_add(x[b]);
+        x[d] ^= x[a];
+        x[d] = x[d].rotate_left(16);
+
+        x[c] = x[c].wrapping_add(x[d]);
+        x[b] ^= x[c];
+        x[b] = x[b].rotate_left(12);
+
+        x[a] = x[a].wrapping_add(x[b]);
+        x[d] ^= x[a];
+        x[d] = x[d].rotate_left(8);
+
+        x[c] = x[c].wrapping_add(x[d]);
+        x[b] ^= x[c];
+        x[b] = x[b].rotate_left(7);
+    }
+
+    /// Apply the 20 rounds (10 double rounds) to a copy of the state and return it.
+    fn rounds(&self) -> [u32; 16] {
+        let mut working_state = self.state;
+
+        for _ in 0..10 {
+            // Column rounds
+            Self::quarter_round(&mut working_state, 0, 4, 8, 12);
+            Self::quarter_round(&mut working_state, 1, 5, 9, 13);
+            Self::quarter_round(&mut working_state, 2, 6, 10, 14);
+            Self::quarter_round(&mut working_state, 3, 7, 11, 15);
+            // Diagonal rounds
+            Self::quarter_round(&mut working_state, 0, 5, 10, 15);
+            Self::quarter_round(&mut working_state, 1, 6, 11, 12);
+            Self::quarter_round(&mut working_state, 2, 7, 8, 13);
+            Self::quarter_round(&mut working_state, 3, 4, 9, 14);
+        }
+
+        working_state
+    }
+
+    /// The ChaCha20 block function. Writes one 64 byte keystream block for the given counter.
+    fn block(&mut self, counter: u32, dst: &mut [u8]) {
+        self.state[12] = counter;
+        let mut working_state = self.rounds();
+
+        for (word, state_word) in working_state.iter_mut().zip(self.state.iter()) {
+            *word = word.wrapping_add(*state_word);
+        }
+
+        write_u32v_le(dst, &working_state);
+        working_state.zeroize();
+    }
+}
+
+/// Apply the ChaCha20 keystream to `input`, starting at the given block counter.
+fn xor_keystream(
+    key: &[u8],
+    nonce: &[u8],
+    initial_counter: u32,
+    input: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    if nonce.len() != 12 {
+        return Err(UnknownCryptoError);
+    }
+    // The block counter must not wrap around while processing input
+    let n_blocks: u64 = ((input.len() as u64) + 63) / 64;
+    if (u64::from(initial_counter) + n_blocks) > (u64::from(u32::max_value()) + 1) {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut chacha_state = ChaChaState::init(key, nonce)?;
+    let mut keystream_block = [0u8; 64];
+    let mut output = input.to_vec();
+
+    for (counter, chunk) in output.chunks_mut(64).enumerate() {
+        chacha_state.block(initial_counter + counter as u32, &mut keystream_block);
+
+        for (out_byte, ks_byte) in chunk.iter_mut().zip(keystream_block.iter()) {
+            *out_byte ^= *ks_byte;
+        }
+    }
+
+    keystream_block.zeroize();
+
+    Ok(output)
+}
+
+/// ChaCha20 encryption as specified in the [RFC 8439](https://tools.ietf.org/html/rfc8439).
+///
+/// # Parameters:
+/// - `key`: The secret key
+/// - `nonce`: The nonce value
+/// - `initial_counter`: The initial block counter
+/// - `plaintext`: The data to be encrypted
+///
+/// See [RFC](https://tools.ietf.org/html/rfc8439#section-2.4) for more information.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the `key` is not 32 bytes
+/// - The length of the `nonce` is not 12 bytes
+/// - The block counter would overflow while encrypting `plaintext`
+///
+/// # Security:
+/// ChaCha20 does not provide any data integrity. A nonce must never be used more than once
+/// with the same key, and it is recommended to use a counter or a random value for the nonce.
+/// Using ChaCha20 without a MAC, such as Poly1305, is almost always a mistake.
+///
+/// # Example:
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::hazardous::chacha20;
+/// use orion::core::util::gen_rand_key;
+///
+/// let key = gen_rand_key(32).unwrap();
+/// let nonce = gen_rand_key(12).unwrap();
+/// let message = "Data to protect".as_bytes();
+///
+/// let ciphertext = chacha20::encrypt(&key, &nonce, 1, message).unwrap();
+/// let plaintext = chacha20::decrypt(&key, &nonce, 1, &ciphertext).unwrap();
+///
+/// assert_eq!(message, &plaintext[..]);
+/// # }
+/// ```
+pub fn encrypt(
+    key: &[u8],
+    nonce: &[u8],
+    initial_counter: u32,
+    plaintext: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    xor_keystream(key, nonce, initial_counter, plaintext)
+}
+
+/// ChaCha20 decryption as specified in the [RFC 8439](https://tools.ietf.org/html/rfc8439).
+/// See `chacha20::encrypt` for the parameters and exceptions.
+pub fn decrypt(
+    key: &[u8],
+    nonce: &[u8],
+    initial_counter: u32,
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    xor_keystream(key, nonce, initial_counter, ciphertext)
+}
+
+/// Return a single 64 byte ChaCha20 keystream block for the given block counter.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the `key` is not 32 bytes
+/// - The length of the `nonce` is not 12 bytes
+pub fn keystream_block(
+    key: &[u8],
+    nonce: &[u8],
+    counter: u32,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    if nonce.len() != 12 {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut chacha_state = ChaChaState::init(key, nonce)?;
+    let mut keystream_block = vec![0u8; 64];
+    chacha_state.block(counter, &mut keystream_block);
+
+    Ok(keystream_block)
+}
+
+/// HChaCha20 as specified in the
+/// [draft RFC](https://tools.ietf.org/html/draft-irtf-cfrg-xchacha-01#section-2.2).
+/// Returns a 32 byte subkey derived from `key` and the 16 byte `nonce`.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the `key` is not 32 bytes
+/// - The length of the `nonce` is not 16 bytes
+pub fn hchacha20(key: &[u8], nonce: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+    if nonce.len() != 16 {
+        return Err(UnknownCryptoError);
+    }
+
+    let chacha_state = ChaChaState::init(key, nonce)?;
+    let mut working_state = chacha_state.rounds();
+    let mut subkey = vec![0u8; 32];
+
+    write_u32v_le(&mut subkey[..16], &working_state[..4]);
+    write_u32v_le(&mut subkey[16..], &working_state[12..]);
+
+    working_state.zeroize();
+
+    Ok(subkey)
+}
+
+#[cfg(test)]
+mod test {
+
+    use hazardous::chacha20::*;
+
+    #[test]
+    fn err_on_wrong_key_len() {
+        assert!(encrypt(&[0u8; 31], &[0u8; 12], 0, &[0u8; 16]).is_err());
+        assert!(encrypt(&[0u8; 33], &[0u8; 12], 0, &[0u8; 16]).is_err());
+        assert!(keystream_block(&[0u8; 31], &[0u8; 12], 0).is_err());
+        assert!(hchacha20(&[0u8; 33], &[0u8; 16]).is_err());
+    }
+
+    #[test]
+    fn err_on_wrong_nonce_len() {
+        assert!(encrypt(&[0u8; 32], &[0u8; 16], 0, &[0u8; 16]).is_err());
+        assert!(decrypt(&[0u8; 32], &[0u8; 8], 0, &[0u8; 16]).is_err());
+        assert!(keystream_block(&[0u8; 32], &[0u8; 16], 0).is_err());
+        assert!(hchacha20(&[0u8; 32], &[0u8; 12]).is_err());
+    }
+
+    #[test]
+    fn err_on_counter_overflow() {
+        assert!(encrypt(&[0u8; 32], &[0u8; 12], u32::max_value(), &[0u8; 64]).is_ok());
+        assert!(encrypt(&[0u8; 32], &[0u8; 12], u32::max_value(), &[0u8; 65]).is_err());
+    }
+
+    #[test]
+    fn empty_plaintext_ok() {
+        assert!(encrypt(&[0u8; 32], &[0u8; 12], 0, &[0u8; 0]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn keystream_matches_encryption_of_zeroes() {
+        let key = [0x42u8; 32];
+        let nonce = [0x24u8; 12];
+
+        let mut expected = keystream_block(&key, &nonce, 7).unwrap();
+        expected.extend_from_slice(&keystream_block(&key, &nonce, 8).unwrap());
+
+        assert_eq!(encrypt(&key, &nonce, 7, &[0u8; 128]).unwrap(), expected);
+    }
+}
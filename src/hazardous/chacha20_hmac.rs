@@ -0,0 +1,261 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::errors::*;
+use core::options::ShaVariantOption;
+use core::types::{Nonce, SecretKey};
+use endian::write_u64_le;
+use hazardous::chacha20;
+use hazardous::hmac::Hmac;
+
+/// Build the HMAC input: `ad || nonce || ciphertext || le64(ad.len()) || le64(ciphertext.len())`.
+/// The lengths are appended so that the boundary between `ad` and the ciphertext is unambiguous.
+fn mac_input(nonce: &[u8], ciphertext: &[u8], ad: &[u8]) -> Vec<u8> {
+    let mut input = Vec::with_capacity(ad.len() + nonce.len() + ciphertext.len() + 16);
+    input.extend_from_slice(ad);
+    input.extend_from_slice(nonce);
+    input.extend_from_slice(ciphertext);
+
+    let mut lengths = [0u8; 16];
+    write_u64_le(&mut lengths[..8], ad.len() as u64);
+    write_u64_le(&mut lengths[8..], ciphertext.len() as u64);
+    input.extend_from_slice(&lengths);
+
+    input
+}
+
+/// Encrypt-then-MAC with ChaCha20 and HMAC.
+///
+/// # About:
+/// The plaintext is encrypted with ChaCha20, starting at block counter 1. An HMAC tag is then
+/// computed over `ad || nonce || ciphertext || le64(ad.len()) || le64(ciphertext.len())` and
+/// appended to the ciphertext. The output is therefore `sha2.output_size()` bytes longer than
+/// the plaintext.
+///
+/// # Parameters:
+/// - `enc_key`: The 32 byte ChaCha20 key
+/// - `mac_key`: The HMAC key
+/// - `nonce`: The 12 byte ChaCha20 nonce
+/// - `plaintext`: The data to be encrypted
+/// - `ad`: Optional additional data, that is authenticated but not encrypted
+/// - `sha2`: Cryptographic hash function for HMAC
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the `enc_key` is not 32 bytes
+/// - The length of the `nonce` is not 12 bytes
+/// - The `mac_key` is empty
+/// - The `ciphertext` passed to `open` is shorter than the HMAC output size
+/// - The `ciphertext` passed to `open` fails authentication
+///
+/// # Security:
+/// `enc_key` and `mac_key` must be independent keys, and should always be generated using a CSPRNG.
+/// The `gen_rand_key` function in `util` can be used for this. The recommended length for the
+/// `mac_key` is the SHA functions digest size in bytes. A nonce must never be used more than once
+/// with the same `enc_key`.
+///
+/// # Example:
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::hazardous::chacha20_hmac;
+/// use orion::core::types::Nonce;
+/// use orion::core::util::gen_rand_key;
+/// use orion::core::options::ShaVariantOption;
+///
+/// let enc_key = gen_rand_key(32).unwrap();
+/// let mac_key = gen_rand_key(32).unwrap();
+/// let nonce = Nonce::from_slice(&[0u8; 12]).unwrap();
+/// let message = "Data to protect".as_bytes();
+///
+/// let ciphertext = chacha20_hmac::seal(
+///     &enc_key, &mac_key, &nonce, message, &[], ShaVariantOption::SHA256
+/// ).unwrap();
+/// let plaintext = chacha20_hmac::open(
+///     &enc_key, &mac_key, &nonce, &ciphertext, &[], ShaVariantOption::SHA256
+/// ).unwrap();
+///
+/// assert_eq!(message, &plaintext[..]);
+/// # }
+/// ```
+pub fn seal(
+    enc_key: &[u8],
+    mac_key: &[u8],
+    nonce: &Nonce,
+    plaintext: &[u8],
+    ad: &[u8],
+    sha2: ShaVariantOption,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    let mut ciphertext = chacha20::encrypt(enc_key, nonce.as_bytes(), 1, plaintext)?;
+
+    let mac = Hmac::new(
+        &SecretKey::from_slice(mac_key)?,
+        &mac_input(nonce.as_bytes(), &ciphertext, ad),
+        sha2,
+    )?;
+    ciphertext.extend_from_slice(mac.finalize().as_bytes());
+
+    Ok(ciphertext)
+}
+
+/// Verify and decrypt data that was sealed with `chacha20_hmac::seal`. The tag is verified,
+/// in constant time, before anything is decrypted. See `chacha20_hmac::seal` for the parameters
+/// and exceptions.
+pub fn open(
+    enc_key: &[u8],
+    mac_key: &[u8],
+    nonce: &Nonce,
+    ciphertext: &[u8],
+    ad: &[u8],
+    sha2: ShaVariantOption,
+) -> Result<Vec<u8>, ValidationCryptoError> {
+    if ciphertext.len() < sha2.output_size() {
+        return Err(ValidationCryptoError);
+    }
+
+    let (ct, tag) = ciphertext.split_at(ciphertext.len() - sha2.output_size());
+
+    let mac = Hmac::new(
+        &SecretKey::from_slice(mac_key)?,
+        &mac_input(nonce.as_bytes(), ct, ad),
+        sha2,
+    )?;
+    mac.verify(tag)?;
+
+    Ok(chacha20::decrypt(enc_key, nonce.as_bytes(), 1, ct)?)
+}
+
+#[cfg(test)]
+mod test {
+
+    extern crate hex;
+    use self::hex::decode;
+    use core::options::ShaVariantOption;
+    use hazardous::chacha20_hmac::*;
+
+    const ENC_KEY: [u8; 32] = [0x42; 32];
+    const MAC_KEY: [u8; 32] = [0x24; 32];
+    const NONCE: [u8; 12] = [0x07; 12];
+
+    fn nonce() -> Nonce {
+        Nonce::from_slice(&NONCE).unwrap()
+    }
+
+    fn seal_256(plaintext: &[u8], ad: &[u8]) -> Vec<u8> {
+        seal(&ENC_KEY, &MAC_KEY, &nonce(), plaintext, ad, ShaVariantOption::SHA256).unwrap()
+    }
+
+    fn open_256(ciphertext: &[u8], ad: &[u8]) -> Result<Vec<u8>, ValidationCryptoError> {
+        open(&ENC_KEY, &MAC_KEY, &nonce(), ciphertext, ad, ShaVariantOption::SHA256)
+    }
+
+    #[test]
+    fn test_known_answer() {
+        let enc_key: Vec<u8> = (0u8..32).collect();
+        let mac_key: Vec<u8> = (32u8..64).collect();
+        let nonce = Nonce::from_slice(&decode("000000000000004a00000000").unwrap()).unwrap();
+        let plaintext = "Ladies and Gentlemen of the class of '99".as_bytes();
+        let sha256 = ShaVariantOption::SHA256;
+        let sha512 = ShaVariantOption::SHA512;
+
+        let expected_256 = decode(
+            "6e2e359a2568f98041ba0728dd0d6981e97e7aec1d4360c20a27afccfd9fae0b\
+             f91b65c5524733abf4ab42fefea22834501e26cfb69b1fdf60d1a8c090ef0516\
+             cef5f6f650f929ed",
+        ).unwrap();
+        let expected_512 = decode(
+            "6e2e359a2568f98041ba0728dd0d6981e97e7aec1d4360c20a27afccfd9fae0b\
+             f91b65c5524733abc6f69128c5b4c554a817f81e7224542137c642345d77f172\
+             c3e9f0add0508084e43e5fe3fd4898a840d876c67248d301042b49611a8dda1c\
+             bacf8c0fabcb1ec7",
+        ).unwrap();
+
+        let actual_256 = seal(&enc_key, &mac_key, &nonce, plaintext, b"header", sha256).unwrap();
+        let actual_512 = seal(&enc_key, &mac_key, &nonce, plaintext, b"", sha512).unwrap();
+
+        assert_eq!(actual_256, expected_256);
+        assert_eq!(actual_512, expected_512);
+        assert_eq!(
+            open(&enc_key, &mac_key, &nonce, &actual_256, b"header", sha256).unwrap(),
+            plaintext
+        );
+    }
+
+    #[test]
+    fn seal_open_empty_plaintext() {
+        let ciphertext = seal_256(b"", b"");
+
+        assert_eq!(ciphertext.len(), 32);
+        assert!(open_256(&ciphertext, b"").unwrap().is_empty());
+    }
+
+    #[test]
+    fn err_on_modified_ciphertext() {
+        let mut ciphertext = seal_256(b"Some message.", b"");
+
+        for idx in 0..ciphertext.len() {
+            ciphertext[idx] ^= 1;
+            assert!(open_256(&ciphertext, b"").is_err());
+            ciphertext[idx] ^= 1;
+        }
+
+        assert!(open_256(&ciphertext, b"").is_ok());
+    }
+
+    #[test]
+    fn err_on_wrong_nonce_ad_or_keys() {
+        let ciphertext = seal_256(b"Some message.", b"ad");
+        let sha256 = ShaVariantOption::SHA256;
+        let sha512 = ShaVariantOption::SHA512;
+        let other_nonce = Nonce::from_slice(&[0x08; 12]).unwrap();
+
+        assert!(open(&ENC_KEY, &MAC_KEY, &other_nonce, &ciphertext, b"ad", sha256).is_err());
+        assert!(open(&ENC_KEY, &[0x25; 32], &nonce(), &ciphertext, b"ad", sha256).is_err());
+        assert!(open(&ENC_KEY, &MAC_KEY, &nonce(), &ciphertext, b"ad", sha512).is_err());
+        assert!(open_256(&ciphertext, b"da").is_err());
+    }
+
+    #[test]
+    fn err_on_ad_ciphertext_boundary_shift() {
+        // Moving bytes between the ad and the ciphertext must not produce a valid tag
+        let ciphertext = seal_256(b"Some message.", b"");
+        let (first, rest) = ciphertext.split_at(1);
+
+        assert!(open_256(rest, first).is_err());
+    }
+
+    #[test]
+    fn err_on_short_ciphertext() {
+        assert!(open_256(&[0u8; 31], b"").is_err());
+    }
+
+    #[test]
+    fn err_on_invalid_key_or_nonce_len() {
+        let sha256 = ShaVariantOption::SHA256;
+
+        let xchacha_nonce = Nonce::from_slice(&[0u8; 24]).unwrap();
+
+        assert!(seal(&[0u8; 31], &MAC_KEY, &nonce(), b"", b"", sha256).is_err());
+        assert!(seal(&ENC_KEY, &MAC_KEY, &xchacha_nonce, b"", b"", sha256).is_err());
+        assert!(seal(&ENC_KEY, &[], &nonce(), b"", b"", sha256).is_err());
+    }
+}
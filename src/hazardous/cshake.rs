@@ -20,28 +20,41 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use byte_tools::write_u64_be;
-use clear_on_drop::clear::Clear;
 use core::errors::*;
 use core::options::KeccakVariantOption;
 use core::util;
-use tiny_keccak::Keccak;
+use hazardous::sp800_185::left_encode;
+use std::{fmt, io};
+use tiny_keccak::{Keccak, XofReader};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 /// cSHAKE as specified in the [NIST SP 800-185](https://csrc.nist.gov/publications/detail/sp/800-185/final).
 ///
 /// Fields `input` and `custom` are zeroed out on drop.
 pub struct CShake {
-    pub input: Vec<u8>,
-    pub name: Vec<u8>,
-    pub custom: Vec<u8>,
-    pub length: usize,
-    pub keccak: KeccakVariantOption,
+    input: Vec<u8>,
+    name: Vec<u8>,
+    custom: Vec<u8>,
+    length: usize,
+    keccak: KeccakVariantOption,
 }
 
 impl Drop for CShake {
     fn drop(&mut self) {
-        Clear::clear(&mut self.input);
-        Clear::clear(&mut self.custom)
+        self.input.zeroize();
+        self.custom.zeroize()
+    }
+}
+
+impl ZeroizeOnDrop for CShake {}
+
+impl fmt::Debug for CShake {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "CShake {{ input: ***, name: {:?}, custom: ***, length: {}, keccak: {:?} }}",
+            self.name, self.length, self.keccak
+        )
     }
 }
 
@@ -69,266 +82,492 @@ impl Drop for CShake {
 /// to values defined by NIST". See [NIST SP 800-185](https://csrc.nist.gov/publications/detail/sp/800-185/final) for more information.
 ///
 /// # Exceptions:
-/// An exception will be thrown if:
+/// An exception will be thrown by `new` if:
 /// - Both `name` and `custom` are empty
 /// - The specified length is zero
-/// - The specified length is greater than 65536
 /// - If the length of either `name` or `custom` is greater than 65536
 ///
+/// An exception will be thrown by `finalize_into` if the length of `dst` is not the specified
+/// length.
+///
 /// The reason that `name` and `custom` cannot both be empty is because that would be equivalent to
 /// a SHAKE call.
 ///
+/// cSHAKE is an XOF, so any output length can be requested. `finalize` allocates the whole
+/// output at once; to read a large output in pieces, such as for a mask or keystream, use
+/// `CShake::init` and `CShakeStream::squeeze_into` instead.
+///
 /// # Security:
 /// cSHAKE128 has a security strength of 128 bits, whereas cSHAKE256 has a security strength of
 /// 256 bits. The recommended output length for cSHAKE128 is 32 and 64 for cSHAKE256.
 ///
 /// # Example:
 /// ```
+/// # #[cfg(feature = "rng")]
+/// # {
 /// use orion::hazardous::cshake::CShake;
 /// use orion::core::util::gen_rand_key;
 /// use orion::core::options::KeccakVariantOption;
 ///
 /// let key = gen_rand_key(32).unwrap();
 ///
-/// let cshake = CShake {
-///     input: key,
-///     name: "".as_bytes().to_vec(),
-///     custom: "Email signature".as_bytes().to_vec(),
-///     length: 32,
-///     keccak: KeccakVariantOption::KECCAK256,
-/// };
+/// let cshake = CShake::new(
+///     &key,
+///     "".as_bytes(),
+///     "Email signature".as_bytes(),
+///     32,
+///     KeccakVariantOption::KECCAK256,
+/// ).unwrap();
 ///
 /// let result = cshake.finalize().unwrap();
 /// assert_eq!(cshake.verify(&result).unwrap(), true);
+/// # }
 /// ```
 
 impl CShake {
-    /// Return the rate in bytes of the respective Keccak sponge function.
-    fn rate(&self) -> u64 {
-        match &self.keccak {
-            KeccakVariantOption::KECCAK256 => 168_u64,
-            KeccakVariantOption::KECCAK512 => 136_u64,
+    /// Initialize a cSHAKE with the given parameters, which are validated upfront.
+    pub fn new(
+        input: &[u8],
+        name: &[u8],
+        custom: &[u8],
+        length: usize,
+        keccak: KeccakVariantOption,
+    ) -> Result<Self, UnknownCryptoError> {
+        if length == 0 {
+            return Err(UnknownCryptoError);
         }
+        // Validate `name` and `custom` the same way as for streaming
+        CShake::init(name, custom, keccak)?;
+
+        Ok(CShake {
+            input: input.to_vec(),
+            name: name.to_vec(),
+            custom: custom.to_vec(),
+            length,
+            keccak,
+        })
     }
 
-    /// Initialize a Keccak hasher.
-    fn keccak_init(&self) -> Keccak {
-        match &self.keccak {
-            KeccakVariantOption::KECCAK256 => Keccak::new(self.rate() as usize, 0x04),
-            KeccakVariantOption::KECCAK512 => Keccak::new(self.rate() as usize, 0x04),
+    /// Return a cSHAKE hash.
+    pub fn finalize(&self) -> Result<Vec<u8>, UnknownCryptoError> {
+        let mut state = CShake::init(&self.name, &self.custom, self.keccak)?;
+        state.absorb(&self.input)?;
+
+        state.squeeze(self.length)
+    }
+
+    /// Write a cSHAKE hash into `dst`, without allocating. The length of `dst` must be the
+    /// length given to `new`.
+    pub fn finalize_into(&self, dst: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if dst.len() != self.length {
+            return Err(UnknownCryptoError);
         }
+
+        let mut state = CShake::init(&self.name, &self.custom, self.keccak)?;
+        state.absorb(&self.input)?;
+
+        state.squeeze_into(dst)
     }
 
-    /// Return a Keccak hash.
-    fn keccak_finalize(&self, mut state: Keccak) -> Vec<u8> {
-        let mut hash = vec![0u8; self.length];
-        state.update(&self.input);
-        // finalize() will call pad(), then keccakf() and finally squeeze()
-        state.finalize(&mut hash);
-        hash
+    /// Verify a cSHAKE hash by comparing one from the current struct fields to the input hash
+    /// passed to the function. Comparison is done in constant time. Both hashes must be
+    /// of equal length.
+    pub fn verify(&self, input: &[u8]) -> Result<bool, ValidationCryptoError> {
+        let own_hash = self.finalize()?;
+
+        if util::compare_ct(&own_hash, input).is_err() {
+            Err(ValidationCryptoError)
+        } else {
+            Ok(true)
+        }
     }
 
-    /// Return a cSHAKE hash.
-    pub fn finalize(&self) -> Result<Vec<u8>, UnknownCryptoError> {
+    /// Initialize a streaming cSHAKE with a function-name and customization string.
+    /// See `CShakeStream`.
+    pub fn init(
+        name: &[u8],
+        custom: &[u8],
+        keccak: KeccakVariantOption,
+    ) -> Result<CShakeStream, UnknownCryptoError> {
         // "When N and S are both empty strings, cSHAKE(X, L, N, S) is equivalent to SHAKE as
         // defined in FIPS 202"
-        if (self.name.is_empty()) && (self.custom.is_empty()) {
+        if (name.is_empty()) && (custom.is_empty()) {
             return Err(UnknownCryptoError);
         }
-        if self.length == 0 || self.length > 65536 {
-            return Err(UnknownCryptoError);
-        }
-        if self.name.len() > 65536 || self.custom.len() > 65536 {
+        if name.len() > 65536 || custom.len() > 65536 {
             return Err(UnknownCryptoError);
         }
 
-        let mut cshake_pad: Keccak = self.keccak_init();
+        let mut cshake_pad = Keccak::new(keccak.block_size(), 0x04);
+        let mut encoded = [0u8; 9];
 
         // Only append the left encoded rate, not the rate itself as with `name` and `custom`
-        cshake_pad.update(&left_encode(self.rate()));
+        let rate = left_encode(keccak.block_size() as u64, &mut encoded);
+        cshake_pad.update(rate);
+        let mut padded_len = rate.len();
+
+        // The below two calls are equivalent to `sp800_185::encode_string`, without copying
+        let name_len = left_encode(name.len() as u64 * 8, &mut encoded);
+        cshake_pad.update(name_len);
+        cshake_pad.update(name);
+        padded_len += name_len.len() + name.len();
+
+        let custom_len = left_encode(custom.len() as u64 * 8, &mut encoded);
+        cshake_pad.update(custom_len);
+        cshake_pad.update(custom);
+        padded_len += custom_len.len() + custom.len();
+
+        // Pad with zeroes before calling pad() when switching to squeezing. bytepad() adds no
+        // zeroes when the encoded strings already fill a whole number of blocks, and
+        // `fill_block` would then permute an extra all-zero block.
+        if padded_len % keccak.block_size() != 0 {
+            cshake_pad.fill_block();
+        }
 
-        // The below two calls are equivalent to encode_string() from the spec
-        cshake_pad.update(&left_encode(self.name.len() as u64 * 8));
-        cshake_pad.update(&self.name);
+        Ok(CShakeStream {
+            absorbing: Some(cshake_pad),
+            squeezing: None,
+        })
+    }
+}
 
-        cshake_pad.update(&left_encode(self.custom.len() as u64 * 8));
-        cshake_pad.update(&self.custom);
+/// Streaming cSHAKE, for hashing input that is not available all at once and for reading
+/// an arbitrary amount of output.
+///
+/// Created with `CShake::init`, which takes the function-name string, customization string
+/// and Keccak variant to use.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - Both `name` and `custom` are empty
+/// - If the length of either `name` or `custom` is greater than 65536
+/// - `absorb` is called after `squeeze`
+/// - `squeeze` is called with a length of zero, or `squeeze_into` with an empty buffer
+///
+/// # Security:
+/// The same considerations apply as for `CShake`. Output read through repeated calls to
+/// `squeeze` is the same as reading all of it at once.
+///
+/// # Example:
+/// ```
+/// use orion::hazardous::cshake::CShake;
+/// use orion::core::options::KeccakVariantOption;
+///
+/// let mut cshake = CShake::init(b"", b"Email signature", KeccakVariantOption::KECCAK256).unwrap();
+/// cshake.absorb("Some ".as_bytes()).unwrap();
+/// cshake.absorb("message.".as_bytes()).unwrap();
+///
+/// let first_block = cshake.squeeze(32).unwrap();
+/// let second_block = cshake.squeeze(32).unwrap();
+/// assert_ne!(first_block, second_block);
+/// ```
+pub struct CShakeStream {
+    absorbing: Option<Keccak>,
+    squeezing: Option<XofReader>,
+}
+
+impl CShakeStream {
+    /// Absorb more input. Can only be called before the first call to `squeeze`.
+    pub fn absorb(&mut self, input: &[u8]) -> Result<(), UnknownCryptoError> {
+        match self.absorbing {
+            Some(ref mut state) => {
+                state.update(input);
+                Ok(())
+            }
+            None => Err(UnknownCryptoError),
+        }
+    }
 
-        // Pad with zeroes before calling pad() in finalize()
-        cshake_pad.fill_block();
+    /// Read the next `length` bytes of output.
+    pub fn squeeze(&mut self, length: usize) -> Result<Vec<u8>, UnknownCryptoError> {
+        let mut hash = vec![0u8; length];
+        self.squeeze_into(&mut hash)?;
 
-        Ok(self.keccak_finalize(cshake_pad))
+        Ok(hash)
     }
 
-    /// Verify a cSHAKE hash by comparing one from the current struct fields to the input hash
-    /// passed to the function. Comparison is done in constant time. Both hashes must be
-    /// of equal length.
-    pub fn verify(&self, input: &[u8]) -> Result<bool, ValidationCryptoError> {
-        let own_hash = self.finalize().unwrap();
+    /// Read the next `dst.len()` bytes of output into `dst`, without allocating.
+    pub fn squeeze_into(&mut self, dst: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if dst.is_empty() {
+            return Err(UnknownCryptoError);
+        }
 
-        if util::compare_ct(&own_hash, input).is_err() {
-            Err(ValidationCryptoError)
-        } else {
-            Ok(true)
+        if let Some(state) = self.absorbing.take() {
+            // xof() will call pad(), then keccakf()
+            self.squeezing = Some(state.xof());
+        }
+
+        match self.squeezing {
+            Some(ref mut reader) => reader.squeeze(dst),
+            None => return Err(UnknownCryptoError),
         }
+
+        Ok(())
     }
 }
 
-/// The left_encode function as specified in the NIST SP 800-185.
-fn left_encode(x: u64) -> Vec<u8> {
-    let mut input = vec![0u8; 9];
-    let mut offset: usize = 0;
-
-    if x == 0 {
-        offset = 8;
-    } else {
-        write_u64_be(&mut input[1..], x.to_le());
-        for idx in &input {
-            if *idx != 0 {
-                break;
-            }
-            offset += 1;
+/// Writing to a `CShakeStream` is equivalent to calling `absorb`, so that data can be
+/// hashed with `std::io::copy`. Writing after `squeeze` has been called returns an error.
+///
+/// # Example:
+/// ```
+/// use orion::hazardous::cshake::CShake;
+/// use orion::core::options::KeccakVariantOption;
+/// use std::io;
+///
+/// let mut file = io::Cursor::new(vec![0u8; 4096]);
+///
+/// let mut cshake = CShake::init(b"", b"File hash", KeccakVariantOption::KECCAK256).unwrap();
+/// io::copy(&mut file, &mut cshake).unwrap();
+/// let hash = cshake.squeeze(32).unwrap();
+/// ```
+impl io::Write for CShakeStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.absorb(buf) {
+            Ok(()) => Ok(buf.len()),
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::Other,
+                "cannot absorb after squeezing",
+            )),
         }
     }
 
-    input[offset - 1] = (9 - offset) as u8;
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
 
-    input[(offset - 1)..].to_vec()
+/// cSHAKE128 with an empty function-name string, in one call without creating a `CShake`.
+/// Returns `length` bytes of output.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `custom` is empty
+/// - If the length of `custom` is greater than 65536
+/// - The specified length is zero
+///
+/// # Example:
+/// ```
+/// use orion::hazardous::cshake;
+///
+/// let hash = cshake::cshake128(b"Some data.", b"Email signature", 32).unwrap();
+/// assert_eq!(hash.len(), 32);
+/// ```
+pub fn cshake128(
+    input: &[u8],
+    custom: &[u8],
+    length: usize,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    cshake_oneshot(input, custom, length, KeccakVariantOption::KECCAK256)
+}
+
+/// cSHAKE256 with an empty function-name string, in one call. See `cshake128`.
+pub fn cshake256(
+    input: &[u8],
+    custom: &[u8],
+    length: usize,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    cshake_oneshot(input, custom, length, KeccakVariantOption::KECCAK512)
+}
+
+/// Absorb `input` and squeeze `length` bytes, with an empty function-name string.
+fn cshake_oneshot(
+    input: &[u8],
+    custom: &[u8],
+    length: usize,
+    keccak: KeccakVariantOption,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    let mut state = CShake::init(&[], custom, keccak)?;
+    state.absorb(input)?;
+
+    state.squeeze(length)
 }
 
 #[cfg(test)]
 mod test {
 
+    extern crate hex;
+    use self::hex::decode;
     use hazardous::cshake::*;
 
     #[test]
-    fn test_left_encode() {
-        let test_1 = left_encode(32);
-        let test_2 = left_encode(255);
-        let test_3 = left_encode(0);
-        let test_4 = left_encode(64);
-        let test_5 = left_encode(u64::max_value());
-
-        assert_eq!(&test_1, &[1, 32]);
-        assert_eq!(&test_2, &[1, 255]);
-        assert_eq!(&test_3, &[1, 0]);
-        assert_eq!(&test_4, &[1, 64]);
-        assert_eq!(&test_5, &[8, 255, 255, 255, 255, 255, 255, 255, 255]);
+    fn encoded_strings_fill_whole_block() {
+        // left_encode(rate) and the encoded `name` and `custom` are exactly one block long, so
+        // bytepad() adds no zero padding
+        let cshake256 = CShake::new(
+            b"abc",
+            &[0x61; 61],
+            &[0x62; 67],
+            32,
+            KeccakVariantOption::KECCAK512,
+        ).unwrap();
+        let cshake128 = CShake::new(
+            b"abc",
+            &[0x61; 80],
+            &[0x62; 80],
+            32,
+            KeccakVariantOption::KECCAK256,
+        ).unwrap();
+
+        assert_eq!(
+            cshake256.finalize().unwrap(),
+            decode("b435b694e1e579e921151630556e7ea8121035901ae17f1050a6da3c64716bdc").unwrap()
+        );
+        assert_eq!(
+            cshake128.finalize().unwrap(),
+            decode("846bc03280582ad0f77e0d954614e0827ce71fa6eecd1a737979d8ad431d274b").unwrap()
+        );
+    }
+
+    #[test]
+    fn oneshot_equals_cshake() {
+        let input = b"\x00\x01\x02\x03";
+        let custom = b"Email Signature";
+
+        for &length in [1, 32, 200, 65537].iter() {
+            let cshake = |keccak| CShake::new(input, b"", custom, length, keccak).unwrap();
+
+            assert_eq!(
+                cshake128(input, custom, length).unwrap(),
+                cshake(KeccakVariantOption::KECCAK256).finalize().unwrap()
+            );
+            assert_eq!(
+                cshake256(input, custom, length).unwrap(),
+                cshake(KeccakVariantOption::KECCAK512).finalize().unwrap()
+            );
+        }
+
+        assert_eq!(
+            cshake128(input, custom, 32).unwrap(),
+            decode("c1c36925b6409a04f1b504fcbca9d82b4017277cb5ed2b2065fc1d3814d5aaf5").unwrap()
+        );
+        assert!(cshake128(input, b"", 32).is_err());
+        assert!(cshake256(input, custom, 0).is_err());
     }
 
     #[test]
     fn err_on_empty_n_c() {
-        let cshake = CShake {
-            input: b"\x00\x01\x02\x03".to_vec(),
-            length: 32,
-            name: b"".to_vec(),
-            custom: b"".to_vec(),
-            keccak: KeccakVariantOption::KECCAK256,
-        };
-
-        assert!(cshake.finalize().is_err());
+        assert!(CShake::new(
+            b"\x00\x01\x02\x03",
+            b"",
+            b"",
+            32,
+            KeccakVariantOption::KECCAK256,
+        ).is_err());
     }
 
     #[test]
     fn empty_custom_ok() {
-        let cshake = CShake {
-            input: b"\x00\x01\x02\x03".to_vec(),
-            length: 32,
-            name: b"Email signature".to_vec(),
-            custom: b"".to_vec(),
-            keccak: KeccakVariantOption::KECCAK256,
-        };
+        let cshake = CShake::new(
+            b"\x00\x01\x02\x03",
+            b"Email signature",
+            b"",
+            32,
+            KeccakVariantOption::KECCAK256,
+        ).unwrap();
 
         assert!(cshake.finalize().is_ok());
     }
 
     #[test]
     fn empty_input_ok() {
-        let cshake = CShake {
-            input: b"".to_vec(),
-            length: 32,
-            name: b"Email signature".to_vec(),
-            custom: b"".to_vec(),
-            keccak: KeccakVariantOption::KECCAK256,
-        };
+        let cshake = CShake::new(
+            b"",
+            b"Email signature",
+            b"",
+            32,
+            KeccakVariantOption::KECCAK256,
+        ).unwrap();
 
         assert!(cshake.finalize().is_ok());
     }
 
     #[test]
     fn err_on_zero_length() {
-        let cshake = CShake {
-            input: b"\x00\x01\x02\x03".to_vec(),
-            length: 0,
-            name: b"Email signature".to_vec(),
-            custom: b"".to_vec(),
-            keccak: KeccakVariantOption::KECCAK256,
-        };
-
-        assert!(cshake.finalize().is_err());
+        assert!(CShake::new(
+            b"\x00\x01\x02\x03",
+            b"Email signature",
+            b"",
+            0,
+            KeccakVariantOption::KECCAK256,
+        ).is_err());
     }
 
     #[test]
-    fn err_on_above_max_length() {
-        let cshake = CShake {
-            input: b"\x00\x01\x02\x03".to_vec(),
-            length: 65537,
-            name: b"Email signature".to_vec(),
-            custom: b"".to_vec(),
-            keccak: KeccakVariantOption::KECCAK256,
-        };
-
-        assert!(cshake.finalize().is_err());
+    fn above_65536_length() {
+        let cshake = CShake::new(
+            b"\x00\x01\x02\x03",
+            b"",
+            b"Email signature",
+            65537 * 3,
+            KeccakVariantOption::KECCAK256,
+        ).unwrap();
+        let hash = cshake.finalize().unwrap();
+
+        // The output is the same as when squeezing it in pieces, and starts with the output
+        // of a shorter length
+        let mut state = CShake::init(b"", b"Email signature", KeccakVariantOption::KECCAK256)
+            .unwrap();
+        state.absorb(b"\x00\x01\x02\x03").unwrap();
+        let mut squeezed = vec![0u8; 65537 * 3];
+        for chunk in squeezed.chunks_mut(65537) {
+            state.squeeze_into(chunk).unwrap();
+        }
+        let short = CShake::new(
+            b"\x00\x01\x02\x03",
+            b"",
+            b"Email signature",
+            32,
+            KeccakVariantOption::KECCAK256,
+        ).unwrap();
+
+        assert_eq!(hash, squeezed);
+        assert_eq!(hash[..32], short.finalize().unwrap()[..]);
+        assert!(cshake.verify(&hash).unwrap());
     }
 
     #[test]
     fn err_on_name_max_length() {
-        let cshake = CShake {
-            input: b"\x00\x01\x02\x03".to_vec(),
-            length: 32,
-            name: vec![0u8; 65537],
-            custom: b"Email signature".to_vec(),
-            keccak: KeccakVariantOption::KECCAK256,
-        };
-
-        assert!(cshake.finalize().is_err());
+        assert!(CShake::new(
+            b"\x00\x01\x02\x03",
+            &vec![0u8; 65537],
+            b"Email signature",
+            32,
+            KeccakVariantOption::KECCAK256,
+        ).is_err());
     }
 
     #[test]
     fn err_on_n_c_max_length() {
-        let cshake = CShake {
-            input: b"\x00\x01\x02\x03".to_vec(),
-            length: 32,
-            name: vec![0u8; 65537],
-            custom: vec![0u8; 65537],
-            keccak: KeccakVariantOption::KECCAK256,
-        };
-
-        assert!(cshake.finalize().is_err());
+        assert!(CShake::new(
+            b"\x00\x01\x02\x03",
+            &vec![0u8; 65537],
+            &vec![0u8; 65537],
+            32,
+            KeccakVariantOption::KECCAK256,
+        ).is_err());
     }
 
     #[test]
     fn err_on_custom_max_length() {
-        let cshake = CShake {
-            input: b"\x00\x01\x02\x03".to_vec(),
-            length: 32,
-            name: b"Email signature".to_vec(),
-            custom: vec![0u8; 65537],
-            keccak: KeccakVariantOption::KECCAK256,
-        };
-
-        assert!(cshake.finalize().is_err());
+        assert!(CShake::new(
+            b"\x00\x01\x02\x03",
+            b"Email signature",
+            &vec![0u8; 65537],
+            32,
+            KeccakVariantOption::KECCAK256,
+        ).is_err());
     }
 
     #[test]
     fn non_8_div_len() {
-        let cshake = CShake {
-            input: b"\x00\x01\x02\x03".to_vec(),
-            length: 17,
-            name: b"".to_vec(),
-            custom: b"Email Signature".to_vec(),
-            keccak: KeccakVariantOption::KECCAK256,
-        };
+        let cshake = CShake::new(
+            b"\x00\x01\x02\x03",
+            b"",
+            b"Email Signature",
+            17,
+            KeccakVariantOption::KECCAK256,
+        ).unwrap();
 
         let expected = b"\xC1\xC3\x69\x25\xB6\x40\x9A\x04\xF1\xB5\x04\xFC\xBC\xA9\xD8\x2B\x40\x17\
                         \x27\x7C\xB5\xED\x2B\x20\x65\xFC\x1D\x38\x14\xD5\xAA\xF5"
@@ -340,13 +579,13 @@ mod test {
 
     #[test]
     fn verify_ok() {
-        let cshake = CShake {
-            input: b"\x00\x01\x02\x03".to_vec(),
-            name: b"".to_vec(),
-            custom: b"Email Signature".to_vec(),
-            length: 32,
-            keccak: KeccakVariantOption::KECCAK256,
-        };
+        let cshake = CShake::new(
+            b"\x00\x01\x02\x03",
+            b"",
+            b"Email Signature",
+            32,
+            KeccakVariantOption::KECCAK256,
+        ).unwrap();
 
         let expected = b"\xC1\xC3\x69\x25\xB6\x40\x9A\x04\xF1\xB5\x04\xFC\xBC\xA9\xD8\x2B\x40\x17\
                         \x27\x7C\xB5\xED\x2B\x20\x65\xFC\x1D\x38\x14\xD5\xAA\xF5"
@@ -358,13 +597,13 @@ mod test {
     #[test]
     fn verify_err() {
         // `name` and `custom` values have been switched here compared to the previous one
-        let cshake = CShake {
-            input: b"\x00\x01\x02\x03".to_vec(),
-            length: 32,
-            name: b"Email signature".to_vec(),
-            custom: b"".to_vec(),
-            keccak: KeccakVariantOption::KECCAK256,
-        };
+        let cshake = CShake::new(
+            b"\x00\x01\x02\x03",
+            b"Email signature",
+            b"",
+            32,
+            KeccakVariantOption::KECCAK256,
+        ).unwrap();
 
         let expected = b"\xC1\xC3\x69\x25\xB6\x40\x9A\x04\xF1\xB5\x04\xFC\xBC\xA9\xD8\x2B\x40\x17\
                         \x27\x7C\xB5\xED\x2B\x20\x65\xFC\x1D\x38\x14\xD5\xAA\xF5"
@@ -372,4 +611,100 @@ mod test {
 
         assert!(cshake.verify(&expected).is_err());
     }
+
+    #[test]
+    fn streaming_equals_one_shot() {
+        let input: Vec<u8> = (0..500).map(|x| x as u8).collect();
+
+        for keccak in [KeccakVariantOption::KECCAK256, KeccakVariantOption::KECCAK512].iter() {
+            let cshake = CShake::new(
+                &input.clone(),
+                b"",
+                b"Email Signature",
+                1000,
+                *keccak,
+            ).unwrap();
+            let expected = cshake.finalize().unwrap();
+
+            for chunk_size in [1, 17, 136, 168, 500].iter() {
+                let mut state = CShake::init(b"", b"Email Signature", *keccak).unwrap();
+                for chunk in input.chunks(*chunk_size) {
+                    state.absorb(chunk).unwrap();
+                }
+
+                let mut actual = Vec::new();
+                for chunk in expected.chunks(*chunk_size) {
+                    actual.extend_from_slice(&state.squeeze(chunk.len()).unwrap());
+                }
+                assert_eq!(actual, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn finalize_into_equals_finalize() {
+        for keccak in [KeccakVariantOption::KECCAK256, KeccakVariantOption::KECCAK512].iter() {
+            let cshake = CShake::new(b"Some input", b"", b"Email Signature", 200, *keccak).unwrap();
+            let mut hash = [0u8; 200];
+            cshake.finalize_into(&mut hash).unwrap();
+            assert_eq!(&hash[..], &cshake.finalize().unwrap()[..]);
+
+            let mut state = CShake::init(b"", b"Email Signature", *keccak).unwrap();
+            state.absorb(b"Some input").unwrap();
+            let mut first = [0u8; 64];
+            let mut second = [0u8; 136];
+            state.squeeze_into(&mut first).unwrap();
+            state.squeeze_into(&mut second).unwrap();
+            assert_eq!(&first[..], &hash[..64]);
+            assert_eq!(&second[..], &hash[64..]);
+
+            assert!(state.squeeze_into(&mut []).is_err());
+            assert!(cshake.finalize_into(&mut [0u8; 199]).is_err());
+        }
+    }
+
+    #[test]
+    fn streaming_err_on_absorb_after_squeeze() {
+        let keccak = KeccakVariantOption::KECCAK256;
+        let mut state = CShake::init(b"", b"Email Signature", keccak).unwrap();
+        state.absorb(b"\x00\x01\x02\x03").unwrap();
+        state.squeeze(32).unwrap();
+
+        assert!(state.absorb(b"\x04").is_err());
+        assert!(state.squeeze(32).is_ok());
+    }
+
+    #[test]
+    fn streaming_err_on_zero_length_squeeze() {
+        let keccak = KeccakVariantOption::KECCAK256;
+        let mut state = CShake::init(b"", b"Email Signature", keccak).unwrap();
+
+        assert!(state.squeeze(0).is_err());
+    }
+
+    #[test]
+    fn streaming_err_on_init() {
+        let keccak = KeccakVariantOption::KECCAK256;
+
+        assert!(CShake::init(b"", b"", keccak).is_err());
+        assert!(CShake::init(&[0u8; 65537], b"", keccak).is_err());
+        assert!(CShake::init(b"", &[0u8; 65537], keccak).is_err());
+    }
+
+    #[test]
+    fn streaming_write() {
+        use std::io::{self, Write};
+
+        let keccak = KeccakVariantOption::KECCAK512;
+        let data: Vec<u8> = (0..10000).map(|x| x as u8).collect();
+
+        let mut written = CShake::init(b"", b"Email Signature", keccak).unwrap();
+        io::copy(&mut io::Cursor::new(&data), &mut written).unwrap();
+
+        let mut absorbed = CShake::init(b"", b"Email Signature", keccak).unwrap();
+        absorbed.absorb(&data).unwrap();
+
+        assert_eq!(written.squeeze(64).unwrap(), absorbed.squeeze(64).unwrap());
+        assert!(written.write_all(b"more").is_err());
+    }
 }
@@ -20,28 +20,41 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use clear_on_drop::clear::Clear;
-use core::options::ShaVariantOption;
+use core::options::{HashFunction, HashState, ShaVariantOption};
 use core::{errors::*, util};
-use hazardous::hmac::Hmac;
+use hazardous::hmac::{
+    finish_keyed, keyed_states, pad_key, padded_states, pbkdf2_hmac, MAX_OUTPUT_SIZE,
+};
+use std::fmt;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 /// HKDF (HMAC-based Extract-and-Expand Key Derivation Function) as specified in the
 /// [RFC 5869](https://tools.ietf.org/html/rfc5869).
 ///
-/// Fields `salt`, `ikm` and `info` are zeroed out on drop.
-pub struct Hkdf {
-    pub salt: Vec<u8>,
-    pub ikm: Vec<u8>,
-    pub info: Vec<u8>,
-    pub length: usize,
-    pub hmac: ShaVariantOption,
+/// The Extract step is done by `new`, so only the pseudorandom key is kept instead of copies of
+/// `salt` and `ikm`. Fields `prk` and `info` are zeroed out on drop.
+pub struct Hkdf<H: HashFunction = ShaVariantOption> {
+    prk: Prk<H>,
+    info: Vec<u8>,
+    length: usize,
+    hmac: H,
 }
 
-impl Drop for Hkdf {
+impl<H: HashFunction> Drop for Hkdf<H> {
     fn drop(&mut self) {
-        Clear::clear(&mut self.salt);
-        Clear::clear(&mut self.ikm);
-        Clear::clear(&mut self.info)
+        self.info.zeroize()
+    }
+}
+
+impl<H: HashFunction> ZeroizeOnDrop for Hkdf<H> {}
+
+impl<H: HashFunction> fmt::Debug for Hkdf<H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Hkdf {{ prk: ***, info: ***, length: {}, hmac: {:?} }}",
+            self.length, self.hmac
+        )
     }
 }
 
@@ -52,12 +65,12 @@ impl Drop for Hkdf {
 /// - `ikm`: Input keying material
 /// - `info`: Optional context and application specific information (can be a zero-length string)
 /// - `length`: Length of output keying material
-/// - `hmac`: HMAC function
+/// - `hmac`: HMAC function, usually a `ShaVariantOption`. Any `HashFunction` can be used.
 ///
 /// See [RFC](https://tools.ietf.org/html/rfc5869#section-2.2) for more information.
 ///
 /// # Exceptions:
-/// An exception will be thrown if:
+/// An exception will be thrown by `new` if:
 /// - The specified length is less than 1
 /// - The specified length is greater than 255 * hash_output_size_in_bytes
 ///
@@ -70,6 +83,8 @@ impl Drop for Hkdf {
 /// # Example:
 /// ### Generating derived key:
 /// ```
+/// # #[cfg(feature = "rng")]
+/// # {
 /// use orion::hazardous::hkdf::Hkdf;
 /// use orion::core::util::gen_rand_key;
 /// use orion::core::options::ShaVariantOption;
@@ -78,18 +93,15 @@ impl Drop for Hkdf {
 /// let salt = gen_rand_key(32).unwrap();
 /// let info = gen_rand_key(32).unwrap();
 ///
-/// let dk = Hkdf {
-///     salt: salt,
-///     ikm: key,
-///     info: info,
-///     length: 50,
-///     hmac: ShaVariantOption::SHA256,
-/// };
+/// let dk = Hkdf::new(&salt, &key, &info, 50, ShaVariantOption::SHA256).unwrap();
 ///
 /// let dk_final = dk.derive_key().unwrap();
+/// # }
 /// ```
 /// ### Verifying derived key:
 /// ```
+/// # #[cfg(feature = "rng")]
+/// # {
 /// use orion::hazardous::hkdf::Hkdf;
 /// use orion::core::util::gen_rand_key;
 /// use orion::core::options::ShaVariantOption;
@@ -98,89 +110,83 @@ impl Drop for Hkdf {
 /// let salt = gen_rand_key(32).unwrap();
 /// let info = gen_rand_key(32).unwrap();
 ///
-/// let dk = Hkdf {
-///     salt: salt,
-///     ikm: key,
-///     info: info,
-///     length: 50,
-///     hmac: ShaVariantOption::SHA256,
-/// };
+/// let dk = Hkdf::new(&salt, &key, &info, 50, ShaVariantOption::SHA256).unwrap();
 ///
 /// let dk_final = dk.derive_key().unwrap();
 ///
 /// assert_eq!(dk.verify(&dk_final).unwrap(), true);
+/// # }
 /// ```
 
-impl Hkdf {
-    /// Return the maximum okm length (255 * hLen).
-    fn max_okmlen(&self) -> usize {
-        match self.hmac.output_size() {
-            32 => 8160,
-            48 => 12240,
-            64 => 16320,
-            _ => panic!(UnknownCryptoError),
-        }
-    }
-
-    /// The HKDF Extract step.
-    pub fn extract(&self, salt: &[u8], ikm: &[u8]) -> Vec<u8> {
-        let prk = Hmac {
-            secret_key: salt.to_vec(),
-            data: ikm.to_vec(),
-            sha2: self.hmac,
-        };
-
-        prk.finalize()
-    }
-
-    /// The HKDF Expand step.
-    pub fn expand(&self, prk: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
-        if self.length > self.max_okmlen() {
+impl<H: HashFunction> Hkdf<H> {
+    /// Initialize an HKDF with the given parameters, which are validated upfront.
+    pub fn new(
+        salt: &[u8],
+        ikm: &[u8],
+        info: &[u8],
+        length: usize,
+        hmac: H,
+    ) -> Result<Self, UnknownCryptoError> {
+        // The maximum okm length is 255 * hLen
+        if length < 1 || length > 255 * hmac.output_size() {
             return Err(UnknownCryptoError);
         }
-        if self.length < 1 {
-            return Err(UnknownCryptoError);
-        }
-
-        let n_iter: usize = 1 + ((self.length - 1) / self.hmac.output_size());
-
-        // con_step will hold the intermediate state of "T_n | info | 0x0n" as described in the RFC
-        let mut con_step: Vec<u8> = Vec::new();
-        let mut okm: Vec<u8> = Vec::new();
 
-        for index in 1..n_iter + 1 {
-            con_step.extend_from_slice(&self.info);
-            con_step.push(index as u8);
-            // Given that n_iter is rounded correctly, then the `index as u8`
-            // should not be able to overflow. If the maximum okmlen is selected,
-            // along with the highest output size, then n_iter will equal exactly `u8::max_value()`
+        Ok(Hkdf {
+            prk: Prk::extract(salt, ikm, hmac),
+            info: info.to_vec(),
+            length,
+            hmac,
+        })
+    }
 
-            // Calling extract as it yields the same result as an HMAC call
-            con_step = self.extract(prk, &con_step);
-            okm.extend_from_slice(&con_step);
+    /// The HKDF Extract step. The pseudorandom key is computed by `new`, so this returns a copy.
+    pub fn extract(&self) -> Prk<H> {
+        Prk {
+            value: self.prk.value.clone(),
+            hmac: self.hmac,
         }
+    }
 
-        okm.truncate(self.length);
+    /// The HKDF Expand step, with the `info` and `length` given to `new`.
+    pub fn expand(&self, prk: &Prk<H>) -> Result<Vec<u8>, UnknownCryptoError> {
+        prk.expand(&self.info, self.length)
+    }
 
-        Ok(okm)
+    /// The HKDF Expand step as an iterator that lazily yields the output blocks T(1), T(2), ...
+    /// of `hmac.output_size()` bytes each. The `length` field is ignored and at most 255 blocks
+    /// are returned. See `HkdfBlocks`.
+    pub fn expand_iter(&self, prk: &Prk<H>) -> HkdfBlocks<H> {
+        prk.expand_iter(&self.info)
     }
 
     /// Combine Extract and Expand to return a derived key.
     pub fn derive_key(&self) -> Result<Vec<u8>, UnknownCryptoError> {
-        let mut prk = self.extract(&self.salt, &self.ikm);
+        self.expand(&self.prk)
+    }
 
-        let dk = self.expand(&prk);
+    /// Combine Extract and Expand, writing the derived key into `dst` without allocating. The
+    /// length of `dst` must be the length given to `new`. See `hkdf_into` for the supported
+    /// block and output sizes.
+    pub fn derive_key_into(&self, dst: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if dst.len() != self.length {
+            return Err(UnknownCryptoError);
+        }
 
-        Clear::clear(&mut prk);
+        let (inner, outer) = keyed_states(&self.prk.value, self.hmac)?;
+        let mut block = [0u8; MAX_OUTPUT_SIZE];
+        let hlen = self.hmac.output_size();
+        expand_blocks::<H>(&inner, &outer, &self.info, &mut block[..hlen], dst);
+        block.zeroize();
 
-        dk
+        Ok(())
     }
 
     /// Verify a derived key by comparing one from the current struct fields to the derived key
     /// passed to the function. Comparison is done in constant time. Both derived keys must be
     /// of equal length.
     pub fn verify(&self, expected_dk: &[u8]) -> Result<bool, ValidationCryptoError> {
-        let own_dk = self.derive_key().unwrap();
+        let own_dk = self.derive_key()?;
 
         if util::compare_ct(&own_dk, expected_dk).is_err() {
             Err(ValidationCryptoError)
@@ -190,89 +196,485 @@ impl Hkdf {
     }
 }
 
+/// The pseudorandom key (PRK) of the HKDF Extract step, created with `Prk::extract` or
+/// `Hkdf::extract`.
+///
+/// Having a separate type for it means that input keying material cannot be passed to Expand
+/// by mistake. A single `Prk` can be expanded any number of times, with different `info`,
+/// without repeating the Extract step.
+///
+/// The pseudorandom key is zeroed out on drop.
+///
+/// # Exceptions:
+/// An exception will be thrown by `from_slice` if:
+/// - The length of `prk` is less than the output size of `hmac`
+///
+/// An exception will be thrown by `expand` and `expand_into` if:
+/// - The specified length, or the length of `dst`, is less than 1
+/// - The specified length, or the length of `dst`, is greater than
+/// 255 * hash_output_size_in_bytes
+///
+/// # Example:
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::hazardous::hkdf::Prk;
+/// use orion::core::util::gen_rand_key;
+/// use orion::core::options::ShaVariantOption;
+///
+/// let salt = gen_rand_key(32).unwrap();
+/// let ikm = gen_rand_key(32).unwrap();
+///
+/// let prk = Prk::extract(&salt, &ikm, ShaVariantOption::SHA256);
+///
+/// let encryption_key = prk.expand("Encryption".as_bytes(), 32).unwrap();
+/// let mac_key = prk.expand("Authentication".as_bytes(), 32).unwrap();
+/// assert_ne!(encryption_key, mac_key);
+/// # }
+/// ```
+pub struct Prk<H: HashFunction = ShaVariantOption> {
+    value: Vec<u8>,
+    hmac: H,
+}
+
+impl<H: HashFunction> Drop for Prk<H> {
+    fn drop(&mut self) {
+        self.value.zeroize()
+    }
+}
+
+impl<H: HashFunction> ZeroizeOnDrop for Prk<H> {}
+
+impl<H: HashFunction> fmt::Debug for Prk<H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Prk {{ value: ***, hmac: {:?} }}", self.hmac)
+    }
+}
+
+impl<H: HashFunction> Prk<H> {
+    /// The HKDF Extract step, computing the pseudorandom key from an optional `salt` and the
+    /// input keying material `ikm`.
+    pub fn extract(salt: &[u8], ikm: &[u8], hmac: H) -> Self {
+        // "if not provided, it is set to a string of HashLen zeros"
+        let zeros = vec![0u8; hmac.output_size()];
+        let salt = if salt.is_empty() { &zeros[..] } else { salt };
+
+        let (mut inner, outer) = padded_states(salt, hmac);
+        inner.update(ikm);
+        let mut value = vec![0u8; hmac.output_size()];
+        finish_keyed::<H>(inner, outer, &mut value);
+
+        Prk { value, hmac }
+    }
+
+    /// Use a pseudorandom key that was computed elsewhere, such as in a test vector.
+    pub fn from_slice(prk: &[u8], hmac: H) -> Result<Self, UnknownCryptoError> {
+        // "PRK: a pseudorandom key of at least HashLen octets"
+        if prk.len() < hmac.output_size() {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(Prk {
+            value: prk.to_vec(),
+            hmac,
+        })
+    }
+
+    /// The HKDF Expand step, returning `length` bytes of output keying material for `info`.
+    pub fn expand(&self, info: &[u8], length: usize) -> Result<Vec<u8>, UnknownCryptoError> {
+        let mut okm = vec![0u8; length];
+        self.expand_into(info, &mut okm)?;
+
+        Ok(okm)
+    }
+
+    /// The HKDF Expand step, filling `dst` with output keying material for `info`.
+    pub fn expand_into(&self, info: &[u8], dst: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        // The maximum okm length is 255 * hLen
+        if dst.is_empty() || dst.len() > 255 * self.hmac.output_size() {
+            return Err(UnknownCryptoError);
+        }
+
+        let (inner, outer) = padded_states(&self.value, self.hmac);
+        let mut block = vec![0u8; self.hmac.output_size()];
+        expand_blocks::<H>(&inner, &outer, info, &mut block, dst);
+        block.zeroize();
+
+        Ok(())
+    }
+
+    /// The HKDF Expand step as an iterator that lazily yields the output blocks T(1), T(2), ...
+    /// for `info`, of `hmac.output_size()` bytes each. At most 255 blocks are returned. See
+    /// `HkdfBlocks`.
+    pub fn expand_iter(&self, info: &[u8]) -> HkdfBlocks<H> {
+        let (ipad, opad) = pad_key(&self.value, self.hmac);
+
+        HkdfBlocks {
+            ipad,
+            opad,
+            info: info.to_vec(),
+            previous: Vec::new(),
+            counter: 1,
+            hmac: self.hmac,
+        }
+    }
+
+    /// Return the pseudorandom key. __**Warning**__: Should not be used unless strictly needed.
+    pub fn unprotected_as_bytes(&self) -> &[u8] {
+        &self.value
+    }
+}
+
+/// Iterator over the output blocks of the HKDF Expand step, created with `Hkdf::expand_iter` or
+/// `Prk::expand_iter`.
+///
+/// The padded pseudorandom key, `info` and the previous output block are zeroed out on drop.
+///
+/// # Example:
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::hazardous::hkdf::Hkdf;
+/// use orion::core::util::gen_rand_key;
+/// use orion::core::options::ShaVariantOption;
+///
+/// let salt = gen_rand_key(32).unwrap();
+/// let ikm = gen_rand_key(32).unwrap();
+///
+/// let hkdf = Hkdf::new(&salt, &ikm, "Record keys".as_bytes(), 32, ShaVariantOption::SHA256)
+///     .unwrap();
+///
+/// let prk = hkdf.extract();
+///
+/// for record_key in hkdf.expand_iter(&prk).take(10) {
+///     // One key per record or epoch
+///     assert_eq!(record_key.len(), 32);
+/// }
+/// # }
+/// ```
+pub struct HkdfBlocks<H: HashFunction = ShaVariantOption> {
+    ipad: Vec<u8>,
+    opad: Vec<u8>,
+    info: Vec<u8>,
+    previous: Vec<u8>,
+    counter: usize,
+    hmac: H,
+}
+
+impl<H: HashFunction> Drop for HkdfBlocks<H> {
+    fn drop(&mut self) {
+        self.ipad.zeroize();
+        self.opad.zeroize();
+        self.info.zeroize();
+        self.previous.zeroize()
+    }
+}
+
+impl<H: HashFunction> ZeroizeOnDrop for HkdfBlocks<H> {}
+
+impl<H: HashFunction> fmt::Debug for HkdfBlocks<H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "HkdfBlocks {{ ipad: ***, opad: ***, info: ***, previous: ***, \
+             counter: {}, hmac: {:?} }}",
+            self.counter, self.hmac
+        )
+    }
+}
+
+impl<H: HashFunction> Iterator for HkdfBlocks<H> {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Vec<u8>> {
+        if self.counter > 255 {
+            return None;
+        }
+
+        // "T(n) = HMAC-Hash(PRK, T(n-1) | info | 0x0n)" as described in the RFC
+        let mut data = Vec::with_capacity(self.previous.len() + self.info.len() + 1);
+        data.extend_from_slice(&self.previous);
+        data.extend_from_slice(&self.info);
+        data.push(self.counter as u8);
+
+        let block = pbkdf2_hmac(&self.ipad, &self.opad, &data, self.hmac);
+        data.zeroize();
+
+        self.previous.zeroize();
+        self.previous = block.clone();
+        self.counter += 1;
+
+        Some(block)
+    }
+}
+
+/// Fill `dst` with the output blocks of the HKDF Expand step, using the states of the padded
+/// pseudorandom key. `block` must be hLen bytes long, and holds the last full block afterwards.
+fn expand_blocks<H: HashFunction>(
+    inner: &H::State,
+    outer: &H::State,
+    info: &[u8],
+    block: &mut [u8],
+    dst: &mut [u8],
+) {
+    for (index, okm_block) in dst.chunks_mut(block.len()).enumerate() {
+        // "T(n) = HMAC-Hash(PRK, T(n-1) | info | 0x0n)" as described in the RFC
+        let mut block_inner = inner.clone();
+        if index > 0 {
+            block_inner.update(block);
+        }
+        block_inner.update(info);
+        block_inner.update(&[(index + 1) as u8]);
+        finish_keyed::<H>(block_inner, outer.clone(), block);
+
+        okm_block.copy_from_slice(&block[..okm_block.len()]);
+    }
+}
+
+/// HKDF Extract and Expand, writing the output keying material into `dst` without allocating.
+/// This is for targets without a heap, where `Hkdf` cannot be used. The length of the output
+/// keying material is the length of `dst`.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of `dst` is less than 1
+/// - The length of `dst` is greater than 255 * hash_output_size_in_bytes
+/// - The block size of `hmac` is greater than 200 bytes or its output size is greater than 64
+///
+/// # Example:
+/// ```
+/// use orion::hazardous::hkdf;
+/// use orion::core::options::ShaVariantOption;
+///
+/// let mut okm = [0u8; 42];
+/// hkdf::hkdf_into(b"Salt", b"Input key", b"Info", ShaVariantOption::SHA256, &mut okm).unwrap();
+/// ```
+pub fn hkdf_into<H: HashFunction>(
+    salt: &[u8],
+    ikm: &[u8],
+    info: &[u8],
+    hmac: H,
+    dst: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    let hlen = hmac.output_size();
+    if dst.is_empty() || dst.len() > 255 * hlen {
+        return Err(UnknownCryptoError);
+    }
+
+    // An empty salt is padded with zeroes by HMAC, which is the same as the string of HashLen
+    // zeros the RFC uses when no salt is provided
+    let (mut inner, outer) = keyed_states(salt, hmac)?;
+    inner.update(ikm);
+    let mut prk = [0u8; MAX_OUTPUT_SIZE];
+    finish_keyed::<H>(inner, outer, &mut prk[..hlen]);
+
+    let (inner, outer) = keyed_states(&prk[..hlen], hmac)?;
+    let mut block = [0u8; MAX_OUTPUT_SIZE];
+    expand_blocks::<H>(&inner, &outer, info, &mut block[..hlen], dst);
+
+    prk.zeroize();
+    block.zeroize();
+
+    Ok(())
+}
+
+/// HKDF-SHA256 Extract and Expand in one call, without creating an `Hkdf`. Returns `length`
+/// bytes of output keying material.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The specified length is less than 1
+/// - The specified length is greater than 255 * hash_output_size_in_bytes
+///
+/// # Example:
+/// ```
+/// use orion::hazardous::hkdf;
+///
+/// let okm = hkdf::hkdf_sha256(b"Salt", b"Input key", b"Info", 42).unwrap();
+/// assert_eq!(okm.len(), 42);
+/// ```
+pub fn hkdf_sha256(
+    salt: &[u8],
+    ikm: &[u8],
+    info: &[u8],
+    length: usize,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    Prk::extract(salt, ikm, ShaVariantOption::SHA256).expand(info, length)
+}
+
+/// HKDF-SHA384 Extract and Expand in one call. See `hkdf_sha256`.
+pub fn hkdf_sha384(
+    salt: &[u8],
+    ikm: &[u8],
+    info: &[u8],
+    length: usize,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    Prk::extract(salt, ikm, ShaVariantOption::SHA384).expand(info, length)
+}
+
+/// HKDF-SHA512 Extract and Expand in one call. See `hkdf_sha256`.
+pub fn hkdf_sha512(
+    salt: &[u8],
+    ikm: &[u8],
+    info: &[u8],
+    length: usize,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    Prk::extract(salt, ikm, ShaVariantOption::SHA512).expand(info, length)
+}
+
 #[cfg(test)]
 mod test {
     extern crate hex;
     use self::hex::decode;
     use core::options::ShaVariantOption;
-    use hazardous::hkdf::Hkdf;
+    use hazardous::hkdf::{hkdf_into, hkdf_sha256, hkdf_sha384, hkdf_sha512, Hkdf, Prk};
 
     #[test]
-    fn hkdf_maximum_length_256() {
-        let hkdf = Hkdf {
-            salt: decode("").unwrap(),
-            ikm: decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap(),
-            info: decode("").unwrap(),
-            // Max allowed length here is 8160
-            length: 9000,
-            hmac: ShaVariantOption::SHA256,
-        };
+    fn hkdf_into_equals_hkdf() {
+        let variants = [
+            ShaVariantOption::SHA256,
+            ShaVariantOption::SHA512,
+            ShaVariantOption::SHA3_256,
+        ];
+
+        for hmac in variants.iter() {
+            for salt in [&b""[..], &b"salt"[..]].iter() {
+                let length = 3 * hmac.output_size() + 5;
+                let expected = Hkdf::new(salt, b"ikm", b"info", length, *hmac).unwrap();
+                let mut okm = vec![0u8; length];
+                hkdf_into(salt, b"ikm", b"info", *hmac, &mut okm).unwrap();
+
+                assert_eq!(okm, expected.derive_key().unwrap());
+
+                let mut dk = vec![0u8; length];
+                expected.derive_key_into(&mut dk).unwrap();
+                assert_eq!(dk, okm);
+                assert!(expected.derive_key_into(&mut dk[1..]).is_err());
+            }
+        }
 
-        let hkdf_extract = hkdf.extract(&hkdf.ikm, &hkdf.salt);
+        let sha256 = ShaVariantOption::SHA256;
+        assert!(hkdf_into(b"salt", b"ikm", b"info", sha256, &mut []).is_err());
+        assert!(hkdf_into(b"salt", b"ikm", b"info", sha256, &mut [0u8; 255 * 32 + 1]).is_err());
+    }
+
+    #[test]
+    fn prk_expand_equals_hkdf() {
+        let sha256 = ShaVariantOption::SHA256;
+        let prk = Prk::extract(b"salt", b"ikm", sha256);
+
+        for &info in [&b""[..], &b"info"[..], &b"other info"[..]].iter() {
+            let hkdf = Hkdf::new(b"salt", b"ikm", info, 100, sha256).unwrap();
+            let okm = prk.expand(info, 100).unwrap();
+            assert_eq!(okm, hkdf.derive_key().unwrap());
+            assert_eq!(okm, hkdf.expand(&prk).unwrap());
+
+            let mut dst = [0u8; 100];
+            prk.expand_into(info, &mut dst).unwrap();
+            assert_eq!(dst[..], okm[..]);
+            assert_eq!(prk.expand_iter(info).next().unwrap(), &okm[..32]);
+        }
 
-        assert!(hkdf.expand(&hkdf_extract).is_err());
-        assert!(hkdf.derive_key().is_err());
+        assert_eq!(
+            prk.unprotected_as_bytes(),
+            Hkdf::new(b"salt", b"ikm", b"", 1, sha256).unwrap().extract().unprotected_as_bytes()
+        );
+        let copy = Prk::from_slice(prk.unprotected_as_bytes(), sha256).unwrap();
+        assert_eq!(copy.expand(b"info", 32).unwrap(), prk.expand(b"info", 32).unwrap());
+        assert_eq!(format!("{:?}", prk), "Prk { value: ***, hmac: SHA256 }");
     }
 
     #[test]
-    fn hkdf_maximum_length_384() {
-        let hkdf = Hkdf {
-            salt: decode("").unwrap(),
-            ikm: decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap(),
-            info: decode("").unwrap(),
-            // Max allowed length here is 12240
-            length: 13000,
-            hmac: ShaVariantOption::SHA384,
-        };
+    fn oneshot_equals_hkdf() {
+        let hkdf = |hmac| Hkdf::new(b"salt", b"ikm", b"info", 100, hmac).unwrap();
+
+        assert_eq!(
+            hkdf_sha256(b"salt", b"ikm", b"info", 100).unwrap(),
+            hkdf(ShaVariantOption::SHA256).derive_key().unwrap()
+        );
+        assert_eq!(
+            hkdf_sha384(b"salt", b"ikm", b"info", 100).unwrap(),
+            hkdf(ShaVariantOption::SHA384).derive_key().unwrap()
+        );
+        assert_eq!(
+            hkdf_sha512(b"salt", b"ikm", b"info", 100).unwrap(),
+            hkdf(ShaVariantOption::SHA512).derive_key().unwrap()
+        );
+
+        assert!(hkdf_sha256(b"salt", b"ikm", b"info", 0).is_err());
+        assert!(hkdf_sha256(b"salt", b"ikm", b"info", 255 * 32 + 1).is_err());
+        assert!(hkdf_sha512(b"salt", b"ikm", b"info", 255 * 64).is_ok());
+    }
 
-        let hkdf_extract = hkdf.extract(&hkdf.ikm, &hkdf.salt);
+    #[test]
+    fn prk_err() {
+        let sha256 = ShaVariantOption::SHA256;
+        let prk = Prk::extract(b"salt", b"ikm", sha256);
+
+        assert!(Prk::from_slice(&[0u8; 31], sha256).is_err());
+        assert!(Prk::from_slice(&[0u8; 64], sha256).is_ok());
+        assert!(prk.expand(b"info", 0).is_err());
+        assert!(prk.expand(b"info", 255 * 32 + 1).is_err());
+        assert!(prk.expand(b"info", 255 * 32).is_ok());
+        assert!(prk.expand_into(b"info", &mut []).is_err());
+    }
 
-        assert!(hkdf.expand(&hkdf_extract).is_err());
-        assert!(hkdf.derive_key().is_err());
+    #[test]
+    fn hkdf_maximum_length_256() {
+        assert!(Hkdf::new(
+            &decode("").unwrap(),
+            &decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap(),
+            &decode("").unwrap(),
+            // Max allowed length here is 8160
+            9000,
+            ShaVariantOption::SHA256,
+        ).is_err());
+    }
+
+    #[test]
+    fn hkdf_maximum_length_384() {
+        assert!(Hkdf::new(
+            &decode("").unwrap(),
+            &decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap(),
+            &decode("").unwrap(),
+            // Max allowed length here is 12240
+            13000,
+            ShaVariantOption::SHA384,
+        ).is_err());
     }
 
     #[test]
     fn hkdf_maximum_length_512() {
-        let hkdf = Hkdf {
-            salt: decode("").unwrap(),
-            ikm: decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap(),
-            info: decode("").unwrap(),
+        assert!(Hkdf::new(
+            &decode("").unwrap(),
+            &decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap(),
+            &decode("").unwrap(),
             // Max allowed length here is 16320
-            length: 17000,
-            hmac: ShaVariantOption::SHA512,
-        };
-
-        let hkdf_extract = hkdf.extract(&hkdf.ikm, &hkdf.salt);
-
-        assert!(hkdf.expand(&hkdf_extract).is_err());
-        assert!(hkdf.derive_key().is_err());
+            17000,
+            ShaVariantOption::SHA512,
+        ).is_err());
     }
 
     #[test]
     fn hkdf_zero_length() {
-        let hkdf = Hkdf {
-            salt: decode("").unwrap(),
-            ikm: decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap(),
-            info: decode("").unwrap(),
-            length: 0,
-            hmac: ShaVariantOption::SHA512,
-        };
-
-        let hkdf_extract = hkdf.extract(&hkdf.ikm, &hkdf.salt);
-
-        assert!(hkdf.expand(&hkdf_extract).is_err());
-        assert!(hkdf.derive_key().is_err());
+        assert!(Hkdf::new(
+            &decode("").unwrap(),
+            &decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap(),
+            &decode("").unwrap(),
+            0,
+            ShaVariantOption::SHA512,
+        ).is_err());
     }
 
     #[test]
     fn hkdf_verify_true() {
-        let hkdf = Hkdf {
-            salt: decode("").unwrap(),
-            ikm: decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap(),
-            info: decode("").unwrap(),
-            length: 42,
-            hmac: ShaVariantOption::SHA256,
-        };
+        let hkdf = Hkdf::new(
+            &decode("").unwrap(),
+            &decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap(),
+            &decode("").unwrap(),
+            42,
+            ShaVariantOption::SHA256,
+        ).unwrap();
 
         let expected_okm = decode(
             "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d\
@@ -285,13 +687,13 @@ mod test {
     #[test]
     fn hkdf_verify_wrong_salt() {
         // Salt value differs between this and the previous test case
-        let hkdf = Hkdf {
-            salt: "salt".as_bytes().to_vec(),
-            ikm: decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap(),
-            info: decode("").unwrap(),
-            length: 42,
-            hmac: ShaVariantOption::SHA256,
-        };
+        let hkdf = Hkdf::new(
+            "salt".as_bytes(),
+            &decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap(),
+            &decode("").unwrap(),
+            42,
+            ShaVariantOption::SHA256,
+        ).unwrap();
 
         let expected_okm = decode(
             "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d\
@@ -303,13 +705,13 @@ mod test {
 
     #[test]
     fn hkdf_verify_wrong_ikm() {
-        let hkdf = Hkdf {
-            salt: decode("").unwrap(),
-            ikm: decode("0b").unwrap(),
-            info: decode("").unwrap(),
-            length: 42,
-            hmac: ShaVariantOption::SHA256,
-        };
+        let hkdf = Hkdf::new(
+            &decode("").unwrap(),
+            &decode("0b").unwrap(),
+            &decode("").unwrap(),
+            42,
+            ShaVariantOption::SHA256,
+        ).unwrap();
 
         let expected_okm = decode(
             "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d\
@@ -320,15 +722,15 @@ mod test {
     }
 
     #[test]
-    fn verify_diff_length_panic() {
+    fn verify_diff_length_err() {
         // Different length than expected okm
-        let hkdf = Hkdf {
-            salt: "salt".as_bytes().to_vec(),
-            ikm: decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap(),
-            info: decode("").unwrap(),
-            length: 75,
-            hmac: ShaVariantOption::SHA256,
-        };
+        let hkdf = Hkdf::new(
+            "salt".as_bytes(),
+            &decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap(),
+            &decode("").unwrap(),
+            75,
+            ShaVariantOption::SHA256,
+        ).unwrap();
 
         let expected_okm = decode(
             "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d\
@@ -337,4 +739,44 @@ mod test {
 
         assert!(hkdf.verify(&expected_okm).is_err());
     }
+
+    #[test]
+    fn expand_iter_equals_expand() {
+        let hkdf = Hkdf::new(
+            &decode("000102030405060708090a0b0c").unwrap(),
+            &decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap(),
+            &decode("f0f1f2f3f4f5f6f7f8f9").unwrap(),
+            8160,
+            ShaVariantOption::SHA256,
+        ).unwrap();
+
+        let prk = hkdf.extract();
+        let okm = hkdf.expand(&prk).unwrap();
+        let blocks: Vec<Vec<u8>> = hkdf.expand_iter(&prk).collect();
+
+        assert_eq!(blocks.len(), 255);
+        assert_eq!(blocks.concat(), okm);
+    }
+
+    #[test]
+    fn expand_iter_rfc_test_case_1() {
+        let hkdf = Hkdf::new(
+            &decode("000102030405060708090a0b0c").unwrap(),
+            &decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap(),
+            &decode("f0f1f2f3f4f5f6f7f8f9").unwrap(),
+            42,
+            ShaVariantOption::SHA256,
+        ).unwrap();
+
+        let expected_okm = decode(
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf\
+             34007208d5b887185865",
+        ).unwrap();
+
+        let prk = hkdf.extract();
+        let mut blocks = hkdf.expand_iter(&prk);
+
+        assert_eq!(blocks.next().unwrap(), &expected_okm[..32]);
+        assert_eq!(&blocks.next().unwrap()[..10], &expected_okm[32..]);
+    }
 }
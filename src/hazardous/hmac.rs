@@ -20,24 +20,37 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use clear_on_drop::clear::Clear;
-use core::options::ShaVariantOption;
-use core::{errors::*, util};
+use core::errors::*;
+use core::options::{HashFunction, HashState, ShaVariantOption};
+use core::types::{SecretKey, Tag};
+#[cfg(not(feature = "rng"))]
+use core::util;
+use std::{fmt, io};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 /// HMAC (Hash-based Message Authentication Code) as specified in the
 /// [RFC 2104](https://tools.ietf.org/html/rfc2104).
 ///
-/// Fields `secret_key` and `data` are zeroed out on drop.
-pub struct Hmac {
-    pub secret_key: Vec<u8>,
-    pub data: Vec<u8>,
-    pub sha2: ShaVariantOption,
+/// The data is absorbed by `new` and not kept. The outer padded key is zeroed out on drop.
+/// The output size of the hash function must be between 1 and `MAX_OUTPUT_SIZE`, so that the
+/// HMAC fits in a `Tag`.
+pub struct Hmac<H: HashFunction = ShaVariantOption> {
+    inner: H::State,
+    outer_pad: Vec<u8>,
+    sha2: H,
 }
 
-impl Drop for Hmac {
+impl<H: HashFunction> Drop for Hmac<H> {
     fn drop(&mut self) {
-        Clear::clear(&mut self.secret_key);
-        Clear::clear(&mut self.data)
+        self.outer_pad.zeroize()
+    }
+}
+
+impl<H: HashFunction> ZeroizeOnDrop for Hmac<H> {}
+
+impl<H: HashFunction> fmt::Debug for Hmac<H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Hmac {{ inner: ***, outer_pad: ***, sha2: {:?} }}", self.sha2)
     }
 }
 
@@ -47,90 +60,114 @@ impl Drop for Hmac {
 /// # Parameters:
 /// - `secret_key`:  The authentication key
 /// - `data`: Data to be authenticated
-/// - `sha2`: Cryptographic hash function
+/// - `sha2`: Cryptographic hash function, usually a `ShaVariantOption`. Any `HashFunction` can
+///   be used.
 ///
 /// See [RFC](https://tools.ietf.org/html/rfc2104#section-2) for more information.
 ///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The output size of `sha2` is 0 or greater than `MAX_OUTPUT_SIZE`
+///
 /// # Security:
-/// The secret key should always be generated using a CSPRNG. The `gen_rand_key` function
-/// in `util` can be used for this.  The recommended length for a secret key is the SHA functions digest
+/// The secret key should always be generated using a CSPRNG. `SecretKey::generate`
+/// can be used for this.  The recommended length for a secret key is the SHA functions digest
 /// size in bytes.
 /// # Example:
 /// ### Generating HMAC:
 /// ```
+/// # #[cfg(feature = "rng")]
+/// # {
 /// use orion::hazardous::hmac::Hmac;
+/// use orion::core::types::SecretKey;
 /// use orion::core::util::gen_rand_key;
 /// use orion::core::options::ShaVariantOption;
 ///
-/// let key = gen_rand_key(32).unwrap();
+/// let key = SecretKey::generate(32).unwrap();
 /// let message = gen_rand_key(32).unwrap();
 ///
-/// let hmac = Hmac {
-///     secret_key: key,
-///     data: message,
-///     sha2: ShaVariantOption::SHA256
-/// };
+/// let hmac = Hmac::new(&key, &message, ShaVariantOption::SHA256).unwrap();
 ///
 /// hmac.finalize();
+/// # }
 /// ```
 /// ### Verifying HMAC:
 /// ```
 /// use orion::hazardous::hmac::Hmac;
+/// use orion::core::types::SecretKey;
 /// use orion::core::options::ShaVariantOption;
 ///
-/// let key = "Some key.";
+/// let key = "Some key.".as_bytes();
 /// let msg = "Some message.";
 ///
-/// let hmac = Hmac {
-///     secret_key: key.as_bytes().to_vec(),
-///     data: msg.as_bytes().to_vec(),
-///     sha2: ShaVariantOption::SHA256
-/// };
-/// let received_hmac = Hmac {
-///     secret_key: key.as_bytes().to_vec(),
-///     data: msg.as_bytes().to_vec(),
-///     sha2: ShaVariantOption::SHA256
-/// };
-/// assert_eq!(hmac.verify(&received_hmac.finalize()).unwrap(), true);
+/// let hmac = Hmac::new(
+///     &SecretKey::from_slice(key).unwrap(),
+///     msg.as_bytes(),
+///     ShaVariantOption::SHA256,
+/// ).unwrap();
+/// let received_hmac = Hmac::new(
+///     &SecretKey::from_slice(key).unwrap(),
+///     msg.as_bytes(),
+///     ShaVariantOption::SHA256,
+/// ).unwrap();
+/// assert_eq!(hmac.verify(received_hmac.finalize().as_bytes()).unwrap(), true);
 /// ```
 
-impl Hmac {
-    /// Pad the key and return inner and outer padding.
-    pub fn pad_key(&self, secret_key: &[u8]) -> (Vec<u8>, Vec<u8>) {
+impl<H: HashFunction> Hmac<H> {
+    /// Initialize a one-shot HMAC over `data`. The data is hashed right away instead of being
+    /// copied, so large inputs are not held in memory twice.
+    pub fn new(secret_key: &SecretKey, data: &[u8], sha2: H) -> Result<Self, UnknownCryptoError> {
+        check_output_size(sha2)?;
+        let (mut inner_pad, outer_pad) = pad_key(secret_key.unprotected_as_bytes(), sha2);
 
-        let mut inner_pad = vec![0x36; self.sha2.blocksize()];
-        let mut outer_pad = vec![0x5C; self.sha2.blocksize()];
+        let mut inner = sha2.init();
+        inner.update(&inner_pad);
+        inner.update(data);
+        inner_pad.zeroize();
 
-        if secret_key.len() > self.sha2.blocksize() {
-            let key = self.sha2.hash(secret_key);
+        Ok(Hmac {
+            inner,
+            outer_pad,
+            sha2,
+        })
+    }
 
-            for index in 0..self.sha2.output_size() {
-                inner_pad[index] ^= key[index];
-                outer_pad[index] ^= key[index];
-            }
-        } else {
-            for index in 0..secret_key.len() {
-                inner_pad[index] ^= secret_key[index];
-                outer_pad[index] ^= secret_key[index];
-            }
-        }
+    /// Initialize a streaming HMAC with a secret key. See `HmacStream`. The same exceptions
+    /// apply as for `new`.
+    pub fn init(secret_key: &SecretKey, sha2: H) -> Result<HmacStream<H>, UnknownCryptoError> {
+        check_output_size(sha2)?;
+        let (mut inner_pad, outer_pad) = pad_key(secret_key.unprotected_as_bytes(), sha2);
+
+        let mut inner = sha2.init();
+        inner.update(&inner_pad);
+        inner_pad.zeroize();
 
-        (inner_pad, outer_pad)
+        Ok(HmacStream {
+            inner,
+            outer_pad,
+            sha2,
+        })
     }
 
     /// Returns an HMAC for a given key and data.
-    pub fn finalize(&self) -> Vec<u8> {
-        let (mut ipad, mut opad) = self.pad_key(&self.secret_key);
+    pub fn finalize(&self) -> Tag {
+        let output_size = self.sha2.output_size();
+        let mut mac = [0u8; MAX_OUTPUT_SIZE];
+        finish_padded(self.inner.clone(), &self.outer_pad, self.sha2, &mut mac[..output_size]);
 
-        ipad.extend_from_slice(&self.data);
-        opad.extend_from_slice(&self.sha2.hash(&ipad));
+        Tag::from_array(mac, output_size)
+    }
 
-        let mac = self.sha2.hash(&opad);
+    /// Write the HMAC into `dst`, without allocating. The length of `dst` must be the output
+    /// size of `sha2`.
+    pub fn finalize_into(&self, dst: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if dst.len() != self.sha2.output_size() {
+            return Err(UnknownCryptoError);
+        }
 
-        Clear::clear(&mut ipad);
-        Clear::clear(&mut opad);
+        finish_padded(self.inner.clone(), &self.outer_pad, self.sha2, dst);
 
-        mac
+        Ok(())
     }
 
     /// Check HMAC validity by computing one from the current struct fields and comparing this
@@ -138,35 +175,253 @@ impl Hmac {
     pub fn verify(&self, expected_hmac: &[u8]) -> Result<bool, ValidationCryptoError> {
         let own_hmac = self.finalize();
 
-        let rand_key = util::gen_rand_key(self.sha2.blocksize()).unwrap();
+        verify_tags(own_hmac.as_bytes(), expected_hmac, self.sha2)
+    }
+}
+
+/// Streaming HMAC, for authenticating data that is not available all at once.
+///
+/// The outer padded key is zeroed out on drop.
+#[derive(Clone)]
+pub struct HmacStream<H: HashFunction = ShaVariantOption> {
+    inner: H::State,
+    outer_pad: Vec<u8>,
+    sha2: H,
+}
+
+impl<H: HashFunction> Drop for HmacStream<H> {
+    fn drop(&mut self) {
+        self.outer_pad.zeroize()
+    }
+}
+
+impl<H: HashFunction> ZeroizeOnDrop for HmacStream<H> {}
+
+impl<H: HashFunction> fmt::Debug for HmacStream<H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "HmacStream {{ inner: ***, outer_pad: ***, sha2: {:?} }}", self.sha2)
+    }
+}
 
-        let nd_round_own = Hmac {
-            secret_key: rand_key.clone(),
-            data: own_hmac,
-            sha2: self.sha2,
-        };
+/// Streaming HMAC, for authenticating data that is not available all at once.
+///
+/// Created with `Hmac::init`, which takes the secret key and the `ShaVariantOption` to use.
+///
+/// # Security:
+/// The same considerations for the secret key apply as for `Hmac`. An `HmacStream`
+/// produces the same output as `Hmac` given the concatenation of all data passed to `update`.
+///
+/// # Example:
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::hazardous::hmac::Hmac;
+/// use orion::core::types::SecretKey;
+/// use orion::core::options::ShaVariantOption;
+///
+/// let key = SecretKey::generate(32).unwrap();
+///
+/// let mut hmac = Hmac::init(&key, ShaVariantOption::SHA256).unwrap();
+/// hmac.update("Some ".as_bytes());
+/// hmac.update("message.".as_bytes());
+/// let tag = hmac.finalize();
+///
+/// let mut hmac = Hmac::init(&key, ShaVariantOption::SHA256).unwrap();
+/// hmac.update("Some message.".as_bytes());
+/// assert_eq!(hmac.verify(tag.as_bytes()).unwrap(), true);
+/// # }
+/// ```
+impl<H: HashFunction> HmacStream<H> {
+    /// Add data to be authenticated.
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
 
-        let nd_round_received = Hmac {
-            secret_key: rand_key,
-            data: expected_hmac.to_vec(),
-            sha2: self.sha2,
-        };
+    /// Return the HMAC of all data passed to `update`.
+    pub fn finalize(mut self) -> Tag {
+        let inner = ::std::mem::replace(&mut self.inner, self.sha2.init());
+        let output_size = self.sha2.output_size();
+        let mut mac = [0u8; MAX_OUTPUT_SIZE];
+        finish_padded(inner, &self.outer_pad, self.sha2, &mut mac[..output_size]);
 
-        if util::compare_ct(&nd_round_own.finalize(), &nd_round_received.finalize()).is_err() {
-            Err(ValidationCryptoError)
-        } else {
-            Ok(true)
+        Tag::from_array(mac, output_size)
+    }
+
+    /// Write the HMAC of all data passed to `update` into `dst`, without allocating. The length
+    /// of `dst` must be the output size of `sha2`.
+    pub fn finalize_into(mut self, dst: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if dst.len() != self.sha2.output_size() {
+            return Err(UnknownCryptoError);
         }
+
+        let inner = ::std::mem::replace(&mut self.inner, self.sha2.init());
+        finish_padded(inner, &self.outer_pad, self.sha2, dst);
+
+        Ok(())
+    }
+
+    /// Check HMAC validity by finalizing the current state and comparing this to the passed
+    /// HMAC. Comparison is done in constant time and with Double-HMAC Verification.
+    pub fn verify(self, expected_hmac: &[u8]) -> Result<bool, ValidationCryptoError> {
+        let sha2 = self.sha2;
+        let own_hmac = self.finalize();
+
+        verify_tags(own_hmac.as_bytes(), expected_hmac, sha2)
     }
 }
 
-/// HMAC used for PBKDF2.
-pub fn pbkdf2_hmac(
-    ipad: &[u8],
-    opad: &[u8],
-    data: &[u8],
-    hmac: ShaVariantOption,
-) -> Vec<u8> {
+#[cfg(feature = "rng")]
+/// Compare two HMACs in constant time with Double-HMAC Verification: both are MAC'ed again
+/// under a fresh random key before comparing, so the comparison leaks nothing about either.
+fn verify_tags<H: HashFunction>(
+    own_hmac: &[u8],
+    expected_hmac: &[u8],
+    sha2: H,
+) -> Result<bool, ValidationCryptoError> {
+    let rand_key = SecretKey::generate(sha2.block_size())?;
+
+    let nd_round_own = Hmac::new(&rand_key, own_hmac, sha2)?;
+    let nd_round_received = Hmac::new(&rand_key, expected_hmac, sha2)?;
+
+    if nd_round_own.finalize() == nd_round_received.finalize() {
+        Ok(true)
+    } else {
+        Err(ValidationCryptoError)
+    }
+}
+
+#[cfg(not(feature = "rng"))]
+/// Compare two HMACs in constant time. Without an RNG there is no key for Double-HMAC
+/// Verification, so only the constant-time comparison is done.
+fn verify_tags<H: HashFunction>(
+    own_hmac: &[u8],
+    expected_hmac: &[u8],
+    _sha2: H,
+) -> Result<bool, ValidationCryptoError> {
+    if util::compare_ct_any_len(expected_hmac, own_hmac) {
+        Ok(true)
+    } else {
+        Err(ValidationCryptoError)
+    }
+}
+
+/// Writing to an `HmacStream` is equivalent to calling `update`, so that data can be
+/// authenticated with `std::io::copy`.
+///
+/// # Example:
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::hazardous::hmac::Hmac;
+/// use orion::core::types::SecretKey;
+/// use orion::core::options::ShaVariantOption;
+/// use std::io;
+///
+/// let mut file = io::Cursor::new(vec![0u8; 4096]);
+///
+/// let key = SecretKey::generate(32).unwrap();
+/// let mut hmac = Hmac::init(&key, ShaVariantOption::SHA256).unwrap();
+/// io::copy(&mut file, &mut hmac).unwrap();
+/// let tag = hmac.finalize();
+/// # }
+/// ```
+impl<H: HashFunction> io::Write for HmacStream<H> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reader adapter that authenticates all data read through it from an underlying reader.
+///
+/// # Security:
+/// The HMAC only covers the data that has actually been read through the adapter, so
+/// the underlying reader should be read to its end before calling `finalize` or `verify`.
+/// Data read before verification has succeeded must be treated as unauthenticated.
+///
+/// # Example:
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::hazardous::hmac::{Hmac, HmacReader};
+/// use orion::core::types::SecretKey;
+/// use orion::core::options::ShaVariantOption;
+/// use std::io;
+///
+/// let key = SecretKey::generate(32).unwrap();
+/// let download = vec![0u8; 4096];
+///
+/// let mut hmac = Hmac::init(&key, ShaVariantOption::SHA256).unwrap();
+/// hmac.update(&download);
+/// let expected_tag = hmac.finalize();
+///
+/// let hmac = Hmac::init(&key, ShaVariantOption::SHA256).unwrap();
+/// let mut reader = HmacReader::new(io::Cursor::new(download), hmac);
+/// let mut file = Vec::new();
+/// io::copy(&mut reader, &mut file).unwrap();
+///
+/// assert_eq!(reader.verify(expected_tag.as_bytes()).unwrap(), true);
+/// # }
+/// ```
+pub struct HmacReader<R: io::Read, H: HashFunction = ShaVariantOption> {
+    inner: R,
+    hmac: HmacStream<H>,
+}
+
+impl<R: io::Read, H: HashFunction> HmacReader<R, H> {
+    /// Wrap a reader, authenticating everything read from it with `hmac`.
+    pub fn new(inner: R, hmac: HmacStream<H>) -> Self {
+        HmacReader { inner, hmac }
+    }
+
+    /// Return the HMAC of all data read so far.
+    pub fn finalize(self) -> Tag {
+        self.hmac.finalize()
+    }
+
+    /// Check the HMAC of all data read so far against the passed HMAC. See `HmacStream::verify`.
+    pub fn verify(self, expected_hmac: &[u8]) -> Result<bool, ValidationCryptoError> {
+        self.hmac.verify(expected_hmac)
+    }
+}
+
+impl<R: io::Read, H: HashFunction> io::Read for HmacReader<R, H> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hmac.update(&buf[..read]);
+
+        Ok(read)
+    }
+}
+
+/// Pad the key and return inner and outer padding.
+pub fn pad_key<H: HashFunction>(secret_key: &[u8], sha2: H) -> (Vec<u8>, Vec<u8>) {
+    let mut inner_pad = vec![0x36; sha2.block_size()];
+    let mut outer_pad = vec![0x5C; sha2.block_size()];
+
+    if secret_key.len() > sha2.block_size() {
+        let key = sha2.hash(secret_key);
+
+        for index in 0..sha2.output_size() {
+            inner_pad[index] ^= key[index];
+            outer_pad[index] ^= key[index];
+        }
+    } else {
+        for index in 0..secret_key.len() {
+            inner_pad[index] ^= secret_key[index];
+            outer_pad[index] ^= secret_key[index];
+        }
+    }
+
+    (inner_pad, outer_pad)
+}
+
+/// HMAC with precomputed inner and outer padding, as used for PBKDF2 and HKDF.
+pub fn pbkdf2_hmac<H: HashFunction>(ipad: &[u8], opad: &[u8], data: &[u8], hmac: H) -> Vec<u8> {
 
     let mut mac = Vec::new();
     mac.extend_from_slice(opad);
@@ -179,50 +434,617 @@ pub fn pbkdf2_hmac(
     mac
 }
 
+/// Largest block size supported by the allocation-free functions. This is the size of the
+/// Keccak-f[1600] state, which bounds the rate of any sponge-based hash.
+pub const MAX_BLOCK_SIZE: usize = 200;
+
+/// Largest output size supported by the allocation-free functions. This is the largest output
+/// size of the `ShaVariantOption`s, and the size of a `Tag`.
+pub const MAX_OUTPUT_SIZE: usize = 64;
+
+/// Check that the output of `sha2` fits in a `Tag`.
+fn check_output_size<H: HashFunction>(sha2: H) -> Result<(), UnknownCryptoError> {
+    let output_size = sha2.output_size();
+    if output_size == 0 || output_size > MAX_OUTPUT_SIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(())
+}
+
+/// Return the inner and outer hash states after absorbing the padded key, without allocating.
+/// Cloning the states computes another HMAC with the same key, without padding it again.
+pub fn keyed_states<H: HashFunction>(
+    secret_key: &[u8],
+    sha2: H,
+) -> Result<(H::State, H::State), UnknownCryptoError> {
+    let block_size = sha2.block_size();
+    let output_size = sha2.output_size();
+    if block_size > MAX_BLOCK_SIZE || output_size > MAX_OUTPUT_SIZE || output_size > block_size {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut inner_pad = [0x36u8; MAX_BLOCK_SIZE];
+    let mut outer_pad = [0x5Cu8; MAX_BLOCK_SIZE];
+    let mut hashed_key = [0u8; MAX_OUTPUT_SIZE];
+
+    let key = if secret_key.len() > block_size {
+        let mut state = sha2.init();
+        state.update(secret_key);
+        state.finalize_into(&mut hashed_key[..output_size]);
+        &hashed_key[..output_size]
+    } else {
+        secret_key
+    };
+    for (index, byte) in key.iter().enumerate() {
+        inner_pad[index] ^= *byte;
+        outer_pad[index] ^= *byte;
+    }
+
+    let mut inner = sha2.init();
+    inner.update(&inner_pad[..block_size]);
+    let mut outer = sha2.init();
+    outer.update(&outer_pad[..block_size]);
+
+    inner_pad.zeroize();
+    outer_pad.zeroize();
+    hashed_key.zeroize();
+
+    Ok((inner, outer))
+}
+
+/// Return the inner and outer hash states after absorbing the padded key, like `keyed_states`.
+/// The pads are allocated on the heap, so there is no limit on the block and output size of
+/// `sha2`.
+pub fn padded_states<H: HashFunction>(secret_key: &[u8], sha2: H) -> (H::State, H::State) {
+    let (mut ipad, mut opad) = pad_key(secret_key, sha2);
+    let mut inner = sha2.init();
+    inner.update(&ipad);
+    let mut outer = sha2.init();
+    outer.update(&opad);
+
+    ipad.zeroize();
+    opad.zeroize();
+
+    (inner, outer)
+}
+
+/// Finish an HMAC from an inner state that has absorbed the data and the outer padded key,
+/// writing the tag into `dst`.
+fn finish_padded<H: HashFunction>(inner: H::State, outer_pad: &[u8], sha2: H, dst: &mut [u8]) {
+    let mut outer = sha2.init();
+    outer.update(outer_pad);
+    finish_keyed::<H>(inner, outer, dst);
+}
+
+/// Finish an HMAC from keyed states that have absorbed the data, writing the tag into `dst`.
+pub fn finish_keyed<H: HashFunction>(inner: H::State, mut outer: H::State, dst: &mut [u8]) {
+    // The inner digest is absorbed before it is overwritten by the tag
+    inner.finalize_into(dst);
+    outer.update(dst);
+    outer.finalize_into(dst);
+}
+
+/// Compute the HMAC of `data` and write it into `dst`, without allocating. This is for targets
+/// without a heap, where `Hmac` cannot be used.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of `dst` is not the output size of `sha2`
+/// - The block size of `sha2` is greater than 200 bytes or its output size is greater than 64
+///
+/// # Example:
+/// ```
+/// use orion::hazardous::hmac;
+/// use orion::core::options::ShaVariantOption;
+///
+/// let mut tag = [0u8; 32];
+/// hmac::hmac_into(b"Secret key", b"Some message.", ShaVariantOption::SHA256, &mut tag).unwrap();
+/// ```
+pub fn hmac_into<H: HashFunction>(
+    secret_key: &[u8],
+    data: &[u8],
+    sha2: H,
+    dst: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if dst.len() != sha2.output_size() {
+        return Err(UnknownCryptoError);
+    }
+
+    let (mut inner, outer) = keyed_states(secret_key, sha2)?;
+    inner.update(data);
+    finish_keyed::<H>(inner, outer, dst);
+
+    Ok(())
+}
+
+/// Compute the HMACs of many independent messages under the same key, writing the tags one
+/// after another into `dst`. The key is padded and absorbed only once, so this is faster than
+/// creating an `Hmac` per message when authenticating many small records.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The output size of `sha2` is 0 or greater than `MAX_OUTPUT_SIZE`
+/// - The length of `dst` is not `messages.len()` times the output size of `sha2`
+///
+/// # Example:
+/// ```
+/// use orion::hazardous::hmac;
+/// use orion::core::options::ShaVariantOption;
+///
+/// let records: [&[u8]; 2] = [b"First record.", b"Second record."];
+/// let mut tags = [0u8; 64];
+/// hmac::hmac_many(b"Secret key", &records, ShaVariantOption::SHA256, &mut tags).unwrap();
+/// ```
+pub fn hmac_many<H: HashFunction>(
+    secret_key: &[u8],
+    messages: &[&[u8]],
+    sha2: H,
+    dst: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    check_output_size(sha2)?;
+    let output_size = sha2.output_size();
+    if dst.len() != messages.len() * output_size {
+        return Err(UnknownCryptoError);
+    }
+
+    let (inner, outer) = padded_states(secret_key, sha2);
+    for (message, tag) in messages.iter().zip(dst.chunks_mut(output_size)) {
+        let mut message_inner = inner.clone();
+        message_inner.update(message);
+        finish_keyed::<H>(message_inner, outer.clone(), tag);
+    }
+
+    Ok(())
+}
+
+/// HMAC-SHA256 of `data` in one call, without creating an `Hmac`.
+///
+/// # Example:
+/// ```
+/// use orion::hazardous::hmac;
+///
+/// let tag = hmac::hmac_sha256(b"Secret key", b"Some message.");
+/// assert_eq!(tag.len(), 32);
+/// ```
+pub fn hmac_sha256(secret_key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut tag = [0u8; 32];
+    hmac_oneshot(secret_key, data, ShaVariantOption::SHA256, &mut tag);
+
+    tag
+}
+
+/// HMAC-SHA384 of `data` in one call, without creating an `Hmac`.
+pub fn hmac_sha384(secret_key: &[u8], data: &[u8]) -> [u8; 48] {
+    let mut tag = [0u8; 48];
+    hmac_oneshot(secret_key, data, ShaVariantOption::SHA384, &mut tag);
+
+    tag
+}
+
+/// HMAC-SHA512 of `data` in one call, without creating an `Hmac`.
+pub fn hmac_sha512(secret_key: &[u8], data: &[u8]) -> [u8; 64] {
+    let mut tag = [0u8; 64];
+    hmac_oneshot(secret_key, data, ShaVariantOption::SHA512, &mut tag);
+
+    tag
+}
+
+/// Write the HMAC of `data` into `dst`, which must be the output size of `sha2`.
+fn hmac_oneshot(secret_key: &[u8], data: &[u8], sha2: ShaVariantOption, dst: &mut [u8]) {
+    let (mut inner, outer) = padded_states(secret_key, sha2);
+    inner.update(data);
+    finish_keyed::<ShaVariantOption>(inner, outer, dst);
+}
+
+#[test]
+fn hmac_oneshot_equals_hmac() {
+    let data = "what do ya want for nothing?".as_bytes();
+
+    for key in [&b"Jefe"[..], &[0xaa; 200][..]].iter() {
+        let secret_key = SecretKey::from_slice(key).unwrap();
+        let hmac = |sha2| Hmac::new(&secret_key, data, sha2).unwrap().finalize();
+
+        assert_eq!(hmac_sha256(key, data)[..], *hmac(ShaVariantOption::SHA256).as_bytes());
+        assert_eq!(hmac_sha384(key, data)[..], *hmac(ShaVariantOption::SHA384).as_bytes());
+        assert_eq!(hmac_sha512(key, data)[..], *hmac(ShaVariantOption::SHA512).as_bytes());
+    }
+}
+
+#[test]
+fn hmac_many_equals_hmac() {
+    let secret_key = SecretKey::from_slice(b"Secret key").unwrap();
+    let messages: [&[u8]; 3] = [b"", b"Some message.", &[0x61; 300]];
+
+    for sha2 in [ShaVariantOption::SHA256, ShaVariantOption::SHA3_512].iter() {
+        let mut tags = vec![0u8; messages.len() * sha2.output_size()];
+        hmac_many(b"Secret key", &messages, *sha2, &mut tags).unwrap();
+
+        for (message, tag) in messages.iter().zip(tags.chunks(sha2.output_size())) {
+            assert_eq!(tag, Hmac::new(&secret_key, message, *sha2).unwrap().finalize().as_bytes());
+        }
+        assert!(hmac_many(b"Secret key", &messages, *sha2, &mut tags[1..]).is_err());
+    }
+
+    assert!(hmac_many(b"Secret key", &[], ShaVariantOption::SHA256, &mut []).is_ok());
+}
+
+#[test]
+fn hmac_into_equals_hmac() {
+    let variants = [
+        ShaVariantOption::SHA256,
+        ShaVariantOption::SHA384,
+        ShaVariantOption::SHA512,
+        ShaVariantOption::SHA512Trunc256,
+        ShaVariantOption::SHA3_256,
+        ShaVariantOption::SHA3_512,
+    ];
+    let data = "what do ya want for nothing?".as_bytes();
+
+    for sha2 in variants.iter() {
+        for key in [&[0x0b; 4][..], &[0x0b; 200][..]].iter() {
+            let secret_key = SecretKey::from_slice(key).unwrap();
+            let expected = Hmac::new(&secret_key, data, *sha2).unwrap().finalize();
+            let mut tag = [0u8; 64];
+            hmac_into(key, data, *sha2, &mut tag[..sha2.output_size()]).unwrap();
+
+            assert_eq!(&tag[..sha2.output_size()], expected.as_bytes());
+            assert!(hmac_into(key, data, *sha2, &mut tag[..sha2.output_size() - 1]).is_err());
+        }
+    }
+}
+
+#[test]
+fn finalize_into_equals_finalize() {
+    let key = SecretKey::from_slice(&[0x0b; 20]).unwrap();
+    let data = "Hi There".as_bytes();
+
+    for sha2 in [ShaVariantOption::SHA384, ShaVariantOption::SHA3_256].iter() {
+        let expected = Hmac::new(&key, data, *sha2).unwrap().finalize();
+        let len = sha2.output_size();
+
+        let mut tag = [0u8; 64];
+        Hmac::new(&key, data, *sha2).unwrap().finalize_into(&mut tag[..len]).unwrap();
+        assert_eq!(&tag[..len], expected.as_bytes());
+
+        let mut stream_tag = [0u8; 64];
+        let mut stream = Hmac::init(&key, *sha2).unwrap();
+        stream.update(data);
+        stream.finalize_into(&mut stream_tag[..len]).unwrap();
+        assert_eq!(&stream_tag[..len], expected.as_bytes());
+
+        assert!(Hmac::new(&key, data, *sha2).unwrap().finalize_into(&mut tag).is_err());
+        assert!(Hmac::init(&key, *sha2).unwrap().finalize_into(&mut tag[..len + 1]).is_err());
+    }
+}
+
 #[test]
 fn finalize_and_veriy_true() {
-    let own_hmac = Hmac {
-        secret_key: "Jefe".as_bytes().to_vec(),
-        data: "what do ya want for nothing?".as_bytes().to_vec(),
-        sha2: ShaVariantOption::SHA256,
-    };
-    let recieved_hmac = Hmac {
-        secret_key: "Jefe".as_bytes().to_vec(),
-        data: "what do ya want for nothing?".as_bytes().to_vec(),
-        sha2: ShaVariantOption::SHA256,
-    };
+    let own_hmac = Hmac::new(
+        &SecretKey::from_slice("Jefe".as_bytes()).unwrap(),
+        "what do ya want for nothing?".as_bytes(),
+        ShaVariantOption::SHA256,
+    ).unwrap();
+    let recieved_hmac = Hmac::new(
+        &SecretKey::from_slice("Jefe".as_bytes()).unwrap(),
+        "what do ya want for nothing?".as_bytes(),
+        ShaVariantOption::SHA256,
+    ).unwrap();
 
-    assert_eq!(own_hmac.verify(&recieved_hmac.finalize()).unwrap(), true);
+    assert_eq!(own_hmac.verify(recieved_hmac.finalize().as_bytes()).unwrap(), true);
 }
 
 #[test]
 fn veriy_false_wrong_secret_key() {
-    let own_hmac = Hmac {
-        secret_key: "Jefe".as_bytes().to_vec(),
-        data: "what do ya want for nothing?".as_bytes().to_vec(),
-        sha2: ShaVariantOption::SHA256,
-    };
-    let false_hmac = Hmac {
-        secret_key: "Jefe".as_bytes().to_vec(),
-        data: "what do ya want for something?".as_bytes().to_vec(),
-        sha2: ShaVariantOption::SHA256,
-    };
+    let own_hmac = Hmac::new(
+        &SecretKey::from_slice("Jefe".as_bytes()).unwrap(),
+        "what do ya want for nothing?".as_bytes(),
+        ShaVariantOption::SHA256,
+    ).unwrap();
+    let false_hmac = Hmac::new(
+        &SecretKey::from_slice("Jefe".as_bytes()).unwrap(),
+        "what do ya want for something?".as_bytes(),
+        ShaVariantOption::SHA256,
+    ).unwrap();
 
-    assert!(own_hmac.verify(&false_hmac.finalize()).is_err());
+    assert!(own_hmac.verify(false_hmac.finalize().as_bytes()).is_err());
 }
 
 #[test]
 fn veriy_false_wrong_data() {
-    let own_hmac = Hmac {
-        secret_key: "Jefe".as_bytes().to_vec(),
-        data: "what do ya want for nothing?".as_bytes().to_vec(),
-        sha2: ShaVariantOption::SHA256,
-    };
-    let false_hmac = Hmac {
-        secret_key: "Jose".as_bytes().to_vec(),
-        data: "what do ya want for nothing?".as_bytes().to_vec(),
-        sha2: ShaVariantOption::SHA256,
-    };
+    let own_hmac = Hmac::new(
+        &SecretKey::from_slice("Jefe".as_bytes()).unwrap(),
+        "what do ya want for nothing?".as_bytes(),
+        ShaVariantOption::SHA256,
+    ).unwrap();
+    let false_hmac = Hmac::new(
+        &SecretKey::from_slice("Jose".as_bytes()).unwrap(),
+        "what do ya want for nothing?".as_bytes(),
+        ShaVariantOption::SHA256,
+    ).unwrap();
+
+    assert!(own_hmac.verify(false_hmac.finalize().as_bytes()).is_err());
+}
+
+#[test]
+fn streaming_equals_one_shot() {
+    let variants = [
+        ShaVariantOption::SHA256,
+        ShaVariantOption::SHA384,
+        ShaVariantOption::SHA512,
+        ShaVariantOption::SHA512Trunc256,
+        ShaVariantOption::SHA3_256,
+        ShaVariantOption::SHA3_512,
+    ];
+    let data: Vec<u8> = (0..1000).map(|x| x as u8).collect();
+    // One short key and one key longer than any blocksize
+    let keys = [vec![0x0b; 20], vec![0xaa; 200]];
+
+    for sha2 in variants.iter() {
+        for key in keys.iter() {
+            let secret_key = SecretKey::from_slice(key).unwrap();
+            let one_shot = Hmac::new(&secret_key, &data, *sha2).unwrap();
+            let expected = one_shot.finalize();
+
+            for chunk_size in [1, 13, 64, 136, 1000].iter() {
+                let mut streaming = Hmac::init(&secret_key, *sha2).unwrap();
+                for chunk in data.chunks(*chunk_size) {
+                    streaming.update(chunk);
+                }
+                assert_eq!(streaming.finalize(), expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn streaming_verify() {
+    let jefe = SecretKey::from_slice("Jefe".as_bytes()).unwrap();
+    let mut own_hmac = Hmac::init(&jefe, ShaVariantOption::SHA256).unwrap();
+    own_hmac.update("what do ya want ".as_bytes());
+    own_hmac.update("for nothing?".as_bytes());
+
+    let mut false_hmac = Hmac::init(&jefe, ShaVariantOption::SHA256).unwrap();
+    false_hmac.update("what do ya want for something?".as_bytes());
+
+    let mut empty_hmac = Hmac::init(&jefe, ShaVariantOption::SHA256).unwrap();
+    empty_hmac.update(&[]);
+
+    let expected = Hmac::new(
+        &SecretKey::from_slice("Jefe".as_bytes()).unwrap(),
+        "what do ya want for nothing?".as_bytes(),
+        ShaVariantOption::SHA256,
+    ).unwrap().finalize();
+
+    assert_eq!(own_hmac.verify(expected.as_bytes()).unwrap(), true);
+    assert!(false_hmac.verify(expected.as_bytes()).is_err());
+    assert!(empty_hmac.verify(expected.as_bytes()).is_err());
+}
+
+#[test]
+fn streaming_write() {
+    use std::io::{self, Write};
+
+    let jefe = SecretKey::from_slice("Jefe".as_bytes()).unwrap();
+    let data: Vec<u8> = (0..10000).map(|x| x as u8).collect();
+
+    let mut written = Hmac::init(&jefe, ShaVariantOption::SHA512).unwrap();
+    io::copy(&mut io::Cursor::new(&data), &mut written).unwrap();
+    written.write_all(b"trailer").unwrap();
+
+    let mut updated = Hmac::init(&jefe, ShaVariantOption::SHA512).unwrap();
+    updated.update(&data);
+    updated.update(b"trailer");
+
+    assert_eq!(written.finalize(), updated.finalize());
+}
+
+#[test]
+fn reader_verify() {
+    use std::io::{self, Read};
+
+    let jefe = SecretKey::from_slice("Jefe".as_bytes()).unwrap();
+    let data: Vec<u8> = (0..10000).map(|x| x as u8).collect();
+
+    let mut hmac = Hmac::init(&jefe, ShaVariantOption::SHA256).unwrap();
+    hmac.update(&data);
+    let expected = hmac.finalize();
+
+    let hmac = Hmac::init(&jefe, ShaVariantOption::SHA256).unwrap();
+    let mut reader = HmacReader::new(io::Cursor::new(&data), hmac);
+    let mut read = Vec::new();
+    reader.read_to_end(&mut read).unwrap();
+
+    assert_eq!(read, data);
+    assert_eq!(reader.verify(expected.as_bytes()).unwrap(), true);
+
+    // Stopping before the end of the stream must not verify
+    let hmac = Hmac::init(&jefe, ShaVariantOption::SHA256).unwrap();
+    let mut reader = HmacReader::new(io::Cursor::new(&data), hmac);
+    let mut partial = [0u8; 100];
+    reader.read_exact(&mut partial).unwrap();
+
+    assert!(reader.verify(expected.as_bytes()).is_err());
+}
+
+#[test]
+fn debug_is_redacted() {
+    let secret_key = SecretKey::from_slice("Jefe".as_bytes()).unwrap();
+    let hmac = Hmac::new(
+        &secret_key,
+        "what do ya want for nothing?".as_bytes(),
+        ShaVariantOption::SHA256,
+    ).unwrap();
+
+    assert_eq!(format!("{:?}", hmac), "Hmac { inner: ***, outer_pad: ***, sha2: SHA256 }");
+    assert_eq!(
+        format!("{:?}", Hmac::init(&secret_key, hmac.sha2).unwrap()),
+        "HmacStream { inner: ***, outer_pad: ***, sha2: SHA256 }"
+    );
+}
+
+#[cfg(test)]
+#[derive(Clone, Copy, Debug)]
+/// SHA-256 implemented outside of `ShaVariantOption`, to test a custom `HashFunction`.
+struct CustomSha256;
+
+#[cfg(test)]
+#[derive(Clone)]
+struct CustomSha256State(::sha2::Sha256);
+
+#[cfg(test)]
+impl HashState for CustomSha256State {
+    fn update(&mut self, data: &[u8]) {
+        use sha2::Digest;
+        self.0.update(data)
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        use sha2::Digest;
+        self.0.finalize().to_vec()
+    }
+}
+
+#[cfg(test)]
+impl HashFunction for CustomSha256 {
+    type State = CustomSha256State;
+
+    fn output_size(self) -> usize {
+        32
+    }
+
+    fn block_size(self) -> usize {
+        64
+    }
+
+    fn init(self) -> CustomSha256State {
+        CustomSha256State(::sha2::Sha256::default())
+    }
+}
+
+#[test]
+fn custom_hash_function() {
+    use core::types::Password;
+    use hazardous::hkdf::Hkdf;
+    use hazardous::pbkdf2::Pbkdf2;
+
+    let sha256 = ShaVariantOption::SHA256;
+    let key = SecretKey::from_slice(&[0x0b; 100]).unwrap();
+    let data = "what do ya want for nothing?".as_bytes();
+
+    let custom = Hmac::new(&key, data, CustomSha256).unwrap().finalize();
+    assert_eq!(custom, Hmac::new(&key, data, sha256).unwrap().finalize());
+
+    let mut streaming = Hmac::init(&key, CustomSha256).unwrap();
+    streaming.update(data);
+    assert!(streaming.verify(custom.as_bytes()).unwrap());
+
+    let hkdf = Hkdf::new(b"salt", b"ikm", b"info", 100, CustomSha256).unwrap();
+    let expected = Hkdf::new(b"salt", b"ikm", b"info", 100, sha256).unwrap();
+    assert_eq!(hkdf.derive_key().unwrap(), expected.derive_key().unwrap());
+
+    let password = Password::from_slice(b"password").unwrap();
+    let pbkdf2 = Pbkdf2::new(&password, b"salt", 100, 40, CustomSha256).unwrap();
+    let expected = Pbkdf2::new(&password, b"salt", 100, 40, sha256).unwrap();
+    assert_eq!(pbkdf2.derive_key().unwrap(), expected.derive_key().unwrap());
+}
+
+#[cfg(test)]
+#[derive(Clone, Copy, Debug)]
+/// A hash function with a 65-byte output, which is too large for a `Tag`.
+struct WideHash;
+
+#[cfg(test)]
+#[derive(Clone)]
+struct WideHashState(::sha2::Sha512);
+
+#[cfg(test)]
+impl HashState for WideHashState {
+    fn update(&mut self, data: &[u8]) {
+        use sha2::Digest;
+        self.0.update(data)
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        use sha2::Digest;
+        let mut digest = self.0.finalize().to_vec();
+        digest.push(0);
+
+        digest
+    }
+}
+
+#[cfg(test)]
+impl HashFunction for WideHash {
+    type State = WideHashState;
+
+    fn output_size(self) -> usize {
+        65
+    }
+
+    fn block_size(self) -> usize {
+        128
+    }
+
+    fn init(self) -> WideHashState {
+        WideHashState(::sha2::Sha512::default())
+    }
+}
+
+#[test]
+fn output_size_too_large() {
+    use hazardous::hkdf::Hkdf;
+
+    let key = SecretKey::from_slice(&[0x0b; 32]).unwrap();
+
+    assert!(Hmac::new(&key, b"data", WideHash).is_err());
+    assert!(Hmac::init(&key, WideHash).is_err());
+    assert!(hmac_many(b"key", &[b"data"], WideHash, &mut [0u8; 65]).is_err());
+    // HKDF does not return a `Tag`, so it is not limited to `MAX_OUTPUT_SIZE`
+    assert!(Hkdf::new(b"salt", b"ikm", b"info", 100, WideHash).unwrap().derive_key().is_ok());
+}
+
+#[cfg(test)]
+#[derive(Clone, Copy, Debug)]
+/// A hash function with an empty output.
+struct EmptyHash;
+
+#[cfg(test)]
+#[derive(Clone)]
+struct EmptyHashState;
+
+#[cfg(test)]
+impl HashState for EmptyHashState {
+    fn update(&mut self, _data: &[u8]) {}
+
+    fn finalize(self) -> Vec<u8> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+impl HashFunction for EmptyHash {
+    type State = EmptyHashState;
+
+    fn output_size(self) -> usize {
+        0
+    }
+
+    fn block_size(self) -> usize {
+        64
+    }
+
+    fn init(self) -> EmptyHashState {
+        EmptyHashState
+    }
+}
+
+#[test]
+fn output_size_zero() {
+    let key = SecretKey::from_slice(&[0x0b; 32]).unwrap();
 
-    assert!(own_hmac.verify(&false_hmac.finalize()).is_err());
+    assert!(Hmac::new(&key, b"data", EmptyHash).is_err());
+    assert!(Hmac::init(&key, EmptyHash).is_err());
+    assert!(hmac_many(b"key", &[b"data", b"more data"], EmptyHash, &mut []).is_err());
+    assert!(hmac_many(b"key", &[], EmptyHash, &mut []).is_err());
 }
@@ -0,0 +1,184 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::{errors::*, util};
+use std::fmt;
+use tiny_keccak;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// KangarooTwelve as specified in the
+/// [KangarooTwelve paper](https://keccak.team/files/KangarooTwelve.pdf).
+///
+/// Fields `input` and `custom` are zeroed out on drop.
+pub struct KangarooTwelve {
+    pub input: Vec<u8>,
+    pub custom: Vec<u8>,
+    pub length: usize,
+}
+
+impl Drop for KangarooTwelve {
+    fn drop(&mut self) {
+        self.input.zeroize();
+        self.custom.zeroize()
+    }
+}
+
+impl ZeroizeOnDrop for KangarooTwelve {}
+
+impl fmt::Debug for KangarooTwelve {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "KangarooTwelve {{ input: ***, custom: ***, length: {} }}", self.length)
+    }
+}
+
+/// KangarooTwelve as specified in the
+/// [KangarooTwelve paper](https://keccak.team/files/KangarooTwelve.pdf).
+///
+/// # Parameters:
+/// - `input`:  The main input string
+/// - `custom`: Customization string (can be a zero-length string)
+/// - `length`: Output length in bytes
+///
+/// KangarooTwelve is an extendable-output function based on the Keccak-p[1600, 12]
+/// permutation. It processes large inputs in chunks of 8192 bytes using a tree hashing mode,
+/// which makes it much faster than cSHAKE for long messages.
+///
+/// ### Notes:
+/// The KangarooTwelve implementation currently relies on the `tiny-keccak` crate. Currently this
+/// crate will produce ***incorrect results on big-endian based systems***. See [issue here](https://github.com/debris/tiny-keccak/issues/15).
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The specified length is zero
+///
+/// # Security:
+/// KangarooTwelve has a security strength of 128 bits. The recommended output length is 32.
+///
+/// # Example:
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::hazardous::kangarootwelve::KangarooTwelve;
+/// use orion::core::util::gen_rand_key;
+///
+/// let data = gen_rand_key(32).unwrap();
+///
+/// let k12 = KangarooTwelve {
+///     input: data,
+///     custom: "File fingerprint".as_bytes().to_vec(),
+///     length: 32,
+/// };
+///
+/// let result = k12.finalize().unwrap();
+/// assert_eq!(k12.verify(&result).unwrap(), true);
+/// # }
+/// ```
+
+impl KangarooTwelve {
+    /// Return a KangarooTwelve hash.
+    pub fn finalize(&self) -> Result<Vec<u8>, UnknownCryptoError> {
+        if self.length == 0 {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut hash = vec![0u8; self.length];
+        tiny_keccak::k12(&self.custom, &self.input, &mut hash);
+
+        Ok(hash)
+    }
+
+    /// Verify a KangarooTwelve hash by comparing one from the current struct fields to the input
+    /// hash passed to the function. Comparison is done in constant time. Both hashes must be
+    /// of equal length.
+    pub fn verify(&self, input: &[u8]) -> Result<bool, ValidationCryptoError> {
+        let own_hash = self.finalize()?;
+
+        if util::compare_ct(&own_hash, input).is_err() {
+            Err(ValidationCryptoError)
+        } else {
+            Ok(true)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use hazardous::kangarootwelve::*;
+
+    #[test]
+    fn err_on_zero_length() {
+        let k12 = KangarooTwelve {
+            input: b"\x00\x01\x02\x03".to_vec(),
+            custom: b"".to_vec(),
+            length: 0,
+        };
+
+        assert!(k12.finalize().is_err());
+        assert!(k12.verify(&[]).is_err());
+    }
+
+    #[test]
+    fn verify_err() {
+        let k12 = KangarooTwelve {
+            input: b"\x00\x01\x02\x03".to_vec(),
+            custom: b"".to_vec(),
+            length: 32,
+        };
+        let other = KangarooTwelve {
+            input: b"\x00\x01\x02\x03".to_vec(),
+            custom: b"Custom".to_vec(),
+            length: 32,
+        };
+
+        assert!(k12.verify(&other.finalize().unwrap()).is_err());
+    }
+
+    #[test]
+    fn verify_err_len() {
+        let k12 = KangarooTwelve {
+            input: b"\x00\x01\x02\x03".to_vec(),
+            custom: b"".to_vec(),
+            length: 32,
+        };
+
+        let hash = k12.finalize().unwrap();
+
+        assert!(k12.verify(&hash[..31]).is_err());
+    }
+
+    #[test]
+    fn shorter_output_is_prefix() {
+        let k12_32 = KangarooTwelve {
+            input: b"\x00\x01\x02\x03".to_vec(),
+            custom: b"".to_vec(),
+            length: 32,
+        };
+        let k12_17 = KangarooTwelve {
+            input: b"\x00\x01\x02\x03".to_vec(),
+            custom: b"".to_vec(),
+            length: 17,
+        };
+
+        assert_eq!(&k12_32.finalize().unwrap()[..17], &k12_17.finalize().unwrap()[..]);
+    }
+}
@@ -31,3 +31,28 @@ pub mod pbkdf2;
 
 /// cSHAKE as specified in the [NIST SP 800-185](https://csrc.nist.gov/publications/detail/sp/800-185/final).
 pub mod cshake;
+
+/// The encoding functions of the [NIST SP 800-185](https://csrc.nist.gov/publications/detail/sp/800-185/final), for building cSHAKE-based constructions.
+pub mod sp800_185;
+
+/// ChaCha20 and HChaCha20 as specified in the [RFC 8439](https://tools.ietf.org/html/rfc8439) and the [draft RFC](https://tools.ietf.org/html/draft-irtf-cfrg-xchacha-01).
+pub mod chacha20;
+
+/// Poly1305 as specified in the [RFC 8439](https://tools.ietf.org/html/rfc8439).
+pub mod poly1305;
+
+/// Streaming authenticated encryption with XChaCha20 and Poly1305, compatible with libsodium's [secretstream](https://download.libsodium.org/doc/secret-key_cryptography/secretstream).
+pub mod secretstream;
+
+/// KangarooTwelve as specified in the [KangarooTwelve paper](https://keccak.team/files/KangarooTwelve.pdf).
+pub mod kangarootwelve;
+
+/// Encrypt-then-MAC with ChaCha20 and HMAC.
+pub mod chacha20_hmac;
+
+/// AEAD_XChaCha20_Poly1305 as specified in the [draft RFC](https://tools.ietf.org/html/draft-irtf-cfrg-xchacha-01).
+pub mod xchacha20poly1305;
+
+/// Implementations of the [RustCrypto](https://github.com/RustCrypto/traits) `Mac` and `ExtendableOutput` traits.
+#[cfg(feature = "rustcrypto")]
+pub mod rustcrypto;
@@ -20,28 +20,44 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use byte_tools::write_u32_be;
-use clear_on_drop::clear::Clear;
-use core::options::ShaVariantOption;
+use core::options::{HashFunction, HashState, ShaVariantOption};
+use core::types::Password;
 use core::{errors::*, util};
+use endian::{read_u32_le, read_u64_le, write_u32_be, write_u32_le, write_u64_le};
 use hazardous::hmac::*;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::fmt;
+use std::time::{Duration, Instant};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 /// PBKDF2 (Password-Based Key Derivation Function 2) as specified in the
 /// [RFC 8018](https://tools.ietf.org/html/rfc8018).
 ///
 /// Fields `password` and `salt` are zeroed out on drop.
-pub struct Pbkdf2 {
-    pub password: Vec<u8>,
-    pub salt: Vec<u8>,
-    pub iterations: usize,
-    pub dklen: usize,
-    pub hmac: ShaVariantOption,
+pub struct Pbkdf2<H: HashFunction = ShaVariantOption> {
+    password: Password,
+    salt: Vec<u8>,
+    iterations: usize,
+    dklen: usize,
+    hmac: H,
 }
 
-impl Drop for Pbkdf2 {
+impl<H: HashFunction> Drop for Pbkdf2<H> {
     fn drop(&mut self) {
-        Clear::clear(&mut self.password);
-        Clear::clear(&mut self.salt)
+        self.salt.zeroize()
+    }
+}
+
+impl<H: HashFunction> ZeroizeOnDrop for Pbkdf2<H> {}
+
+impl<H: HashFunction> fmt::Debug for Pbkdf2<H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Pbkdf2 {{ password: ***, salt: ***, iterations: {}, dklen: {}, hmac: {:?} }}",
+            self.iterations, self.dklen, self.hmac
+        )
     }
 }
 
@@ -55,10 +71,13 @@ impl Drop for Pbkdf2 {
 /// - `dklen`: Length of the derived key
 /// - `hmac`: Pseudorandom function
 ///
+/// The pseudorandom function is HMAC with the selected hash function, which may be any of the
+/// SHA2 or SHA3 variants in `ShaVariantOption`, or any other `HashFunction`.
+///
 /// See [RFC](https://tools.ietf.org/html/rfc8018#section-5.2) for more information.
 ///
 /// # Exceptions:
-/// An exception will be thrown if:
+/// An exception will be thrown by `new` if:
 /// - The specified dklen is less than 1
 /// - The specified dklen is greater than (2^32 - 1) * hLen
 /// - The specified iteration count is less than 1
@@ -70,134 +89,225 @@ impl Drop for Pbkdf2 {
 /// # Example:
 /// ### Generating derived key:
 /// ```
+/// # #[cfg(feature = "rng")]
+/// # {
 /// use orion::hazardous::pbkdf2::Pbkdf2;
+/// use orion::core::types::Password;
 /// use orion::core::util::gen_rand_key;
 /// use orion::core::options::ShaVariantOption;
 ///
-/// let password = gen_rand_key(32).unwrap();
+/// let password = Password::from_slice("Secret password".as_bytes()).unwrap();
 /// let salt = gen_rand_key(32).unwrap();
 ///
-/// let dk = Pbkdf2 {
-///     password: password,
-///     salt: salt,
-///     iterations: 10000,
-///     dklen: 64,
-///     hmac: ShaVariantOption::SHA256
-/// };
+/// let dk = Pbkdf2::new(&password, &salt, 10000, 64, ShaVariantOption::SHA256).unwrap();
 ///
 /// dk.derive_key().unwrap();
+/// # }
 /// ```
 /// ### Verifying derived key:
 /// ```
+/// # #[cfg(feature = "rng")]
+/// # {
 /// use orion::hazardous::pbkdf2::Pbkdf2;
+/// use orion::core::types::Password;
 /// use orion::core::util::gen_rand_key;
 /// use orion::core::options::ShaVariantOption;
 ///
-/// let password = gen_rand_key(32).unwrap();
+/// let password = Password::from_slice("Secret password".as_bytes()).unwrap();
 /// let salt = gen_rand_key(32).unwrap();
 ///
-/// let dk = Pbkdf2 {
-///     password: password,
-///     salt: salt,
-///     iterations: 10000,
-///     dklen: 64,
-///     hmac: ShaVariantOption::SHA256
-/// };
+/// let dk = Pbkdf2::new(&password, &salt, 10000, 64, ShaVariantOption::SHA256).unwrap();
 ///
 /// let derived_key = dk.derive_key().unwrap();
 /// assert_eq!(dk.verify(&derived_key).unwrap(), true);
+/// # }
 /// ```
 
-impl Pbkdf2 {
-    /// Return the maximum derived key dklen ((2^32 - 1) * hLen).
-    fn max_dklen(&self) -> usize {
-        match self.hmac.output_size() {
-            32 => 137_438_953_440,
-            48 => 206_158_430_160,
-            64 => 274_877_906_880,
-            _ => panic!(UnknownCryptoError),
+impl<H: HashFunction> Pbkdf2<H> {
+    /// Initialize a PBKDF2 with the given parameters, which are validated upfront.
+    pub fn new(
+        password: &Password,
+        salt: &[u8],
+        iterations: usize,
+        dklen: usize,
+        hmac: H,
+    ) -> Result<Self, UnknownCryptoError> {
+        if iterations < 1 {
+            return Err(UnknownCryptoError);
+        }
+        // The maximum dklen is (2^32 - 1) * hLen
+        if dklen < 1 || dklen as u64 > 0xffff_ffff * hmac.output_size() as u64 {
+            return Err(UnknownCryptoError);
         }
-    }
 
-    /// Returns a PRK using HMAC as the PRF. The parameters `ipad` and `opad` are constructed
-    /// in the `derive_key`. They are used to speed up HMAC calls.
-    fn prf(&self, ipad: &[u8], opad: &[u8], data: &[u8]) -> Vec<u8> {
-        pbkdf2_hmac(ipad, opad, data, self.hmac)
+        Ok(Pbkdf2 {
+            password: Password::from_slice(password.unprotected_as_bytes())?,
+            salt: salt.to_vec(),
+            iterations,
+            dklen,
+            hmac,
+        })
     }
 
-    /// Function F as described in the RFC.
-    fn function_f(&self, index: u32, ipad: &[u8], opad: &[u8], salt_ext: &mut [u8]) -> Vec<u8> {
-
-        let pos = salt_ext.len() - 4;
-        write_u32_be(&mut salt_ext[pos..], index);
+    /// Main PBKDF2 function. Returns a derived key.
+    pub fn derive_key(&self) -> Result<Vec<u8>, UnknownCryptoError> {
+        let hlen = self.hmac.output_size();
+        let (inner, outer) = padded_states(self.password.unprotected_as_bytes(), self.hmac);
+        let mut u_step = vec![0u8; hlen];
+        let mut f_result = vec![0u8; hlen];
+        let mut derived_key = vec![0u8; self.dklen];
+
+        for (index, dk_block) in derived_key.chunks_mut(hlen).enumerate() {
+            // The block index cannot overflow, because dklen is at most (2^32 - 1) * hLen
+            function_f::<H>(
+                &inner,
+                &outer,
+                &self.salt,
+                (index + 1) as u32,
+                self.iterations,
+                &mut u_step,
+                &mut f_result,
+            );
+            dk_block.copy_from_slice(&f_result[..dk_block.len()]);
+        }
 
-        // First iteration
-        let mut f_result: Vec<u8> = self.prf(ipad, opad, &salt_ext);
+        u_step.zeroize();
+        f_result.zeroize();
 
-        // Remaining iterations
-        if self.iterations > 1 {
+        Ok(derived_key)
+    }
 
-            let mut u_step = Vec::new();
-            u_step.extend_from_slice(&f_result);
+    /// Write the derived key into `dst`, without allocating. The length of `dst` must be the
+    /// dklen given to `new`. See `pbkdf2_into` for the supported block and output sizes.
+    pub fn derive_key_into(&self, dst: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if dst.len() != self.dklen {
+            return Err(UnknownCryptoError);
+        }
 
-            for _ in 1..self.iterations {
-                u_step = self.prf(ipad, opad, &u_step);
+        pbkdf2_into(
+            self.password.unprotected_as_bytes(),
+            &self.salt,
+            self.iterations,
+            self.hmac,
+            dst,
+        )
+    }
 
-                for index in 0..f_result.len() {
-                    f_result[index] ^= u_step[index];
-                }
-            }
-        }
+    #[cfg(feature = "parallel")]
+    /// Derive a key like `derive_key`, but compute the hLen-sized blocks of the derived key in
+    /// parallel on rayon's thread pool. Every block runs all iterations, so this only helps when
+    /// dklen is longer than the output size of the HMAC. The result is the same as that of
+    /// `derive_key`.
+    pub fn derive_key_parallel(&self) -> Result<Vec<u8>, UnknownCryptoError>
+    where
+        H::State: Sync,
+    {
+        let hlen = self.hmac.output_size();
+        let (inner, outer) = padded_states(self.password.unprotected_as_bytes(), self.hmac);
+        let (salt, iterations) = (&self.salt, self.iterations);
+        let mut derived_key = vec![0u8; self.dklen];
+
+        derived_key
+            .par_chunks_mut(hlen)
+            .enumerate()
+            .for_each(|(index, dk_block)| {
+                let mut u_step = vec![0u8; hlen];
+                let mut f_result = vec![0u8; hlen];
+                // The block index cannot overflow, because dklen is at most (2^32 - 1) * hLen
+                function_f::<H>(
+                    &inner,
+                    &outer,
+                    salt,
+                    (index + 1) as u32,
+                    iterations,
+                    &mut u_step,
+                    &mut f_result,
+                );
+                dk_block.copy_from_slice(&f_result[..dk_block.len()]);
+
+                u_step.zeroize();
+                f_result.zeroize();
+            });
 
-        f_result
+        Ok(derived_key)
     }
 
-    /// Main PBKDF2 function. Returns a derived key.
-    pub fn derive_key(&self) -> Result<Vec<u8>, UnknownCryptoError> {
-        if self.iterations < 1 {
-            return Err(UnknownCryptoError);
-        }
-        if self.dklen > self.max_dklen() {
-            return Err(UnknownCryptoError);
+    /// Start a derivation that is run in bounded slices with `resume`, instead of all at once
+    /// like `derive_key`. See `Pbkdf2Checkpoint`.
+    pub fn checkpoint(&self) -> Pbkdf2Checkpoint {
+        let hlen = self.hmac.output_size();
+
+        Pbkdf2Checkpoint {
+            iterations: self.iterations,
+            block: 1,
+            iteration: 0,
+            u_step: vec![0u8; hlen],
+            f_result: vec![0u8; hlen],
+            derived_key: vec![0u8; self.dklen],
         }
-        if self.dklen < 1 {
+    }
+
+    /// Run at most `max_iterations` more iterations of the derivation in `checkpoint`. Returns
+    /// the derived key once all iterations of all blocks are done, and `None` otherwise. The
+    /// result is the same as that of `derive_key`.
+    ///
+    /// An exception will be thrown if `max_iterations` is less than 1, or if `checkpoint` was
+    /// not created by a `Pbkdf2` with the same iteration count, dklen and `hmac`.
+    pub fn resume(
+        &self,
+        checkpoint: &mut Pbkdf2Checkpoint,
+        max_iterations: usize,
+    ) -> Result<Option<Vec<u8>>, UnknownCryptoError> {
+        let hlen = self.hmac.output_size();
+        if max_iterations < 1
+            || checkpoint.iterations != self.iterations
+            || checkpoint.u_step.len() != hlen
+            || checkpoint.derived_key.len() != self.dklen
+        {
             return Err(UnknownCryptoError);
         }
 
-        let hlen_blocks: usize = 1 + ((self.dklen - 1) / self.hmac.output_size());
-
-        let pad_const = Hmac {
-            secret_key: Vec::new(),
-            data: Vec::new(),
-            sha2: self.hmac,
-        };
-        let (mut ipad, mut opad) = pad_const.pad_key(&self.password);
-        let mut salt_ext = self.salt.clone();
-        // We need 4 bytes of space for the index value
-        salt_ext.extend_from_slice(&[0u8; 4]);
-        let mut derived_key: Vec<u8> = Vec::new();
-
-        for index in 1..hlen_blocks + 1 {
-            derived_key.extend_from_slice(&self.function_f(index as u32, &ipad, &opad, &mut salt_ext));
-            // Given that hlen_blocks is rounded correctly, then the `index as u32`
-            // should not be able to overflow. If the maximum dklen is selected,
-            // along with the highest output size, then hlen_blocks will equal
-            // exactly `u32::max_value()`
+        let (inner, outer) = padded_states(self.password.unprotected_as_bytes(), self.hmac);
+        let mut budget = max_iterations;
+
+        while budget > 0 && !checkpoint.is_finished() {
+            if checkpoint.iteration == 0 {
+                u_first::<H>(
+                    &inner,
+                    &outer,
+                    &self.salt,
+                    checkpoint.block,
+                    &mut checkpoint.u_step,
+                    &mut checkpoint.f_result,
+                );
+            } else {
+                u_next::<H>(&inner, &outer, &mut checkpoint.u_step, &mut checkpoint.f_result);
+            }
+            checkpoint.iteration += 1;
+            budget -= 1;
+
+            if checkpoint.iteration == self.iterations {
+                let start = (checkpoint.block as usize - 1) * hlen;
+                let end = ::std::cmp::min(start + hlen, self.dklen);
+                let f_result = &checkpoint.f_result[..end - start];
+                checkpoint.derived_key[start..end].copy_from_slice(f_result);
+                checkpoint.block += 1;
+                checkpoint.iteration = 0;
+            }
         }
 
-        Clear::clear(&mut ipad);
-        Clear::clear(&mut opad);
-
-        derived_key.truncate(self.dklen);
-
-        Ok(derived_key)
+        if checkpoint.is_finished() {
+            Ok(Some(checkpoint.derived_key.clone()))
+        } else {
+            Ok(None)
+        }
     }
 
     /// Verify a derived key by comparing one from the current struct fields with the derived key
     /// passed to the function. Comparison is done in constant time. Both derived keys must be
     /// of equal length.
     pub fn verify(&self, expected_dk: &[u8]) -> Result<bool, ValidationCryptoError> {
-        let own_dk = self.derive_key().unwrap();
+        let own_dk = self.derive_key()?;
 
         if util::compare_ct(&own_dk, expected_dk).is_err() {
             Err(ValidationCryptoError)
@@ -207,94 +317,526 @@ impl Pbkdf2 {
     }
 }
 
+/// Function F as described in the RFC, for the block `index`. The result is written into
+/// `f_result`, and `u_step` is used as scratch space. Both must be hLen bytes long. The
+/// padded password was absorbed into `inner` and `outer` once, so each iteration only clones
+/// the states instead of hashing the padded password again.
+fn function_f<H: HashFunction>(
+    inner: &H::State,
+    outer: &H::State,
+    salt: &[u8],
+    index: u32,
+    iterations: usize,
+    u_step: &mut [u8],
+    f_result: &mut [u8],
+) {
+    u_first::<H>(inner, outer, salt, index, u_step, f_result);
+
+    for _ in 1..iterations {
+        u_next::<H>(inner, outer, u_step, f_result);
+    }
+}
+
+/// The first iteration of function F, which sets both `u_step` and `f_result` to U_1.
+fn u_first<H: HashFunction>(
+    inner: &H::State,
+    outer: &H::State,
+    salt: &[u8],
+    index: u32,
+    u_step: &mut [u8],
+    f_result: &mut [u8],
+) {
+    let mut index_be = [0u8; 4];
+    write_u32_be(&mut index_be, index);
+
+    let mut u_inner = inner.clone();
+    u_inner.update(salt);
+    u_inner.update(&index_be);
+    finish_keyed::<H>(u_inner, outer.clone(), u_step);
+    f_result.copy_from_slice(u_step);
+}
+
+/// One of the remaining iterations of function F, which replaces `u_step` with the next U and
+/// XORs it into `f_result`.
+fn u_next<H: HashFunction>(
+    inner: &H::State,
+    outer: &H::State,
+    u_step: &mut [u8],
+    f_result: &mut [u8],
+) {
+    let mut u_inner = inner.clone();
+    u_inner.update(u_step);
+    finish_keyed::<H>(u_inner, outer.clone(), u_step);
+
+    for (f, u) in f_result.iter_mut().zip(u_step.iter()) {
+        *f ^= *u;
+    }
+}
+
+// Version, iteration count, completed iterations of the current block, block index, hLen and
+// dklen
+const CHECKPOINT_HEADER_SIZE: usize = 1 + 8 + 8 + 4 + 4 + 8;
+const CHECKPOINT_VERSION: u8 = 1;
+
+/// The intermediate state of a PBKDF2 derivation, created with `Pbkdf2::checkpoint`.
+///
+/// A long derivation can be split into slices of a bounded number of iterations with
+/// `Pbkdf2::resume`, so that it can yield to a scheduler in between. The state can be
+/// stored with `to_bytes` and restored with `from_bytes`, to survive the suspension of the
+/// process. The password and salt are not part of the checkpoint, and must be given to
+/// `Pbkdf2::new` again when resuming.
+///
+/// All fields are zeroed out on drop.
+///
+/// # Security:
+/// The checkpoint holds intermediate HMAC outputs and the finished blocks of the derived key,
+/// so it must be protected like the derived key itself when stored.
+///
+/// # Example:
+/// ```
+/// use orion::hazardous::pbkdf2::{Pbkdf2, Pbkdf2Checkpoint};
+/// use orion::core::types::Password;
+/// use orion::core::options::ShaVariantOption;
+///
+/// let password = Password::from_slice("Secret password".as_bytes()).unwrap();
+/// let dk = Pbkdf2::new(&password, b"Some salt", 10000, 64, ShaVariantOption::SHA256).unwrap();
+///
+/// let mut checkpoint = dk.checkpoint();
+/// let derived_key = loop {
+///     if let Some(derived_key) = dk.resume(&mut checkpoint, 1000).unwrap() {
+///         break derived_key;
+///     }
+///     // Store the progress, to continue after being suspended
+///     let stored = checkpoint.to_bytes();
+///     checkpoint = Pbkdf2Checkpoint::from_bytes(&stored).unwrap();
+/// };
+///
+/// assert_eq!(derived_key, dk.derive_key().unwrap());
+/// ```
+pub struct Pbkdf2Checkpoint {
+    iterations: usize,
+    block: u32,
+    iteration: usize,
+    u_step: Vec<u8>,
+    f_result: Vec<u8>,
+    derived_key: Vec<u8>,
+}
+
+impl Drop for Pbkdf2Checkpoint {
+    fn drop(&mut self) {
+        self.u_step.zeroize();
+        self.f_result.zeroize();
+        self.derived_key.zeroize()
+    }
+}
+
+impl ZeroizeOnDrop for Pbkdf2Checkpoint {}
+
+impl fmt::Debug for Pbkdf2Checkpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Pbkdf2Checkpoint {{ iterations: {}, block: {}, iteration: {}, u_step: ***, \
+             f_result: ***, derived_key: *** }}",
+            self.iterations, self.block, self.iteration
+        )
+    }
+}
+
+impl Pbkdf2Checkpoint {
+    /// Return true if all blocks of the derived key have been computed.
+    pub fn is_finished(&self) -> bool {
+        (u64::from(self.block) - 1) * self.u_step.len() as u64 >= self.derived_key.len() as u64
+    }
+
+    /// Serialize the checkpoint into an opaque byte string, which can be restored with
+    /// `from_bytes`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let hlen = self.u_step.len();
+        let mut bytes = vec![0u8; CHECKPOINT_HEADER_SIZE];
+        bytes[0] = CHECKPOINT_VERSION;
+        write_u64_le(&mut bytes[1..9], self.iterations as u64);
+        write_u64_le(&mut bytes[9..17], self.iteration as u64);
+        write_u32_le(&mut bytes[17..21], self.block);
+        write_u32_le(&mut bytes[21..25], hlen as u32);
+        write_u64_le(&mut bytes[25..33], self.derived_key.len() as u64);
+
+        bytes.extend_from_slice(&self.u_step);
+        bytes.extend_from_slice(&self.f_result);
+        bytes.extend_from_slice(&self.derived_key);
+
+        bytes
+    }
+
+    /// Restore a checkpoint serialized with `to_bytes`.
+    ///
+    /// An exception will be thrown if `bytes` is not a well-formed checkpoint.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, UnknownCryptoError> {
+        if bytes.len() < CHECKPOINT_HEADER_SIZE || bytes[0] != CHECKPOINT_VERSION {
+            return Err(UnknownCryptoError);
+        }
+
+        let iterations = read_u64_le(&bytes[1..9]);
+        let iteration = read_u64_le(&bytes[9..17]);
+        let block = read_u32_le(&bytes[17..21]);
+        let hlen = u64::from(read_u32_le(&bytes[21..25]));
+        let dklen = read_u64_le(&bytes[25..33]);
+
+        let remaining = (bytes.len() - CHECKPOINT_HEADER_SIZE) as u64;
+
+        if iterations < 1
+            || iterations > usize::max_value() as u64
+            || iteration >= iterations
+            || block < 1
+            || hlen < 1
+            || dklen < 1
+            || hlen > remaining
+            || dklen > remaining
+            || 2 * hlen + dklen != remaining
+            || (u64::from(block) - 1) * hlen > dklen
+        {
+            return Err(UnknownCryptoError);
+        }
+
+        let (u_step, rest) = bytes[CHECKPOINT_HEADER_SIZE..].split_at(hlen as usize);
+        let (f_result, derived_key) = rest.split_at(hlen as usize);
+
+        Ok(Pbkdf2Checkpoint {
+            iterations: iterations as usize,
+            block,
+            iteration: iteration as usize,
+            u_step: u_step.to_vec(),
+            f_result: f_result.to_vec(),
+            derived_key: derived_key.to_vec(),
+        })
+    }
+}
+
+/// PBKDF2, writing the derived key into `dst` without allocating. This is for targets without
+/// a heap, where `Pbkdf2` cannot be used. The length of the derived key is the length of
+/// `dst`.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of `dst` is less than 1
+/// - The length of `dst` is greater than (2^32 - 1) * hLen
+/// - The specified iteration count is less than 1
+/// - The block size of `hmac` is greater than 200 bytes or its output size is greater than 64
+///
+/// # Example:
+/// ```
+/// use orion::hazardous::pbkdf2;
+/// use orion::core::options::ShaVariantOption;
+///
+/// let mut dk = [0u8; 64];
+/// pbkdf2::pbkdf2_into(b"Secret password", b"Some salt", 10000, ShaVariantOption::SHA256, &mut dk)
+///     .unwrap();
+/// ```
+pub fn pbkdf2_into<H: HashFunction>(
+    password: &[u8],
+    salt: &[u8],
+    iterations: usize,
+    hmac: H,
+    dst: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    let hlen = hmac.output_size();
+    if iterations < 1 || dst.is_empty() || dst.len() as u64 > 0xffff_ffff * hlen as u64 {
+        return Err(UnknownCryptoError);
+    }
+
+    let (inner, outer) = keyed_states(password, hmac)?;
+    let mut u_step = [0u8; MAX_OUTPUT_SIZE];
+    let mut f_result = [0u8; MAX_OUTPUT_SIZE];
+
+    for (index, dk_block) in dst.chunks_mut(hlen).enumerate() {
+        // The block index cannot overflow, because the length of `dst` is at most
+        // (2^32 - 1) * hLen
+        function_f::<H>(
+            &inner,
+            &outer,
+            salt,
+            (index + 1) as u32,
+            iterations,
+            &mut u_step[..hlen],
+            &mut f_result[..hlen],
+        );
+        dk_block.copy_from_slice(&f_result[..dk_block.len()]);
+    }
+
+    u_step.zeroize();
+    f_result.zeroize();
+
+    Ok(())
+}
+
+/// Return the number of iterations for which PBKDF2 with the given `hmac` takes roughly
+/// `target_duration` on this machine.
+///
+/// # About:
+/// The iteration count is doubled, starting at 1000, until deriving a single block takes at
+/// least an eighth of `target_duration`. The result is then scaled linearly to the target.
+/// Because of this, running `calibrate` takes between roughly a quarter and half of
+/// `target_duration`.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `target_duration` is zero
+///
+/// # Security:
+/// The returned value depends on the current load of the machine. It is meant to help pick a
+/// fixed iteration count when deploying, not to be called each time a password is hashed.
+///
+/// # Example:
+/// ```
+/// use orion::hazardous::pbkdf2;
+/// use orion::core::options::ShaVariantOption;
+/// use std::time::Duration;
+///
+/// let iterations =
+///     pbkdf2::calibrate(Duration::from_millis(10), ShaVariantOption::SHA512Trunc256).unwrap();
+/// assert!(iterations >= 1);
+/// ```
+pub fn calibrate<H: HashFunction>(
+    target_duration: Duration,
+    hmac: H,
+) -> Result<usize, UnknownCryptoError> {
+    if target_duration == Duration::from_secs(0) {
+        return Err(UnknownCryptoError);
+    }
+
+    let target = duration_as_secs(target_duration);
+
+    let password = Password::from_slice(&[0x61; 16])?;
+    let salt = [0x73; 16];
+    let mut iterations: usize = 1000;
+
+    loop {
+        let pbkdf2 = Pbkdf2::new(&password, &salt, iterations, hmac.output_size(), hmac)?;
+
+        let start = Instant::now();
+        pbkdf2.derive_key()?;
+        let elapsed = duration_as_secs(start.elapsed());
+
+        if elapsed >= target / 8.0 || iterations >= (u32::max_value() / 2) as usize {
+            let scaled = (iterations as f64 * (target / elapsed.max(1e-9))).round();
+            return Ok(scaled.max(1.0).min(f64::from(u32::max_value())) as usize);
+        }
+
+        iterations *= 2;
+    }
+}
+
+fn duration_as_secs(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) * 1e-9
+}
+
 #[cfg(test)]
 mod test {
 
     extern crate hex;
     use self::hex::decode;
     use core::options::ShaVariantOption;
-    use hazardous::pbkdf2::Pbkdf2;
+    use core::types::Password;
+    use hazardous::pbkdf2::{calibrate, pbkdf2_into, Pbkdf2, Pbkdf2Checkpoint};
+    use std::time::Duration;
+
+    #[test]
+    fn resume_equals_derive_key() {
+        let password = Password::from_slice(b"password").unwrap();
+
+        for hmac in [ShaVariantOption::SHA256, ShaVariantOption::SHA3_512].iter() {
+            for dklen in [1, hmac.output_size(), 3 * hmac.output_size() + 5].iter() {
+                for max_iterations in [1, 7, 50, 1000].iter() {
+                    let dk = Pbkdf2::new(&password, b"salt", 50, *dklen, *hmac).unwrap();
+                    let mut checkpoint = dk.checkpoint();
+
+                    let derived_key = loop {
+                        let progress = dk.resume(&mut checkpoint, *max_iterations).unwrap();
+                        if let Some(derived_key) = progress {
+                            break derived_key;
+                        }
+                        checkpoint = Pbkdf2Checkpoint::from_bytes(&checkpoint.to_bytes()).unwrap();
+                    };
+
+                    assert!(checkpoint.is_finished());
+                    assert_eq!(derived_key, dk.derive_key().unwrap());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn resume_rejects_other_parameters() {
+        let password = Password::from_slice(b"password").unwrap();
+        let dk = Pbkdf2::new(&password, b"salt", 50, 32, ShaVariantOption::SHA256).unwrap();
+        let mut checkpoint = dk.checkpoint();
+
+        assert!(dk.resume(&mut checkpoint, 0).is_err());
+
+        let other_iterations = Pbkdf2::new(&password, b"salt", 51, 32, ShaVariantOption::SHA256);
+        let other_dklen = Pbkdf2::new(&password, b"salt", 50, 33, ShaVariantOption::SHA256);
+        let other_hmac = Pbkdf2::new(&password, b"salt", 50, 32, ShaVariantOption::SHA512);
+        assert!(other_iterations.unwrap().resume(&mut checkpoint, 10).is_err());
+        assert!(other_dklen.unwrap().resume(&mut checkpoint, 10).is_err());
+        assert!(other_hmac.unwrap().resume(&mut checkpoint, 10).is_err());
+    }
+
+    #[test]
+    fn checkpoint_from_bytes_rejects_malformed() {
+        let password = Password::from_slice(b"password").unwrap();
+        let dk = Pbkdf2::new(&password, b"salt", 50, 32, ShaVariantOption::SHA256).unwrap();
+        let mut checkpoint = dk.checkpoint();
+        dk.resume(&mut checkpoint, 10).unwrap();
+        let bytes = checkpoint.to_bytes();
+
+        assert!(Pbkdf2Checkpoint::from_bytes(&bytes).is_ok());
+        assert!(Pbkdf2Checkpoint::from_bytes(&[]).is_err());
+        assert!(Pbkdf2Checkpoint::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[0] = 2;
+        assert!(Pbkdf2Checkpoint::from_bytes(&wrong_version).is_err());
+
+        let mut wrong_dklen = bytes.clone();
+        wrong_dklen[25..33].copy_from_slice(&[0xff; 8]);
+        assert!(Pbkdf2Checkpoint::from_bytes(&wrong_dklen).is_err());
+
+        let mut finished_iteration = bytes.clone();
+        finished_iteration[9] = 50;
+        assert!(Pbkdf2Checkpoint::from_bytes(&finished_iteration).is_err());
+    }
+
+    #[test]
+    fn checkpoint_debug_is_redacted() {
+        let password = Password::from_slice(b"password").unwrap();
+        let dk = Pbkdf2::new(&password, b"salt", 50, 32, ShaVariantOption::SHA256).unwrap();
+
+        assert_eq!(
+            format!("{:?}", dk.checkpoint()),
+            "Pbkdf2Checkpoint { iterations: 50, block: 1, iteration: 0, u_step: ***, \
+             f_result: ***, derived_key: *** }"
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn derive_key_parallel_equals_derive_key() {
+        let password = Password::from_slice(b"password").unwrap();
+
+        for hmac in [ShaVariantOption::SHA256, ShaVariantOption::SHA3_512].iter() {
+            for dklen in [1, hmac.output_size(), 5 * hmac.output_size() + 3].iter() {
+                let dk = Pbkdf2::new(&password, b"salt", 50, *dklen, *hmac).unwrap();
+
+                assert_eq!(dk.derive_key_parallel().unwrap(), dk.derive_key().unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn pbkdf2_into_equals_pbkdf2() {
+        let password = Password::from_slice(b"password").unwrap();
+
+        for hmac in [ShaVariantOption::SHA256, ShaVariantOption::SHA3_512].iter() {
+            let dklen = 2 * hmac.output_size() + 3;
+            let expected = Pbkdf2::new(&password, b"salt", 50, dklen, *hmac).unwrap();
+            let mut dk = vec![0u8; dklen];
+            pbkdf2_into(b"password", b"salt", 50, *hmac, &mut dk).unwrap();
+
+            assert_eq!(dk, expected.derive_key().unwrap());
+
+            let mut dk_into = vec![0u8; dklen];
+            expected.derive_key_into(&mut dk_into).unwrap();
+            assert_eq!(dk_into, dk);
+            assert!(expected.derive_key_into(&mut dk_into[1..]).is_err());
+        }
+
+        let sha256 = ShaVariantOption::SHA256;
+        assert!(pbkdf2_into(b"password", b"salt", 0, sha256, &mut [0u8; 32]).is_err());
+        assert!(pbkdf2_into(b"password", b"salt", 1, sha256, &mut []).is_err());
+    }
+
+    #[test]
+    fn calibrate_scales_with_target() {
+        let sha256 = ShaVariantOption::SHA256;
+
+        let short = calibrate(Duration::from_millis(5), sha256).unwrap();
+        let long = calibrate(Duration::from_millis(80), sha256).unwrap();
+
+        assert!(short >= 1);
+        assert!(long > short);
+        assert!(calibrate(Duration::from_secs(0), sha256).is_err());
+    }
 
     #[test]
     fn dklen_too_high_sha256() {
         let too_long = ((2_u64.pow(32) - 1) * 32 as u64) as usize + 1;
 
-        let dk = Pbkdf2 {
-            password: "password".as_bytes().to_vec(),
-            salt: "salt".as_bytes().to_vec(),
-            iterations: 1,
-            dklen: too_long,
-            hmac: ShaVariantOption::SHA256,
-        };
-
-        assert!(dk.derive_key().is_err());
+        assert!(Pbkdf2::new(
+            &Password::from_slice("password".as_bytes()).unwrap(),
+            "salt".as_bytes(),
+            1,
+            too_long,
+            ShaVariantOption::SHA256,
+        ).is_err());
     }
 
     #[test]
     fn dklen_too_high_sha384() {
         let too_long = ((2_u64.pow(32) - 1) * 48 as u64) as usize + 1;
 
-        let dk = Pbkdf2 {
-            password: "password".as_bytes().to_vec(),
-            salt: "salt".as_bytes().to_vec(),
-            iterations: 1,
-            dklen: too_long,
-            hmac: ShaVariantOption::SHA384,
-        };
-
-        assert!(dk.derive_key().is_err());
+        assert!(Pbkdf2::new(
+            &Password::from_slice("password".as_bytes()).unwrap(),
+            "salt".as_bytes(),
+            1,
+            too_long,
+            ShaVariantOption::SHA384,
+        ).is_err());
     }
 
     #[test]
     fn dklen_too_high_sha512() {
         let too_long = ((2_u64.pow(32) - 1) * 64 as u64) as usize + 1;
 
-        let dk = Pbkdf2 {
-            password: "password".as_bytes().to_vec(),
-            salt: "salt".as_bytes().to_vec(),
-            iterations: 1,
-            dklen: too_long,
-            hmac: ShaVariantOption::SHA512,
-        };
-
-        assert!(dk.derive_key().is_err());
+        assert!(Pbkdf2::new(
+            &Password::from_slice("password".as_bytes()).unwrap(),
+            "salt".as_bytes(),
+            1,
+            too_long,
+            ShaVariantOption::SHA512,
+        ).is_err());
     }
 
     #[test]
     fn zero_iterations_err() {
-        let dk = Pbkdf2 {
-            password: "password".as_bytes().to_vec(),
-            salt: "salt".as_bytes().to_vec(),
-            iterations: 0,
-            dklen: 15,
-            hmac: ShaVariantOption::SHA256,
-        };
-
-        assert!(dk.derive_key().is_err());
+        assert!(Pbkdf2::new(
+            &Password::from_slice("password".as_bytes()).unwrap(),
+            "salt".as_bytes(),
+            0,
+            15,
+            ShaVariantOption::SHA256,
+        ).is_err());
     }
 
     #[test]
     fn zero_dklen_err() {
-        let dk = Pbkdf2 {
-            password: "password".as_bytes().to_vec(),
-            salt: "salt".as_bytes().to_vec(),
-            iterations: 2,
-            dklen: 0,
-            hmac: ShaVariantOption::SHA256,
-        };
-
-        assert!(dk.derive_key().is_err());
+        assert!(Pbkdf2::new(
+            &Password::from_slice("password".as_bytes()).unwrap(),
+            "salt".as_bytes(),
+            2,
+            0,
+            ShaVariantOption::SHA256,
+        ).is_err());
     }
 
     #[test]
     fn verify_true() {
-        let dk = Pbkdf2 {
-            password: "pass\0word".as_bytes().to_vec(),
-            salt: "sa\0lt".as_bytes().to_vec(),
-            iterations: 4096,
-            dklen: 16,
-            hmac: ShaVariantOption::SHA512,
-        };
+        let dk = Pbkdf2::new(
+            &Password::from_slice("pass\0word".as_bytes()).unwrap(),
+            "sa\0lt".as_bytes(),
+            4096,
+            16,
+            ShaVariantOption::SHA512,
+        ).unwrap();
 
         let expected_dk = decode("9d9e9c4cd21fe4be24d5b8244c759665").unwrap();
 
@@ -304,13 +846,13 @@ mod test {
     #[test]
     fn verify_false_wrong_salt() {
         // Salt value differs between this and the previous test case
-        let dk = Pbkdf2 {
-            password: "pass\0word".as_bytes().to_vec(),
-            salt: "".as_bytes().to_vec(),
-            iterations: 4096,
-            dklen: 16,
-            hmac: ShaVariantOption::SHA512,
-        };
+        let dk = Pbkdf2::new(
+            &Password::from_slice("pass\0word".as_bytes()).unwrap(),
+            "".as_bytes(),
+            4096,
+            16,
+            ShaVariantOption::SHA512,
+        ).unwrap();
 
         let expected_dk = decode("9d9e9c4cd21fe4be24d5b8244c759665").unwrap();
 
@@ -319,13 +861,13 @@ mod test {
 
     #[test]
     fn verify_false_wrong_password() {
-        let dk = Pbkdf2 {
-            password: "none".as_bytes().to_vec(),
-            salt: "sa\0lt".as_bytes().to_vec(),
-            iterations: 4096,
-            dklen: 16,
-            hmac: ShaVariantOption::SHA512,
-        };
+        let dk = Pbkdf2::new(
+            &Password::from_slice("none".as_bytes()).unwrap(),
+            "sa\0lt".as_bytes(),
+            4096,
+            16,
+            ShaVariantOption::SHA512,
+        ).unwrap();
 
         let expected_dk = decode("9d9e9c4cd21fe4be24d5b8244c759665").unwrap();
 
@@ -335,13 +877,13 @@ mod test {
     #[test]
     fn verify_diff_dklen_error() {
         // Different dklen than expected dk
-        let dk = Pbkdf2 {
-            password: "pass\0word".as_bytes().to_vec(),
-            salt: "sa\0lt".as_bytes().to_vec(),
-            iterations: 4096,
-            dklen: 32,
-            hmac: ShaVariantOption::SHA512,
-        };
+        let dk = Pbkdf2::new(
+            &Password::from_slice("pass\0word".as_bytes()).unwrap(),
+            "sa\0lt".as_bytes(),
+            4096,
+            32,
+            ShaVariantOption::SHA512,
+        ).unwrap();
 
         let expected_dk = decode("9d9e9c4cd21fe4be24d5b8244c759665").unwrap();
 
@@ -350,13 +892,13 @@ mod test {
 
     #[test]
     fn verify_diff_iter_error() {
-        let dk = Pbkdf2 {
-            password: "pass\0word".as_bytes().to_vec(),
-            salt: "sa\0lt".as_bytes().to_vec(),
-            iterations: 800,
-            dklen: 16,
-            hmac: ShaVariantOption::SHA512,
-        };
+        let dk = Pbkdf2::new(
+            &Password::from_slice("pass\0word".as_bytes()).unwrap(),
+            "sa\0lt".as_bytes(),
+            800,
+            16,
+            ShaVariantOption::SHA512,
+        ).unwrap();
 
         let expected_dk = decode("9d9e9c4cd21fe4be24d5b8244c759665").unwrap();
 
@@ -0,0 +1,362 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::{errors::*, util};
+use endian::{read_u32_le, write_u32_le};
+use std::fmt;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Poly1305 one-time authenticator as specified in the
+/// [RFC 8439](https://tools.ietf.org/html/rfc8439).
+///
+/// All state is zeroed out on drop.
+pub struct Poly1305 {
+    r: [u32; 5],
+    h: [u32; 5],
+    pad: [u32; 4],
+    buffer: [u8; 16],
+    leftover: usize,
+}
+
+impl Drop for Poly1305 {
+    fn drop(&mut self) {
+        self.r.zeroize();
+        self.h.zeroize();
+        self.pad.zeroize();
+        self.buffer.zeroize()
+    }
+}
+
+impl ZeroizeOnDrop for Poly1305 {}
+
+impl fmt::Debug for Poly1305 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Poly1305 {{ r: ***, h: ***, pad: ***, buffer: ***, leftover: {} }}",
+            self.leftover
+        )
+    }
+}
+
+/// Poly1305 one-time authenticator as specified in the
+/// [RFC 8439](https://tools.ietf.org/html/rfc8439).
+///
+/// # Parameters:
+/// - `one_time_key`: The 32 byte one-time key
+/// - `data`: Data to be authenticated
+///
+/// See [RFC](https://tools.ietf.org/html/rfc8439#section-2.5) for more information.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the `one_time_key` is not 32 bytes
+///
+/// # Security:
+/// A key must never be used to authenticate more than one message. Poly1305 is meant to be
+/// used with a key that is derived from a stream cipher, such as ChaCha20, for each message.
+///
+/// # Example:
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::hazardous::poly1305::Poly1305;
+/// use orion::core::util::gen_rand_key;
+///
+/// let one_time_key = gen_rand_key(32).unwrap();
+///
+/// let mut poly = Poly1305::init(&one_time_key).unwrap();
+/// poly.update("Some ".as_bytes());
+/// poly.update("message.".as_bytes());
+/// let tag = poly.finalize();
+///
+/// let mut poly = Poly1305::init(&one_time_key).unwrap();
+/// poly.update("Some message.".as_bytes());
+/// assert_eq!(poly.verify(&tag).unwrap(), true);
+/// # }
+/// ```
+impl Poly1305 {
+    /// Initialize a Poly1305 state with a one-time key.
+    pub fn init(one_time_key: &[u8]) -> Result<Self, UnknownCryptoError> {
+        if one_time_key.len() != 32 {
+            return Err(UnknownCryptoError);
+        }
+
+        // Clamp r and split it into 26-bit limbs
+        let r = [
+            read_u32_le(&one_time_key[0..4]) & 0x3ff_ffff,
+            (read_u32_le(&one_time_key[3..7]) >> 2) & 0x3ff_ff03,
+            (read_u32_le(&one_time_key[6..10]) >> 4) & 0x3ff_c0ff,
+            (read_u32_le(&one_time_key[9..13]) >> 6) & 0x3f0_3fff,
+            (read_u32_le(&one_time_key[12..16]) >> 8) & 0x00f_ffff,
+        ];
+
+        let pad = [
+            read_u32_le(&one_time_key[16..20]),
+            read_u32_le(&one_time_key[20..24]),
+            read_u32_le(&one_time_key[24..28]),
+            read_u32_le(&one_time_key[28..32]),
+        ];
+
+        Ok(Poly1305 {
+            r,
+            h: [0u32; 5],
+            pad,
+            buffer: [0u8; 16],
+            leftover: 0,
+        })
+    }
+
+    /// Process a single 16 byte block. `hibit` is 1 << 24 for full blocks and 0 for the
+    /// final padded block.
+    fn process_block(&mut self, block: &[u8], hibit: u32) {
+        let r0 = u64::from(self.r[0]);
+        let r1 = u64::from(self.r[1]);
+        let r2 = u64::from(self.r[2]);
+        let r3 = u64::from(self.r[3]);
+        let r4 = u64::from(self.r[4]);
+
+        let s1 = r1 * 5;
+        let s2 = r2 * 5;
+        let s3 = r3 * 5;
+        let s4 = r4 * 5;
+
+        let h0 = u64::from(self.h[0] + (read_u32_le(&block[0..4]) & 0x3ff_ffff));
+        let h1 = u64::from(self.h[1] + ((read_u32_le(&block[3..7]) >> 2) & 0x3ff_ffff));
+        let h2 = u64::from(self.h[2] + ((read_u32_le(&block[6..10]) >> 4) & 0x3ff_ffff));
+        let h3 = u64::from(self.h[3] + ((read_u32_le(&block[9..13]) >> 6) & 0x3ff_ffff));
+        let h4 = u64::from(self.h[4] + ((read_u32_le(&block[12..16]) >> 8) | hibit));
+
+        let d0 = (h0 * r0) + (h1 * s4) + (h2 * s3) + (h3 * s2) + (h4 * s1);
+        let mut d1 = (h0 * r1) + (h1 * r0) + (h2 * s4) + (h3 * s3) + (h4 * s2);
+        let mut d2 = (h0 * r2) + (h1 * r1) + (h2 * r0) + (h3 * s4) + (h4 * s3);
+        let mut d3 = (h0 * r3) + (h1 * r2) + (h2 * r1) + (h3 * r0) + (h4 * s4);
+        let mut d4 = (h0 * r4) + (h1 * r3) + (h2 * r2) + (h3 * r1) + (h4 * r0);
+
+        // Partial reduction mod 2^130 - 5
+        let mut c = d0 >> 26;
+        self.h[0] = (d0 as u32) & 0x3ff_ffff;
+        d1 += c;
+        c = d1 >> 26;
+        self.h[1] = (d1 as u32) & 0x3ff_ffff;
+        d2 += c;
+        c = d2 >> 26;
+        self.h[2] = (d2 as u32) & 0x3ff_ffff;
+        d3 += c;
+        c = d3 >> 26;
+        self.h[3] = (d3 as u32) & 0x3ff_ffff;
+        d4 += c;
+        c = d4 >> 26;
+        self.h[4] = (d4 as u32) & 0x3ff_ffff;
+        self.h[0] += (c as u32) * 5;
+        let c = self.h[0] >> 26;
+        self.h[0] &= 0x3ff_ffff;
+        self.h[1] += c;
+    }
+
+    /// Update the state with `data`. Can be called multiple times.
+    pub fn update(&mut self, data: &[u8]) {
+        let mut data = data;
+
+        if self.leftover > 0 {
+            let want = ::std::cmp::min(16 - self.leftover, data.len());
+            self.buffer[self.leftover..self.leftover + want].copy_from_slice(&data[..want]);
+            self.leftover += want;
+            data = &data[want..];
+
+            if self.leftover < 16 {
+                return;
+            }
+
+            let block = self.buffer;
+            self.process_block(&block, 1 << 24);
+            self.leftover = 0;
+        }
+
+        while data.len() >= 16 {
+            self.process_block(&data[..16], 1 << 24);
+            data = &data[16..];
+        }
+
+        if !data.is_empty() {
+            self.buffer[..data.len()].copy_from_slice(data);
+            self.leftover = data.len();
+        }
+    }
+
+    /// Return the 16 byte Poly1305 tag for the data passed to `update`.
+    pub fn finalize(mut self) -> [u8; 16] {
+        if self.leftover > 0 {
+            let mut block = [0u8; 16];
+            block[..self.leftover].copy_from_slice(&self.buffer[..self.leftover]);
+            block[self.leftover] = 1;
+            self.process_block(&block, 0);
+        }
+
+        let mut h0 = self.h[0];
+        let mut h1 = self.h[1];
+        let mut h2 = self.h[2];
+        let mut h3 = self.h[3];
+        let mut h4 = self.h[4];
+
+        // Fully carry h
+        let mut c = h1 >> 26;
+        h1 &= 0x3ff_ffff;
+        h2 += c;
+        c = h2 >> 26;
+        h2 &= 0x3ff_ffff;
+        h3 += c;
+        c = h3 >> 26;
+        h3 &= 0x3ff_ffff;
+        h4 += c;
+        c = h4 >> 26;
+        h4 &= 0x3ff_ffff;
+        h0 += c * 5;
+        c = h0 >> 26;
+        h0 &= 0x3ff_ffff;
+        h1 += c;
+
+        // Compute h + -p
+        let mut g0 = h0.wrapping_add(5);
+        c = g0 >> 26;
+        g0 &= 0x3ff_ffff;
+        let mut g1 = h1.wrapping_add(c);
+        c = g1 >> 26;
+        g1 &= 0x3ff_ffff;
+        let mut g2 = h2.wrapping_add(c);
+        c = g2 >> 26;
+        g2 &= 0x3ff_ffff;
+        let mut g3 = h3.wrapping_add(c);
+        c = g3 >> 26;
+        g3 &= 0x3ff_ffff;
+        let mut g4 = h4.wrapping_add(c).wrapping_sub(1 << 26);
+
+        // Select h if h < p, or h + -p if h >= p, without branching
+        let mut mask = (g4 >> 31).wrapping_sub(1);
+        g0 &= mask;
+        g1 &= mask;
+        g2 &= mask;
+        g3 &= mask;
+        g4 &= mask;
+        mask = !mask;
+        h0 = (h0 & mask) | g0;
+        h1 = (h1 & mask) | g1;
+        h2 = (h2 & mask) | g2;
+        h3 = (h3 & mask) | g3;
+        h4 = (h4 & mask) | g4;
+
+        // h = h % 2^128
+        h0 |= h1 << 26;
+        h1 = (h1 >> 6) | (h2 << 20);
+        h2 = (h2 >> 12) | (h3 << 14);
+        h3 = (h3 >> 18) | (h4 << 8);
+
+        // tag = (h + pad) % 2^128
+        let mut f = u64::from(h0) + u64::from(self.pad[0]);
+        h0 = f as u32;
+        f = u64::from(h1) + u64::from(self.pad[1]) + (f >> 32);
+        h1 = f as u32;
+        f = u64::from(h2) + u64::from(self.pad[2]) + (f >> 32);
+        h2 = f as u32;
+        f = u64::from(h3) + u64::from(self.pad[3]) + (f >> 32);
+        h3 = f as u32;
+
+        let mut tag = [0u8; 16];
+        write_u32_le(&mut tag[0..4], h0);
+        write_u32_le(&mut tag[4..8], h1);
+        write_u32_le(&mut tag[8..12], h2);
+        write_u32_le(&mut tag[12..16], h3);
+
+        tag
+    }
+
+    /// Verify a Poly1305 tag by finalizing the current state and comparing the result to
+    /// `expected`. Comparison is done in constant time.
+    pub fn verify(self, expected: &[u8]) -> Result<bool, ValidationCryptoError> {
+        let own_tag = self.finalize();
+
+        if util::compare_ct(&own_tag, expected).is_err() {
+            Err(ValidationCryptoError)
+        } else {
+            Ok(true)
+        }
+    }
+}
+
+/// One-shot Poly1305. Returns the 16 byte tag for `data` under `one_time_key`.
+pub fn poly1305(one_time_key: &[u8], data: &[u8]) -> Result<[u8; 16], UnknownCryptoError> {
+    let mut poly = Poly1305::init(one_time_key)?;
+    poly.update(data);
+
+    Ok(poly.finalize())
+}
+
+#[cfg(test)]
+mod test {
+
+    use hazardous::poly1305::*;
+
+    #[test]
+    fn err_on_wrong_key_len() {
+        assert!(Poly1305::init(&[0u8; 31]).is_err());
+        assert!(Poly1305::init(&[0u8; 33]).is_err());
+        assert!(poly1305(&[0u8; 16], &[0u8; 16]).is_err());
+    }
+
+    #[test]
+    fn verify_err() {
+        let key = [0x42u8; 32];
+        let mut tag = poly1305(&key, "Some message.".as_bytes()).unwrap();
+        tag[0] ^= 1;
+
+        let mut poly = Poly1305::init(&key).unwrap();
+        poly.update("Some message.".as_bytes());
+
+        assert!(poly.verify(&tag).is_err());
+    }
+
+    #[test]
+    fn verify_err_len() {
+        let key = [0x42u8; 32];
+        let tag = poly1305(&key, "Some message.".as_bytes()).unwrap();
+
+        let mut poly = Poly1305::init(&key).unwrap();
+        poly.update("Some message.".as_bytes());
+
+        assert!(poly.verify(&tag[..15]).is_err());
+    }
+
+    #[test]
+    fn update_split_equals_one_shot() {
+        let key = [0x42u8; 32];
+        let data = [0x17u8; 100];
+        let expected = poly1305(&key, &data).unwrap();
+
+        for split in 0..data.len() {
+            let mut poly = Poly1305::init(&key).unwrap();
+            poly.update(&data[..split]);
+            poly.update(&data[split..]);
+
+            assert_eq!(poly.finalize(), expected);
+        }
+    }
+}
@@ -0,0 +1,212 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::options::ShaVariantOption;
+use core::types::SecretKey;
+use crypto_mac::generic_array::typenum::{U128, U136, U32, U48, U64, U72};
+use crypto_mac::generic_array::GenericArray;
+use crypto_mac::{InvalidKeyLength, Mac, MacResult};
+use digest::{ExtendableOutput, Input, XofReader};
+use hazardous::cshake::CShakeStream;
+use hazardous::hmac::{Hmac, HmacStream};
+
+macro_rules! impl_mac {
+    ($name:ident, $sha2:expr, $key_size:ty, $output_size:ty, $doc:expr) => {
+        #[doc = $doc]
+        ///
+        /// The `KeySize` is the blocksize of the hash function, but `new_varkey` accepts keys of
+        /// any non-zero length.
+        #[derive(Clone)]
+        pub struct $name {
+            initial: HmacStream,
+            state: HmacStream,
+        }
+
+        impl Mac for $name {
+            type OutputSize = $output_size;
+            type KeySize = $key_size;
+
+            fn new(key: &GenericArray<u8, Self::KeySize>) -> Self {
+                Self::new_varkey(key).unwrap()
+            }
+
+            fn new_varkey(key: &[u8]) -> Result<Self, InvalidKeyLength> {
+                let secret_key = match SecretKey::from_slice(key) {
+                    Ok(secret_key) => secret_key,
+                    Err(_) => return Err(InvalidKeyLength),
+                };
+                let initial = match Hmac::init(&secret_key, $sha2) {
+                    Ok(initial) => initial,
+                    Err(_) => return Err(InvalidKeyLength),
+                };
+
+                Ok($name {
+                    state: initial.clone(),
+                    initial,
+                })
+            }
+
+            fn input(&mut self, data: &[u8]) {
+                self.state.update(data);
+            }
+
+            fn reset(&mut self) {
+                self.state = self.initial.clone();
+            }
+
+            fn result(self) -> MacResult<Self::OutputSize> {
+                MacResult::new(GenericArray::clone_from_slice(self.state.finalize().as_bytes()))
+            }
+        }
+    };
+}
+
+impl_mac!(HmacSha256, ShaVariantOption::SHA256, U64, U32, "HMAC-SHA256 as a RustCrypto `Mac`.");
+impl_mac!(HmacSha384, ShaVariantOption::SHA384, U128, U48, "HMAC-SHA384 as a RustCrypto `Mac`.");
+impl_mac!(HmacSha512, ShaVariantOption::SHA512, U128, U64, "HMAC-SHA512 as a RustCrypto `Mac`.");
+impl_mac!(
+    HmacSha512Trunc256,
+    ShaVariantOption::SHA512Trunc256,
+    U128,
+    U32,
+    "HMAC-SHA512/256 as a RustCrypto `Mac`."
+);
+impl_mac!(
+    HmacSha3_256,
+    ShaVariantOption::SHA3_256,
+    U136,
+    U32,
+    "HMAC-SHA3-256 as a RustCrypto `Mac`."
+);
+impl_mac!(
+    HmacSha3_512,
+    ShaVariantOption::SHA3_512,
+    U72,
+    U64,
+    "HMAC-SHA3-512 as a RustCrypto `Mac`."
+);
+
+/// Input to a `CShakeStream` is absorbed, so it panics if `squeeze` has already been called.
+impl Input for CShakeStream {
+    fn input<B: AsRef<[u8]>>(&mut self, data: B) {
+        self.absorb(data.as_ref()).unwrap();
+    }
+}
+
+impl ExtendableOutput for CShakeStream {
+    type Reader = CShakeReader;
+
+    fn xof_result(self) -> CShakeReader {
+        CShakeReader { state: self }
+    }
+}
+
+/// Reader for the output of a `CShakeStream`, created with `ExtendableOutput::xof_result`.
+///
+/// # Example:
+/// ```
+/// extern crate digest;
+/// extern crate orion;
+///
+/// use digest::{ExtendableOutput, Input, XofReader};
+/// use orion::core::options::KeccakVariantOption;
+/// use orion::hazardous::cshake::CShake;
+///
+/// let mut cshake = CShake::init(b"", b"Email signature", KeccakVariantOption::KECCAK256).unwrap();
+/// cshake.input("Some message.");
+///
+/// let mut hash = [0u8; 32];
+/// cshake.xof_result().read(&mut hash);
+/// ```
+pub struct CShakeReader {
+    state: CShakeStream,
+}
+
+impl XofReader for CShakeReader {
+    fn read(&mut self, buffer: &mut [u8]) {
+        if !buffer.is_empty() {
+            buffer.copy_from_slice(&self.state.squeeze(buffer.len()).unwrap());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use core::options::{KeccakVariantOption, ShaVariantOption};
+    use core::types::SecretKey;
+    use crypto_mac::Mac;
+    use digest::{ExtendableOutput, Input, XofReader};
+    use hazardous::cshake::CShake;
+    use hazardous::hmac::Hmac;
+    use hazardous::rustcrypto::*;
+
+    #[test]
+    fn mac_matches_hmac() {
+        let key = SecretKey::from_slice(&[0x0b; 20]).unwrap();
+
+        let mut hmac = Hmac::init(&key, ShaVariantOption::SHA512).unwrap();
+        hmac.update(b"Hi There");
+        let expected = hmac.finalize();
+
+        let mut mac = HmacSha512::new_varkey(&[0x0b; 20]).unwrap();
+        mac.input(b"Hi ");
+        mac.input(b"There");
+
+        assert_eq!(&mac.clone().result().code()[..], expected.as_bytes());
+        assert!(mac.verify(expected.as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn mac_reset() {
+        let mut mac = HmacSha256::new_varkey(b"Jefe").unwrap();
+        mac.input(b"Some data");
+        let first = mac.result_reset();
+        mac.input(b"Some data");
+
+        assert_eq!(first.code(), mac.result().code());
+    }
+
+    #[test]
+    fn mac_err_on_empty_key() {
+        assert!(HmacSha3_256::new_varkey(&[]).is_err());
+    }
+
+    #[test]
+    fn xof_matches_cshake() {
+        let mut expected = CShake::init(b"", b"Custom", KeccakVariantOption::KECCAK512).unwrap();
+        expected.absorb(b"Some data").unwrap();
+        let expected = expected.squeeze(100).unwrap();
+
+        let mut cshake = CShake::init(b"", b"Custom", KeccakVariantOption::KECCAK512).unwrap();
+        cshake.input(b"Some ");
+        cshake.input(b"data");
+        let mut reader = cshake.xof_result();
+
+        let mut actual = [0u8; 100];
+        reader.read(&mut actual[..40]);
+        reader.read(&mut []);
+        reader.read(&mut actual[40..]);
+
+        assert_eq!(&actual[..], &expected[..]);
+    }
+}
@@ -0,0 +1,473 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+#[cfg(feature = "rng")]
+use core::util::OsRandom;
+use core::util::SecureRandom;
+use core::{errors::*, util};
+use endian::write_u64_le;
+use hazardous::chacha20;
+use hazardous::poly1305::Poly1305;
+use std::fmt;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Size of the header in bytes.
+pub const HEADERBYTES: usize = 24;
+/// Size of the secret key in bytes.
+pub const KEYBYTES: usize = 32;
+/// Size of the ciphertext expansion in bytes. That is one encrypted tag byte and 16 bytes
+/// for the Poly1305 tag.
+pub const ABYTES: usize = 17;
+/// Maximum size of a single message in bytes.
+pub const MESSAGEBYTES_MAX: u64 = 64 * ((1 << 32) - 2);
+
+/// Zero padding used for Poly1305 input.
+const PAD0: [u8; 16] = [0u8; 16];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// Tag attached to each message in a stream.
+pub enum StreamTag {
+    /// The most common tag, that doesn't add any information about the nature of the message.
+    MESSAGE,
+    /// Indicates that the message marks the end of a set of messages, but not the end of the
+    /// stream.
+    PUSH,
+    /// Forget the key used to encrypt this message and the previous ones, and derive a new
+    /// secret key.
+    REKEY,
+    /// Indicates that the message marks the end of the stream, and erases the secret key used
+    /// to encrypt the previous sequence.
+    FINAL,
+}
+
+impl StreamTag {
+    /// Return the byte representation of the tag.
+    fn as_byte(self) -> u8 {
+        match self {
+            StreamTag::MESSAGE => 0x00,
+            StreamTag::PUSH => 0x01,
+            StreamTag::REKEY => 0x02,
+            StreamTag::FINAL => 0x03,
+        }
+    }
+
+    /// Return the tag matching a byte, if any.
+    fn from_byte(byte: u8) -> Option<StreamTag> {
+        match byte {
+            0x00 => Some(StreamTag::MESSAGE),
+            0x01 => Some(StreamTag::PUSH),
+            0x02 => Some(StreamTag::REKEY),
+            0x03 => Some(StreamTag::FINAL),
+            _ => None,
+        }
+    }
+}
+
+/// Streaming authenticated encryption using XChaCha20 and Poly1305, compatible with
+/// libsodium's `crypto_secretstream_xchacha20poly1305`.
+///
+/// Fields `key` and `nonce` are zeroed out on drop.
+pub struct SecretStream {
+    key: [u8; 32],
+    nonce: [u8; 12],
+}
+
+impl Drop for SecretStream {
+    fn drop(&mut self) {
+        self.key.zeroize();
+        self.nonce.zeroize()
+    }
+}
+
+impl ZeroizeOnDrop for SecretStream {}
+
+impl fmt::Debug for SecretStream {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecretStream {{ key: ***, nonce: *** }}")
+    }
+}
+
+/// Streaming authenticated encryption using XChaCha20 and Poly1305, compatible with
+/// libsodium's [secretstream](https://download.libsodium.org/doc/secret-key_cryptography/secretstream).
+///
+/// # About:
+/// A stream is a sequence of messages, each encrypted with `push` and decrypted with `pull`
+/// in the same order. Each message is authenticated together with its position in the stream,
+/// so that messages that are reordered, duplicated or removed are detected. The end of the
+/// stream is marked by sending the last message with `StreamTag::FINAL`. The receiver must check
+/// that the stream ended with this tag, in order to detect truncation.
+///
+/// # Parameters:
+/// - `secret_key`: The secret key
+/// - `header`: The header created by `init_push`, which the receiver needs to `init_pull`
+/// - `plaintext`/`ciphertext`: A single message of the stream
+/// - `ad`: Optional additional data, that is authenticated but not encrypted
+/// - `tag`: The tag to be attached to the message
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the `secret_key` is not 32 bytes
+/// - The length of the `header` is not 24 bytes
+/// - The length of a message is greater than `MESSAGEBYTES_MAX`
+/// - A ciphertext is shorter than `ABYTES`
+/// - A ciphertext fails authentication
+///
+/// # Security:
+/// The secret key should always be generated using a CSPRNG. The `gen_rand_key` function
+/// in `util` can be used for this. The header is generated randomly by `init_push`, so the
+/// same key may be used for multiple streams.
+///
+/// # Example:
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::hazardous::secretstream::{SecretStream, StreamTag};
+/// use orion::core::util::gen_rand_key;
+///
+/// let key = gen_rand_key(32).unwrap();
+///
+/// let (mut sender, header) = SecretStream::init_push(&key).unwrap();
+/// let c1 = sender.push("First chunk".as_bytes(), &[], StreamTag::MESSAGE).unwrap();
+/// let c2 = sender.push("Last chunk".as_bytes(), &[], StreamTag::FINAL).unwrap();
+///
+/// let mut receiver = SecretStream::init_pull(&key, &header).unwrap();
+/// let (m1, tag1) = receiver.pull(&c1, &[]).unwrap();
+/// let (m2, tag2) = receiver.pull(&c2, &[]).unwrap();
+///
+/// assert_eq!(&m1[..], "First chunk".as_bytes());
+/// assert_eq!(tag1, StreamTag::MESSAGE);
+/// assert_eq!(&m2[..], "Last chunk".as_bytes());
+/// assert_eq!(tag2, StreamTag::FINAL);
+/// # }
+/// ```
+impl SecretStream {
+    #[cfg(feature = "rng")]
+    /// Initialize a stream for encryption. Returns the state and the header that must be
+    /// sent to the receiver.
+    pub fn init_push(secret_key: &[u8]) -> Result<(SecretStream, Vec<u8>), UnknownCryptoError> {
+        Self::init_push_with(&mut OsRandom, secret_key)
+    }
+
+    /// Initialize a stream for encryption, using `rng` to generate the header. See
+    /// `init_push`.
+    pub fn init_push_with<R: SecureRandom>(
+        rng: &mut R,
+        secret_key: &[u8],
+    ) -> Result<(SecretStream, Vec<u8>), UnknownCryptoError> {
+        let header = util::gen_rand_key_with(rng, HEADERBYTES)?;
+        let state = Self::init_pull(secret_key, &header)?;
+
+        Ok((state, header))
+    }
+
+    /// Initialize a stream for decryption, using the header created by `init_push`.
+    pub fn init_pull(secret_key: &[u8], header: &[u8]) -> Result<SecretStream, UnknownCryptoError> {
+        if secret_key.len() != KEYBYTES || header.len() != HEADERBYTES {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut subkey = chacha20::hchacha20(secret_key, &header[..16])?;
+        let mut state = SecretStream {
+            key: [0u8; 32],
+            nonce: [0u8; 12],
+        };
+        state.key.copy_from_slice(&subkey);
+        state.nonce[4..].copy_from_slice(&header[16..]);
+        state.reset_counter();
+
+        subkey.zeroize();
+
+        Ok(state)
+    }
+
+    /// Set the 32-bit counter in the first four bytes of the nonce to one.
+    fn reset_counter(&mut self) {
+        self.nonce[..4].copy_from_slice(&[1, 0, 0, 0]);
+    }
+
+    /// Increment the little-endian 32-bit counter in the first four bytes of the nonce.
+    /// Returns true if the counter wrapped around to zero.
+    fn increment_counter(&mut self) -> bool {
+        let mut carry: u16 = 1;
+        for byte in self.nonce[..4].iter_mut() {
+            carry += u16::from(*byte);
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+
+        self.nonce[..4] == [0, 0, 0, 0]
+    }
+
+    /// Explicitly derive a new key and nonce for the stream. This is done automatically
+    /// after a message tagged with `StreamTag::REKEY` and when the counter wraps around.
+    /// If used, `rekey` must be called at the same position in the stream by both the
+    /// sender and the receiver.
+    pub fn rekey(&mut self) {
+        let mut new_key_and_inonce = [0u8; 40];
+        new_key_and_inonce[..32].copy_from_slice(&self.key);
+        new_key_and_inonce[32..].copy_from_slice(&self.nonce[4..]);
+
+        // The key and nonce lengths are always valid here
+        let mut keystream = chacha20::keystream_block(&self.key, &self.nonce, 0).unwrap();
+        for (byte, ks_byte) in new_key_and_inonce.iter_mut().zip(keystream.iter()) {
+            *byte ^= *ks_byte;
+        }
+
+        self.key.copy_from_slice(&new_key_and_inonce[..32]);
+        self.nonce[4..].copy_from_slice(&new_key_and_inonce[32..]);
+        self.reset_counter();
+
+        new_key_and_inonce.zeroize();
+        keystream.zeroize();
+    }
+
+    /// Initialize the Poly1305 state for the current message and authenticate `ad`.
+    fn init_poly1305(&self, ad: &[u8]) -> Poly1305 {
+        let mut poly_key = chacha20::keystream_block(&self.key, &self.nonce, 0).unwrap();
+        let mut poly = Poly1305::init(&poly_key[..32]).unwrap();
+        poly_key.zeroize();
+
+        poly.update(ad);
+        poly.update(&PAD0[..((16 - (ad.len() % 16)) % 16)]);
+
+        poly
+    }
+
+    /// Authenticate the lengths of `ad` and the encrypted tag block plus the ciphertext.
+    fn finalize_poly1305(mut poly: Poly1305, ad_len: usize, ct_len: usize) -> [u8; 16] {
+        // libsodium computes this padding as (0x10 - 64 + mlen) & 0xf, so it is kept for
+        // compatibility
+        poly.update(&PAD0[..(ct_len & 0xf)]);
+
+        let mut len_block = [0u8; 16];
+        write_u64_le(&mut len_block[..8], ad_len as u64);
+        write_u64_le(&mut len_block[8..], 64 + ct_len as u64);
+        poly.update(&len_block);
+
+        poly.finalize()
+    }
+
+    /// Update the nonce with the MAC of the last message and rekey if needed.
+    fn advance(&mut self, mac: &[u8], tag: StreamTag) {
+        for (n, m) in self.nonce[4..].iter_mut().zip(mac.iter()) {
+            *n ^= *m;
+        }
+
+        let wrapped = self.increment_counter();
+        if wrapped || (tag.as_byte() & StreamTag::REKEY.as_byte()) != 0 {
+            self.rekey();
+        }
+    }
+
+    /// Encrypt and authenticate a single message of the stream. The output is `ABYTES`
+    /// longer than `plaintext`.
+    pub fn push(
+        &mut self,
+        plaintext: &[u8],
+        ad: &[u8],
+        tag: StreamTag,
+    ) -> Result<Vec<u8>, UnknownCryptoError> {
+        if plaintext.len() as u64 > MESSAGEBYTES_MAX {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut poly = self.init_poly1305(ad);
+
+        let mut tag_block = [0u8; 64];
+        tag_block[0] = tag.as_byte();
+        let mut tag_block = chacha20::encrypt(&self.key, &self.nonce, 1, &tag_block)?;
+        poly.update(&tag_block);
+
+        let ciphertext = chacha20::encrypt(&self.key, &self.nonce, 2, plaintext)?;
+        poly.update(&ciphertext);
+        let mac = Self::finalize_poly1305(poly, ad.len(), ciphertext.len());
+
+        let mut out = Vec::with_capacity(plaintext.len() + ABYTES);
+        out.push(tag_block[0]);
+        out.extend_from_slice(&ciphertext);
+        out.extend_from_slice(&mac);
+
+        tag_block.zeroize();
+        self.advance(&mac, tag);
+
+        Ok(out)
+    }
+
+    /// Verify and decrypt a single message of the stream. Returns the plaintext together
+    /// with the tag that was attached to it.
+    pub fn pull(
+        &mut self,
+        ciphertext: &[u8],
+        ad: &[u8],
+    ) -> Result<(Vec<u8>, StreamTag), ValidationCryptoError> {
+        if ciphertext.len() < ABYTES || (ciphertext.len() - ABYTES) as u64 > MESSAGEBYTES_MAX {
+            return Err(ValidationCryptoError);
+        }
+
+        let ct_len = ciphertext.len() - ABYTES;
+        let mut poly = self.init_poly1305(ad);
+
+        let mut tag_block = [0u8; 64];
+        tag_block[0] = ciphertext[0];
+        let mut tag_block = chacha20::decrypt(&self.key, &self.nonce, 1, &tag_block)?;
+        let tag_byte = tag_block[0];
+        tag_block[0] = ciphertext[0];
+        poly.update(&tag_block);
+        tag_block.zeroize();
+
+        let ct = &ciphertext[1..(1 + ct_len)];
+        poly.update(ct);
+        let mac = Self::finalize_poly1305(poly, ad.len(), ct_len);
+
+        if util::compare_ct(&mac, &ciphertext[(1 + ct_len)..]).is_err() {
+            return Err(ValidationCryptoError);
+        }
+
+        let tag = match StreamTag::from_byte(tag_byte) {
+            Some(tag) => tag,
+            None => return Err(ValidationCryptoError),
+        };
+
+        let plaintext = chacha20::decrypt(&self.key, &self.nonce, 2, ct)?;
+        self.advance(&mac, tag);
+
+        Ok((plaintext, tag))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use hazardous::secretstream::*;
+
+    fn stream_pair() -> (SecretStream, SecretStream) {
+        let key = [0x42u8; 32];
+        let (sender, header) = SecretStream::init_push_with(&mut FixedRandom(7), &key).unwrap();
+        let receiver = SecretStream::init_pull(&key, &header).unwrap();
+
+        (sender, receiver)
+    }
+
+    #[test]
+    fn err_on_wrong_key_len() {
+        assert!(SecretStream::init_push_with(&mut FixedRandom(7), &[0u8; 31]).is_err());
+        assert!(SecretStream::init_pull(&[0u8; 33], &[0u8; 24]).is_err());
+    }
+
+    struct FixedRandom(u8);
+
+    impl SecureRandom for FixedRandom {
+        fn fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), UnknownCryptoError> {
+            for byte in dst.iter_mut() {
+                *byte = self.0;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn init_push_with_custom_rng() {
+        let key = [0x42u8; 32];
+        let (mut sender, header) = SecretStream::init_push_with(&mut FixedRandom(7), &key).unwrap();
+        assert_eq!(header, vec![7u8; HEADERBYTES]);
+
+        let mut receiver = SecretStream::init_pull(&key, &header).unwrap();
+        let ciphertext = sender.push(b"Message", &[], StreamTag::FINAL).unwrap();
+        let (message, tag) = receiver.pull(&ciphertext, &[]).unwrap();
+        assert_eq!(&message[..], b"Message");
+        assert_eq!(tag, StreamTag::FINAL);
+    }
+
+    #[test]
+    fn err_on_wrong_header_len() {
+        assert!(SecretStream::init_pull(&[0u8; 32], &[0u8; 23]).is_err());
+        assert!(SecretStream::init_pull(&[0u8; 32], &[0u8; 25]).is_err());
+    }
+
+    #[test]
+    fn err_on_short_ciphertext() {
+        let (_, mut receiver) = stream_pair();
+
+        assert!(receiver.pull(&[0u8; ABYTES - 1], &[]).is_err());
+    }
+
+    #[test]
+    fn err_on_modified_ciphertext() {
+        let (mut sender, mut receiver) = stream_pair();
+        let mut ciphertext = sender.push(b"Some message.", &[], StreamTag::MESSAGE).unwrap();
+
+        for idx in 0..ciphertext.len() {
+            ciphertext[idx] ^= 1;
+            assert!(receiver.pull(&ciphertext, &[]).is_err());
+            ciphertext[idx] ^= 1;
+        }
+
+        assert!(receiver.pull(&ciphertext, &[]).is_ok());
+    }
+
+    #[test]
+    fn err_on_wrong_ad() {
+        let (mut sender, mut receiver) = stream_pair();
+        let ciphertext = sender.push(b"Some message.", b"ad", StreamTag::MESSAGE).unwrap();
+
+        assert!(receiver.pull(&ciphertext, b"da").is_err());
+        assert!(receiver.pull(&ciphertext, b"ad").is_ok());
+    }
+
+    #[test]
+    fn err_on_reordered_messages() {
+        let (mut sender, mut receiver) = stream_pair();
+        let c1 = sender.push(b"First", &[], StreamTag::MESSAGE).unwrap();
+        let c2 = sender.push(b"Second", &[], StreamTag::MESSAGE).unwrap();
+
+        assert!(receiver.pull(&c2, &[]).is_err());
+        assert!(receiver.pull(&c1, &[]).is_ok());
+        assert!(receiver.pull(&c1, &[]).is_err());
+        assert!(receiver.pull(&c2, &[]).is_ok());
+    }
+
+    #[test]
+    fn explicit_rekey() {
+        let (mut sender, mut receiver) = stream_pair();
+        let c1 = sender.push(b"First", &[], StreamTag::MESSAGE).unwrap();
+        sender.rekey();
+        let c2 = sender.push(b"Second", &[], StreamTag::FINAL).unwrap();
+
+        assert!(receiver.pull(&c1, &[]).is_ok());
+        assert!(receiver.pull(&c2, &[]).is_err());
+        receiver.rekey();
+        assert_eq!(receiver.pull(&c2, &[]).unwrap().1, StreamTag::FINAL);
+    }
+
+    #[test]
+    fn rekey_on_counter_wrap() {
+        let (mut sender, _) = stream_pair();
+        sender.nonce[..4].copy_from_slice(&[0xff, 0xff, 0xff, 0xff]);
+        let key_before = sender.key;
+
+        sender.push(b"", &[], StreamTag::MESSAGE).unwrap();
+
+        assert_ne!(sender.key, key_before);
+        assert_eq!(&sender.nonce[..4], &[1, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,201 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! The encoding functions `left_encode`, `right_encode` and `encode_string`, as specified in
+//! the [NIST SP 800-185](https://csrc.nist.gov/publications/detail/sp/800-185/final).
+//!
+//! These are the building blocks of cSHAKE, KMAC, TupleHash and ParallelHash. They make
+//! inputs of variable length unambiguous, so that different sequences of inputs are never
+//! absorbed as the same string.
+//!
+//! # Example:
+//! ```
+//! use orion::hazardous::sp800_185;
+//!
+//! let mut buf = [0u8; 9];
+//! assert_eq!(sp800_185::left_encode(256, &mut buf), &[2, 1, 0]);
+//! assert_eq!(sp800_185::right_encode(256, &mut buf), &[1, 0, 2]);
+//! assert_eq!(sp800_185::encode_string(b"abc").unwrap(), &[1, 24, b'a', b'b', b'c']);
+//! ```
+
+use core::errors::*;
+
+/// Write `x` big-endian into the last 8 bytes of `buf`, and return the number of bytes
+/// needed to encode it. That is at least 1, as 0 is encoded as a single zero byte.
+fn write_minimal(x: u64, buf: &mut [u8; 9]) -> usize {
+    *buf = [0u8; 9];
+    buf[1..].copy_from_slice(&x.to_be_bytes());
+
+    let leading_zeroes = buf[1..].iter().take_while(|byte| **byte == 0).count();
+    if leading_zeroes == 8 {
+        1
+    } else {
+        8 - leading_zeroes
+    }
+}
+
+/// The left_encode function as specified in the NIST SP 800-185: the number of bytes of `x`,
+/// followed by `x` big-endian in as few bytes as possible.
+/// The encoding is written into `buf`, and the used part of it is returned.
+pub fn left_encode(x: u64, buf: &mut [u8; 9]) -> &[u8] {
+    let n = write_minimal(x, buf);
+    buf[8 - n] = n as u8;
+
+    &buf[8 - n..]
+}
+
+/// The right_encode function as specified in the NIST SP 800-185: `x` big-endian in as few
+/// bytes as possible, followed by the number of bytes of `x`.
+/// The encoding is written into `buf`, and the used part of it is returned.
+pub fn right_encode(x: u64, buf: &mut [u8; 9]) -> &[u8] {
+    let n = write_minimal(x, buf);
+    buf.copy_within(9 - n.., 8 - n);
+    buf[8] = n as u8;
+
+    &buf[8 - n..]
+}
+
+/// The encode_string function as specified in the NIST SP 800-185: `left_encode` of the
+/// length of `s` in bits, followed by `s`.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of `s` in bits does not fit in a `u64`
+pub fn encode_string(s: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+    let bits = (s.len() as u64)
+        .checked_mul(8)
+        .ok_or(UnknownCryptoError)?;
+
+    let mut buf = [0u8; 9];
+    let mut encoded = left_encode(bits, &mut buf).to_vec();
+    encoded.extend_from_slice(s);
+
+    Ok(encoded)
+}
+
+#[cfg(test)]
+mod test {
+
+    use hazardous::sp800_185::*;
+
+    /// Straightforward encoding of `x` in the fewest big-endian bytes, at least one.
+    fn minimal_bytes(x: u64) -> Vec<u8> {
+        let mut bytes = vec![];
+        let mut rest = x;
+        loop {
+            bytes.insert(0, rest as u8);
+            rest >>= 8;
+            if rest == 0 {
+                return bytes;
+            }
+        }
+    }
+
+    #[test]
+    fn test_left_encode() {
+        let mut buf = [0u8; 9];
+
+        assert_eq!(
+            left_encode(u64::max_value(), &mut buf),
+            &[8, 255, 255, 255, 255, 255, 255, 255, 255]
+        );
+        assert_eq!(left_encode(32, &mut buf), &[1, 32]);
+        assert_eq!(left_encode(255, &mut buf), &[1, 255]);
+        // The buffer is cleared, so the output does not depend on earlier calls
+        assert_eq!(left_encode(0, &mut buf), &[1, 0]);
+        assert_eq!(left_encode(64, &mut buf), &[1, 64]);
+        assert_eq!(left_encode(256, &mut buf), &[2, 1, 0]);
+        assert_eq!(left_encode(168, &mut buf), &[1, 168]);
+        assert_eq!(left_encode(136, &mut buf), &[1, 136]);
+    }
+
+    #[test]
+    fn test_right_encode() {
+        let mut buf = [0u8; 9];
+
+        assert_eq!(
+            right_encode(u64::max_value(), &mut buf),
+            &[255, 255, 255, 255, 255, 255, 255, 255, 8]
+        );
+        assert_eq!(right_encode(0, &mut buf), &[0, 1]);
+        assert_eq!(right_encode(255, &mut buf), &[255, 1]);
+        assert_eq!(right_encode(256, &mut buf), &[1, 0, 2]);
+        // The buffer is cleared, so the output does not depend on earlier calls
+        assert_eq!(right_encode(1, &mut buf), &[1, 1]);
+    }
+
+    #[test]
+    fn encode_boundaries() {
+        let mut buf = [0u8; 9];
+
+        // Every power of two, and the values on both sides of it, covers each encoded length
+        // and each byte boundary
+        let mut values = vec![0u64, u64::max_value()];
+        for shift in 0..64 {
+            let power = 1u64 << shift;
+            values.extend_from_slice(&[power - 1, power, power + 1]);
+        }
+
+        for &x in values.iter() {
+            let bytes = minimal_bytes(x);
+            let n = bytes.len() as u8;
+
+            let mut expected_left = vec![n];
+            expected_left.extend_from_slice(&bytes);
+            assert_eq!(left_encode(x, &mut buf), &expected_left[..]);
+
+            let mut expected_right = bytes.clone();
+            expected_right.push(n);
+            assert_eq!(right_encode(x, &mut buf), &expected_right[..]);
+        }
+    }
+
+    #[test]
+    fn encode_every_byte() {
+        let mut buf = [0u8; 9];
+
+        // Each byte value in each position of an 8 byte encoding
+        for position in 0..8 {
+            for byte in 0..=255u64 {
+                let x = (byte << (8 * position)) | (1 << 63);
+                let mut expected = vec![8u8];
+                expected.extend_from_slice(&x.to_be_bytes());
+                assert_eq!(left_encode(x, &mut buf), &expected[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_string() {
+        assert_eq!(encode_string(b"").unwrap(), &[1, 0]);
+        assert_eq!(encode_string(b"abc").unwrap(), &[1, 24, b'a', b'b', b'c']);
+
+        let long = [0x61; 32];
+        let encoded = encode_string(&long).unwrap();
+        assert_eq!(encoded[..3], [2, 1, 0]);
+        assert_eq!(encoded[3..], long[..]);
+
+        let encoded = encode_string(b"Email Signature").unwrap();
+        assert_eq!(encoded[..2], [1, 120]);
+        assert_eq!(encoded[2..], b"Email Signature"[..]);
+    }
+}
@@ -0,0 +1,244 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::types::Nonce;
+use core::{errors::*, util};
+use endian::write_u64_le;
+use hazardous::chacha20;
+use hazardous::poly1305::Poly1305;
+use zeroize::Zeroize;
+
+/// Size of the Poly1305 tag in bytes.
+pub const TAGBYTES: usize = 16;
+
+/// Zero padding used for Poly1305 input.
+const PAD0: [u8; 16] = [0u8; 16];
+
+/// Derive the ChaCha20 subkey and nonce from the 24 byte XChaCha20 nonce.
+fn subkey_and_nonce(
+    secret_key: &[u8],
+    nonce: &Nonce,
+) -> Result<(Vec<u8>, [u8; 12]), UnknownCryptoError> {
+    let nonce = nonce.as_bytes();
+    if nonce.len() != 24 {
+        return Err(UnknownCryptoError);
+    }
+
+    let subkey = chacha20::hchacha20(secret_key, &nonce[..16])?;
+    let mut chacha_nonce = [0u8; 12];
+    chacha_nonce[4..].copy_from_slice(&nonce[16..]);
+
+    Ok((subkey, chacha_nonce))
+}
+
+/// Compute the Poly1305 tag over `ad` and `ciphertext`.
+fn poly1305_tag(
+    subkey: &[u8],
+    nonce: &[u8],
+    ciphertext: &[u8],
+    ad: &[u8],
+) -> Result<[u8; 16], UnknownCryptoError> {
+    let mut poly_key = chacha20::keystream_block(subkey, nonce, 0)?;
+    let mut poly = Poly1305::init(&poly_key[..32])?;
+    poly_key.zeroize();
+
+    poly.update(ad);
+    poly.update(&PAD0[..((16 - (ad.len() % 16)) % 16)]);
+    poly.update(ciphertext);
+    poly.update(&PAD0[..((16 - (ciphertext.len() % 16)) % 16)]);
+
+    let mut len_block = [0u8; 16];
+    write_u64_le(&mut len_block[..8], ad.len() as u64);
+    write_u64_le(&mut len_block[8..], ciphertext.len() as u64);
+    poly.update(&len_block);
+
+    Ok(poly.finalize())
+}
+
+/// AEAD_XChaCha20_Poly1305 as specified in the
+/// [draft RFC](https://tools.ietf.org/html/draft-irtf-cfrg-xchacha-01#section-2.3).
+///
+/// # About:
+/// The plaintext is encrypted with XChaCha20, starting at block counter 1, and a Poly1305 tag
+/// over `ad` and the ciphertext is appended. The output is therefore `TAGBYTES` longer than
+/// the plaintext.
+///
+/// # Parameters:
+/// - `secret_key`: The 32 byte secret key
+/// - `nonce`: The 24 byte nonce
+/// - `plaintext`: The data to be encrypted
+/// - `ad`: Optional additional data, that is authenticated but not encrypted
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of the `secret_key` is not 32 bytes
+/// - The length of the `nonce` is not 24 bytes
+/// - The `ciphertext` passed to `open` is shorter than `TAGBYTES`
+/// - The `ciphertext` passed to `open` fails authentication
+///
+/// # Security:
+/// The secret key should always be generated using a CSPRNG. A nonce must never be used more
+/// than once with the same key. The 24 byte nonce is large enough to be generated randomly
+/// with `Nonce::generate`.
+///
+/// # Example:
+/// ```
+/// # #[cfg(feature = "rng")]
+/// # {
+/// use orion::hazardous::xchacha20poly1305;
+/// use orion::core::types::Nonce;
+/// use orion::core::util::gen_rand_key;
+///
+/// let secret_key = gen_rand_key(32).unwrap();
+/// let nonce = Nonce::generate(24).unwrap();
+/// let message = "Data to protect".as_bytes();
+///
+/// let ciphertext = xchacha20poly1305::seal(&secret_key, &nonce, message, &[]).unwrap();
+/// let plaintext = xchacha20poly1305::open(&secret_key, &nonce, &ciphertext, &[]).unwrap();
+///
+/// assert_eq!(message, &plaintext[..]);
+/// # }
+/// ```
+pub fn seal(
+    secret_key: &[u8],
+    nonce: &Nonce,
+    plaintext: &[u8],
+    ad: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    let (mut subkey, chacha_nonce) = subkey_and_nonce(secret_key, nonce)?;
+
+    let res = chacha20::encrypt(&subkey, &chacha_nonce, 1, plaintext).and_then(|mut ct| {
+        let tag = poly1305_tag(&subkey, &chacha_nonce, &ct, ad)?;
+        ct.extend_from_slice(&tag);
+
+        Ok(ct)
+    });
+    subkey.zeroize();
+
+    res
+}
+
+/// Verify and decrypt data that was sealed with `xchacha20poly1305::seal`. The tag is verified,
+/// in constant time, before anything is decrypted. See `xchacha20poly1305::seal` for the
+/// parameters and exceptions.
+pub fn open(
+    secret_key: &[u8],
+    nonce: &Nonce,
+    ciphertext: &[u8],
+    ad: &[u8],
+) -> Result<Vec<u8>, ValidationCryptoError> {
+    if ciphertext.len() < TAGBYTES {
+        return Err(ValidationCryptoError);
+    }
+
+    let (mut subkey, chacha_nonce) = subkey_and_nonce(secret_key, nonce)?;
+    let (ct, tag) = ciphertext.split_at(ciphertext.len() - TAGBYTES);
+
+    let res = poly1305_tag(&subkey, &chacha_nonce, ct, ad)
+        .and_then(|expected| util::compare_ct(&expected, tag))
+        .and_then(|_| chacha20::decrypt(&subkey, &chacha_nonce, 1, ct));
+    subkey.zeroize();
+
+    Ok(res?)
+}
+
+#[cfg(test)]
+mod test {
+
+    extern crate hex;
+    use self::hex::decode;
+    use hazardous::xchacha20poly1305::*;
+
+    const KEY: [u8; 32] = [0x42; 32];
+
+    fn nonce() -> Nonce {
+        Nonce::from_slice(&[0x07; 24]).unwrap()
+    }
+
+    #[test]
+    fn test_known_answer() {
+        // Test vector from draft-irtf-cfrg-xchacha-01, Appendix A.3.1
+        let secret_key: Vec<u8> = (0x80u8..0xa0).collect();
+        let nonce = Nonce::from_slice(&(0x40u8..0x58).collect::<Vec<u8>>()).unwrap();
+        let ad = decode("50515253c0c1c2c3c4c5c6c7").unwrap();
+        let plaintext = "Ladies and Gentlemen of the class of '99: If I could offer you only one \
+                         tip for the future, sunscreen would be it."
+            .as_bytes();
+        let expected = decode(
+            "bd6d179d3e83d43b9576579493c0e939572a1700252bfaccbed2902c21396cbb\
+             731c7f1b0b4aa6440bf3a82f4eda7e39ae64c6708c54c216cb96b72e1213b452\
+             2f8c9ba40db5d945b11b69b982c1bb9e3f3fac2bc369488f76b2383565d3fff9\
+             21f9664c97637da9768812f615c68b13b52ec0875924c1c7987947deafd8780a\
+             cf49",
+        ).unwrap();
+
+        let actual = seal(&secret_key, &nonce, plaintext, &ad).unwrap();
+
+        assert_eq!(actual, expected);
+        assert_eq!(open(&secret_key, &nonce, &actual, &ad).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn seal_open_empty_plaintext() {
+        let ciphertext = seal(&KEY, &nonce(), b"", b"").unwrap();
+
+        assert_eq!(ciphertext.len(), TAGBYTES);
+        assert!(open(&KEY, &nonce(), &ciphertext, b"").unwrap().is_empty());
+    }
+
+    #[test]
+    fn err_on_modified_ciphertext() {
+        let mut ciphertext = seal(&KEY, &nonce(), b"Some message.", b"").unwrap();
+
+        for idx in 0..ciphertext.len() {
+            ciphertext[idx] ^= 1;
+            assert!(open(&KEY, &nonce(), &ciphertext, b"").is_err());
+            ciphertext[idx] ^= 1;
+        }
+
+        assert!(open(&KEY, &nonce(), &ciphertext, b"").is_ok());
+    }
+
+    #[test]
+    fn err_on_wrong_nonce_ad_or_key() {
+        let ciphertext = seal(&KEY, &nonce(), b"Some message.", b"ad").unwrap();
+        let other_nonce = Nonce::from_slice(&[0x08; 24]).unwrap();
+
+        assert!(open(&KEY, &other_nonce, &ciphertext, b"ad").is_err());
+        assert!(open(&[0x43; 32], &nonce(), &ciphertext, b"ad").is_err());
+        assert!(open(&KEY, &nonce(), &ciphertext, b"da").is_err());
+    }
+
+    #[test]
+    fn err_on_short_ciphertext() {
+        assert!(open(&KEY, &nonce(), &[0u8; 15], b"").is_err());
+    }
+
+    #[test]
+    fn err_on_invalid_key_or_nonce_len() {
+        let chacha_nonce = Nonce::from_slice(&[0u8; 12]).unwrap();
+
+        assert!(seal(&[0u8; 31], &nonce(), b"", b"").is_err());
+        assert!(seal(&KEY, &chacha_nonce, b"", b"").is_err());
+        assert!(open(&KEY, &chacha_nonce, &[0u8; 16], b"").is_err());
+    }
+}
@@ -22,22 +22,47 @@
 
 #![forbid(dead_code, warnings, unsafe_code, unused_imports)]
 
-extern crate byte_tools;
-extern crate clear_on_drop;
 extern crate constant_time_eq;
+#[cfg(feature = "rng")]
 extern crate rand;
 extern crate sha2;
 extern crate tiny_keccak;
+extern crate zeroize;
+
+#[cfg(feature = "rustcrypto")]
+extern crate crypto_mac;
+#[cfg(feature = "rustcrypto")]
+extern crate digest;
+#[cfg(all(feature = "rng", target_arch = "wasm32"))]
+extern crate getrandom;
+#[cfg(feature = "parallel")]
+extern crate rayon;
+#[cfg(feature = "mlock")]
+extern crate region;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "wasm")]
+extern crate wasm_bindgen;
 
 /// Core functionality such as generating a salt/key/IV/nonce.
 pub mod core;
 
+/// Endian conversions between integers and byte slices.
+mod endian;
+
 /// High-level API with safer defaults. Includes HMAC, HKDF, PBKDF2 and cSHAKE.
 pub mod default;
 
 /// Low-level API.
 pub mod hazardous;
 
+/// Re-exports of the commonly used default functions, options and types.
+pub mod prelude;
+
 /// Testing module for orion.
 #[cfg(test)]
 pub mod tests;
+
+/// JavaScript bindings for the default API, with wasm-bindgen.
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,60 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # Example:
+//! ```
+//! # #[cfg(feature = "rng")]
+//! # {
+//! use orion::prelude::*;
+//!
+//! let key = SecretKey::generate(64).unwrap();
+//! let msg = "Some message.".as_bytes();
+//!
+//! let expected_hmac = hmac(&key, msg).unwrap();
+//! assert!(hmac_verify(&expected_hmac, &key, msg).unwrap());
+//! # }
+//! ```
+
+pub use core::errors::{UnknownCryptoError, ValidationCryptoError};
+pub use core::options::{HashFunction, HashState, KeccakVariantOption, ShaVariantOption};
+pub use core::types::{CounterNonce, Nonce, Password, ProtectedBuffer, Salt, SecretKey, Tag};
+pub use core::util::{gen_rand_key_with, SecureRandom};
+pub use default::{HashStream, HmacStream, Keyring, Keystore};
+pub use default::{
+    commit_verify, cshake, cshake_verify, cshake_verify_with_length, cshake_with_length, decrypt,
+    decrypt_committing, decrypt_file, decrypt_with_password, derive_path, derive_subkey,
+    envelope_decrypt, hash, hash_file, hkdf, hkdf_from_slice, hkdf_verify, hkdf_verify_from_slice,
+    hkdf_verify_with_variant, hkdf_with_variant, hmac, hmac_file, hmac_from_slice, hmac_verify,
+    hmac_verify_from_slice, password_hash_verify, pbkdf2_verify, pbkdf2_verify_from_slice,
+    pbkdf2_verify_legacy, pbkdf2_with_iterations_verify, verify_commitment,
+};
+
+#[cfg(feature = "rng")]
+pub use core::util::{
+    gen_rand_key, gen_rand_key_32, gen_rand_key_64, gen_rand_keys, gen_rand_range, gen_rand_u32,
+    gen_rand_u64, secure_rand_bytes, OsRandom,
+};
+#[cfg(feature = "rng")]
+pub use default::{
+    commit, encrypt, encrypt_committing, encrypt_file, encrypt_with_password, envelope_encrypt,
+    gen_passphrase, gen_token, password_hash, pbkdf2, pbkdf2_from_slice, pbkdf2_with_iterations,
+};
@@ -0,0 +1,184 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Parser for the response (`.rsp`) files of the NIST Cryptographic Algorithm Validation
+// Program, so that the vector files can be used exactly as they are published.
+//
+// A file consists of sections, each started by one or more `[Name = value]` headers, which
+// contain test cases of `Name = value` lines separated by empty lines. Lines starting with
+// `#` are comments.
+
+extern crate hex;
+use self::hex::decode;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+/// A test case, made of the `Name = value` lines of one block.
+pub struct TestCase {
+    fields: HashMap<String, String>,
+}
+
+impl TestCase {
+    /// Return the value of `name`, if the test case has it.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields.get(name).map(|value| value.as_str())
+    }
+
+    /// Return the value of `name` as a string. Panics if the test case does not have it.
+    pub fn string(&self, name: &str) -> &str {
+        self.get(name)
+            .unwrap_or_else(|| panic!("missing field {}", name))
+    }
+
+    /// Return the value of `name` decoded from hex.
+    pub fn bytes(&self, name: &str) -> Vec<u8> {
+        decode(self.string(name)).unwrap()
+    }
+
+    /// Return the value of `name` as a decimal number.
+    pub fn number(&self, name: &str) -> usize {
+        self.string(name).parse().unwrap()
+    }
+}
+
+/// A section, with the parameters of its headers and the test cases that follow them.
+pub struct Section {
+    params: HashMap<String, String>,
+    pub cases: Vec<TestCase>,
+}
+
+impl Section {
+    /// Return the value of the header parameter `name`, if the section has it.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.get(name).map(|value| value.as_str())
+    }
+}
+
+/// Split a `Name = value` line, with or without spaces around the `=`.
+fn split_field(line: &str) -> (String, String) {
+    let mut parts = line.splitn(2, '=');
+    let name = parts.next().unwrap().trim();
+    let value = parts.next().unwrap_or("").trim();
+
+    (name.to_string(), value.to_string())
+}
+
+/// Parse the response file format from `reader`.
+pub fn parse<R: BufRead>(reader: R) -> Vec<Section> {
+    let mut sections: Vec<Section> = Vec::new();
+    let mut fields = HashMap::new();
+    // Consecutive headers belong to the same section
+    let mut in_headers = false;
+
+    for line in reader.lines() {
+        let line = line.unwrap();
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            if !fields.is_empty() {
+                if sections.is_empty() {
+                    sections.push(Section {
+                        params: HashMap::new(),
+                        cases: Vec::new(),
+                    });
+                }
+                let fields = ::std::mem::replace(&mut fields, HashMap::new());
+                sections.last_mut().unwrap().cases.push(TestCase { fields });
+            }
+            continue;
+        }
+
+        if line.starts_with('[') && line.ends_with(']') {
+            if !in_headers {
+                sections.push(Section {
+                    params: HashMap::new(),
+                    cases: Vec::new(),
+                });
+                in_headers = true;
+            }
+            let (name, value) = split_field(&line[1..line.len() - 1]);
+            sections.last_mut().unwrap().params.insert(name, value);
+            continue;
+        }
+
+        in_headers = false;
+        let (name, value) = split_field(line);
+        fields.insert(name, value);
+    }
+
+    if !fields.is_empty() {
+        if sections.is_empty() {
+            sections.push(Section {
+                params: HashMap::new(),
+                cases: Vec::new(),
+            });
+        }
+        sections.last_mut().unwrap().cases.push(TestCase { fields });
+    }
+
+    sections
+}
+
+/// Parse the response file at `path`, relative to the crate root.
+pub fn from_file(path: &str) -> Vec<Section> {
+    parse(BufReader::new(File::open(path).unwrap()))
+}
+
+#[test]
+fn parse_sections_and_cases() {
+    let rsp = b"#  CAVS 11.0\n\
+        #  Some information\n\
+        \n\
+        [L=32]\n\
+        [Mode = Test]\n\
+        \n\
+        Count = 0\n\
+        Key = 0102\n\
+        Msg =\n\
+        \n\
+        Count = 1\n\
+        Key = ff\n\
+        \n\
+        [L = 48]\n\
+        \n\
+        Seed = 00\n\
+        Len = 8";
+
+    let sections = parse(&rsp[..]);
+    assert_eq!(sections.len(), 2);
+
+    assert_eq!(sections[0].param("L"), Some("32"));
+    assert_eq!(sections[0].param("Mode"), Some("Test"));
+    assert_eq!(sections[0].cases.len(), 2);
+    assert_eq!(sections[0].cases[0].number("Count"), 0);
+    assert_eq!(sections[0].cases[0].bytes("Key"), vec![1, 2]);
+    assert_eq!(sections[0].cases[0].bytes("Msg"), Vec::<u8>::new());
+    assert_eq!(sections[0].cases[1].bytes("Key"), vec![0xff]);
+    assert_eq!(sections[0].cases[1].get("Msg"), None);
+
+    assert_eq!(sections[1].param("L"), Some("48"));
+    assert_eq!(sections[1].param("Mode"), None);
+    assert_eq!(sections[1].cases.len(), 1);
+    assert_eq!(sections[1].cases[0].string("Seed"), "00");
+    assert_eq!(sections[1].cases[0].number("Len"), 8);
+}
@@ -0,0 +1,252 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Property-based tests, which cross-check HMAC, HKDF, PBKDF2 and cSHAKE on random inputs
+// against the independent implementations from RustCrypto. They catch mistakes in the
+// parameters, such as key padding, output lengths and block counters, that fixed test vectors
+// may not reach.
+
+#[cfg(test)]
+mod cross_verification {
+
+    extern crate hkdf;
+    extern crate hmac;
+    extern crate pbkdf2;
+    extern crate quickcheck;
+    extern crate sha2;
+    extern crate sha3;
+    use self::hmac::Mac;
+    use self::quickcheck::QuickCheck;
+    use self::sha3::digest::{ExtendableOutput, Update, XofReader};
+    use core::options::{KeccakVariantOption, ShaVariantOption};
+    use core::types::{Password, SecretKey};
+    use hazardous::cshake::CShake;
+    use hazardous::hkdf::Hkdf;
+    use hazardous::hmac::{hmac_into, Hmac};
+    use hazardous::pbkdf2::{pbkdf2_into, Pbkdf2};
+
+    const VARIANTS: [ShaVariantOption; 6] = [
+        ShaVariantOption::SHA256,
+        ShaVariantOption::SHA384,
+        ShaVariantOption::SHA512,
+        ShaVariantOption::SHA512Trunc256,
+        ShaVariantOption::SHA3_256,
+        ShaVariantOption::SHA3_512,
+    ];
+
+    // Dispatch on a hash function type, so every RustCrypto reference is instantiated for each
+    // `ShaVariantOption`
+    macro_rules! with_hash {
+        ($sha2:expr, $hash:ident => $body:expr) => {
+            match $sha2 {
+                ShaVariantOption::SHA256 => {
+                    type $hash = sha2::Sha256;
+                    $body
+                }
+                ShaVariantOption::SHA384 => {
+                    type $hash = sha2::Sha384;
+                    $body
+                }
+                ShaVariantOption::SHA512 => {
+                    type $hash = sha2::Sha512;
+                    $body
+                }
+                ShaVariantOption::SHA512Trunc256 => {
+                    type $hash = sha2::Sha512_256;
+                    $body
+                }
+                ShaVariantOption::SHA3_256 => {
+                    type $hash = sha3::Sha3_256;
+                    $body
+                }
+                ShaVariantOption::SHA3_512 => {
+                    type $hash = sha3::Sha3_512;
+                    $body
+                }
+            }
+        };
+    }
+
+    fn variant(selector: u8) -> ShaVariantOption {
+        VARIANTS[selector as usize % VARIANTS.len()]
+    }
+
+    fn reference_hmac(sha2: ShaVariantOption, key: &[u8], data: &[u8]) -> Vec<u8> {
+        with_hash!(sha2, H => {
+            let mut mac = hmac::Hmac::<H>::new_from_slice(key).unwrap();
+            Mac::update(&mut mac, data);
+            mac.finalize().into_bytes().to_vec()
+        })
+    }
+
+    fn reference_hkdf(
+        sha2: ShaVariantOption,
+        salt: &[u8],
+        ikm: &[u8],
+        info: &[u8],
+        okm: &mut [u8],
+    ) {
+        with_hash!(sha2, H => {
+            hkdf::Hkdf::<H>::new(Some(salt), ikm).expand(info, okm).unwrap()
+        })
+    }
+
+    fn reference_pbkdf2(
+        sha2: ShaVariantOption,
+        password: &[u8],
+        salt: &[u8],
+        iterations: u32,
+        dk: &mut [u8],
+    ) {
+        with_hash!(sha2, H => pbkdf2::pbkdf2_hmac::<H>(password, salt, iterations, dk))
+    }
+
+    fn prop_hmac(selector: u8, key: Vec<u8>, data: Vec<u8>) -> bool {
+        let sha2 = variant(selector);
+        let expected = reference_hmac(sha2, &key, &data);
+
+        let mut actual = vec![0u8; sha2.output_size()];
+        hmac_into(&key, &data, sha2, &mut actual).unwrap();
+        if actual != expected {
+            return false;
+        }
+
+        match SecretKey::from_slice(&key) {
+            Ok(secret_key) => {
+                let one_shot = Hmac::new(&secret_key, &data, sha2).unwrap();
+                let mut stream = Hmac::init(&secret_key, sha2).unwrap();
+                for chunk in data.chunks(7) {
+                    stream.update(chunk);
+                }
+
+                one_shot.finalize().as_bytes() == &expected[..]
+                    && stream.finalize().as_bytes() == &expected[..]
+            }
+            Err(_) => key.is_empty(),
+        }
+    }
+
+    fn prop_hkdf(selector: u8, salt: Vec<u8>, ikm: Vec<u8>, info: Vec<u8>, length: u16) -> bool {
+        let sha2 = variant(selector);
+        let length = 1 + length as usize % (255 * sha2.output_size());
+        let mut expected = vec![0u8; length];
+        reference_hkdf(sha2, &salt, &ikm, &info, &mut expected);
+
+        let hkdf = Hkdf::new(&salt, &ikm, &info, length, sha2).unwrap();
+        let mut into = vec![0u8; length];
+        hkdf.derive_key_into(&mut into).unwrap();
+
+        hkdf.derive_key().unwrap() == expected && into == expected
+    }
+
+    fn prop_pbkdf2(
+        selector: u8,
+        password: Vec<u8>,
+        salt: Vec<u8>,
+        iterations: u8,
+        dklen: u8,
+    ) -> bool {
+        let sha2 = variant(selector);
+        let iterations = 1 + iterations as usize % 64;
+        let dklen = 1 + dklen as usize;
+        let mut expected = vec![0u8; dklen];
+        reference_pbkdf2(sha2, &password, &salt, iterations as u32, &mut expected);
+
+        let mut into = vec![0u8; dklen];
+        pbkdf2_into(&password, &salt, iterations, sha2, &mut into).unwrap();
+        if into != expected {
+            return false;
+        }
+
+        match Password::from_slice(&password) {
+            Ok(password) => {
+                let pbkdf2 = Pbkdf2::new(&password, &salt, iterations, dklen, sha2).unwrap();
+                pbkdf2.derive_key().unwrap() == expected
+            }
+            Err(_) => password.is_empty(),
+        }
+    }
+
+    fn prop_cshake(
+        keccak256: bool,
+        input: Vec<u8>,
+        name: Vec<u8>,
+        custom: Vec<u8>,
+        length: u16,
+    ) -> bool {
+        // cSHAKE with both strings empty is SHAKE, which is rejected by orion
+        if name.is_empty() && custom.is_empty() {
+            return CShake::new(&input, &name, &custom, 32, KeccakVariantOption::KECCAK256)
+                .is_err();
+        }
+
+        let length = 1 + length as usize % 1024;
+        let mut expected = vec![0u8; length];
+        let keccak = if keccak256 {
+            let mut reference = sha3::CShake128::from_core(
+                sha3::CShake128Core::new_with_function_name(&name, &custom),
+            );
+            reference.update(&input);
+            reference.finalize_xof().read(&mut expected);
+            KeccakVariantOption::KECCAK256
+        } else {
+            let mut reference = sha3::CShake256::from_core(
+                sha3::CShake256Core::new_with_function_name(&name, &custom),
+            );
+            reference.update(&input);
+            reference.finalize_xof().read(&mut expected);
+            KeccakVariantOption::KECCAK512
+        };
+
+        let cshake = CShake::new(&input, &name, &custom, length, keccak).unwrap();
+
+        cshake.finalize().unwrap() == expected
+    }
+
+    #[test]
+    fn hmac_equals_rustcrypto() {
+        QuickCheck::new()
+            .tests(500)
+            .quickcheck(prop_hmac as fn(u8, Vec<u8>, Vec<u8>) -> bool);
+    }
+
+    #[test]
+    fn hkdf_equals_rustcrypto() {
+        QuickCheck::new()
+            .tests(200)
+            .quickcheck(prop_hkdf as fn(u8, Vec<u8>, Vec<u8>, Vec<u8>, u16) -> bool);
+    }
+
+    #[test]
+    fn pbkdf2_equals_rustcrypto() {
+        QuickCheck::new()
+            .tests(200)
+            .quickcheck(prop_pbkdf2 as fn(u8, Vec<u8>, Vec<u8>, u8, u8) -> bool);
+    }
+
+    #[test]
+    fn cshake_equals_rustcrypto() {
+        QuickCheck::new()
+            .tests(300)
+            .quickcheck(prop_cshake as fn(bool, Vec<u8>, Vec<u8>, Vec<u8>, u16) -> bool);
+    }
+}
@@ -30,17 +30,18 @@ mod custom_test_vectors {
     extern crate hex;
     use self::hex::decode;
     use core::options::ShaVariantOption;
+    use core::types::Password;
     use hazardous::pbkdf2::Pbkdf2;
 
     #[test]
     fn sha256_test_case_1() {
-        let actual_dk = Pbkdf2 {
-            password: "password".as_bytes().to_vec(),
-            salt: "salt".as_bytes().to_vec(),
-            iterations: 1,
-            dklen: 20,
-            hmac: ShaVariantOption::SHA256,
-        };
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("password".as_bytes()).unwrap(),
+            "salt".as_bytes(),
+            1,
+            20,
+            ShaVariantOption::SHA256,
+        ).unwrap();
 
         let expected_dk = decode("120fb6cffcf8b32c43e7225256c4f837a86548c9").unwrap();
 
@@ -49,13 +50,13 @@ mod custom_test_vectors {
 
     #[test]
     fn sha256_test_case_2() {
-        let actual_dk = Pbkdf2 {
-            password: "password".as_bytes().to_vec(),
-            salt: "salt".as_bytes().to_vec(),
-            iterations: 2,
-            dklen: 20,
-            hmac: ShaVariantOption::SHA256,
-        };
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("password".as_bytes()).unwrap(),
+            "salt".as_bytes(),
+            2,
+            20,
+            ShaVariantOption::SHA256,
+        ).unwrap();
 
         let expected_dk = decode("ae4d0c95af6b46d32d0adff928f06dd02a303f8e").unwrap();
 
@@ -64,13 +65,13 @@ mod custom_test_vectors {
 
     #[test]
     fn sha256_test_case_3() {
-        let actual_dk = Pbkdf2 {
-            password: "password".as_bytes().to_vec(),
-            salt: "salt".as_bytes().to_vec(),
-            iterations: 4096,
-            dklen: 20,
-            hmac: ShaVariantOption::SHA256,
-        };
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("password".as_bytes()).unwrap(),
+            "salt".as_bytes(),
+            4096,
+            20,
+            ShaVariantOption::SHA256,
+        ).unwrap();
 
         let expected_dk = decode("c5e478d59288c841aa530db6845c4c8d962893a0").unwrap();
 
@@ -79,13 +80,13 @@ mod custom_test_vectors {
 
     #[test]
     fn sha256_test_case_4() {
-        let actual_dk = Pbkdf2 {
-            password: "passwordPASSWORDpassword".as_bytes().to_vec(),
-            salt: "saltSALTsaltSALTsaltSALTsaltSALTsalt".as_bytes().to_vec(),
-            iterations: 4096,
-            dklen: 25,
-            hmac: ShaVariantOption::SHA256,
-        };
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("passwordPASSWORDpassword".as_bytes()).unwrap(),
+            "saltSALTsaltSALTsaltSALTsaltSALTsalt".as_bytes(),
+            4096,
+            25,
+            ShaVariantOption::SHA256,
+        ).unwrap();
 
         let expected_dk = decode("348c89dbcbd32b2f32d814b8116e84cf2b17347ebc1800181c").unwrap();
 
@@ -94,13 +95,13 @@ mod custom_test_vectors {
 
     #[test]
     fn sha256_test_case_5() {
-        let actual_dk = Pbkdf2 {
-            password: "pass\0word".as_bytes().to_vec(),
-            salt: "sa\0lt".as_bytes().to_vec(),
-            iterations: 4096,
-            dklen: 16,
-            hmac: ShaVariantOption::SHA256,
-        };
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("pass\0word".as_bytes()).unwrap(),
+            "sa\0lt".as_bytes(),
+            4096,
+            16,
+            ShaVariantOption::SHA256,
+        ).unwrap();
 
         let expected_dk = decode("89b69d0516f829893c696226650a8687").unwrap();
 
@@ -109,13 +110,13 @@ mod custom_test_vectors {
 
     #[test]
     fn sha384_test_case_1() {
-        let actual_dk = Pbkdf2 {
-            password: "password".as_bytes().to_vec(),
-            salt: "salt".as_bytes().to_vec(),
-            iterations: 1,
-            dklen: 20,
-            hmac: ShaVariantOption::SHA384,
-        };
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("password".as_bytes()).unwrap(),
+            "salt".as_bytes(),
+            1,
+            20,
+            ShaVariantOption::SHA384,
+        ).unwrap();
 
         let expected_dk = decode("c0e14f06e49e32d73f9f52ddf1d0c5c719160923").unwrap();
 
@@ -124,13 +125,13 @@ mod custom_test_vectors {
 
     #[test]
     fn sha384_test_case_2() {
-        let actual_dk = Pbkdf2 {
-            password: "password".as_bytes().to_vec(),
-            salt: "salt".as_bytes().to_vec(),
-            iterations: 2,
-            dklen: 20,
-            hmac: ShaVariantOption::SHA384,
-        };
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("password".as_bytes()).unwrap(),
+            "salt".as_bytes(),
+            2,
+            20,
+            ShaVariantOption::SHA384,
+        ).unwrap();
 
         let expected_dk = decode("54f775c6d790f21930459162fc535dbf04a93918").unwrap();
 
@@ -139,13 +140,13 @@ mod custom_test_vectors {
 
     #[test]
     fn sha384_test_case_3() {
-        let actual_dk = Pbkdf2 {
-            password: "password".as_bytes().to_vec(),
-            salt: "salt".as_bytes().to_vec(),
-            iterations: 4096,
-            dklen: 20,
-            hmac: ShaVariantOption::SHA384,
-        };
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("password".as_bytes()).unwrap(),
+            "salt".as_bytes(),
+            4096,
+            20,
+            ShaVariantOption::SHA384,
+        ).unwrap();
 
         let expected_dk = decode("559726be38db125bc85ed7895f6e3cf574c7a01c").unwrap();
 
@@ -154,13 +155,13 @@ mod custom_test_vectors {
 
     #[test]
     fn sha384_test_case_4() {
-        let actual_dk = Pbkdf2 {
-            password: "passwordPASSWORDpassword".as_bytes().to_vec(),
-            salt: "saltSALTsaltSALTsaltSALTsaltSALTsalt".as_bytes().to_vec(),
-            iterations: 4096,
-            dklen: 25,
-            hmac: ShaVariantOption::SHA384,
-        };
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("passwordPASSWORDpassword".as_bytes()).unwrap(),
+            "saltSALTsaltSALTsaltSALTsaltSALTsalt".as_bytes(),
+            4096,
+            25,
+            ShaVariantOption::SHA384,
+        ).unwrap();
 
         let expected_dk = decode("819143ad66df9a552559b9e131c52ae6c5c1b0eed18f4d283b").unwrap();
 
@@ -169,13 +170,13 @@ mod custom_test_vectors {
 
     #[test]
     fn sha384_test_case_5() {
-        let actual_dk = Pbkdf2 {
-            password: "pass\0word".as_bytes().to_vec(),
-            salt: "sa\0lt".as_bytes().to_vec(),
-            iterations: 4096,
-            dklen: 16,
-            hmac: ShaVariantOption::SHA384,
-        };
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("pass\0word".as_bytes()).unwrap(),
+            "sa\0lt".as_bytes(),
+            4096,
+            16,
+            ShaVariantOption::SHA384,
+        ).unwrap();
 
         let expected_dk = decode("a3f00ac8657e095f8e0823d232fc60b3").unwrap();
 
@@ -184,13 +185,13 @@ mod custom_test_vectors {
 
     #[test]
     fn sha512_test_case_1() {
-        let actual_dk = Pbkdf2 {
-            password: "password".as_bytes().to_vec(),
-            salt: "salt".as_bytes().to_vec(),
-            iterations: 1,
-            dklen: 20,
-            hmac: ShaVariantOption::SHA512,
-        };
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("password".as_bytes()).unwrap(),
+            "salt".as_bytes(),
+            1,
+            20,
+            ShaVariantOption::SHA512,
+        ).unwrap();
 
         let expected_dk = decode("867f70cf1ade02cff3752599a3a53dc4af34c7a6").unwrap();
 
@@ -199,13 +200,13 @@ mod custom_test_vectors {
 
     #[test]
     fn sha512_test_case_2() {
-        let actual_dk = Pbkdf2 {
-            password: "password".as_bytes().to_vec(),
-            salt: "salt".as_bytes().to_vec(),
-            iterations: 2,
-            dklen: 20,
-            hmac: ShaVariantOption::SHA512,
-        };
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("password".as_bytes()).unwrap(),
+            "salt".as_bytes(),
+            2,
+            20,
+            ShaVariantOption::SHA512,
+        ).unwrap();
 
         let expected_dk = decode("e1d9c16aa681708a45f5c7c4e215ceb66e011a2e").unwrap();
 
@@ -214,13 +215,13 @@ mod custom_test_vectors {
 
     #[test]
     fn sha512_test_case_3() {
-        let actual_dk = Pbkdf2 {
-            password: "password".as_bytes().to_vec(),
-            salt: "salt".as_bytes().to_vec(),
-            iterations: 4096,
-            dklen: 20,
-            hmac: ShaVariantOption::SHA512,
-        };
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("password".as_bytes()).unwrap(),
+            "salt".as_bytes(),
+            4096,
+            20,
+            ShaVariantOption::SHA512,
+        ).unwrap();
 
         let expected_dk = decode("d197b1b33db0143e018b12f3d1d1479e6cdebdcc").unwrap();
 
@@ -229,13 +230,13 @@ mod custom_test_vectors {
 
     #[test]
     fn sha512_test_case_4() {
-        let actual_dk = Pbkdf2 {
-            password: "passwordPASSWORDpassword".as_bytes().to_vec(),
-            salt: "saltSALTsaltSALTsaltSALTsaltSALTsalt".as_bytes().to_vec(),
-            iterations: 4096,
-            dklen: 25,
-            hmac: ShaVariantOption::SHA512,
-        };
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("passwordPASSWORDpassword".as_bytes()).unwrap(),
+            "saltSALTsaltSALTsaltSALTsaltSALTsalt".as_bytes(),
+            4096,
+            25,
+            ShaVariantOption::SHA512,
+        ).unwrap();
 
         let expected_dk = decode("8c0511f4c6e597c6ac6315d8f0362e225f3c501495ba23b868").unwrap();
 
@@ -244,16 +245,166 @@ mod custom_test_vectors {
 
     #[test]
     fn sha512_test_case_5() {
-        let actual_dk = Pbkdf2 {
-            password: "pass\0word".as_bytes().to_vec(),
-            salt: "sa\0lt".as_bytes().to_vec(),
-            iterations: 4096,
-            dklen: 16,
-            hmac: ShaVariantOption::SHA512,
-        };
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("pass\0word".as_bytes()).unwrap(),
+            "sa\0lt".as_bytes(),
+            4096,
+            16,
+            ShaVariantOption::SHA512,
+        ).unwrap();
 
         let expected_dk = decode("9d9e9c4cd21fe4be24d5b8244c759665").unwrap();
 
         assert_eq!(expected_dk, actual_dk.derive_key().unwrap());
     }
+
+    #[test]
+    fn sha3_256_test_case_1() {
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("password".as_bytes()).unwrap(),
+            "salt".as_bytes(),
+            1,
+            20,
+            ShaVariantOption::SHA3_256,
+        ).unwrap();
+
+        let expected_dk = decode("94613f3ee2ea730e0b06754f3fc816d4f87c9be9").unwrap();
+
+        assert_eq!(expected_dk, actual_dk.derive_key().unwrap());
+    }
+
+    #[test]
+    fn sha3_256_test_case_2() {
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("password".as_bytes()).unwrap(),
+            "salt".as_bytes(),
+            2,
+            20,
+            ShaVariantOption::SHA3_256,
+        ).unwrap();
+
+        let expected_dk = decode("4c915baedd1773383e77fcfe38114ca7514010ad").unwrap();
+
+        assert_eq!(expected_dk, actual_dk.derive_key().unwrap());
+    }
+
+    #[test]
+    fn sha3_256_test_case_3() {
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("password".as_bytes()).unwrap(),
+            "salt".as_bytes(),
+            4096,
+            20,
+            ShaVariantOption::SHA3_256,
+        ).unwrap();
+
+        let expected_dk = decode("778b6e237a0f49621549ff70d218d2080756b9fb").unwrap();
+
+        assert_eq!(expected_dk, actual_dk.derive_key().unwrap());
+    }
+
+    #[test]
+    fn sha3_256_test_case_4() {
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("passwordPASSWORDpassword".as_bytes()).unwrap(),
+            "saltSALTsaltSALTsaltSALTsaltSALTsalt".as_bytes(),
+            4096,
+            25,
+            ShaVariantOption::SHA3_256,
+        ).unwrap();
+
+        let expected_dk = decode("7aef8f1ad8c7f12205334f624d4af9e2863121618f7a0b3209").unwrap();
+
+        assert_eq!(expected_dk, actual_dk.derive_key().unwrap());
+    }
+
+    #[test]
+    fn sha3_256_test_case_5() {
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("pass\0word".as_bytes()).unwrap(),
+            "sa\0lt".as_bytes(),
+            4096,
+            16,
+            ShaVariantOption::SHA3_256,
+        ).unwrap();
+
+        let expected_dk = decode("98e5503130ffdd69603da78cbb12e9be").unwrap();
+
+        assert_eq!(expected_dk, actual_dk.derive_key().unwrap());
+    }
+
+    #[test]
+    fn sha3_512_test_case_1() {
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("password".as_bytes()).unwrap(),
+            "salt".as_bytes(),
+            1,
+            20,
+            ShaVariantOption::SHA3_512,
+        ).unwrap();
+
+        let expected_dk = decode("f7a2684630ec0f81f23abbf606278deeaad1a350").unwrap();
+
+        assert_eq!(expected_dk, actual_dk.derive_key().unwrap());
+    }
+
+    #[test]
+    fn sha3_512_test_case_2() {
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("password".as_bytes()).unwrap(),
+            "salt".as_bytes(),
+            2,
+            20,
+            ShaVariantOption::SHA3_512,
+        ).unwrap();
+
+        let expected_dk = decode("d6824ab17801706ad465f3196eb80dde20378696").unwrap();
+
+        assert_eq!(expected_dk, actual_dk.derive_key().unwrap());
+    }
+
+    #[test]
+    fn sha3_512_test_case_3() {
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("password".as_bytes()).unwrap(),
+            "salt".as_bytes(),
+            4096,
+            20,
+            ShaVariantOption::SHA3_512,
+        ).unwrap();
+
+        let expected_dk = decode("2bfaf2d5ceb6d10f5e262cd902488cfd4489614e").unwrap();
+
+        assert_eq!(expected_dk, actual_dk.derive_key().unwrap());
+    }
+
+    #[test]
+    fn sha3_512_test_case_4() {
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("passwordPASSWORDpassword".as_bytes()).unwrap(),
+            "saltSALTsaltSALTsaltSALTsaltSALTsalt".as_bytes(),
+            4096,
+            25,
+            ShaVariantOption::SHA3_512,
+        ).unwrap();
+
+        let expected_dk = decode("d60791a4ed27195d813f35510351b9d1ff9ad4262153944609").unwrap();
+
+        assert_eq!(expected_dk, actual_dk.derive_key().unwrap());
+    }
+
+    #[test]
+    fn sha3_512_test_case_5() {
+        let actual_dk = Pbkdf2::new(
+            &Password::from_slice("pass\0word".as_bytes()).unwrap(),
+            "sa\0lt".as_bytes(),
+            4096,
+            16,
+            ShaVariantOption::SHA3_512,
+        ).unwrap();
+
+        let expected_dk = decode("c0da8018507821037c76801cccf3cc8a").unwrap();
+
+        assert_eq!(expected_dk, actual_dk.derive_key().unwrap());
+    }
 }
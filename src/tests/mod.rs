@@ -20,9 +20,18 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+/// Parser for NIST CAVP response files.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cavp;
+
 /// Test HMAC against NIST test vectors.
+#[cfg(not(target_arch = "wasm32"))]
 pub mod nist_hmac;
 
+/// Test SHA-2 against NIST test vectors.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod nist_sha;
+
 /// Test HMAC aginast RFC test vectors.
 pub mod rfc_hmac;
 
@@ -40,3 +49,31 @@ pub mod official_cshake;
 
 /// Test HMAC against IETF Draft test vectors.
 pub mod other_hmac;
+
+/// Test ChaCha20 against RFC test vectors.
+pub mod rfc_chacha20;
+
+/// Test Poly1305 against RFC test vectors.
+pub mod rfc_poly1305;
+
+/// Test secretstream against libsodium-compatible test vectors.
+pub mod other_secretstream;
+
+/// Test KangarooTwelve against official test vectors.
+pub mod official_kangarootwelve;
+
+/// Test HMAC, HKDF and XChaCha20Poly1305 against Wycheproof test vectors.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod wycheproof;
+
+/// Test HMAC, HKDF, PBKDF2 and cSHAKE against RustCrypto with random inputs.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cross_verification;
+
+/// Test that constant-time routines do not leak timing, in the style of dudect.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod timing;
+
+/// Test the high-level API, randomness and the JavaScript bindings on wasm32.
+#[cfg(target_arch = "wasm32")]
+pub mod wasm;
@@ -14,62 +14,52 @@
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
 // Testing against NIST CAVP HMACVS test vectors
-extern crate ring;
-use self::ring::{error, test};
+// from the unmodified `HMAC.rsp` response file. Only the sections for SHA-256, SHA-384 and
+// SHA-512 are used, including the test vectors with truncated MACs.
+
 use core::options::ShaVariantOption;
+use core::types::SecretKey;
+use core::util;
 use hazardous::hmac::Hmac;
-
-fn hmac_test_runner(
-    option: ShaVariantOption,
-    key: &[u8],
-    input: &[u8],
-    output: &[u8],
-    is_ok: bool,
-) -> Result<(), error::Unspecified> {
-    let hmac = Hmac {
-        secret_key: key.to_vec(),
-        data: input.to_vec(),
-        sha2: option,
-    };
-
-    let digest = hmac.finalize();
-
-    assert_eq!(is_ok, digest == output);
-
-    // To conform with the Result construction of compare functions
-    match is_ok {
-        true => {
-            assert_eq!(is_ok, hmac.verify(output).unwrap());
-        }
-        false => {
-            assert!(hmac.verify(output).is_err());
-        }
-    }
-
-    Ok(())
-}
+use tests::cavp;
 
 #[test]
 fn hmac_tests() {
-    test::from_file("src/tests/test_data/HMAC_fmt.rsp", |section, test_case| {
-        assert_eq!(section, "");
-        let digest_alg = test_case.consume_string("HMAC");
-        let key_value = test_case.consume_bytes("Key");
-        let mut input = test_case.consume_bytes("Input");
-        let output = test_case.consume_bytes("Output");
+    let mut tested = 0;
 
-        let alg = match digest_alg.as_ref() {
-            "SHA256" => ShaVariantOption::SHA256,
-            "SHA384" => ShaVariantOption::SHA384,
-            "SHA512" => ShaVariantOption::SHA512,
-            _ => panic!("option not found"),
+    for section in cavp::from_file("src/tests/test_data/HMAC.rsp") {
+        // The section header is the output size of the hash function in bytes
+        let option = match section.param("L").unwrap() {
+            "32" => ShaVariantOption::SHA256,
+            "48" => ShaVariantOption::SHA384,
+            "64" => ShaVariantOption::SHA512,
+            _ => continue,
         };
 
-        hmac_test_runner(alg, &key_value[..], &input[..], &output[..], true)?;
+        for test_case in section.cases.iter() {
+            let key = test_case.bytes("Key");
+            let mut input = test_case.bytes("Msg");
+            let expected = test_case.bytes("Mac");
+            assert_eq!(key.len(), test_case.number("Klen"));
+            assert_eq!(expected.len(), test_case.number("Tlen"));
+
+            let secret_key = SecretKey::from_slice(&key).unwrap();
+            let hmac = Hmac::new(&secret_key, &input, option).unwrap();
+            let mac = hmac.finalize();
+            assert!(util::compare_ct(&mac.as_bytes()[..expected.len()], &expected).is_ok());
+            if expected.len() == option.output_size() {
+                assert!(hmac.verify(&expected).unwrap());
+            }
 
-        // Tamper with the input and check that verification fails
-        input[0] ^= 1;
+            // Tamper with the input and check that the MAC no longer matches
+            input[0] ^= 1;
+            let tampered = Hmac::new(&secret_key, &input, option).unwrap().finalize();
+            assert!(util::compare_ct(&tampered.as_bytes()[..expected.len()], &expected).is_err());
+
+            tested += 1;
+        }
+    }
 
-        hmac_test_runner(alg, &key_value[..], &input[..], &output[..], false)
-    });
+    // 225 test vectors for SHA-256, 300 for SHA-384 and 375 for SHA-512
+    assert_eq!(tested, 900);
 }
@@ -0,0 +1,138 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Testing against the NIST CAVP SHAVS test vectors for SHA-256, SHA-384 and SHA-512, from the
+// unmodified short message, long message and Monte Carlo response files.
+
+use core::options::{HashFunction, HashState, ShaVariantOption};
+use tests::cavp;
+
+/// Run the short or long message tests in `file_name`, returning how many were run.
+fn message_tests(file_name: &str, option: ShaVariantOption) -> usize {
+    let mut tested = 0;
+
+    for section in cavp::from_file(&format!("src/tests/test_data/{}", file_name)) {
+        assert_eq!(
+            section.param("L").unwrap(),
+            option.output_size().to_string()
+        );
+
+        for test_case in section.cases.iter() {
+            // `Len` is in bits. An empty message is written as `Msg = 00`.
+            let len = test_case.number("Len");
+            assert_eq!(len % 8, 0);
+            let msg = &test_case.bytes("Msg")[..len / 8];
+            let expected = test_case.bytes("MD");
+
+            assert_eq!(option.hash(msg), expected);
+
+            let mut state = option.init();
+            for chunk in msg.chunks(option.block_size() + 1) {
+                state.update(chunk);
+            }
+            assert_eq!(state.finalize(), expected);
+
+            tested += 1;
+        }
+    }
+
+    tested
+}
+
+/// Run the Monte Carlo test in `file_name`, as described in the SHAVS specification.
+fn monte_carlo_test(file_name: &str, option: ShaVariantOption) {
+    let sections = cavp::from_file(&format!("src/tests/test_data/{}", file_name));
+    assert_eq!(sections.len(), 1);
+    let cases = &sections[0].cases;
+
+    let mut seed = cases[0].bytes("Seed");
+    for (count, checkpoint) in cases[1..].iter().enumerate() {
+        assert_eq!(checkpoint.number("COUNT"), count);
+
+        let mut md = [seed.clone(), seed.clone(), seed.clone()];
+        for _ in 0..1000 {
+            let mut state = option.init();
+            state.update(&md[0]);
+            state.update(&md[1]);
+            state.update(&md[2]);
+            let next = state.finalize();
+
+            md = [md[1].clone(), md[2].clone(), next];
+        }
+        seed = md[2].clone();
+
+        assert_eq!(seed, checkpoint.bytes("MD"));
+    }
+
+    assert_eq!(cases.len(), 101);
+}
+
+#[test]
+fn sha256_messages() {
+    assert_eq!(
+        message_tests("SHA256ShortMsg.rsp", ShaVariantOption::SHA256),
+        65
+    );
+    assert_eq!(
+        message_tests("SHA256LongMsg.rsp", ShaVariantOption::SHA256),
+        64
+    );
+}
+
+#[test]
+fn sha384_messages() {
+    assert_eq!(
+        message_tests("SHA384ShortMsg.rsp", ShaVariantOption::SHA384),
+        129
+    );
+    assert_eq!(
+        message_tests("SHA384LongMsg.rsp", ShaVariantOption::SHA384),
+        128
+    );
+}
+
+#[test]
+fn sha512_messages() {
+    assert_eq!(
+        message_tests("SHA512ShortMsg.rsp", ShaVariantOption::SHA512),
+        129
+    );
+    assert_eq!(
+        message_tests("SHA512LongMsg.rsp", ShaVariantOption::SHA512),
+        128
+    );
+}
+
+#[test]
+fn sha256_monte_carlo() {
+    monte_carlo_test("SHA256Monte.rsp", ShaVariantOption::SHA256);
+}
+
+#[test]
+fn sha384_monte_carlo() {
+    monte_carlo_test("SHA384Monte.rsp", ShaVariantOption::SHA384);
+}
+
+#[test]
+fn sha512_monte_carlo() {
+    monte_carlo_test("SHA512Monte.rsp", ShaVariantOption::SHA512);
+}
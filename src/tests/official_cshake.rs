@@ -30,13 +30,13 @@ mod kcp_test_vectors {
 
     #[test]
     fn cshake128_test_case_1() {
-        let cshake = CShake {
-            input: b"\x00\x01\x02\x03".to_vec(),
-            name: b"".to_vec(),
-            custom: b"Email Signature".to_vec(),
-            length: 32,
-            keccak: KeccakVariantOption::KECCAK256,
-        };
+        let cshake = CShake::new(
+            b"\x00\x01\x02\x03",
+            b"",
+            b"Email Signature",
+            32,
+            KeccakVariantOption::KECCAK256,
+        ).unwrap();
 
         let expected = b"\xC1\xC3\x69\x25\xB6\x40\x9A\x04\xF1\xB5\x04\xFC\xBC\xA9\xD8\x2B\x40\x17\
                         \x27\x7C\xB5\xED\x2B\x20\x65\xFC\x1D\x38\x14\xD5\xAA\xF5"
@@ -48,8 +48,8 @@ mod kcp_test_vectors {
 
     #[test]
     fn cshake_128_test_case_2() {
-        let cshake = CShake {
-            input: b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0A\x0B\x0C\x0D\x0E\x0F\x10\x11\x12\
+        let cshake = CShake::new(
+            &b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0A\x0B\x0C\x0D\x0E\x0F\x10\x11\x12\
                     \x13\x14\x15\x16\x17\x18\x19\x1A\x1B\x1C\x1D\x1E\x1F\x20\x21\x22\x23\x24\x25\
                     \x26\x27\x28\x29\x2A\x2B\x2C\x2D\x2E\x2F\x30\x31\x32\x33\x34\x35\x36\x37\x38\
                     \x39\x3A\x3B\x3C\x3D\x3E\x3F\x40\x41\x42\x43\x44\x45\x46\x47\x48\x49\x4A\x4B\
@@ -61,11 +61,11 @@ mod kcp_test_vectors {
                     \xAB\xAC\xAD\xAE\xAF\xB0\xB1\xB2\xB3\xB4\xB5\xB6\xB7\xB8\xB9\xBA\xBB\xBC\xBD\
                     \xBE\xBF\xC0\xC1\xC2\xC3\xC4\xC5\xC6\xC7"
                 .to_vec(),
-            name: b"".to_vec(),
-            custom: b"Email Signature".to_vec(),
-            length: 32,
-            keccak: KeccakVariantOption::KECCAK256,
-        };
+            b"",
+            b"Email Signature",
+            32,
+            KeccakVariantOption::KECCAK256,
+        ).unwrap();
 
         let expected = b"\xC5\x22\x1D\x50\xE4\xF8\x22\xD9\x6A\x2E\x88\x81\xA9\x61\x42\x0F\x29\x4B\
                         \x7B\x24\xFE\x3D\x20\x94\xBA\xED\x2C\x65\x24\xCC\x16\x6B"
@@ -77,13 +77,13 @@ mod kcp_test_vectors {
 
     #[test]
     fn cshake_256_test_case_1() {
-        let cshake = CShake {
-            input: b"\x00\x01\x02\x03".to_vec(),
-            name: b"".to_vec(),
-            custom: b"Email Signature".to_vec(),
-            length: 64,
-            keccak: KeccakVariantOption::KECCAK512,
-        };
+        let cshake = CShake::new(
+            b"\x00\x01\x02\x03",
+            b"",
+            b"Email Signature",
+            64,
+            KeccakVariantOption::KECCAK512,
+        ).unwrap();
 
         let expected = b"\xD0\x08\x82\x8E\x2B\x80\xAC\x9D\x22\x18\xFF\xEE\x1D\x07\x0C\x48\xB8\
                         \xE4\xC8\x7B\xFF\x32\xC9\x69\x9D\x5B\x68\x96\xEE\xE0\xED\xD1\x64\x02\
@@ -97,8 +97,8 @@ mod kcp_test_vectors {
 
     #[test]
     fn cshake_256_test_case_2() {
-        let cshake = CShake {
-            input: b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0A\x0B\x0C\x0D\x0E\x0F\x10\x11\x12\
+        let cshake = CShake::new(
+            &b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0A\x0B\x0C\x0D\x0E\x0F\x10\x11\x12\
                     \x13\x14\x15\x16\x17\x18\x19\x1A\x1B\x1C\x1D\x1E\x1F\x20\x21\x22\x23\x24\x25\
                     \x26\x27\x28\x29\x2A\x2B\x2C\x2D\x2E\x2F\x30\x31\x32\x33\x34\x35\x36\x37\x38\
                     \x39\x3A\x3B\x3C\x3D\x3E\x3F\x40\x41\x42\x43\x44\x45\x46\x47\x48\x49\x4A\x4B\
@@ -110,11 +110,11 @@ mod kcp_test_vectors {
                     \xAB\xAC\xAD\xAE\xAF\xB0\xB1\xB2\xB3\xB4\xB5\xB6\xB7\xB8\xB9\xBA\xBB\xBC\xBD\
                     \xBE\xBF\xC0\xC1\xC2\xC3\xC4\xC5\xC6\xC7"
                 .to_vec(),
-            name: b"".to_vec(),
-            custom: b"Email Signature".to_vec(),
-            length: 64,
-            keccak: KeccakVariantOption::KECCAK512,
-        };
+            b"",
+            b"Email Signature",
+            64,
+            KeccakVariantOption::KECCAK512,
+        ).unwrap();
 
         let expected = b"\x07\xDC\x27\xB1\x1E\x51\xFB\xAC\x75\xBC\x7B\x3C\x1D\x98\x3E\x8B\x4B\x85\
                         \xFB\x1D\xEF\xAF\x21\x89\x12\xAC\x86\x43\x02\x73\x09\x17\x27\xF4\x2B\x17\
@@ -0,0 +1,140 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Testing against the test vectors from the [KangarooTwelve paper](https://keccak.team/files/KangarooTwelve.pdf),
+// along with additional vectors around the 8192 byte chunk boundary computed from the specification.
+
+#[cfg(test)]
+mod kangarootwelve_test_vectors {
+
+    extern crate hex;
+    use self::hex::decode;
+    use hazardous::kangarootwelve::KangarooTwelve;
+
+    /// The pattern string ptn(n) from the paper: repeating 0x00 to 0xFA.
+    fn ptn(n: usize) -> Vec<u8> {
+        (0..n).map(|idx| (idx % 251) as u8).collect()
+    }
+
+    fn k12_test_runner(input: &[u8], custom: &[u8], length: usize, expected: &[u8]) {
+        let k12 = KangarooTwelve {
+            input: input.to_vec(),
+            custom: custom.to_vec(),
+            length,
+        };
+
+        let actual = k12.finalize().unwrap();
+
+        assert_eq!(&actual[(length - expected.len())..], expected);
+        assert_eq!(k12.verify(&actual).unwrap(), true);
+    }
+
+    #[test]
+    fn test_empty_32() {
+        let expected =
+            decode("1ac2d450fc3b4205d19da7bfca1b37513c0803577ac7167f06fe2ce1f0ef39e5").unwrap();
+
+        k12_test_runner(b"", b"", 32, &expected);
+    }
+
+    #[test]
+    fn test_empty_64() {
+        let expected = decode(
+            "1ac2d450fc3b4205d19da7bfca1b37513c0803577ac7167f06fe2ce1f0ef39e5\
+             4269c056b8c82e48276038b6d292966cc07a3d4645272e31ff38508139eb0a71",
+        ).unwrap();
+
+        k12_test_runner(b"", b"", 64, &expected);
+    }
+
+    #[test]
+    fn test_empty_10032_last_32() {
+        let expected =
+            decode("e8dc563642f7228c84684c898405d3a834799158c079b12880277a1d28e2ff6d").unwrap();
+
+        k12_test_runner(b"", b"", 10032, &expected);
+    }
+
+    #[test]
+    fn test_ptn_17_pow() {
+        let expected = [
+            "6bf75fa2239198db4772e36478f8e19b0f371205f6a9a93a273f51df37122888",
+            "0c315ebcdedbf61426de7dcf8fb725d1e74675d7f5327a5067f367b108ecb67c",
+            "cb552e2ec77d9910701d578b457ddf772c12e322e4ee7fe417f92c758f0d59d0",
+            "8701045e22205345ff4dda05555cbb5c3af1a771c2b89baef37db43d9998b9fe",
+        ];
+
+        for (idx, exp) in expected.iter().enumerate() {
+            k12_test_runner(&ptn(17usize.pow(idx as u32 + 1)), b"", 32, &decode(exp).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_custom() {
+        k12_test_runner(
+            b"",
+            &ptn(1),
+            32,
+            &decode("fab658db63e94a246188bf7af69a133045f46ee984c56e3c3328caaf1aa1a583").unwrap(),
+        );
+        k12_test_runner(
+            b"\xff",
+            &ptn(41),
+            32,
+            &decode("d848c5068ced736f4462159b9867fd4c20b808acc3d5bc48e0b06ba0a3762ec4").unwrap(),
+        );
+        k12_test_runner(
+            b"\xff\xff\xff",
+            &ptn(41 * 41),
+            32,
+            &decode("c389e5009ae57120854c2e8c64670ac01358cf4c1baf89447a724234dc7ced74").unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_chunk_boundary() {
+        k12_test_runner(
+            &ptn(8191),
+            b"",
+            32,
+            &decode("1b577636f723643e990cc7d6a659837436fd6a103626600eb8301cd1dbe553d6").unwrap(),
+        );
+        k12_test_runner(
+            &ptn(8192),
+            b"",
+            32,
+            &decode("48f256f6772f9edfb6a8b661ec92dc93b95ebd05a08a17b39ae3490870c926c3").unwrap(),
+        );
+        k12_test_runner(
+            &ptn(8192),
+            &ptn(8189),
+            32,
+            &decode("3ed12f70fb05ddb58689510ab3e4d23c6c6033849aa01e1d8c220a297fedcd0b").unwrap(),
+        );
+        k12_test_runner(
+            &ptn(8192),
+            &ptn(8190),
+            32,
+            &decode("6a7c1b6a5cd0d8c9ca943a4a216cc64604559a2ea45f78570a15253d67ba00ae").unwrap(),
+        );
+    }
+}
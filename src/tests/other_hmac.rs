@@ -27,6 +27,7 @@ mod ietf_draft {
     extern crate hex;
     use self::hex::decode;
     use core::options::ShaVariantOption;
+    use core::types::SecretKey;
     use hazardous::hmac::*;
 
     fn hmac_test_runner(
@@ -37,16 +38,12 @@ mod ietf_draft {
         trunc: Option<usize>,
         should_be: bool,
     ) -> bool {
-        let mac = Hmac {
-            secret_key: secret_key.to_vec(),
-            data: data.to_vec(),
-            sha2,
-        };
+        let mac = Hmac::new(&SecretKey::from_slice(secret_key).unwrap(), &data, sha2).unwrap();
 
-        let (ipad, opad) = mac.pad_key(&mac.secret_key);
+        let (ipad, opad) = pad_key(secret_key, sha2);
 
-        let mut def_hmac = mac.finalize();
-        let mut pbkdf2_hmac = pbkdf2_hmac(&ipad, &opad, &mac.data, mac.sha2);
+        let mut def_hmac = mac.finalize().as_bytes().to_vec();
+        let mut pbkdf2_hmac = pbkdf2_hmac(&ipad, &opad, &data, sha2);
 
         match trunc {
             Some(ref length) => {
@@ -288,3 +285,57 @@ mod ietf_draft {
         ));
     }
 }
+
+// Testing HMAC-SHA3 against custom test vectors generated with Python's hmac and hashlib modules.
+#[cfg(test)]
+mod custom_sha3 {
+
+    extern crate hex;
+    use self::hex::decode;
+    use core::options::ShaVariantOption;
+    use core::types::SecretKey;
+    use hazardous::hmac::*;
+
+    fn hmac_test_runner(secret_key: &[u8], data: &[u8], sha2: ShaVariantOption, expected: &[u8]) {
+        let mac = Hmac::new(&SecretKey::from_slice(secret_key).unwrap(), &data, sha2).unwrap();
+
+        let (ipad, opad) = pad_key(secret_key, sha2);
+
+        assert_eq!(mac.finalize().as_bytes(), expected);
+        assert_eq!(pbkdf2_hmac(&ipad, &opad, &data, sha2), expected);
+        assert_eq!(mac.verify(expected).unwrap(), true);
+    }
+
+    #[test]
+    fn test_case_1() {
+        let secret_key = "Jefe".as_bytes();
+        let data = "what do ya want for nothing?".as_bytes();
+
+        let expected_hmac_256 =
+            decode("c7d4072e788877ae3596bbb0da73b887c9171f93095b294ae857fbe2645e1ba5").unwrap();
+        let expected_hmac_512 = decode(
+            "5a4bfeab6166427c7a3647b747292b8384537cdb89afb3bf5665e4c5e709350b\
+             287baec921fd7ca0ee7a0c31d022a95e1fc92ba9d77df883960275beb4e62024",
+        ).unwrap();
+
+        hmac_test_runner(secret_key, data, ShaVariantOption::SHA3_256, &expected_hmac_256);
+        hmac_test_runner(secret_key, data, ShaVariantOption::SHA3_512, &expected_hmac_512);
+    }
+
+    #[test]
+    fn test_case_2() {
+        // Key is longer than the blocksize of both variants
+        let secret_key = vec![0xaa; 200];
+        let data = "long key".as_bytes();
+
+        let expected_hmac_256 =
+            decode("c520e9c2447a31b094d2f7efcbdfafc68371bdd61de9a2bc19032e6d1af44b50").unwrap();
+        let expected_hmac_512 = decode(
+            "a563c11747586b5927131648d83867716efb2a0a64c1fc5556f46932c2e484c1\
+             0940b8419a51a5098eaa172b85739a75dcbc8d09274b553d7566ac18f719cdcf",
+        ).unwrap();
+
+        hmac_test_runner(&secret_key, data, ShaVariantOption::SHA3_256, &expected_hmac_256);
+        hmac_test_runner(&secret_key, data, ShaVariantOption::SHA3_512, &expected_hmac_512);
+    }
+}
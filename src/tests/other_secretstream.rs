@@ -0,0 +1,96 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Testing against test vectors generated with an independent implementation of libsodium's
+// crypto_secretstream_xchacha20poly1305.
+
+#[cfg(test)]
+mod libsodium_compat {
+
+    extern crate hex;
+    use self::hex::decode;
+    use hazardous::secretstream::*;
+
+    #[test]
+    fn test_stream() {
+        let key: Vec<u8> = (0u8..32).collect();
+        let header: Vec<u8> = (100u8..124).collect();
+        let long_msg: Vec<u8> = (0u8..200).collect();
+        let long_ad: Vec<u8> = (0u8..17).collect();
+
+        let messages: Vec<(&[u8], &[u8], StreamTag, Vec<u8>)> = vec![
+            (
+                b"",
+                b"",
+                StreamTag::MESSAGE,
+                decode("5fd5ec9f27982984c6c795bc5f0c7c4685").unwrap(),
+            ),
+            (
+                b"Ladies and Gentlemen of the class of '99",
+                b"ad",
+                StreamTag::MESSAGE,
+                decode(
+                    "4bcd686ff6b85936caa545740273c84c24dad168f331b2d7aa56d20d154008a9\
+                     73632f0205923432b6e35155960f2fcb7136fb38490ebd9e39",
+                ).unwrap(),
+            ),
+            (
+                &long_msg,
+                b"",
+                StreamTag::PUSH,
+                decode(
+                    "39725365e9aff030cfed7254cb6efadb6103a21fd6f0cf23860a1ec0fbc14f3f\
+                     05e8f612fa1eb17b412dc85b85f680addbf3a4c092b1290f3e14aef1590fb958\
+                     370fb7d425e1b570940ca1f52f305c5451cdfa630cb1936360cd5534f575ae63\
+                     1d86e4ceefb1db25c1fb0a793127f42bb5867e9bb343d3246b9df334779aca57\
+                     99c2d19d7c1ef8a15817c26eba51aa5b12aaa117bd40d109920ab9cdb97940cc\
+                     4004f93e14c83552d8c773a2ee4c46d93226801030a1f23f5595a2cf8eadaefb\
+                     94c1ffde80a5ce3f2042209897d7fbc396c219fa5cd10d84f6",
+                ).unwrap(),
+            ),
+            (
+                b"after rekey",
+                &long_ad,
+                StreamTag::REKEY,
+                decode("7d31c4695daaf3905f781128f8cf6c2de33dd45d68b2ed35d18fc62d").unwrap(),
+            ),
+            (
+                b"last",
+                b"",
+                StreamTag::FINAL,
+                decode("2883da2e432bf964d9a9a674ec5390588e9f95673e").unwrap(),
+            ),
+        ];
+
+        let mut sender = SecretStream::init_pull(&key, &header).unwrap();
+        let mut receiver = SecretStream::init_pull(&key, &header).unwrap();
+
+        for (plaintext, ad, tag, expected) in messages {
+            let ciphertext = sender.push(plaintext, ad, tag).unwrap();
+            assert_eq!(ciphertext, expected);
+
+            let (decrypted, decrypted_tag) = receiver.pull(&ciphertext, ad).unwrap();
+            assert_eq!(&decrypted[..], plaintext);
+            assert_eq!(decrypted_tag, tag);
+        }
+    }
+}
@@ -0,0 +1,109 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Testing against RFC 8439 test vectors.
+
+#[cfg(test)]
+mod rfc8439 {
+
+    extern crate hex;
+    use self::hex::decode;
+    use hazardous::chacha20;
+
+    #[test]
+    fn test_block_function() {
+        let key =
+            decode("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f").unwrap();
+        let nonce = decode("000000090000004a00000000").unwrap();
+
+        let expected = decode(
+            "10f1e7e4d13b5915500fdd1fa32071c4c7d1f4c733c068030422aa9ac3d46c4e\
+             d2826446079faa0914c2d705d98b02a2b5129cd1de164eb9cbd083e8a2503c4e",
+        ).unwrap();
+
+        assert_eq!(chacha20::keystream_block(&key, &nonce, 1).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_encryption_sunscreen() {
+        let key =
+            decode("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f").unwrap();
+        let nonce = decode("000000000000004a00000000").unwrap();
+        let plaintext = "Ladies and Gentlemen of the class of '99: If I could offer you only one \
+                         tip for the future, sunscreen would be it."
+            .as_bytes();
+
+        let expected = decode(
+            "6e2e359a2568f98041ba0728dd0d6981e97e7aec1d4360c20a27afccfd9fae0b\
+             f91b65c5524733ab8f593dabcd62b3571639d624e65152ab8f530c359f0861d8\
+             07ca0dbf500d6a6156a38e088a22b65e52bc514d16ccf806818ce91ab7793736\
+             5af90bbf74a35be6b40b8eedf2785e42874d",
+        ).unwrap();
+
+        assert_eq!(chacha20::encrypt(&key, &nonce, 1, plaintext).unwrap(), expected);
+        assert_eq!(chacha20::decrypt(&key, &nonce, 1, &expected).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn test_encryption_appendix_a_2_2() {
+        let key =
+            decode("0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+        let nonce = decode("000000000000000000000002").unwrap();
+        let plaintext = "Any submission to the IETF intended by the Contributor for publication \
+                         as all or part of an IETF Internet-Draft or RFC and any statement made \
+                         within the context of an IETF activity is considered an \"IETF \
+                         Contribution\". Such statements include oral statements in IETF \
+                         sessions, as well as written and electronic communications made at any \
+                         time or place, which are addressed to"
+            .as_bytes();
+
+        let expected = decode(
+            "a3fbf07df3fa2fde4f376ca23e82737041605d9f4f4f57bd8cff2c1d4b7955ec\
+             2a97948bd3722915c8f3d337f7d370050e9e96d647b7c39f56e031ca5eb6250d\
+             4042e02785ececfa4b4bb5e8ead0440e20b6e8db09d881a7c6132f420e527950\
+             42bdfa7773d8a9051447b3291ce1411c680465552aa6c405b7764d5e87bea85a\
+             d00f8449ed8f72d0d662ab052691ca66424bc86d2df80ea41f43abf937d3259d\
+             c4b2d0dfb48a6c9139ddd7f76966e928e635553ba76c5c879d7b35d49eb2e62b\
+             0871cdac638939e25e8a1e0ef9d5280fa8ca328b351c3c765989cbcf3daa8b6c\
+             cc3aaf9f3979c92b3720fc88dc95ed84a1be059c6499b9fda236e7e818b04b0b\
+             c39c1e876b193bfe5569753f88128cc08aaa9b63d1a16f80ef2554d7189c411f\
+             5869ca52c5b83fa36ff216b9c1d30062bebcfd2dc5bce0911934fda79a86f6e6\
+             98ced759c3ff9b6477338f3da4f9cd8514ea9982ccafb341b2384dd902f3d1ab\
+             7ac61dd29c6f21ba5b862f3730e37cfdc4fd806c22f221",
+        ).unwrap();
+
+        assert_eq!(chacha20::encrypt(&key, &nonce, 1, plaintext).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_hchacha20() {
+        // Test vector from the draft RFC on XChaCha, section 2.2.1
+        let key =
+            decode("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f").unwrap();
+        let nonce = decode("000000090000004a0000000031415927").unwrap();
+
+        let expected =
+            decode("82413b4227b27bfed30e42508a877d73a0f9e4d58a74a853c12ec41326d3ecdc").unwrap();
+
+        assert_eq!(chacha20::hchacha20(&key, &nonce).unwrap(), expected);
+    }
+}
@@ -28,17 +28,17 @@ mod rfc5869 {
     extern crate hex;
     use self::hex::decode;
     use core::options::ShaVariantOption;
-    use hazardous::hkdf::Hkdf;
+    use hazardous::hkdf::{Hkdf, Prk};
 
     #[test]
     fn test_case_1() {
-        let hkdf = Hkdf {
-            salt: decode("000102030405060708090a0b0c").unwrap(),
-            ikm: decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap(),
-            info: decode("f0f1f2f3f4f5f6f7f8f9").unwrap(),
-            length: 42,
-            hmac: ShaVariantOption::SHA256,
-        };
+        let hkdf = Hkdf::new(
+            &decode("000102030405060708090a0b0c").unwrap(),
+            &decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap(),
+            &decode("f0f1f2f3f4f5f6f7f8f9").unwrap(),
+            42,
+            ShaVariantOption::SHA256,
+        ).unwrap();
 
         let expected_prk =
             decode("077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5").unwrap();
@@ -48,34 +48,38 @@ mod rfc5869 {
              34007208d5b887185865",
         ).unwrap();
 
-        let actual_prk = hkdf.extract(&hkdf.salt, &hkdf.ikm);
+        let actual_prk = hkdf.extract();
 
-        assert_eq!(actual_prk, expected_prk);
+        assert_eq!(actual_prk.unprotected_as_bytes(), &expected_prk[..]);
         assert_eq!(hkdf.expand(&actual_prk).unwrap(), expected_okm);
         assert_eq!(hkdf.derive_key().unwrap(), expected_okm);
+
+        let prk = Prk::from_slice(&expected_prk, ShaVariantOption::SHA256).unwrap();
+        let info = decode("f0f1f2f3f4f5f6f7f8f9").unwrap();
+        assert_eq!(prk.expand(&info, 42).unwrap(), expected_okm);
     }
 
     #[test]
     fn test_case_2() {
-        let hkdf = Hkdf {
-            salt: decode(
+        let hkdf = Hkdf::new(
+            &decode(
                 "606162636465666768696a6b6c6d6e6f707172737475767778797a7b7c7d7e7f\
                  808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f\
                  a0a1a2a3a4a5a6a7a8a9aaabacadaeaf",
             ).unwrap(),
-            ikm: decode(
+            &decode(
                 "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f\
                  202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f\
                  404142434445464748494a4b4c4d4e4f",
             ).unwrap(),
-            info: decode(
+            &decode(
                 "b0b1b2b3b4b5b6b7b8b9babbbcbdbebfc0c1c2c3c4c5c6c7c8c9cacbcccdcecf\
                  d0d1d2d3d4d5d6d7d8d9dadbdcdddedfe0e1e2e3e4e5e6e7e8e9eaebecedeeef\
                  f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff",
             ).unwrap(),
-            length: 82,
-            hmac: ShaVariantOption::SHA256,
-        };
+            82,
+            ShaVariantOption::SHA256,
+        ).unwrap();
 
         let expected_prk =
             decode("06a6b88c5853361a06104c9ceb35b45cef760014904671014a193f40c15fc244").unwrap();
@@ -86,22 +90,22 @@ mod rfc5869 {
              cc30c58179ec3e87c14c01d5c1f3434f1d87",
         ).unwrap();
 
-        let actual_prk = hkdf.extract(&hkdf.salt, &hkdf.ikm);
+        let actual_prk = hkdf.extract();
 
-        assert_eq!(actual_prk, expected_prk);
+        assert_eq!(actual_prk.unprotected_as_bytes(), &expected_prk[..]);
         assert_eq!(hkdf.expand(&actual_prk).unwrap(), expected_okm);
         assert_eq!(hkdf.derive_key().unwrap(), expected_okm);
     }
 
     #[test]
     fn test_case_3() {
-        let hkdf = Hkdf {
-            salt: decode("").unwrap(),
-            ikm: decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap(),
-            info: decode("").unwrap(),
-            length: 42,
-            hmac: ShaVariantOption::SHA256,
-        };
+        let hkdf = Hkdf::new(
+            &decode("").unwrap(),
+            &decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap(),
+            &decode("").unwrap(),
+            42,
+            ShaVariantOption::SHA256,
+        ).unwrap();
 
         let expected_prk =
             decode("19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04").unwrap();
@@ -111,9 +115,9 @@ mod rfc5869 {
              9d201395faa4b61a96c8",
         ).unwrap();
 
-        let actual_prk = hkdf.extract(&hkdf.salt, &hkdf.ikm);
+        let actual_prk = hkdf.extract();
 
-        assert_eq!(actual_prk, expected_prk);
+        assert_eq!(actual_prk.unprotected_as_bytes(), &expected_prk[..]);
         assert_eq!(hkdf.expand(&actual_prk).unwrap(), expected_okm);
         assert_eq!(hkdf.derive_key().unwrap(), expected_okm);
     }
@@ -27,6 +27,7 @@ mod rfc4231 {
     extern crate hex;
     use self::hex::decode;
     use core::options::ShaVariantOption;
+    use core::types::SecretKey;
     use hazardous::hmac::*;
 
     fn hmac_test_runner(
@@ -37,16 +38,12 @@ mod rfc4231 {
         trunc: Option<usize>,
         should_be: bool,
     ) -> bool {
-        let mac = Hmac {
-            secret_key: secret_key.to_vec(),
-            data: data.to_vec(),
-            sha2,
-        };
+        let mac = Hmac::new(&SecretKey::from_slice(secret_key).unwrap(), &data, sha2).unwrap();
 
-        let (ipad, opad) = mac.pad_key(&mac.secret_key);
+        let (ipad, opad) = pad_key(secret_key, sha2);
 
-        let mut def_hmac = mac.finalize();
-        let mut pbkdf2_hmac = pbkdf2_hmac(&ipad, &opad, &mac.data, mac.sha2);
+        let mut def_hmac = mac.finalize().as_bytes().to_vec();
+        let mut pbkdf2_hmac = pbkdf2_hmac(&ipad, &opad, &data, sha2);
 
         match trunc {
             Some(ref length) => {
@@ -27,17 +27,18 @@ mod rfc7914 {
     extern crate hex;
     use self::hex::decode;
     use core::options::ShaVariantOption;
+    use core::types::Password;
     use hazardous::pbkdf2::Pbkdf2;
 
     #[test]
     fn rfc7914_test_case_1() {
-        let dk = Pbkdf2 {
-            password: "passwd".as_bytes().to_vec(),
-            salt: "salt".as_bytes().to_vec(),
-            iterations: 1,
-            dklen: 64,
-            hmac: ShaVariantOption::SHA256,
-        };
+        let dk = Pbkdf2::new(
+            &Password::from_slice("passwd".as_bytes()).unwrap(),
+            "salt".as_bytes(),
+            1,
+            64,
+            ShaVariantOption::SHA256,
+        ).unwrap();
 
         let expected_dk = decode(
             "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
@@ -49,13 +50,13 @@ mod rfc7914 {
 
     #[test]
     fn rfc7914_test_case_2() {
-        let dk = Pbkdf2 {
-            password: "Password".as_bytes().to_vec(),
-            salt: "NaCl".as_bytes().to_vec(),
-            iterations: 80000,
-            dklen: 64,
-            hmac: ShaVariantOption::SHA256,
-        };
+        let dk = Pbkdf2::new(
+            &Password::from_slice("Password".as_bytes()).unwrap(),
+            "NaCl".as_bytes(),
+            80000,
+            64,
+            ShaVariantOption::SHA256,
+        ).unwrap();
 
         let expected_dk = decode(
             "4ddcd8f60b98be21830cee5ef22701f9641a4418d04c0414aeff08876b34ab56\
@@ -0,0 +1,84 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Testing against RFC 8439 test vectors.
+
+#[cfg(test)]
+mod rfc8439 {
+
+    extern crate hex;
+    use self::hex::decode;
+    use hazardous::poly1305::*;
+
+    fn poly1305_test_runner(key: &[u8], data: &[u8], expected: &[u8]) {
+        assert_eq!(poly1305(key, data).unwrap(), expected);
+
+        // Feed the same data in uneven pieces
+        let mut poly = Poly1305::init(key).unwrap();
+        for chunk in data.chunks(7) {
+            poly.update(chunk);
+        }
+        assert_eq!(poly.verify(expected).unwrap(), true);
+    }
+
+    #[test]
+    fn test_case_2_5_2() {
+        let key =
+            decode("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b").unwrap();
+        let expected = decode("a8061dc1305136c6c22b8baf0c0127a9").unwrap();
+
+        poly1305_test_runner(&key, "Cryptographic Forum Research Group".as_bytes(), &expected);
+    }
+
+    #[test]
+    fn test_case_a_3_5() {
+        let key =
+            decode("0200000000000000000000000000000000000000000000000000000000000000").unwrap();
+        let data = decode("ffffffffffffffffffffffffffffffff").unwrap();
+        let expected = decode("03000000000000000000000000000000").unwrap();
+
+        poly1305_test_runner(&key, &data, &expected);
+    }
+
+    #[test]
+    fn test_case_a_3_6() {
+        let key =
+            decode("02000000000000000000000000000000ffffffffffffffffffffffffffffffff").unwrap();
+        let data = decode("02000000000000000000000000000000").unwrap();
+        let expected = decode("03000000000000000000000000000000").unwrap();
+
+        poly1305_test_runner(&key, &data, &expected);
+    }
+
+    #[test]
+    fn test_case_a_3_8() {
+        let key =
+            decode("0100000000000000000000000000000000000000000000000000000000000000").unwrap();
+        let data = decode(
+            "fffffffffffffffffffffffffffffffffbfefefefefefefefefefefefefefefe\
+             01010101010101010101010101010101",
+        ).unwrap();
+        let expected = decode("00000000000000000000000000000000").unwrap();
+
+        poly1305_test_runner(&key, &data, &expected);
+    }
+}
@@ -0,0 +1,282 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Statistical timing tests in the style of [dudect](https://github.com/oreparaz/dudect), which
+// check that the routines claimed to run in constant time do so in practice.
+//
+// Each test prepares inputs of two classes, measures the operation on them in random order
+// and compares the two timing distributions with Welch's t-test. Both classes are chosen to be
+// equal for a constant-time implementation, for example a tag that is wrong in its first byte
+// and one that is wrong in its last byte, so that an early exit shows up as a difference.
+//
+// The tests are ignored by default, because they take a while and only give meaningful
+// results with optimizations and on a quiet machine. Run them with:
+// cargo test --release -- --ignored timing
+
+#[cfg(test)]
+mod timing {
+
+    use core::options::ShaVariantOption;
+    use core::types::{Nonce, SecretKey};
+    use core::util;
+    use hazardous::hmac::Hmac;
+    use hazardous::poly1305::{poly1305, Poly1305};
+    use hazardous::xchacha20poly1305;
+    use std::hint::black_box;
+    use std::time::Instant;
+
+    const MEASUREMENTS: usize = 100_000;
+    // dudect considers a t-value above 10 to be a certain leak
+    const T_THRESHOLD: f64 = 10.0;
+
+    /// xorshift64*, used to pick the classes and random inputs. It does not depend on the
+    /// `rng` feature and is cheap compared to the measured operations.
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+
+            self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+        }
+
+        fn fill(&mut self, dst: &mut [u8]) {
+            for byte in dst.iter_mut() {
+                *byte = self.next() as u8;
+            }
+        }
+    }
+
+    /// Running mean and variance, with Welford's algorithm.
+    #[derive(Default)]
+    struct Stats {
+        n: f64,
+        mean: f64,
+        m2: f64,
+    }
+
+    impl Stats {
+        fn push(&mut self, x: f64) {
+            self.n += 1.0;
+            let delta = x - self.mean;
+            self.mean += delta / self.n;
+            self.m2 += delta * (x - self.mean);
+        }
+
+        fn variance(&self) -> f64 {
+            self.m2 / (self.n - 1.0)
+        }
+    }
+
+    /// Welch's t-value of two samples.
+    fn welch_t(a: &Stats, b: &Stats) -> f64 {
+        (a.mean - b.mean) / (a.variance() / a.n + b.variance() / b.n).sqrt()
+    }
+
+    /// Measure `operation` on inputs made by `prepare` for randomly chosen classes, and return
+    /// the largest absolute t-value between the classes. Like dudect, the measurements are
+    /// also tested after cropping them at several percentiles, because the upper tail is
+    /// dominated by interrupts and other noise.
+    fn max_t<I, P, F>(mut prepare: P, mut operation: F) -> f64
+    where
+        P: FnMut(bool, &mut XorShift) -> I,
+        F: FnMut(&I),
+    {
+        let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+        let classes: Vec<bool> = (0..MEASUREMENTS).map(|_| rng.next() & 1 == 1).collect();
+        let inputs: Vec<I> = classes
+            .iter()
+            .map(|class| prepare(*class, &mut rng))
+            .collect();
+
+        let mut timings = Vec::with_capacity(MEASUREMENTS);
+        for input in inputs.iter() {
+            let start = Instant::now();
+            operation(black_box(input));
+            timings.push(start.elapsed().subsec_nanos() as f64);
+        }
+
+        let mut sorted = timings.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let mut max = 0f64;
+        for percentile in [50, 75, 90, 95, 99, 100].iter() {
+            let crop = sorted[(MEASUREMENTS - 1) * percentile / 100];
+            let mut stats = [Stats::default(), Stats::default()];
+            for (timing, class) in timings.iter().zip(classes.iter()) {
+                if *timing <= crop {
+                    stats[*class as usize].push(*timing);
+                }
+            }
+
+            if stats[0].n > 1.0 && stats[1].n > 1.0 {
+                max = max.max(welch_t(&stats[0], &stats[1]).abs());
+            }
+        }
+
+        max
+    }
+
+    /// Return a copy of `valid` that is wrong in its first byte, or in its last one if `class`
+    /// is set.
+    fn tamper(valid: &[u8], class: bool) -> Vec<u8> {
+        let mut tampered = valid.to_vec();
+        let idx = if class { tampered.len() - 1 } else { 0 };
+        tampered[idx] ^= 1;
+
+        tampered
+    }
+
+    #[test]
+    #[ignore]
+    fn timing_detects_early_exit() {
+        // The harness must be able to find the leak of a comparison that exits early
+        let expected = vec![0x61u8; 4096];
+        let t = max_t(
+            |class, _| tamper(&expected, class),
+            |input| {
+                black_box(input[..] == expected[..]);
+            },
+        );
+
+        assert!(t > T_THRESHOLD, "t = {}", t);
+    }
+
+    #[test]
+    #[ignore]
+    fn timing_compare_ct() {
+        let expected = [0x61u8; 64];
+        let t = max_t(
+            |class, _| tamper(&expected, class),
+            |input| {
+                black_box(util::compare_ct(input, &expected).is_ok());
+            },
+        );
+
+        assert!(t < T_THRESHOLD, "t = {}", t);
+    }
+
+    #[test]
+    #[ignore]
+    fn timing_compare_ct_any_len() {
+        let expected = [0x61u8; 64];
+        let t = max_t(
+            |class, _| tamper(&expected, class),
+            |input| {
+                black_box(util::compare_ct_any_len(input, &expected));
+            },
+        );
+
+        assert!(t < T_THRESHOLD, "t = {}", t);
+    }
+
+    #[test]
+    #[ignore]
+    fn timing_hmac_verify() {
+        let secret_key = SecretKey::from_slice(&[0x42; 32]).unwrap();
+        let hmac = Hmac::new(&secret_key, b"Some message.", ShaVariantOption::SHA256).unwrap();
+        let tag = hmac.finalize();
+        let t = max_t(
+            |class, _| tamper(tag.as_bytes(), class),
+            |input| {
+                black_box(hmac.verify(input).is_ok());
+            },
+        );
+
+        assert!(t < T_THRESHOLD, "t = {}", t);
+    }
+
+    #[test]
+    #[ignore]
+    fn timing_poly1305_verify() {
+        let key = [0x42u8; 32];
+        let tag = poly1305(&key, b"Some message.").unwrap();
+        let t = max_t(
+            |class, _| tamper(&tag, class),
+            |input| {
+                let mut poly = Poly1305::init(&key).unwrap();
+                poly.update(b"Some message.");
+                black_box(poly.verify(input).is_ok());
+            },
+        );
+
+        assert!(t < T_THRESHOLD, "t = {}", t);
+    }
+
+    #[test]
+    #[ignore]
+    fn timing_xchacha20poly1305_open() {
+        let key = [0x42u8; 32];
+        let nonce = Nonce::from_slice(&[0x07; 24]).unwrap();
+        let sealed = xchacha20poly1305::seal(&key, &nonce, &[0x61; 64], &[]).unwrap();
+        let t = max_t(
+            |class, _| tamper(&sealed, class),
+            |input| {
+                black_box(xchacha20poly1305::open(&key, &nonce, input, &[]).is_err());
+            },
+        );
+
+        assert!(t < T_THRESHOLD, "t = {}", t);
+    }
+
+    #[test]
+    #[ignore]
+    fn timing_unpad() {
+        // The padding is at the same position for both classes, so only the content of the
+        // block differs: zero bytes or random bytes before the 0x80 barrier
+        let t = max_t(
+            |class, rng| {
+                let mut data = [0u8; 48];
+                if class {
+                    rng.fill(&mut data);
+                }
+                util::pad(&data, 64).unwrap()
+            },
+            |input| {
+                black_box(util::unpad(input, 64).unwrap());
+            },
+        );
+
+        assert!(t < T_THRESHOLD, "t = {}", t);
+    }
+
+    #[test]
+    #[ignore]
+    fn timing_hex() {
+        let t = max_t(
+            |class, rng| {
+                let mut data = [0u8; 64];
+                if class {
+                    rng.fill(&mut data);
+                }
+                util::to_hex(&data)
+            },
+            |input| {
+                black_box(util::from_hex(input).unwrap());
+            },
+        );
+
+        assert!(t < T_THRESHOLD, "t = {}", t);
+    }
+}
@@ -0,0 +1,165 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Run with `wasm-pack test --node` or `cargo test --target wasm32-unknown-unknown` with
+// wasm-bindgen-test-runner configured as the runner.
+
+#[cfg(test)]
+mod wasm32 {
+
+    extern crate hex;
+    extern crate wasm_bindgen_test;
+    use self::hex::decode;
+    use self::wasm_bindgen_test::*;
+    use core::options::ShaVariantOption;
+    use core::types::{Salt, SecretKey};
+    use core::util;
+    use default;
+    use hazardous::hkdf::Hkdf;
+    use hazardous::hmac::Hmac;
+
+    #[wasm_bindgen_test]
+    fn os_random_fills_buffer() {
+        let mut first = [0u8; 32];
+        let mut second = [0u8; 32];
+        util::secure_rand_bytes(&mut first).unwrap();
+        util::secure_rand_bytes(&mut second).unwrap();
+
+        assert!(first != [0u8; 32]);
+        assert!(first != second);
+    }
+
+    #[wasm_bindgen_test]
+    fn hmac_rfc4231_test_case_2() {
+        let mac = Hmac::new(
+            &SecretKey::from_slice(b"Jefe").unwrap(),
+            b"what do ya want for nothing?",
+            ShaVariantOption::SHA256,
+        ).unwrap();
+
+        let expected =
+            decode("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843").unwrap();
+
+        assert_eq!(mac.finalize().as_bytes(), &expected[..]);
+    }
+
+    #[wasm_bindgen_test]
+    fn hkdf_rfc5869_test_case_1() {
+        let hkdf = Hkdf::new(
+            &decode("000102030405060708090a0b0c").unwrap(),
+            &decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap(),
+            &decode("f0f1f2f3f4f5f6f7f8f9").unwrap(),
+            42,
+            ShaVariantOption::SHA256,
+        ).unwrap();
+
+        let expected_okm = decode(
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf\
+             34007208d5b887185865",
+        ).unwrap();
+
+        assert_eq!(hkdf.derive_key().unwrap(), expected_okm);
+    }
+
+    #[wasm_bindgen_test]
+    fn default_hmac_and_hkdf_with_generated_inputs() {
+        let key = SecretKey::generate(64).unwrap();
+        let salt = Salt::generate(32).unwrap();
+        let msg = b"Some message.";
+
+        let tag = default::hmac(&key, msg).unwrap();
+        assert!(default::hmac_verify(&tag, &key, msg).unwrap());
+
+        let okm = default::hkdf(&salt, msg, b"Some info.", 32).unwrap();
+        assert!(default::hkdf_verify(&okm, &salt, msg, b"Some info.", 32).unwrap());
+    }
+}
+
+#[cfg(all(test, feature = "wasm", feature = "rng"))]
+mod bindings {
+
+    extern crate wasm_bindgen_test;
+    use self::wasm_bindgen_test::*;
+    use core::types::{Password, SecretKey};
+    use default;
+    use wasm;
+
+    #[wasm_bindgen_test]
+    fn hmac_matches_default() {
+        let key = wasm::gen_secret_key(64).unwrap();
+        let tag = wasm::hmac(&key, b"Some message.").unwrap();
+
+        let expected = default::hmac(&SecretKey::from_slice(&key).unwrap(), b"Some message.");
+        assert_eq!(tag, expected.unwrap().as_bytes());
+        assert!(wasm::hmac_verify(&tag, &key, b"Some message."));
+        assert!(!wasm::hmac_verify(&tag, &key, b"Some message"));
+        assert!(!wasm::hmac_verify(&tag[..31], &key, b"Some message."));
+        assert!(wasm::hmac(&key[..63], b"Some message.").is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn hkdf_and_cshake() {
+        let salt = wasm::gen_salt(32).unwrap();
+        let okm = wasm::hkdf(&salt, b"input", b"info", 42).unwrap();
+        assert_eq!(okm.len(), 42);
+        assert!(wasm::hkdf_verify(&okm, &salt, b"input", b"info"));
+        assert!(!wasm::hkdf_verify(&okm, &salt, b"input", b"other info"));
+        assert!(wasm::hkdf(&salt[..15], b"input", b"info", 42).is_err());
+
+        let hash = wasm::cshake(b"input", b"custom").unwrap();
+        assert!(wasm::cshake_verify(&hash, b"input", b"custom"));
+        assert!(!wasm::cshake_verify(&hash, b"input", b"other"));
+        assert!(wasm::cshake(b"input", b"").is_err());
+
+        assert_eq!(wasm::hash(b"data"), default::hash(b"data"));
+    }
+
+    #[wasm_bindgen_test]
+    fn passwords() {
+        let dk = wasm::pbkdf2(b"Secret password").unwrap();
+        assert!(wasm::pbkdf2_verify(&dk, b"Secret password"));
+        assert!(!wasm::pbkdf2_verify(&dk, b"Secret passwore"));
+        assert!(!wasm::pbkdf2_verify(&dk, b""));
+
+        let hash = wasm::password_hash(b"Secret password").unwrap();
+        let password = Password::from_slice(b"Secret password").unwrap();
+        assert!(default::password_hash_verify(&hash, &password).unwrap());
+        assert!(wasm::password_hash_verify(&hash, b"Secret password"));
+        assert!(!wasm::password_hash_verify(&hash, b"Secret passwore"));
+        assert!(wasm::password_hash(b"Short").is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn encryption() {
+        let key = wasm::gen_secret_key(32).unwrap();
+        let ciphertext = wasm::encrypt(&key, b"Secret message").unwrap();
+        assert_eq!(wasm::decrypt(&key, &ciphertext).unwrap(), b"Secret message");
+        assert!(wasm::decrypt(&key, &ciphertext[1..]).is_err());
+        assert!(wasm::decrypt(&[], &ciphertext).is_err());
+
+        let password = b"Secret password";
+        let ciphertext = wasm::encrypt_with_password(password, b"Secret message").unwrap();
+        let plaintext = wasm::decrypt_with_password(password, &ciphertext).unwrap();
+        assert_eq!(plaintext, b"Secret message");
+        assert!(wasm::decrypt_with_password(b"Secret passwore", &ciphertext).is_err());
+    }
+}
@@ -0,0 +1,188 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Testing against the [Wycheproof](https://github.com/C2SP/wycheproof) test vectors, which
+// cover edge cases such as empty keys and messages, keys longer than the block size, truncated
+// and modified tags, empty salts, maximal output sizes and invalid nonce lengths. The vector
+// files in `test_data/wycheproof` are copied unmodified and are licensed under Apache 2.0.
+
+#[cfg(test)]
+mod wycheproof {
+
+    extern crate hex;
+    extern crate serde_json;
+    use self::hex::decode;
+    use self::serde_json::Value;
+    use core::options::ShaVariantOption;
+    use core::types::{Nonce, SecretKey};
+    use core::util;
+    use hazardous::hkdf::Hkdf;
+    use hazardous::hmac::{hmac_into, Hmac};
+    use hazardous::xchacha20poly1305;
+    use std::fs::File;
+
+    /// Call `test_runner` on every test case of a vector file, with the test group it is in.
+    fn for_each_test<F: FnMut(&Value, &Value)>(file_name: &str, mut test_runner: F) {
+        let path = format!("src/tests/test_data/wycheproof/{}", file_name);
+        let vectors: Value = serde_json::from_reader(File::open(path).unwrap()).unwrap();
+        let mut count = 0;
+
+        for group in vectors["testGroups"].as_array().unwrap() {
+            for test in group["tests"].as_array().unwrap() {
+                test_runner(group, test);
+                count += 1;
+            }
+        }
+
+        assert_eq!(count, vectors["numberOfTests"].as_u64().unwrap());
+    }
+
+    fn bytes(test: &Value, field: &str) -> Vec<u8> {
+        decode(test[field].as_str().unwrap()).unwrap()
+    }
+
+    fn is_valid(test: &Value) -> bool {
+        match test["result"].as_str().unwrap() {
+            "valid" => true,
+            "invalid" => false,
+            result => panic!("unexpected result {} in tcId {}", result, test["tcId"]),
+        }
+    }
+
+    fn hmac_test_runner(file_name: &str, hmac: ShaVariantOption) {
+        for_each_test(file_name, |group, test| {
+            let key = bytes(test, "key");
+            let msg = bytes(test, "msg");
+            let expected = bytes(test, "tag");
+            let tag_size = group["tagSize"].as_u64().unwrap() as usize / 8;
+            assert_eq!(expected.len(), tag_size);
+
+            // SecretKey cannot be empty, so `hmac_into` is used for all keys
+            let mut tag = vec![0u8; hmac.output_size()];
+            hmac_into(&key, &msg, hmac, &mut tag).unwrap();
+            let tag_matches = util::compare_ct(&tag[..tag_size], &expected).is_ok();
+            assert_eq!(tag_matches, is_valid(test), "tcId {}", test["tcId"]);
+
+            if !key.is_empty() && tag_size == hmac.output_size() {
+                let secret_key = SecretKey::from_slice(&key).unwrap();
+                let one_shot = Hmac::new(&secret_key, &msg, hmac).unwrap();
+                assert_eq!(one_shot.verify(&expected).is_ok(), is_valid(test));
+            }
+        });
+    }
+
+    fn hkdf_test_runner(file_name: &str, hmac: ShaVariantOption) {
+        for_each_test(file_name, |_, test| {
+            let ikm = bytes(test, "ikm");
+            let salt = bytes(test, "salt");
+            let info = bytes(test, "info");
+            let size = test["size"].as_u64().unwrap() as usize;
+
+            match Hkdf::new(&salt, &ikm, &info, size, hmac) {
+                Ok(hkdf) => {
+                    assert!(is_valid(test), "tcId {}", test["tcId"]);
+                    let okm = bytes(test, "okm");
+                    assert_eq!(hkdf.derive_key().unwrap(), okm, "tcId {}", test["tcId"]);
+                    assert!(hkdf.verify(&okm).unwrap());
+                }
+                Err(_) => assert!(!is_valid(test), "tcId {}", test["tcId"]),
+            }
+        });
+    }
+
+    #[test]
+    fn hmac_sha256() {
+        hmac_test_runner("hmac_sha256_test.json", ShaVariantOption::SHA256);
+    }
+
+    #[test]
+    fn hmac_sha384() {
+        hmac_test_runner("hmac_sha384_test.json", ShaVariantOption::SHA384);
+    }
+
+    #[test]
+    fn hmac_sha512() {
+        hmac_test_runner("hmac_sha512_test.json", ShaVariantOption::SHA512);
+    }
+
+    #[test]
+    fn hmac_sha512_256() {
+        hmac_test_runner("hmac_sha512_256_test.json", ShaVariantOption::SHA512Trunc256);
+    }
+
+    #[test]
+    fn hmac_sha3_256() {
+        hmac_test_runner("hmac_sha3_256_test.json", ShaVariantOption::SHA3_256);
+    }
+
+    #[test]
+    fn hmac_sha3_512() {
+        hmac_test_runner("hmac_sha3_512_test.json", ShaVariantOption::SHA3_512);
+    }
+
+    #[test]
+    fn hkdf_sha256() {
+        hkdf_test_runner("hkdf_sha256_test.json", ShaVariantOption::SHA256);
+    }
+
+    #[test]
+    fn hkdf_sha384() {
+        hkdf_test_runner("hkdf_sha384_test.json", ShaVariantOption::SHA384);
+    }
+
+    #[test]
+    fn hkdf_sha512() {
+        hkdf_test_runner("hkdf_sha512_test.json", ShaVariantOption::SHA512);
+    }
+
+    #[test]
+    fn xchacha20poly1305() {
+        for_each_test("xchacha20_poly1305_test.json", |_, test| {
+            let key = bytes(test, "key");
+            let aad = bytes(test, "aad");
+            let msg = bytes(test, "msg");
+            let mut sealed = bytes(test, "ct");
+            sealed.extend_from_slice(&bytes(test, "tag"));
+
+            // Nonces of the wrong length are rejected, either by `Nonce` or by the AEAD
+            let nonce = match Nonce::from_slice(&bytes(test, "iv")) {
+                Ok(nonce) => nonce,
+                Err(_) => {
+                    assert!(!is_valid(test), "tcId {}", test["tcId"]);
+                    return;
+                }
+            };
+
+            match xchacha20poly1305::open(&key, &nonce, &sealed, &aad) {
+                Ok(plaintext) => {
+                    assert!(is_valid(test), "tcId {}", test["tcId"]);
+                    assert_eq!(plaintext, msg);
+                    assert_eq!(xchacha20poly1305::seal(&key, &nonce, &msg, &aad).unwrap(), sealed);
+                }
+                Err(_) => {
+                    assert!(!is_valid(test), "tcId {}", test["tcId"]);
+                    assert!(xchacha20poly1305::seal(&key, &nonce, &msg, &aad) != Ok(sealed));
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,182 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! JavaScript bindings for the default API, generated with
+//! [wasm-bindgen](https://crates.io/crates/wasm-bindgen).
+//!
+//! Byte arrays are passed in and returned as `Uint8Array`, and all calls are synchronous.
+//! Every function behaves like the function of the same name in `default`, and throws an
+//! `Error` where that function returns an error. The verification functions return `false`
+//! instead of throwing when the input does not match.
+//!
+//! The bindings are exported from the WebAssembly module of any crate that depends on orion
+//! with the `wasm` feature and is built as a `cdylib`, for example with `wasm-pack`:
+//! ```js
+//! import * as orion from "./pkg/my_crate.js";
+//!
+//! const tag = orion.hmac(key, new TextEncoder().encode("Some message."));
+//! console.assert(orion.hmacVerify(tag, key, new TextEncoder().encode("Some message.")));
+//! ```
+
+#[cfg(feature = "rng")]
+use core::types::Salt;
+use core::types::{Password, SecretKey, Tag};
+use default;
+use std::fmt;
+use wasm_bindgen::prelude::*;
+
+/// Convert an orion error into a JavaScript `Error`.
+fn js_error<E: fmt::Display>(err: E) -> JsError {
+    JsError::new(&err.to_string())
+}
+
+/// HMAC-SHA512/256. See `default::hmac`.
+#[wasm_bindgen]
+pub fn hmac(secret_key: &[u8], data: &[u8]) -> Result<Vec<u8>, JsError> {
+    default::hmac_from_slice(secret_key, data)
+        .map(|tag| tag.as_bytes().to_vec())
+        .map_err(js_error)
+}
+
+/// Verify an HMAC-SHA512/256 in constant time. See `default::hmac_verify`.
+#[wasm_bindgen(js_name = hmacVerify)]
+pub fn hmac_verify(expected_hmac: &[u8], secret_key: &[u8], data: &[u8]) -> bool {
+    Tag::from_slice(expected_hmac)
+        .map(|tag| default::hmac_verify_from_slice(&tag, secret_key, data).is_ok())
+        .unwrap_or(false)
+}
+
+/// HKDF-HMAC-SHA512/256. See `default::hkdf`.
+#[wasm_bindgen]
+pub fn hkdf(salt: &[u8], input: &[u8], info: &[u8], len: usize) -> Result<Vec<u8>, JsError> {
+    default::hkdf_from_slice(salt, input, info, len).map_err(js_error)
+}
+
+/// Verify an HKDF-HMAC-SHA512/256 derived key in constant time. See `default::hkdf_verify`.
+#[wasm_bindgen(js_name = hkdfVerify)]
+pub fn hkdf_verify(expected_dk: &[u8], salt: &[u8], input: &[u8], info: &[u8]) -> bool {
+    default::hkdf_verify_from_slice(expected_dk, salt, input, info, expected_dk.len()).is_ok()
+}
+
+#[cfg(feature = "rng")]
+/// PBKDF2-HMAC-SHA512/256 with a random salt. See `default::pbkdf2`.
+#[wasm_bindgen]
+pub fn pbkdf2(password: &[u8]) -> Result<String, JsError> {
+    default::pbkdf2_from_slice(password).map_err(js_error)
+}
+
+/// Verify a PBKDF2-HMAC-SHA512/256 derived key in constant time. See `default::pbkdf2_verify`.
+#[wasm_bindgen(js_name = pbkdf2Verify)]
+pub fn pbkdf2_verify(expected_dk: &str, password: &[u8]) -> bool {
+    Password::from_slice(password)
+        .map(|password| default::pbkdf2_verify(expected_dk, &password).is_ok())
+        .unwrap_or(false)
+}
+
+#[cfg(feature = "rng")]
+/// Hash a password and return a PHC string. See `default::password_hash`.
+#[wasm_bindgen(js_name = passwordHash)]
+pub fn password_hash(password: &[u8]) -> Result<String, JsError> {
+    let password = Password::from_slice(password).map_err(js_error)?;
+
+    default::password_hash(&password).map_err(js_error)
+}
+
+/// Verify a password against a PHC string. See `default::password_hash_verify`.
+#[wasm_bindgen(js_name = passwordHashVerify)]
+pub fn password_hash_verify(hash: &str, password: &[u8]) -> bool {
+    Password::from_slice(password)
+        .map(|password| default::password_hash_verify(hash, &password).is_ok())
+        .unwrap_or(false)
+}
+
+/// SHA-512/256. See `default::hash`.
+#[wasm_bindgen]
+pub fn hash(data: &[u8]) -> Vec<u8> {
+    default::hash(data).to_vec()
+}
+
+/// cSHAKE256 with a 64 byte output. See `default::cshake`.
+#[wasm_bindgen]
+pub fn cshake(input: &[u8], custom: &[u8]) -> Result<Vec<u8>, JsError> {
+    default::cshake(input, custom)
+        .map(|hash| hash.to_vec())
+        .map_err(js_error)
+}
+
+/// Verify a cSHAKE256 hash in constant time. See `default::cshake_verify`.
+#[wasm_bindgen(js_name = cshakeVerify)]
+pub fn cshake_verify(expected: &[u8], input: &[u8], custom: &[u8]) -> bool {
+    default::cshake_verify(expected, input, custom).is_ok()
+}
+
+#[cfg(feature = "rng")]
+/// Authenticated encryption with XChaCha20-Poly1305. See `default::encrypt`.
+#[wasm_bindgen]
+pub fn encrypt(secret_key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, JsError> {
+    let secret_key = SecretKey::from_slice(secret_key).map_err(js_error)?;
+
+    default::encrypt(&secret_key, plaintext).map_err(js_error)
+}
+
+/// Authenticated decryption with XChaCha20-Poly1305. See `default::decrypt`.
+#[wasm_bindgen]
+pub fn decrypt(secret_key: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, JsError> {
+    let secret_key = SecretKey::from_slice(secret_key).map_err(js_error)?;
+
+    default::decrypt(&secret_key, ciphertext).map_err(js_error)
+}
+
+#[cfg(feature = "rng")]
+/// Password-based authenticated encryption. See `default::encrypt_with_password`.
+#[wasm_bindgen(js_name = encryptWithPassword)]
+pub fn encrypt_with_password(password: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, JsError> {
+    let password = Password::from_slice(password).map_err(js_error)?;
+
+    default::encrypt_with_password(&password, plaintext).map_err(js_error)
+}
+
+/// Password-based authenticated decryption. See `default::decrypt_with_password`.
+#[wasm_bindgen(js_name = decryptWithPassword)]
+pub fn decrypt_with_password(password: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, JsError> {
+    let password = Password::from_slice(password).map_err(js_error)?;
+
+    default::decrypt_with_password(&password, ciphertext).map_err(js_error)
+}
+
+#[cfg(feature = "rng")]
+/// Generate a random salt. See `core::types::Salt::generate`.
+#[wasm_bindgen(js_name = genSalt)]
+pub fn gen_salt(length: usize) -> Result<Vec<u8>, JsError> {
+    Salt::generate(length)
+        .map(|salt| salt.as_bytes().to_vec())
+        .map_err(js_error)
+}
+
+#[cfg(feature = "rng")]
+/// Generate a random secret key. See `core::types::SecretKey::generate`.
+#[wasm_bindgen(js_name = genSecretKey)]
+pub fn gen_secret_key(length: usize) -> Result<Vec<u8>, JsError> {
+    SecretKey::generate(length)
+        .map(|key| key.unprotected_as_bytes().to_vec())
+        .map_err(js_error)
+}